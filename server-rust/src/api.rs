@@ -1,27 +1,147 @@
+use std::{collections::BTreeMap, sync::Arc};
+
 use axum::{
-    extract::{Path, Query, State},
-    http::{header, HeaderValue, StatusCode},
-    response::IntoResponse,
-    Json,
+    body::Body,
+    extract::{FromRef, Path, Query, State},
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    middleware,
+    response::{AppendHeaders, IntoResponse},
+    routing::{get, post, put},
+    Extension, Json, Router,
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use tokio_util::io::ReaderStream;
 use uuid::Uuid;
 
 use crate::{
+    audit::{capture_audit_context, AuditContext},
+    config::ServerConfig,
+    control_plan,
     domain::{
-        AcquisitionMetadata, Analysis, ComplianceStatus, ImageReference, ServerLifecycleStatus,
+        AcquisitionMetadata, Analysis, AnalysisCorrection, AnalysisVerification, AuditEvent,
+        AuditEventType, AuditExport, AuditExportFormat, AuditExportStatus, ComplianceStatus,
+        ControlChart, ControlGap, ControlPlan, CorrectiveAction, CorrectiveActionStatus,
+        CsvDialect, Deviation, DeviationStatus, ElectronicSignature, FailedCheck, ImageReference,
+        IntegrityCheckRun, IntegrityReport, Job, JobKind, JobStatus, ProductionEvent,
+        ProductionEventKind, ReportPeriod, ReviewClaim, ReviewDecision, ReviewReason,
+        SecondaryReview, ServerLifecycleStatus, ShiftReport, SignatureMeaning,
+        SignedExportManifest, SignedRecordType, SpcRule, SpcSignal, ThresholdForecast,
+        TimestampSubject, TrustedTimestamp, User, UserRole,
     },
-    reporting,
-    storage::{AnalysisStore, StorageError, UpsertResult},
+    exports::ExportSigner,
+    forecast, reporting,
+    rules::{self, VersionedAnalysisDecision},
+    shift_report, signatures, spc,
+    statistics::{self, MAX_REPORT_PERIODS},
+    storage::{
+        AnalysisStore, AuditEventFilter, ClaimOutcome, CorrectionOutcome, CorrectiveActionOutcome,
+        ProductionEventOutcome, ReviewOutcome, SpcSignalFilter, StorageError, UpsertResult,
+    },
+    timestamping::TsaClient,
     validation,
+    verification::Verifier,
 };
 
+/// Bounds of the check interval of a control plan (5 minutes to a week).
+const MIN_CONTROL_INTERVAL_MINUTES: u32 = 5;
+const MAX_CONTROL_INTERVAL_MINUTES: u32 = 7 * 24 * 60;
+/// Upper bound on the `limit` of a control chart request.
+const MAX_SPC_CHART_POINTS: usize = 1000;
+/// Response headers carrying the hash chain heads an export was built from.
+const CHAIN_HEAD_ANALYSES_HEADER: &str = "x-chain-head-analyses";
+const CHAIN_HEAD_AUDIT_EVENTS_HEADER: &str = "x-chain-head-audit-events";
+/// Id of the signed manifest, served by `GET /v1/exports/{id}/manifest`.
+const EXPORT_ID_HEADER: &str = "x-export-id";
+const EXPORT_SIGNATURE_HEADER: &str = "x-export-signature";
+/// Present when the manifest was timestamped by the TSA.
+const EXPORT_TIMESTAMP_ID_HEADER: &str = "x-export-timestamp-id";
+
+#[derive(Clone)]
+pub struct AppState {
+    pub store: AnalysisStore,
+    pub verifier: Arc<Verifier>,
+    pub export_signer: Arc<ExportSigner>,
+    /// Set when `TSA_URL` is configured.
+    pub tsa: Option<Arc<TsaClient>>,
+    pub config: Arc<ServerConfig>,
+}
+
+impl FromRef<AppState> for AnalysisStore {
+    fn from_ref(state: &AppState) -> Self {
+        state.store.clone()
+    }
+}
+
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/v1/analyses", post(create_analysis))
+        .route("/v1/analyses/history", get(analyses_history))
+        .route("/v1/analyses/:id", get(analysis_detail))
+        .route("/v1/analyses/:id/ack", get(analysis_ack))
+        .route("/v1/analyses/:id/corrections", post(create_correction))
+        .route("/v1/analyses/audit-export", post(export_audit_register))
+        .route(
+            "/v1/analyses/:id/secondary-review",
+            post(create_secondary_review),
+        )
+        .route("/v1/deviations", get(list_deviations))
+        .route("/v1/deviations/:id", get(deviation_detail))
+        .route(
+            "/v1/corrective-actions",
+            get(list_corrective_actions).post(create_corrective_action),
+        )
+        .route(
+            "/v1/corrective-actions/:id/complete",
+            post(complete_corrective_action),
+        )
+        .route(
+            "/v1/corrective-actions/:id/verify",
+            post(verify_corrective_action),
+        )
+        .route(
+            "/v1/production-events",
+            get(list_production_events).post(create_production_event),
+        )
+        .route("/v1/review-queue", get(review_queue))
+        .route("/v1/review-queue/:id/claim", post(claim_review))
+        .route("/v1/review-queue/:id/release", post(release_review_claim))
+        .route("/v1/admin/jobs", get(admin_jobs))
+        .route("/v1/audit-events", get(list_audit_events))
+        .route("/v1/admin/integrity", get(verify_integrity))
+        .route("/v1/exports/:id", get(download_audit_export))
+        .route("/v1/exports/:id/status", get(audit_export_status))
+        .route("/v1/exports/:id/manifest", get(export_manifest))
+        .route("/v1/timestamps", get(list_timestamps))
+        .route("/v1/timestamps/:id/token", get(timestamp_token))
+        .route(
+            "/v1/signatures",
+            get(list_signatures).post(create_signature),
+        )
+        .route("/v1/reports/compliance", get(compliance_report))
+        .route("/v1/spc/baths/:bath_id/chart", get(spc_chart))
+        .route("/v1/spc/signals", get(list_spc_signals))
+        .route("/v1/forecasts/baths/:bath_id", get(threshold_forecast))
+        .route("/v1/control-plans", get(list_control_plans))
+        .route("/v1/control-plans/:bath_id", put(update_control_plan))
+        .route("/v1/control-gaps", get(list_control_gaps))
+        .route("/v1/shift-reports", get(list_shift_reports))
+        .route("/v1/shift-reports/:id", get(get_shift_report))
+        .route("/v1/admin/users", get(admin_users))
+        .route("/v1/admin/users/:user_id", put(upsert_user))
+        .layer(middleware::from_fn(capture_audit_context))
+        .with_state(state)
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateAnalysisPayload {
     pub client_analysis_id: Uuid,
     pub sample_id: String,
+    pub operator_id: String,
+    pub bath_id: String,
+    pub parent_analysis_id: Option<Uuid>,
     pub ppm_estime: f32,
     pub ppm_min: f32,
     pub ppm_max: f32,
@@ -43,6 +163,47 @@ pub struct CreateAnalysisResponse {
     pub received_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CorrectionPayload {
+    pub corrected_by: String,
+    pub justification: String,
+    pub sample_id: Option<String>,
+    pub operator_id: Option<String>,
+    pub bath_id: Option<String>,
+    #[serde(default)]
+    pub void: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CorrectionResponse {
+    pub server_analysis_id: Uuid,
+    pub supersedes_analysis_id: Uuid,
+    pub version: u32,
+    pub server_lifecycle_status: ServerLifecycleStatus,
+    pub received_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AnalysisVersionSummary {
+    pub server_analysis_id: Uuid,
+    pub version: u32,
+    pub sample_id: String,
+    pub operator_id: String,
+    pub bath_id: String,
+    pub server_lifecycle_status: ServerLifecycleStatus,
+    pub received_at: DateTime<Utc>,
+    pub correction: Option<AnalysisCorrection>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AnalysisDetailResponse {
+    pub analysis: Analysis,
+    pub superseded_by_analysis_id: Option<Uuid>,
+    pub verification: Option<AnalysisVerification>,
+    pub versions: Vec<AnalysisVersionSummary>,
+    pub signatures: Vec<ElectronicSignature>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct HistoryQuery {
     pub limit: Option<usize>,
@@ -54,6 +215,9 @@ pub struct HistoryQuery {
 pub struct HistoryItem {
     pub server_analysis_id: Uuid,
     pub sample_id: String,
+    pub operator_id: String,
+    pub bath_id: String,
+    pub parent_analysis_id: Option<Uuid>,
     pub ppm_estime: f32,
     pub ppm_min: f32,
     pub ppm_max: f32,
@@ -81,27 +245,468 @@ pub struct AnalysisAckResponse {
     pub validated_schema: bool,
     pub validated_business_rules: bool,
     pub queued_for_secondary_review: bool,
+    pub failed_checks: Vec<FailedCheck>,
+    pub review_reasons: Vec<ReviewReason>,
+    pub server_ppm_estime: Option<f32>,
+    pub server_decision: Option<VersionedAnalysisDecision>,
+    pub verified_at: Option<DateTime<Utc>>,
     pub ack_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ReviewQueueQuery {
+    pub reason: Option<ReviewReason>,
+    pub min_age_minutes: Option<i64>,
+    pub max_age_minutes: Option<i64>,
+    pub include_claimed: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReviewQueueItem {
+    pub server_analysis_id: Uuid,
+    pub sample_id: String,
+    pub operator_id: String,
+    pub ppm_estime: f32,
+    pub server_ppm_estime: Option<f32>,
+    pub compliance_status: ComplianceStatus,
+    pub analysis_result: String,
+    pub confidence: f32,
+    pub server_lifecycle_status: ServerLifecycleStatus,
+    pub review_reasons: Vec<ReviewReason>,
+    pub failed_checks: Vec<FailedCheck>,
+    pub captured_at: DateTime<Utc>,
+    pub received_at: DateTime<Utc>,
+    pub age_minutes: i64,
+    pub claim: Option<ReviewClaim>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReviewQueueResponse {
+    pub items: Vec<ReviewQueueItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeviationsQuery {
+    pub status: Option<DeviationStatus>,
+    pub bath_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeviationItem {
+    pub deviation_id: Uuid,
+    pub bath_id: String,
+    pub status: DeviationStatus,
+    pub opening_analysis_id: Uuid,
+    pub opening_compliance_status: ComplianceStatus,
+    pub opened_at: DateTime<Utc>,
+    pub closing_analysis_id: Option<Uuid>,
+    pub closed_at: Option<DateTime<Utc>>,
+    /// Time to re-control for closed deviations, time open so far otherwise.
+    pub elapsed_minutes: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeviationsResponse {
+    pub items: Vec<DeviationItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCorrectiveActionPayload {
+    pub analysis_id: Option<Uuid>,
+    pub deviation_id: Option<Uuid>,
+    pub description: String,
+    pub owner_id: String,
+    pub due_at: DateTime<Utc>,
+    pub created_by: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompleteCorrectiveActionPayload {
+    pub completed_by: String,
+    pub action_taken: String,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyCorrectiveActionPayload {
+    pub verified_by: String,
+    pub verification_comment: String,
+    pub verified_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CorrectiveActionsQuery {
+    pub status: Option<CorrectiveActionStatus>,
+    pub analysis_id: Option<Uuid>,
+    pub deviation_id: Option<Uuid>,
+    pub overdue: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CorrectiveActionsResponse {
+    pub items: Vec<CorrectiveAction>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProductionEventPayload {
+    pub bath_id: String,
+    pub kind: ProductionEventKind,
+    pub recorded_by: String,
+    pub occurred_at: DateTime<Utc>,
+    pub reason: String,
+    #[serde(default)]
+    pub linked_analysis_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProductionEventsQuery {
+    pub bath_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProductionEventsResponse {
+    pub items: Vec<ProductionEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClaimPayload {
+    pub reviewer_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SecondaryReviewPayload {
+    pub reviewer_id: String,
+    pub decision: ReviewDecision,
+    pub comment: Option<String>,
+    pub reviewed_at: DateTime<Utc>,
+    pub signature: SignatureCredentials,
+}
+
+/// Re-authentication supplied at signing time.
+#[derive(Debug, Deserialize)]
+pub struct SignatureCredentials {
+    pub password: String,
+    pub meaning: SignatureMeaning,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SecondaryReviewResponse {
+    pub server_analysis_id: Uuid,
+    pub server_lifecycle_status: ServerLifecycleStatus,
+    pub secondary_review_status: String,
+    pub decision: ReviewDecision,
+    pub signature: ElectronicSignature,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SignaturePayload {
+    pub record_type: SignedRecordType,
+    pub signer_id: String,
+    #[serde(flatten)]
+    pub credentials: SignatureCredentials,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SignaturesQuery {
+    pub record_type: Option<SignedRecordType>,
+    pub record_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SignaturesResponse {
+    pub items: Vec<ElectronicSignature>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TimestampsQuery {
+    pub subject_type: Option<TimestampSubject>,
+    pub subject_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimestampsResponse {
+    pub items: Vec<TrustedTimestamp>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JobsQuery {
+    pub status: Option<JobStatus>,
+    pub kind: Option<JobKind>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobsResponse {
+    pub counts: BTreeMap<JobStatus, i64>,
+    pub items: Vec<Job>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UserPayload {
+    pub display_name: String,
+    pub roles: Vec<UserRole>,
+    pub active: Option<bool>,
+    /// Signing password; left unchanged when absent.
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsersResponse {
+    pub items: Vec<User>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditEventsQuery {
+    pub event_type: Option<AuditEventType>,
+    pub analysis_id: Option<Uuid>,
+    pub actor: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditEventsResponse {
+    pub items: Vec<AuditEvent>,
+    pub next_cursor: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct AuditExportPayload {
     pub from: Option<DateTime<Utc>>,
     pub to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub format: AuditExportFormat,
+    /// Applies to the CSV register, alone or inside the ZIP bundle.
+    #[serde(default)]
+    pub csv_dialect: CsvDialect,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Csv,
+    Pdf,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ComplianceReportQuery {
+    #[serde(default)]
+    pub period: ReportPeriod,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub bath_id: Option<String>,
+    #[serde(default)]
+    pub format: ReportFormat,
+    /// Applies to `format=csv`.
+    #[serde(default)]
+    pub csv_dialect: CsvDialect,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SpcChartQuery {
+    /// Most recent points to plot, `SPC_WINDOW_POINTS` when absent.
+    pub limit: Option<usize>,
 }
 
-pub async fn health() -> Json<serde_json::Value> {
-    Json(json!({"status":"ok","service":"peroxyde-server"}))
+#[derive(Debug, Deserialize)]
+pub struct SpcSignalsQuery {
+    pub bath_id: Option<String>,
+    pub rule: Option<SpcRule>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
 }
 
-pub async fn create_analysis(
+#[derive(Debug, Deserialize)]
+pub struct ThresholdForecastQuery {
+    /// Next scheduled check; the last check plus `CHECK_INTERVAL_HOURS` when
+    /// absent.
+    pub next_check_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ControlPlanPayload {
+    pub interval_minutes: u32,
+    #[serde(default)]
+    pub grace_minutes: u32,
+    pub active: Option<bool>,
+    pub updated_by: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ControlPlansQuery {
+    pub bath_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ControlPlansResponse {
+    pub items: Vec<ControlPlan>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlGapsFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ControlGapsQuery {
+    pub bath_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub format: ControlGapsFormat,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ControlGapsResponse {
+    pub evaluated_at: DateTime<Utc>,
+    pub items: Vec<ControlGap>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShiftReportsQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShiftReportSummary {
+    pub id: Uuid,
+    pub shift_start: DateTime<Utc>,
+    pub shift_end: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+    pub checks: usize,
+    pub conforme_production: usize,
+    pub taux_bas: usize,
+    pub seuil_depasse: usize,
+    pub rejected_captures: usize,
+    pub open_deviations: usize,
+    pub pending_reviews: usize,
+    pub missed_checks: usize,
+}
+
+impl From<ShiftReport> for ShiftReportSummary {
+    fn from(report: ShiftReport) -> Self {
+        Self {
+            id: report.id,
+            shift_start: report.shift_start,
+            shift_end: report.shift_end,
+            generated_at: report.generated_at,
+            checks: report.checks.len(),
+            conforme_production: report.conforme_production,
+            taux_bas: report.taux_bas,
+            seuil_depasse: report.seuil_depasse,
+            rejected_captures: report.rejected_captures,
+            open_deviations: report.open_deviations.len(),
+            pending_reviews: report.pending_reviews.len(),
+            missed_checks: report.missed_checks.len(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShiftReportsResponse {
+    pub items: Vec<ShiftReportSummary>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShiftReportFormat {
+    #[default]
+    Json,
+    Pdf,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShiftReportQuery {
+    #[serde(default)]
+    pub format: ShiftReportFormat,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpcSignalsResponse {
+    pub items: Vec<SpcSignal>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditExportResponse {
+    pub export_id: Uuid,
+    pub status: AuditExportStatus,
+    pub format: AuditExportFormat,
+    pub csv_dialect: CsvDialect,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub requested_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub record_count: Option<usize>,
+    pub error: Option<String>,
+    /// Set once the file is ready, until it expires.
+    pub download_url: Option<String>,
+}
+
+impl From<AuditExport> for AuditExportResponse {
+    fn from(export: AuditExport) -> Self {
+        Self {
+            download_url: (export.status == AuditExportStatus::Ready)
+                .then(|| format!("/v1/exports/{}", export.id)),
+            export_id: export.id,
+            status: export.status,
+            format: export.format,
+            csv_dialect: export.csv_dialect,
+            from: export.from,
+            to: export.to,
+            requested_at: export.requested_at,
+            completed_at: export.completed_at,
+            expires_at: export.expires_at,
+            record_count: export.record_count,
+            error: export.error,
+        }
+    }
+}
+
+/// Not audited. Reports `degraded` while the latest scheduled integrity check
+/// failed; `integrity` is null until the first check completes.
+pub async fn health(
     State(store): State<AnalysisStore>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let last_check = store
+        .latest_audit_event(AuditEventType::IntegrityCheckCompleted)
+        .await
+        .map_err(storage_error)?
+        .and_then(|event| serde_json::from_value::<IntegrityCheckRun>(event.details).ok());
+    let healthy = last_check.as_ref().is_none_or(|run| run.healthy);
+    Ok(Json(json!({
+        "status": if healthy { "ok" } else { "degraded" },
+        "service": "peroxyde-server",
+        "integrity": last_check.map(|run| json!({
+            "healthy": run.healthy,
+            "failures": run.failures,
+            "checked_at": run.finished_at,
+        })),
+    })))
+}
+
+pub async fn create_analysis(
+    State(state): State<AppState>,
+    Extension(audit): Extension<AuditContext>,
     Json(payload): Json<CreateAnalysisPayload>,
 ) -> Result<(StatusCode, Json<CreateAnalysisResponse>), (StatusCode, Json<serde_json::Value>)> {
     let analysis = Analysis {
         id: Uuid::new_v4(),
         client_analysis_id: payload.client_analysis_id,
         sample_id: payload.sample_id,
+        operator_id: payload.operator_id,
+        bath_id: payload.bath_id,
+        parent_analysis_id: payload.parent_analysis_id,
         ppm_estime: payload.ppm_estime,
         ppm_min: payload.ppm_min,
         ppm_max: payload.ppm_max,
@@ -116,64 +721,409 @@ pub async fn create_analysis(
         image: payload.image,
         acquisition_metadata: payload.acquisition_metadata,
         server_lifecycle_status: ServerLifecycleStatus::Recu,
+        version: 1,
+        supersedes_analysis_id: None,
+        correction: None,
     };
+    let client_analysis_id = analysis.client_analysis_id;
 
-    validation::validate_analysis(&analysis).map_err(|message| {
-        (
+    let audit = audit.with_device(device_label(&analysis.acquisition_metadata));
+
+    let validated = match validation::validate_analysis(&analysis) {
+        Ok(()) => validate_parent(&state.store, &analysis).await,
+        Err(message) => Err((
             StatusCode::UNPROCESSABLE_ENTITY,
             Json(json!({"error":message})),
-        )
-    })?;
+        )),
+    };
+    if let Err((status, Json(body))) = validated {
+        audit
+            .record(
+                &state.store,
+                AuditEventType::AnalysisRejected,
+                None,
+                Some(&analysis.operator_id),
+                json!({
+                    "client_analysis_id": analysis.client_analysis_id,
+                    "sample_id": analysis.sample_id,
+                    "error": body["error"],
+                }),
+            )
+            .await
+            .map_err(storage_error)?;
+        return Err((status, Json(body)));
+    }
 
-    match store
+    match state
+        .store
         .upsert_analysis(analysis)
         .await
         .map_err(storage_error)?
     {
-        UpsertResult::Inserted(saved) => Ok((
-            StatusCode::ACCEPTED,
-            Json(CreateAnalysisResponse {
-                server_analysis_id: saved.id,
-                server_lifecycle_status: saved.server_lifecycle_status,
-                received_at: saved.received_at,
-            }),
-        )),
-        UpsertResult::IdempotentReplay(saved) => Ok((
-            StatusCode::OK,
-            Json(CreateAnalysisResponse {
-                server_analysis_id: saved.id,
-                server_lifecycle_status: saved.server_lifecycle_status,
-                received_at: saved.received_at,
-            }),
-        )),
-        UpsertResult::Conflict => Err((
-            StatusCode::CONFLICT,
-            Json(json!({"error":"client_analysis_id already exists with different payload"})),
-        )),
+        UpsertResult::Inserted(saved) => {
+            audit
+                .record(
+                    &state.store,
+                    AuditEventType::AnalysisReceived,
+                    Some(saved.id),
+                    Some(&saved.operator_id),
+                    json!({"summary": reporting::audit_line(&saved)}),
+                )
+                .await
+                .map_err(storage_error)?;
+            audit
+                .record(
+                    &state.store,
+                    AuditEventType::AnalysisSchemaValidated,
+                    Some(saved.id),
+                    Some(&saved.operator_id),
+                    json!({"client_analysis_id": saved.client_analysis_id}),
+                )
+                .await
+                .map_err(storage_error)?;
+
+            // Image decoding and colorimetric re-analysis run in the worker
+            // pool; the phone polls the acknowledgement for the verdict.
+            state
+                .store
+                .enqueue_job(
+                    JobKind::VerifyAnalysis,
+                    saved.id,
+                    state.config.job_max_attempts,
+                )
+                .await
+                .map_err(storage_error)?;
+
+            Ok((
+                StatusCode::ACCEPTED,
+                Json(CreateAnalysisResponse {
+                    server_analysis_id: saved.id,
+                    server_lifecycle_status: saved.server_lifecycle_status,
+                    received_at: saved.received_at,
+                }),
+            ))
+        }
+        UpsertResult::IdempotentReplay(saved) => {
+            audit
+                .record(
+                    &state.store,
+                    AuditEventType::AnalysisReceived,
+                    Some(saved.id),
+                    Some(&saved.operator_id),
+                    json!({"idempotent_replay": true}),
+                )
+                .await
+                .map_err(storage_error)?;
+            Ok((
+                StatusCode::OK,
+                Json(CreateAnalysisResponse {
+                    server_analysis_id: saved.id,
+                    server_lifecycle_status: saved.server_lifecycle_status,
+                    received_at: saved.received_at,
+                }),
+            ))
+        }
+        UpsertResult::Conflict => {
+            audit
+                .record(
+                    &state.store,
+                    AuditEventType::AnalysisRejected,
+                    None,
+                    None,
+                    json!({
+                        "client_analysis_id": client_analysis_id,
+                        "error": "client_analysis_id already exists with different payload",
+                    }),
+                )
+                .await
+                .map_err(storage_error)?;
+            Err((
+                StatusCode::CONFLICT,
+                Json(json!({"error":"client_analysis_id already exists with different payload"})),
+            ))
+        }
     }
 }
 
-pub async fn analyses_history(
-    State(store): State<AnalysisStore>,
-    Query(query): Query<HistoryQuery>,
-) -> Result<Json<HistoryResponse>, (StatusCode, Json<serde_json::Value>)> {
-    let limit = query.limit.unwrap_or(20).clamp(1, 100);
-    let offset = query
-        .cursor
-        .as_deref()
-        .and_then(|cursor| cursor.parse::<usize>().ok())
-        .unwrap_or(0);
+/// Creates the next version of an analysis. Only identification fields can
+/// change; the measurement itself is copied and verified again by the worker.
+pub async fn create_correction(
+    State(state): State<AppState>,
+    Extension(audit): Extension<AuditContext>,
+    Path(server_analysis_id): Path<Uuid>,
+    Json(payload): Json<CorrectionPayload>,
+) -> Result<(StatusCode, Json<CorrectionResponse>), (StatusCode, Json<serde_json::Value>)> {
+    let unprocessable = |message: &str| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({"error":message})),
+        )
+    };
 
-    let (analyses, next_cursor) = store
-        .list_paginated(limit, offset, query.sample_id.as_deref())
+    if payload.corrected_by.trim().is_empty() || payload.justification.trim().is_empty() {
+        return Err(unprocessable("corrected_by and justification are required"));
+    }
+    let authorised = state
+        .store
+        .find_user(&payload.corrected_by)
         .await
-        .map_err(storage_error)?;
-
+        .map_err(storage_error)?
+        .is_some_and(|user| {
+            [
+                UserRole::Qualite,
+                UserRole::ChefEquipe,
+                UserRole::Administrateur,
+            ]
+            .into_iter()
+            .any(|role| user.has_role(role))
+        });
+    if !authorised {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(
+                json!({"error":"corrections require a quality, team leader or administrator user"}),
+            ),
+        ));
+    }
+
+    let original = state
+        .store
+        .find_by_id(server_analysis_id)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error":"analysis not found"})),
+            )
+        })?;
+
+    let mut corrected = Analysis {
+        id: Uuid::new_v4(),
+        client_analysis_id: Uuid::new_v4(),
+        received_at: Utc::now(),
+        server_lifecycle_status: ServerLifecycleStatus::Recu,
+        version: original.version + 1,
+        supersedes_analysis_id: Some(original.id),
+        correction: Some(AnalysisCorrection {
+            justification: payload.justification,
+            corrected_by: payload.corrected_by,
+            voided: payload.void,
+        }),
+        ..original.clone()
+    };
+    if let Some(sample_id) = payload.sample_id {
+        corrected.sample_id = sample_id;
+    }
+    if let Some(operator_id) = payload.operator_id {
+        corrected.operator_id = operator_id;
+    }
+    if let Some(bath_id) = payload.bath_id {
+        corrected.bath_id = bath_id;
+    }
+    if !payload.void
+        && corrected.sample_id == original.sample_id
+        && corrected.operator_id == original.operator_id
+        && corrected.bath_id == original.bath_id
+    {
+        return Err(unprocessable("correction does not change anything"));
+    }
+    validation::validate_analysis(&corrected).map_err(unprocessable)?;
+
+    let saved = match state
+        .store
+        .record_correction(corrected)
+        .await
+        .map_err(storage_error)?
+    {
+        CorrectionOutcome::Recorded(saved) => saved,
+        CorrectionOutcome::NotFound => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({"error":"analysis not found"})),
+            ))
+        }
+        CorrectionOutcome::AlreadySuperseded(successor) => {
+            return Err((
+                StatusCode::CONFLICT,
+                Json(json!({
+                    "error":"analysis already superseded; correct the latest version",
+                    "superseded_by_analysis_id": successor
+                })),
+            ))
+        }
+    };
+
+    state
+        .store
+        .enqueue_job(
+            JobKind::VerifyAnalysis,
+            saved.id,
+            state.config.job_max_attempts,
+        )
+        .await
+        .map_err(storage_error)?;
+    if let Some(correction) = &saved.correction {
+        audit
+            .record(
+                &state.store,
+                AuditEventType::AnalysisCorrected,
+                Some(saved.id),
+                Some(&correction.corrected_by),
+                json!({
+                    "supersedes_analysis_id": original.id,
+                    "version": saved.version,
+                    "justification": correction.justification,
+                    "voided": correction.voided,
+                }),
+            )
+            .await
+            .map_err(storage_error)?;
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CorrectionResponse {
+            server_analysis_id: saved.id,
+            supersedes_analysis_id: original.id,
+            version: saved.version,
+            server_lifecycle_status: saved.server_lifecycle_status,
+            received_at: saved.received_at,
+        }),
+    ))
+}
+
+pub async fn analysis_detail(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Path(server_analysis_id): Path<Uuid>,
+) -> Result<Json<AnalysisDetailResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let analysis = store
+        .find_by_id(server_analysis_id)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error":"analysis not found"})),
+            )
+        })?;
+    let superseded_by_analysis_id = store
+        .find_successor(analysis.id)
+        .await
+        .map_err(storage_error)?;
+    let verification = store
+        .find_verification(analysis.id)
+        .await
+        .map_err(storage_error)?;
+    let versions = store
+        .list_version_chain(analysis.id)
+        .await
+        .map_err(storage_error)?
+        .into_iter()
+        .map(|version| AnalysisVersionSummary {
+            server_analysis_id: version.id,
+            version: version.version,
+            sample_id: version.sample_id,
+            operator_id: version.operator_id,
+            bath_id: version.bath_id,
+            server_lifecycle_status: version.server_lifecycle_status,
+            received_at: version.received_at,
+            correction: version.correction,
+        })
+        .collect();
+    let signatures = store
+        .list_signatures(
+            Some(SignedRecordType::SecondaryReview),
+            Some(analysis.id.to_string()),
+        )
+        .await
+        .map_err(storage_error)?;
+    audit
+        .record(
+            &store,
+            AuditEventType::AnalysisViewed,
+            Some(analysis.id),
+            None,
+            json!({}),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    Ok(Json(AnalysisDetailResponse {
+        analysis,
+        superseded_by_analysis_id,
+        verification,
+        versions,
+        signatures,
+    }))
+}
+
+/// A re-control must follow its parent on the same bath, otherwise it cannot
+/// close the parent's deviation.
+async fn validate_parent(
+    store: &AnalysisStore,
+    analysis: &Analysis,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let Some(parent_id) = analysis.parent_analysis_id else {
+        return Ok(());
+    };
+    let unprocessable = |message: &str| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({"error":message})),
+        )
+    };
+
+    let parent = store
+        .find_by_id(parent_id)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| unprocessable("parent_analysis_id not found"))?;
+    if parent.bath_id != analysis.bath_id {
+        return Err(unprocessable("parent analysis belongs to another bath"));
+    }
+    if parent.captured_at > analysis.captured_at {
+        return Err(unprocessable("parent analysis was captured later"));
+    }
+    Ok(())
+}
+
+pub async fn analyses_history(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<HistoryResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let offset = query
+        .cursor
+        .as_deref()
+        .and_then(|cursor| cursor.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let (analyses, next_cursor) = store
+        .list_paginated(limit, offset, query.sample_id.as_deref())
+        .await
+        .map_err(storage_error)?;
+    audit
+        .record(
+            &store,
+            AuditEventType::AnalysisHistoryViewed,
+            None,
+            None,
+            json!({"sample_id": query.sample_id, "limit": limit, "offset": offset}),
+        )
+        .await
+        .map_err(storage_error)?;
+
     let items = analyses
         .into_iter()
         .map(|analysis| HistoryItem {
             server_analysis_id: analysis.id,
             sample_id: analysis.sample_id,
+            operator_id: analysis.operator_id,
+            bath_id: analysis.bath_id,
+            parent_analysis_id: analysis.parent_analysis_id,
             ppm_estime: analysis.ppm_estime,
             ppm_min: analysis.ppm_min,
             ppm_max: analysis.ppm_max,
@@ -187,232 +1137,4904 @@ pub async fn analyses_history(
             received_at: analysis.received_at,
             server_lifecycle_status: analysis.server_lifecycle_status,
         })
-        .collect();
+        .collect();
+
+    Ok(Json(HistoryResponse { items, next_cursor }))
+}
+
+pub async fn analysis_ack(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Path(server_analysis_id): Path<Uuid>,
+) -> Result<Json<AnalysisAckResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let analysis = store
+        .find_by_id(server_analysis_id)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error":"analysis not found"})),
+            )
+        })?;
+    let verification = store
+        .find_verification(analysis.id)
+        .await
+        .map_err(storage_error)?;
+    let reviewed = store
+        .find_secondary_review(analysis.id)
+        .await
+        .map_err(storage_error)?
+        .is_some();
+    audit
+        .record(
+            &store,
+            AuditEventType::AnalysisAckViewed,
+            Some(analysis.id),
+            None,
+            json!({}),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    let response = match verification {
+        Some(verification) => AnalysisAckResponse {
+            server_analysis_id: analysis.id,
+            server_lifecycle_status: analysis.server_lifecycle_status,
+            validated_schema: verification.validated_schema,
+            validated_business_rules: verification.validated_business_rules,
+            queued_for_secondary_review: verification.queued_for_secondary_review() && !reviewed,
+            failed_checks: verification.failed_checks,
+            review_reasons: verification.review_reasons,
+            server_ppm_estime: verification.server_ppm_estime,
+            server_decision: verification.server_decision,
+            verified_at: Some(verification.verified_at),
+            ack_at: Utc::now(),
+        },
+        None => AnalysisAckResponse {
+            server_analysis_id: analysis.id,
+            server_lifecycle_status: analysis.server_lifecycle_status,
+            validated_schema: false,
+            validated_business_rules: false,
+            queued_for_secondary_review: false,
+            failed_checks: Vec::new(),
+            review_reasons: Vec::new(),
+            server_ppm_estime: None,
+            server_decision: None,
+            verified_at: None,
+            ack_at: Utc::now(),
+        },
+    };
+
+    Ok(Json(response))
+}
+
+pub async fn list_deviations(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Query(query): Query<DeviationsQuery>,
+) -> Result<Json<DeviationsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let now = Utc::now();
+    let items = store
+        .list_deviations(query.status, query.bath_id.clone())
+        .await
+        .map_err(storage_error)?
+        .into_iter()
+        .map(|deviation| deviation_item(deviation, now))
+        .collect();
+    audit
+        .record(
+            &store,
+            AuditEventType::DeviationsViewed,
+            None,
+            None,
+            json!({"status": query.status, "bath_id": query.bath_id}),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    Ok(Json(DeviationsResponse { items }))
+}
+
+pub async fn deviation_detail(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Path(deviation_id): Path<Uuid>,
+) -> Result<Json<DeviationItem>, (StatusCode, Json<serde_json::Value>)> {
+    let deviation = store
+        .find_deviation(deviation_id)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error":"deviation not found"})),
+            )
+        })?;
+    audit
+        .record(
+            &store,
+            AuditEventType::DeviationsViewed,
+            Some(deviation.opening_analysis_id),
+            None,
+            json!({"deviation_id": deviation.id}),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    Ok(Json(deviation_item(deviation, Utc::now())))
+}
+
+fn deviation_item(deviation: Deviation, now: DateTime<Utc>) -> DeviationItem {
+    DeviationItem {
+        elapsed_minutes: deviation.elapsed(now).num_minutes(),
+        deviation_id: deviation.id,
+        bath_id: deviation.bath_id,
+        status: deviation.status,
+        opening_analysis_id: deviation.opening_analysis_id,
+        opening_compliance_status: deviation.opening_compliance_status,
+        opened_at: deviation.opened_at,
+        closing_analysis_id: deviation.closing_analysis_id,
+        closed_at: deviation.closed_at,
+    }
+}
+
+pub async fn create_corrective_action(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Json(payload): Json<CreateCorrectiveActionPayload>,
+) -> Result<(StatusCode, Json<CorrectiveAction>), (StatusCode, Json<serde_json::Value>)> {
+    let unprocessable = |message: &str| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({"error":message})),
+        )
+    };
+
+    if payload.description.trim().is_empty()
+        || payload.owner_id.trim().is_empty()
+        || payload.created_by.trim().is_empty()
+    {
+        return Err(unprocessable(
+            "description, owner_id and created_by are required",
+        ));
+    }
+
+    // Actions raised from a deviation are attached to its opening analysis.
+    let analysis_id = match payload.deviation_id {
+        Some(deviation_id) => {
+            let deviation = store
+                .find_deviation(deviation_id)
+                .await
+                .map_err(storage_error)?
+                .ok_or_else(|| unprocessable("deviation not found"))?;
+            if payload
+                .analysis_id
+                .is_some_and(|id| id != deviation.opening_analysis_id)
+            {
+                return Err(unprocessable(
+                    "analysis_id does not match the deviation's opening analysis",
+                ));
+            }
+            deviation.opening_analysis_id
+        }
+        None => payload
+            .analysis_id
+            .ok_or_else(|| unprocessable("analysis_id or deviation_id is required"))?,
+    };
+
+    let analysis = store
+        .find_by_id(analysis_id)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| unprocessable("analysis not found"))?;
+    let flagged_by_server = store
+        .find_verification(analysis.id)
+        .await
+        .map_err(storage_error)?
+        .is_some_and(|verification| {
+            verification
+                .review_reasons
+                .contains(&ReviewReason::NonCompliant)
+        });
+    if analysis.compliance_status == ComplianceStatus::ConformeProduction && !flagged_by_server {
+        return Err(unprocessable(
+            "corrective actions are attached to non-compliant analyses",
+        ));
+    }
+
+    if !store
+        .find_user(&payload.owner_id)
+        .await
+        .map_err(storage_error)?
+        .is_some_and(|owner| owner.active)
+    {
+        return Err(unprocessable("owner_id is not an active user"));
+    }
+
+    let action = CorrectiveAction {
+        id: Uuid::new_v4(),
+        analysis_id,
+        deviation_id: payload.deviation_id,
+        description: payload.description,
+        owner_id: payload.owner_id,
+        due_at: payload.due_at,
+        status: CorrectiveActionStatus::Open,
+        created_by: payload.created_by,
+        created_at: Utc::now(),
+        action_taken: None,
+        completed_by: None,
+        completed_at: None,
+        verified_by: None,
+        verification_comment: None,
+        verified_at: None,
+    };
+    store
+        .create_corrective_action(action.clone())
+        .await
+        .map_err(storage_error)?;
+    audit
+        .record(
+            &store,
+            AuditEventType::CorrectiveActionCreated,
+            Some(action.analysis_id),
+            Some(&action.created_by),
+            json!({
+                "corrective_action_id": action.id,
+                "deviation_id": action.deviation_id,
+                "owner_id": action.owner_id,
+                "due_at": action.due_at,
+            }),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    Ok((StatusCode::CREATED, Json(action)))
+}
+
+pub async fn list_corrective_actions(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Query(query): Query<CorrectiveActionsQuery>,
+) -> Result<Json<CorrectiveActionsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let now = Utc::now();
+    let items = store
+        .list_corrective_actions(query.status, query.analysis_id, query.deviation_id)
+        .await
+        .map_err(storage_error)?
+        .into_iter()
+        .filter(|action| {
+            query
+                .overdue
+                .is_none_or(|overdue| action.is_overdue(now) == overdue)
+        })
+        .collect();
+    audit
+        .record(
+            &store,
+            AuditEventType::CorrectiveActionsViewed,
+            query.analysis_id,
+            None,
+            json!({"status": query.status, "deviation_id": query.deviation_id}),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    Ok(Json(CorrectiveActionsResponse { items }))
+}
+
+pub async fn complete_corrective_action(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Path(action_id): Path<Uuid>,
+    Json(payload): Json<CompleteCorrectiveActionPayload>,
+) -> Result<Json<CorrectiveAction>, (StatusCode, Json<serde_json::Value>)> {
+    if payload.completed_by.trim().is_empty() || payload.action_taken.trim().is_empty() {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({"error":"completed_by and action_taken are required"})),
+        ));
+    }
+
+    let outcome = store
+        .complete_corrective_action(
+            action_id,
+            payload.completed_by,
+            payload.action_taken,
+            payload.completed_at.unwrap_or_else(Utc::now),
+        )
+        .await
+        .map_err(storage_error)?;
+    let action = corrective_action_response(outcome)?;
+    audit
+        .record(
+            &store,
+            AuditEventType::CorrectiveActionCompleted,
+            Some(action.analysis_id),
+            action.completed_by.as_deref(),
+            json!({"corrective_action_id": action.id, "action_taken": action.action_taken}),
+        )
+        .await
+        .map_err(storage_error)?;
+    Ok(action)
+}
+
+/// Closure needs an active quality user other than the one who did the work.
+pub async fn verify_corrective_action(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Path(action_id): Path<Uuid>,
+    Json(payload): Json<VerifyCorrectiveActionPayload>,
+) -> Result<Json<CorrectiveAction>, (StatusCode, Json<serde_json::Value>)> {
+    if payload.verified_by.trim().is_empty() || payload.verification_comment.trim().is_empty() {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({"error":"verified_by and verification_comment are required"})),
+        ));
+    }
+
+    let action = store
+        .find_corrective_action(action_id)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error":"corrective action not found"})),
+            )
+        })?;
+    let verifier = store
+        .find_user(&payload.verified_by)
+        .await
+        .map_err(storage_error)?;
+    if action.completed_by.as_deref() == Some(payload.verified_by.as_str())
+        || !verifier.is_some_and(|user| user.has_role(UserRole::Qualite))
+    {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({
+                "error":"verification requires a quality user other than the one who completed the action"
+            })),
+        ));
+    }
+
+    let outcome = store
+        .verify_corrective_action(
+            action_id,
+            payload.verified_by,
+            payload.verification_comment,
+            payload.verified_at.unwrap_or_else(Utc::now),
+        )
+        .await
+        .map_err(storage_error)?;
+    let action = corrective_action_response(outcome)?;
+    audit
+        .record(
+            &store,
+            AuditEventType::CorrectiveActionVerified,
+            Some(action.analysis_id),
+            action.verified_by.as_deref(),
+            json!({
+                "corrective_action_id": action.id,
+                "verification_comment": action.verification_comment,
+            }),
+        )
+        .await
+        .map_err(storage_error)?;
+    Ok(action)
+}
+
+fn corrective_action_response(
+    outcome: CorrectiveActionOutcome,
+) -> Result<Json<CorrectiveAction>, (StatusCode, Json<serde_json::Value>)> {
+    match outcome {
+        CorrectiveActionOutcome::Updated(action) => Ok(Json(*action)),
+        CorrectiveActionOutcome::NotFound => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({"error":"corrective action not found"})),
+        )),
+        CorrectiveActionOutcome::InvalidStatus(status) => Err((
+            StatusCode::CONFLICT,
+            Json(
+                json!({"error":"corrective action is not in the expected status","status":status}),
+            ),
+        )),
+    }
+}
+
+pub async fn create_production_event(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Json(payload): Json<ProductionEventPayload>,
+) -> Result<(StatusCode, Json<ProductionEvent>), (StatusCode, Json<serde_json::Value>)> {
+    if payload.bath_id.trim().is_empty()
+        || payload.recorded_by.trim().is_empty()
+        || payload.reason.trim().is_empty()
+    {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({"error":"bath_id, recorded_by and reason are required"})),
+        ));
+    }
+
+    let event = ProductionEvent {
+        id: Uuid::new_v4(),
+        bath_id: payload.bath_id,
+        kind: payload.kind,
+        recorded_by: payload.recorded_by,
+        occurred_at: payload.occurred_at,
+        reason: payload.reason,
+        linked_analysis_ids: payload.linked_analysis_ids,
+        recontrol_analysis_id: None,
+        recorded_at: Utc::now(),
+    };
+    let (bath_id, kind, recorded_by) =
+        (event.bath_id.clone(), event.kind, event.recorded_by.clone());
+
+    let outcome = store
+        .record_production_event(event)
+        .await
+        .map_err(storage_error)?;
+    let (event_type, details) = match &outcome {
+        ProductionEventOutcome::Recorded(event) => (
+            AuditEventType::ProductionEventRecorded,
+            json!({
+                "production_event_id": event.id,
+                "bath_id": bath_id,
+                "kind": kind,
+                "recontrol_analysis_id": event.recontrol_analysis_id,
+            }),
+        ),
+        refused => (
+            AuditEventType::ProductionEventRefused,
+            json!({"bath_id": bath_id, "kind": kind, "outcome": format!("{refused:?}")}),
+        ),
+    };
+    audit
+        .record(&store, event_type, None, Some(&recorded_by), details)
+        .await
+        .map_err(storage_error)?;
+
+    match outcome {
+        ProductionEventOutcome::Recorded(event) => Ok((StatusCode::CREATED, Json(*event))),
+        ProductionEventOutcome::UnknownAnalysis(analysis_id) => Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({"error":"linked analysis not found","analysis_id":analysis_id})),
+        )),
+        ProductionEventOutcome::OutOfOrder(last_occurred_at) => Err((
+            StatusCode::CONFLICT,
+            Json(json!({
+                "error":"event predates the last recorded event of this bath",
+                "last_occurred_at": last_occurred_at
+            })),
+        )),
+        ProductionEventOutcome::AlreadyStopped => Err((
+            StatusCode::CONFLICT,
+            Json(json!({"error":"production is already stopped"})),
+        )),
+        ProductionEventOutcome::NotStopped => Err((
+            StatusCode::CONFLICT,
+            Json(json!({"error":"production is not stopped"})),
+        )),
+        ProductionEventOutcome::NoCompliantRecontrol { stopped_at } => Err((
+            StatusCode::CONFLICT,
+            Json(json!({
+                "error":"restart requires a compliant re-control analysis after the stop",
+                "stopped_at": stopped_at
+            })),
+        )),
+    }
+}
+
+pub async fn list_production_events(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Query(query): Query<ProductionEventsQuery>,
+) -> Result<Json<ProductionEventsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let items = store
+        .list_production_events(query.bath_id.clone(), query.from, query.to)
+        .await
+        .map_err(storage_error)?;
+    audit
+        .record(
+            &store,
+            AuditEventType::ProductionEventsViewed,
+            None,
+            None,
+            json!({"bath_id": query.bath_id, "from": query.from, "to": query.to}),
+        )
+        .await
+        .map_err(storage_error)?;
+    Ok(Json(ProductionEventsResponse { items }))
+}
+
+pub async fn review_queue(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Query(query): Query<ReviewQueueQuery>,
+) -> Result<Json<ReviewQueueResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let now = Utc::now();
+    let received_before = query
+        .min_age_minutes
+        .map(|minutes| now - chrono::Duration::minutes(minutes));
+    let received_after = query
+        .max_age_minutes
+        .map(|minutes| now - chrono::Duration::minutes(minutes));
+    let include_claimed = query.include_claimed.unwrap_or(true);
+
+    let entries = store
+        .list_review_queue(received_after, received_before)
+        .await
+        .map_err(storage_error)?;
+
+    let items = entries
+        .into_iter()
+        .filter(|entry| {
+            query
+                .reason
+                .is_none_or(|reason| entry.verification.review_reasons.contains(&reason))
+        })
+        .map(|entry| ReviewQueueItem {
+            server_analysis_id: entry.analysis.id,
+            sample_id: entry.analysis.sample_id,
+            operator_id: entry.analysis.operator_id,
+            ppm_estime: entry.analysis.ppm_estime,
+            server_ppm_estime: entry.verification.server_ppm_estime,
+            compliance_status: entry.analysis.compliance_status,
+            analysis_result: entry.analysis.analysis_result,
+            confidence: entry.analysis.confidence,
+            server_lifecycle_status: entry.analysis.server_lifecycle_status,
+            review_reasons: entry.verification.review_reasons,
+            failed_checks: entry.verification.failed_checks,
+            captured_at: entry.analysis.captured_at,
+            received_at: entry.analysis.received_at,
+            age_minutes: (now - entry.analysis.received_at).num_minutes(),
+            // Expired claims are shown as unassigned.
+            claim: entry.claim.filter(|claim| claim.is_active(now)),
+        })
+        .filter(|item| include_claimed || item.claim.is_none())
+        .collect();
+    audit
+        .record(
+            &store,
+            AuditEventType::ReviewQueueViewed,
+            None,
+            None,
+            json!({"reason": query.reason, "include_claimed": include_claimed}),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    Ok(Json(ReviewQueueResponse { items }))
+}
+
+pub async fn claim_review(
+    State(state): State<AppState>,
+    Extension(audit): Extension<AuditContext>,
+    Path(server_analysis_id): Path<Uuid>,
+    Json(payload): Json<ClaimPayload>,
+) -> Result<Json<ReviewClaim>, (StatusCode, Json<serde_json::Value>)> {
+    if payload.reviewer_id.trim().is_empty() {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({"error":"reviewer_id is required"})),
+        ));
+    }
+    ensure_independent_reviewer(
+        &state.store,
+        &audit,
+        server_analysis_id,
+        &payload.reviewer_id,
+    )
+    .await?;
+
+    let expires_at =
+        Utc::now() + chrono::Duration::minutes(state.config.review_claim_timeout_minutes);
+    match state
+        .store
+        .claim_review(server_analysis_id, &payload.reviewer_id, expires_at)
+        .await
+        .map_err(storage_error)?
+    {
+        ClaimOutcome::Claimed(claim) => {
+            audit
+                .record(
+                    &state.store,
+                    AuditEventType::ReviewClaimed,
+                    Some(claim.analysis_id),
+                    Some(&claim.reviewer_id),
+                    json!({"expires_at": claim.expires_at}),
+                )
+                .await
+                .map_err(storage_error)?;
+            Ok(Json(claim))
+        }
+        ClaimOutcome::HeldBy(claim) => Err((
+            StatusCode::CONFLICT,
+            Json(json!({"error":"analysis already claimed","claim":claim})),
+        )),
+        ClaimOutcome::NotQueued => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({"error":"analysis is not awaiting secondary review"})),
+        )),
+    }
+}
+
+pub async fn release_review_claim(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Path(server_analysis_id): Path<Uuid>,
+    Json(payload): Json<ClaimPayload>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    let released = store
+        .release_claim(server_analysis_id, &payload.reviewer_id)
+        .await
+        .map_err(storage_error)?;
+    if released {
+        audit
+            .record(
+                &store,
+                AuditEventType::ReviewClaimReleased,
+                Some(server_analysis_id),
+                Some(&payload.reviewer_id),
+                json!({}),
+            )
+            .await
+            .map_err(storage_error)?;
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({"error":"no claim held by this reviewer"})),
+        ))
+    }
+}
+
+pub async fn create_secondary_review(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Path(server_analysis_id): Path<Uuid>,
+    Json(payload): Json<SecondaryReviewPayload>,
+) -> Result<Json<SecondaryReviewResponse>, (StatusCode, Json<serde_json::Value>)> {
+    if payload.reviewer_id.trim().is_empty() {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({"error":"reviewer_id is required"})),
+        ));
+    }
+    ensure_independent_reviewer(&store, &audit, server_analysis_id, &payload.reviewer_id).await?;
+    let record_id = server_analysis_id.to_string();
+    let signer = reauthenticate_signer(
+        &store,
+        &audit,
+        &payload.reviewer_id,
+        &payload.signature.password,
+        SignedRecordType::SecondaryReview,
+        &record_id,
+    )
+    .await?;
+    let record_hash = store
+        .analysis_record_hash(server_analysis_id)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error":"analysis not found"})),
+            )
+        })?;
+    let signature = signatures::sign(
+        SignedRecordType::SecondaryReview,
+        record_id,
+        record_hash,
+        &signer,
+        payload.signature.meaning,
+        Utc::now(),
+    );
+
+    let review = SecondaryReview {
+        id: Uuid::new_v4(),
+        analysis_id: server_analysis_id,
+        reviewer_id: payload.reviewer_id,
+        decision: payload.decision,
+        comment: payload.comment,
+        reviewed_at: payload.reviewed_at,
+        recorded_at: Utc::now(),
+    };
+    let decision = review.decision;
+    let reviewer_id = review.reviewer_id.clone();
+
+    match store
+        .record_secondary_review(review, signature.clone())
+        .await
+        .map_err(storage_error)?
+    {
+        ReviewOutcome::Recorded(status) => {
+            audit
+                .record(
+                    &store,
+                    AuditEventType::AnalysisSecondaryReviewCompleted,
+                    Some(server_analysis_id),
+                    Some(&reviewer_id),
+                    json!({"decision": decision}),
+                )
+                .await
+                .map_err(storage_error)?;
+            record_signature_applied(&store, &audit, &signature).await?;
+            Ok(Json(SecondaryReviewResponse {
+                server_analysis_id,
+                server_lifecycle_status: status,
+                secondary_review_status: "completed".to_string(),
+                decision,
+                signature,
+            }))
+        }
+        ReviewOutcome::NotFound => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({"error":"analysis not found"})),
+        )),
+        ReviewOutcome::NotReviewable(status) => Err((
+            StatusCode::CONFLICT,
+            Json(json!({
+                "error":"analysis has not been verified by the server yet",
+                "server_lifecycle_status": status
+            })),
+        )),
+        ReviewOutcome::AlreadyReviewed => Err((
+            StatusCode::CONFLICT,
+            Json(json!({"error":"secondary review already recorded"})),
+        )),
+        ReviewOutcome::ClaimedByOther(claim) => Err((
+            StatusCode::CONFLICT,
+            Json(json!({"error":"analysis claimed by another reviewer","claim":claim})),
+        )),
+    }
+}
+
+/// Re-authenticates `signer_id` with `password` right before a signature is
+/// applied; any failure is traced and answered with a uniform 401.
+async fn reauthenticate_signer(
+    store: &AnalysisStore,
+    audit: &AuditContext,
+    signer_id: &str,
+    password: &str,
+    record_type: SignedRecordType,
+    record_id: &str,
+) -> Result<User, (StatusCode, Json<serde_json::Value>)> {
+    let user = store
+        .find_user(signer_id)
+        .await
+        .map_err(storage_error)?
+        .filter(|user| user.active);
+    let password_hash = store
+        .find_password_hash(signer_id)
+        .await
+        .map_err(storage_error)?;
+
+    let authenticated = match (&user, password_hash) {
+        (Some(_), Some(password_hash)) => {
+            let password = password.to_string();
+            tokio::task::spawn_blocking(move || {
+                signatures::verify_password(&password_hash, &password)
+            })
+            .await
+            .unwrap_or(false)
+        }
+        _ => false,
+    };
+    match user {
+        Some(user) if authenticated => Ok(user),
+        _ => {
+            audit
+                .record(
+                    store,
+                    AuditEventType::ElectronicSignatureRefused,
+                    None,
+                    Some(signer_id),
+                    json!({
+                        "record_type": record_type,
+                        "record_id": record_id,
+                        "reason": "reauthentication_failed",
+                    }),
+                )
+                .await
+                .map_err(storage_error)?;
+            Err((
+                StatusCode::UNAUTHORIZED,
+                Json(json!({"error":"re-authentication failed"})),
+            ))
+        }
+    }
+}
+
+async fn record_signature_applied(
+    store: &AnalysisStore,
+    audit: &AuditContext,
+    signature: &ElectronicSignature,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let analysis_id = match signature.record_type {
+        SignedRecordType::SecondaryReview => signature.record_id.parse().ok(),
+        _ => None,
+    };
+    audit
+        .record(
+            store,
+            AuditEventType::ElectronicSignatureApplied,
+            analysis_id,
+            Some(&signature.signer_id),
+            json!({
+                "signature_id": signature.id,
+                "record_type": signature.record_type,
+                "record_id": signature.record_id,
+                "record_hash": signature.record_hash,
+                "meaning": signature.meaning,
+                "signature_hash": signature.signature_hash,
+            }),
+        )
+        .await
+        .map_err(storage_error)
+}
+
+/// Signs the calibration or rules currently in force. Secondary reviews are
+/// signed through their own endpoint.
+pub async fn create_signature(
+    State(state): State<AppState>,
+    Extension(audit): Extension<AuditContext>,
+    Json(payload): Json<SignaturePayload>,
+) -> Result<(StatusCode, Json<ElectronicSignature>), (StatusCode, Json<serde_json::Value>)> {
+    let (record_id, record_hash) = match payload.record_type {
+        SignedRecordType::CalibrationActivation => {
+            let calibration = state.verifier.calibration();
+            (
+                calibration.calibration_version.clone(),
+                calibration.source_sha256.clone(),
+            )
+        }
+        SignedRecordType::RulesChange => (
+            rules::ANALYSIS_RULES_VERSION.to_string(),
+            rules::rules_digest(),
+        ),
+        SignedRecordType::SecondaryReview => {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(json!({
+                    "error": "secondary reviews are signed through /v1/analyses/{id}/secondary-review"
+                })),
+            ));
+        }
+    };
+
+    let signer = reauthenticate_signer(
+        &state.store,
+        &audit,
+        &payload.signer_id,
+        &payload.credentials.password,
+        payload.record_type,
+        &record_id,
+    )
+    .await?;
+    if !signer.has_role(UserRole::Qualite) {
+        audit
+            .record(
+                &state.store,
+                AuditEventType::ElectronicSignatureRefused,
+                None,
+                Some(&signer.user_id),
+                json!({
+                    "record_type": payload.record_type,
+                    "record_id": record_id,
+                    "reason": "signer_lacks_quality_role",
+                }),
+            )
+            .await
+            .map_err(storage_error)?;
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({
+                "error": "only quality may sign calibration and rules",
+                "reason": "signer_lacks_quality_role",
+            })),
+        ));
+    }
+
+    let signature = signatures::sign(
+        payload.record_type,
+        record_id,
+        record_hash,
+        &signer,
+        payload.credentials.meaning,
+        Utc::now(),
+    );
+    state
+        .store
+        .record_signature(signature.clone())
+        .await
+        .map_err(storage_error)?;
+    record_signature_applied(&state.store, &audit, &signature).await?;
+    Ok((StatusCode::CREATED, Json(signature)))
+}
+
+pub async fn list_signatures(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Query(query): Query<SignaturesQuery>,
+) -> Result<Json<SignaturesResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let items = store
+        .list_signatures(query.record_type, query.record_id.clone())
+        .await
+        .map_err(storage_error)?;
+    audit
+        .record(
+            &store,
+            AuditEventType::SignaturesViewed,
+            None,
+            None,
+            json!({"record_type": query.record_type, "record_id": query.record_id}),
+        )
+        .await
+        .map_err(storage_error)?;
+    Ok(Json(SignaturesResponse { items }))
+}
+
+/// Four-eyes rule: the second reading must come from an active quality user
+/// who neither performed nor corrected the scan, in any of its versions.
+/// Every refusal is kept as an audit event.
+async fn ensure_independent_reviewer(
+    store: &AnalysisStore,
+    audit: &AuditContext,
+    analysis_id: Uuid,
+    reviewer_id: &str,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let versions = store
+        .list_version_chain(analysis_id)
+        .await
+        .map_err(storage_error)?;
+    let analysis = versions
+        .iter()
+        .find(|version| version.id == analysis_id)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error":"analysis not found"})),
+            )
+        })?;
+    let reviewer = store.find_user(reviewer_id).await.map_err(storage_error)?;
+
+    // A correction may reassign the operator: every version of the chain counts.
+    let refusal = if analysis.operator_id.trim().is_empty() {
+        Some("operator_unknown")
+    } else if versions
+        .iter()
+        .any(|version| version.operator_id == reviewer_id)
+    {
+        Some("reviewer_is_operator")
+    } else if versions.iter().any(|version| {
+        version
+            .correction
+            .as_ref()
+            .is_some_and(|correction| correction.corrected_by == reviewer_id)
+    }) {
+        Some("reviewer_is_corrector")
+    } else if !reviewer.is_some_and(|user| user.has_role(UserRole::Qualite)) {
+        Some("reviewer_lacks_quality_role")
+    } else {
+        None
+    };
+    let Some(refusal) = refusal else {
+        return Ok(());
+    };
+
+    audit
+        .record(
+            store,
+            AuditEventType::AnalysisSecondaryReviewRefused,
+            Some(analysis.id),
+            Some(reviewer_id),
+            json!({
+                "reason": refusal,
+                "operator_id": analysis.operator_id,
+            }),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    Err((
+        StatusCode::FORBIDDEN,
+        Json(json!({"error":"secondary review refused","reason":refusal})),
+    ))
+}
+
+pub async fn admin_users(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+) -> Result<Json<UsersResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let items = store.list_users().await.map_err(storage_error)?;
+    audit
+        .record(&store, AuditEventType::UsersViewed, None, None, json!({}))
+        .await
+        .map_err(storage_error)?;
+    Ok(Json(UsersResponse { items }))
+}
+
+pub async fn upsert_user(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Path(user_id): Path<String>,
+    Json(payload): Json<UserPayload>,
+) -> Result<Json<User>, (StatusCode, Json<serde_json::Value>)> {
+    if user_id.trim().is_empty() || payload.display_name.trim().is_empty() {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({"error":"user_id and display_name are required"})),
+        ));
+    }
+    let password_hash = match payload.password {
+        Some(password) if password.chars().count() < signatures::MIN_PASSWORD_LENGTH => {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(json!({
+                    "error": format!(
+                        "password must be at least {} characters",
+                        signatures::MIN_PASSWORD_LENGTH
+                    )
+                })),
+            ));
+        }
+        Some(password) => Some(
+            tokio::task::spawn_blocking(move || signatures::hash_password(&password))
+                .await
+                .map_err(|err| err.to_string())
+                .and_then(|hashed| hashed)
+                .map_err(|err| {
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({ "error": err })),
+                    )
+                })?,
+        ),
+        None => None,
+    };
+
+    let user = User {
+        user_id,
+        display_name: payload.display_name,
+        roles: payload.roles,
+        active: payload.active.unwrap_or(true),
+        updated_at: Utc::now(),
+    };
+    store
+        .upsert_user(user.clone())
+        .await
+        .map_err(storage_error)?;
+    let password_changed = password_hash.is_some();
+    if let Some(password_hash) = password_hash {
+        store
+            .set_user_password(user.user_id.clone(), password_hash)
+            .await
+            .map_err(storage_error)?;
+    }
+    audit
+        .record(
+            &store,
+            AuditEventType::UserUpserted,
+            None,
+            None,
+            json!({
+                "user_id": user.user_id,
+                "roles": user.roles,
+                "active": user.active,
+                "password_changed": password_changed,
+            }),
+        )
+        .await
+        .map_err(storage_error)?;
+    Ok(Json(user))
+}
+
+pub async fn admin_jobs(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Query(query): Query<JobsQuery>,
+) -> Result<Json<JobsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    let items = store
+        .list_jobs(query.status, query.kind, limit)
+        .await
+        .map_err(storage_error)?;
+    let counts = store
+        .count_jobs_by_status()
+        .await
+        .map_err(storage_error)?
+        .into_iter()
+        .collect();
+    audit
+        .record(
+            &store,
+            AuditEventType::JobsViewed,
+            None,
+            None,
+            json!({"status": query.status, "kind": query.kind}),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    Ok(Json(JobsResponse { counts, items }))
+}
+
+/// Compliance statistics per day, ISO week or month, as JSON, CSV or PDF.
+/// Without `from`, covers the last 30 days, 12 weeks or 12 months.
+pub async fn compliance_report(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Query(query): Query<ComplianceReportQuery>,
+) -> Result<axum::response::Response, (StatusCode, Json<serde_json::Value>)> {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or_else(|| {
+        to - chrono::Duration::days(match query.period {
+            ReportPeriod::Day => 30,
+            ReportPeriod::Week => 7 * 12,
+            ReportPeriod::Month => 365,
+        })
+    });
+    if from > to {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({"error":"from must not be after to"})),
+        ));
+    }
+    if query.period.starts(from, to).len() > MAX_REPORT_PERIODS {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({
+                "error": "too many periods, use a larger period or a shorter range",
+                "max_periods": MAX_REPORT_PERIODS,
+            })),
+        ));
+    }
+
+    let observations = store
+        .list_compliance_observations(from, to, query.bath_id.clone())
+        .await
+        .map_err(storage_error)?;
+    let deviations = store
+        .list_deviations(None, query.bath_id.clone())
+        .await
+        .map_err(storage_error)?;
+    let report = statistics::compliance_report(
+        query.period,
+        from,
+        to,
+        query.bath_id,
+        &observations,
+        &deviations,
+    );
+    audit
+        .record(
+            &store,
+            AuditEventType::ComplianceReportViewed,
+            None,
+            None,
+            json!({
+                "period": report.period,
+                "from": report.from,
+                "to": report.to,
+                "bath_id": report.bath_id,
+                "format": query.format,
+                "csv_dialect": query.csv_dialect,
+            }),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    let (content_type, file_name, body) = match query.format {
+        ReportFormat::Json => return Ok(Json(report).into_response()),
+        ReportFormat::Csv => (
+            "text/csv; charset=utf-8",
+            "rapport-conformite.csv",
+            statistics::report_csv(&report, query.csv_dialect).map_err(export_error)?,
+        ),
+        ReportFormat::Pdf => (
+            "application/pdf",
+            "rapport-conformite.pdf",
+            statistics::report_pdf(&report),
+        ),
+    };
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename={file_name}"),
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Individuals / moving range chart of the validated checks of a bath.
+pub async fn spc_chart(
+    State(state): State<AppState>,
+    Extension(audit): Extension<AuditContext>,
+    Path(bath_id): Path<String>,
+    Query(query): Query<SpcChartQuery>,
+) -> Result<Json<ControlChart>, (StatusCode, Json<serde_json::Value>)> {
+    let limit = query
+        .limit
+        .unwrap_or(state.config.spc_window_points)
+        .clamp(1, MAX_SPC_CHART_POINTS);
+    let points = state
+        .store
+        .list_spc_points(bath_id.clone(), limit)
+        .await
+        .map_err(storage_error)?;
+    let baseline = state
+        .store
+        .find_spc_baseline(bath_id.clone())
+        .await
+        .map_err(storage_error)?;
+    let chart = spc::control_chart(
+        bath_id,
+        baseline
+            .as_ref()
+            .map_or(state.config.spc_baseline_points.max(2), |baseline| {
+                baseline.baseline_points
+            }),
+        baseline.map(|baseline| baseline.limits),
+        &points,
+    );
+    audit
+        .record(
+            &state.store,
+            AuditEventType::SpcChartViewed,
+            None,
+            None,
+            json!({"bath_id": chart.bath_id, "points": chart.points.len()}),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    Ok(Json(chart))
+}
+
+pub async fn list_spc_signals(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Query(query): Query<SpcSignalsQuery>,
+) -> Result<Json<SpcSignalsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let items = store
+        .list_spc_signals(SpcSignalFilter {
+            bath_id: query.bath_id.clone(),
+            rule: query.rule,
+            from: query.from,
+            to: query.to,
+        })
+        .await
+        .map_err(storage_error)?;
+    audit
+        .record(
+            &store,
+            AuditEventType::SpcSignalsViewed,
+            None,
+            None,
+            json!({
+                "bath_id": query.bath_id,
+                "rule": query.rule,
+                "from": query.from,
+                "to": query.to,
+            }),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    Ok(Json(SpcSignalsResponse { items }))
+}
+
+/// When the bath is expected to fall below the low threshold.
+pub async fn threshold_forecast(
+    State(state): State<AppState>,
+    Extension(audit): Extension<AuditContext>,
+    Path(bath_id): Path<String>,
+    Query(query): Query<ThresholdForecastQuery>,
+) -> Result<Json<ThresholdForecast>, (StatusCode, Json<serde_json::Value>)> {
+    let points = state
+        .store
+        .list_spc_points(bath_id.clone(), state.config.forecast_window_points)
+        .await
+        .map_err(storage_error)?;
+    let check_interval = control_plan::check_interval(
+        &state.store,
+        &bath_id,
+        chrono::Duration::hours(state.config.check_interval_hours),
+    )
+    .await
+    .map_err(storage_error)?;
+    let forecast = forecast::threshold_forecast(
+        bath_id,
+        &points,
+        state.config.forecast_dosing_jump_ppm,
+        check_interval,
+        query.next_check_at,
+    );
+    audit
+        .record(
+            &state.store,
+            AuditEventType::ThresholdForecastViewed,
+            None,
+            None,
+            json!({
+                "bath_id": forecast.bath_id,
+                "status": forecast.status,
+                "predicted_crossing_at": forecast.predicted_crossing_at,
+                "alert": forecast.alert,
+            }),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    Ok(Json(forecast))
+}
+
+/// Records a new version of the control plan of a bath, in force from now.
+pub async fn update_control_plan(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Path(bath_id): Path<String>,
+    Json(payload): Json<ControlPlanPayload>,
+) -> Result<Json<ControlPlan>, (StatusCode, Json<serde_json::Value>)> {
+    if bath_id.trim().is_empty() || payload.updated_by.trim().is_empty() {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({"error":"bath_id and updated_by are required"})),
+        ));
+    }
+    if !(MIN_CONTROL_INTERVAL_MINUTES..=MAX_CONTROL_INTERVAL_MINUTES)
+        .contains(&payload.interval_minutes)
+    {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({
+                "error": "interval_minutes is out of range",
+                "min": MIN_CONTROL_INTERVAL_MINUTES,
+                "max": MAX_CONTROL_INTERVAL_MINUTES,
+            })),
+        ));
+    }
+    let authorised = store
+        .find_user(&payload.updated_by)
+        .await
+        .map_err(storage_error)?
+        .is_some_and(|user| {
+            user.has_role(UserRole::Qualite) || user.has_role(UserRole::Administrateur)
+        });
+    if !authorised {
+        audit
+            .record(
+                &store,
+                AuditEventType::ControlPlanRefused,
+                None,
+                Some(&payload.updated_by),
+                json!({"bath_id": bath_id}),
+            )
+            .await
+            .map_err(storage_error)?;
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({"error":"control plans require a quality or administrator user"})),
+        ));
+    }
+
+    let plan = ControlPlan {
+        id: Uuid::new_v4(),
+        bath_id,
+        interval_minutes: payload.interval_minutes,
+        grace_minutes: payload.grace_minutes,
+        active: payload.active.unwrap_or(true),
+        effective_from: Utc::now(),
+        updated_by: payload.updated_by,
+    };
+    store
+        .insert_control_plan(plan.clone())
+        .await
+        .map_err(storage_error)?;
+    audit
+        .record(
+            &store,
+            AuditEventType::ControlPlanUpdated,
+            None,
+            Some(&plan.updated_by),
+            json!({
+                "plan_id": plan.id,
+                "bath_id": plan.bath_id,
+                "interval_minutes": plan.interval_minutes,
+                "grace_minutes": plan.grace_minutes,
+                "active": plan.active,
+            }),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    Ok(Json(plan))
+}
+
+/// Every version of the control plans; the latest of each bath is in force.
+pub async fn list_control_plans(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Query(query): Query<ControlPlansQuery>,
+) -> Result<Json<ControlPlansResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let items = store
+        .list_control_plans(query.bath_id.clone())
+        .await
+        .map_err(storage_error)?;
+    audit
+        .record(
+            &store,
+            AuditEventType::ControlPlansViewed,
+            None,
+            None,
+            json!({"bath_id": query.bath_id}),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    Ok(Json(ControlPlansResponse { items }))
+}
+
+pub async fn list_control_gaps(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Query(query): Query<ControlGapsQuery>,
+) -> Result<axum::response::Response, (StatusCode, Json<serde_json::Value>)> {
+    let evaluated_at = Utc::now();
+    let items = control_plan::list_gaps(
+        &store,
+        query.bath_id.clone(),
+        query.from,
+        query.to,
+        evaluated_at,
+    )
+    .await
+    .map_err(storage_error)?;
+    audit
+        .record(
+            &store,
+            AuditEventType::ControlGapsViewed,
+            None,
+            None,
+            json!({
+                "bath_id": query.bath_id,
+                "from": query.from,
+                "to": query.to,
+                "format": query.format,
+            }),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    match query.format {
+        ControlGapsFormat::Json => Ok(Json(ControlGapsResponse {
+            evaluated_at,
+            items,
+        })
+        .into_response()),
+        ControlGapsFormat::Csv => Ok((
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    "attachment; filename=controles-manquants.csv".to_string(),
+                ),
+            ],
+            control_plan::gaps_csv(&items).map_err(export_error)?,
+        )
+            .into_response()),
+    }
+}
+
+/// Handover reports generated at the end of each shift, latest first.
+pub async fn list_shift_reports(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Query(query): Query<ShiftReportsQuery>,
+) -> Result<Json<ShiftReportsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let reports = store
+        .list_shift_reports(query.from, query.to)
+        .await
+        .map_err(storage_error)?;
+    audit
+        .record(
+            &store,
+            AuditEventType::ShiftReportsViewed,
+            None,
+            None,
+            json!({"from": query.from, "to": query.to}),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    Ok(Json(ShiftReportsResponse {
+        items: reports.into_iter().map(ShiftReportSummary::from).collect(),
+    }))
+}
+
+pub async fn get_shift_report(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ShiftReportQuery>,
+) -> Result<axum::response::Response, (StatusCode, Json<serde_json::Value>)> {
+    let report = store
+        .find_shift_report(id)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error":"shift report not found"})),
+            )
+        })?;
+    audit
+        .record(
+            &store,
+            AuditEventType::ShiftReportViewed,
+            None,
+            None,
+            json!({"shift_report_id": id, "format": query.format}),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    match query.format {
+        ShiftReportFormat::Json => Ok(Json(report).into_response()),
+        ShiftReportFormat::Pdf => Ok((
+            [
+                (header::CONTENT_TYPE, "application/pdf".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!(
+                        "attachment; filename=passation-poste-{}.pdf",
+                        reporting::paris_time(report.shift_start).format("%Y%m%d-%Hh")
+                    ),
+                ),
+            ],
+            shift_report::shift_report_pdf(&report),
+        )
+            .into_response()),
+    }
+}
+
+/// Queues the register; a `generate_audit_export` job writes the file,
+/// signs its manifest and moves the exported analyses to `exporte_audit`.
+pub async fn export_audit_register(
+    State(state): State<AppState>,
+    Extension(audit): Extension<AuditContext>,
+    Json(payload): Json<AuditExportPayload>,
+) -> Result<(StatusCode, Json<AuditExportResponse>), (StatusCode, Json<serde_json::Value>)> {
+    let export = AuditExport {
+        id: Uuid::new_v4(),
+        format: payload.format,
+        csv_dialect: payload.csv_dialect,
+        from: payload.from,
+        to: payload.to,
+        status: AuditExportStatus::Queued,
+        requested_by: audit.actor.clone(),
+        requested_at: Utc::now(),
+        completed_at: None,
+        expires_at: None,
+        record_count: None,
+        file_sha256: None,
+        error: None,
+    };
+    state
+        .store
+        .insert_audit_export(export.clone())
+        .await
+        .map_err(storage_error)?;
+    state
+        .store
+        .enqueue_job(
+            JobKind::GenerateAuditExport,
+            export.id,
+            state.config.job_max_attempts,
+        )
+        .await
+        .map_err(storage_error)?;
+    audit
+        .record(
+            &state.store,
+            AuditEventType::AuditExportRequested,
+            None,
+            None,
+            json!({
+                "export_id": export.id,
+                "format": export.format,
+                "csv_dialect": export.csv_dialect,
+                "from": export.from,
+                "to": export.to,
+            }),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(AuditExportResponse::from(export)),
+    ))
+}
+
+pub async fn audit_export_status(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Path(export_id): Path<Uuid>,
+) -> Result<Json<AuditExportResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let export = find_audit_export(&store, export_id).await?;
+    audit
+        .record(
+            &store,
+            AuditEventType::AuditExportViewed,
+            None,
+            None,
+            json!({"export_id": export_id, "status": export.status}),
+        )
+        .await
+        .map_err(storage_error)?;
+    Ok(Json(AuditExportResponse::from(export)))
+}
+
+/// Serves the generated file with its manifest id, signature and chain heads
+/// in the response headers. `409` until the export is ready, `410` once it
+/// has expired.
+pub async fn download_audit_export(
+    State(state): State<AppState>,
+    Extension(audit): Extension<AuditContext>,
+    Path(export_id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let store = &state.store;
+    let export = find_audit_export(store, export_id).await?;
+    match export.status {
+        AuditExportStatus::Ready => {}
+        AuditExportStatus::Expired => {
+            return Err((
+                StatusCode::GONE,
+                Json(json!({"error":"export expired", "expires_at": export.expires_at})),
+            ))
+        }
+        status => {
+            return Err((
+                StatusCode::CONFLICT,
+                Json(json!({"error":"export not ready", "status": status, "detail": export.error})),
+            ))
+        }
+    }
+    let signed = store
+        .find_export_manifest(export_id)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| export_error("ready export without manifest".to_string()))?;
+    let path = std::path::Path::new(&state.config.export_dir).join(export.stored_file_name());
+    // Streamed from disk: a register covering years does not have to fit in
+    // memory.
+    let file = tokio::fs::File::open(&path)
+        .await
+        .map_err(|err| export_error(format!("{}: {err}", path.display())))?;
+    let timestamp = store
+        .list_timestamps(
+            Some(TimestampSubject::ExportManifest),
+            Some(export_id.to_string()),
+        )
+        .await
+        .map_err(storage_error)?
+        .into_iter()
+        .next();
+    audit
+        .record(
+            store,
+            AuditEventType::AuditExportDownloaded,
+            None,
+            None,
+            json!({"export_id": export_id, "file_sha256": signed.manifest.file_sha256}),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    let manifest = &signed.manifest;
+    let timestamp_header: Vec<_> = timestamp
+        .map(|timestamp| {
+            (
+                HeaderName::from_static(EXPORT_TIMESTAMP_ID_HEADER),
+                hex_header_value(&timestamp.id.to_string()),
+            )
+        })
+        .into_iter()
+        .collect();
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(export.format.content_type()),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                HeaderValue::from_str(&format!(
+                    "attachment; filename={}",
+                    export.format.file_name()
+                ))
+                .expect("ascii export file name"),
+            ),
+            (
+                HeaderName::from_static(EXPORT_ID_HEADER),
+                hex_header_value(&manifest.export_id.to_string()),
+            ),
+            (
+                HeaderName::from_static(EXPORT_SIGNATURE_HEADER),
+                hex_header_value(&signed.signature),
+            ),
+            (
+                HeaderName::from_static(CHAIN_HEAD_ANALYSES_HEADER),
+                hex_header_value(&manifest.chain_head_analyses),
+            ),
+            (
+                HeaderName::from_static(CHAIN_HEAD_AUDIT_EVENTS_HEADER),
+                hex_header_value(&manifest.chain_head_audit_events),
+            ),
+        ],
+        AppendHeaders(timestamp_header),
+        Body::from_stream(ReaderStream::new(file)),
+    ))
+}
+
+async fn find_audit_export(
+    store: &AnalysisStore,
+    export_id: Uuid,
+) -> Result<AuditExport, (StatusCode, Json<serde_json::Value>)> {
+    store
+        .find_audit_export(export_id)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error":"export not found"})),
+            )
+        })
+}
+
+fn export_error(err: String) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({"error": err})),
+    )
+}
+
+pub async fn export_manifest(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Path(export_id): Path<Uuid>,
+) -> Result<Json<SignedExportManifest>, (StatusCode, Json<serde_json::Value>)> {
+    let signed = store
+        .find_export_manifest(export_id)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error":"export not found"})),
+            )
+        })?;
+    audit
+        .record(
+            &store,
+            AuditEventType::ExportManifestViewed,
+            None,
+            None,
+            json!({"export_id": export_id}),
+        )
+        .await
+        .map_err(storage_error)?;
+    Ok(Json(signed))
+}
+
+pub async fn verify_integrity(
+    State(state): State<AppState>,
+    Extension(audit): Extension<AuditContext>,
+) -> Result<Json<IntegrityReport>, (StatusCode, Json<serde_json::Value>)> {
+    let tsa_certificate = state.tsa.as_ref().map(|tsa| tsa.certificate().clone());
+    let report = state
+        .store
+        .verify_chains(tsa_certificate)
+        .await
+        .map_err(storage_error)?;
+    audit
+        .record(
+            &state.store,
+            AuditEventType::IntegrityVerified,
+            None,
+            None,
+            json!({
+                "intact": report.intact,
+                "analyses": report.analyses,
+                "audit_events": report.audit_events,
+                "timestamps": report.timestamps,
+            }),
+        )
+        .await
+        .map_err(storage_error)?;
+    Ok(Json(report))
+}
+
+pub async fn list_timestamps(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Query(query): Query<TimestampsQuery>,
+) -> Result<Json<TimestampsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let items = store
+        .list_timestamps(query.subject_type, query.subject_id.clone())
+        .await
+        .map_err(storage_error)?;
+    audit
+        .record(
+            &store,
+            AuditEventType::TimestampsViewed,
+            None,
+            None,
+            json!({"subject_type": query.subject_type, "subject_id": query.subject_id}),
+        )
+        .await
+        .map_err(storage_error)?;
+    Ok(Json(TimestampsResponse { items }))
+}
+
+/// DER token as returned by the TSA, for `openssl ts -verify -token_in`.
+pub async fn timestamp_token(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Path(timestamp_id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let timestamp = store
+        .find_timestamp(timestamp_id)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error":"timestamp not found"})),
+            )
+        })?;
+    audit
+        .record(
+            &store,
+            AuditEventType::TimestampsViewed,
+            None,
+            None,
+            json!({"timestamp_id": timestamp_id}),
+        )
+        .await
+        .map_err(storage_error)?;
+    Ok((
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/octet-stream"),
+        )],
+        timestamp.token,
+    ))
+}
+
+fn hex_header_value(value: &str) -> HeaderValue {
+    // Hex digests and uuids are always valid header values.
+    HeaderValue::from_str(value).expect("hex header value")
+}
+
+pub async fn list_audit_events(
+    State(store): State<AnalysisStore>,
+    Extension(audit): Extension<AuditContext>,
+    Query(query): Query<AuditEventsQuery>,
+) -> Result<Json<AuditEventsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+    let offset = query
+        .cursor
+        .as_deref()
+        .and_then(|cursor| cursor.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    // Recorded before listing so the consultation itself is on the trail.
+    audit
+        .record(
+            &store,
+            AuditEventType::AuditEventsViewed,
+            None,
+            None,
+            json!({
+                "event_type": query.event_type,
+                "analysis_id": query.analysis_id,
+                "actor": query.actor,
+                "from": query.from,
+                "to": query.to,
+            }),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    let (items, next_cursor) = store
+        .list_audit_events(
+            AuditEventFilter {
+                event_type: query.event_type,
+                analysis_id: query.analysis_id,
+                actor: query.actor,
+                from: query.from,
+                to: query.to,
+            },
+            limit,
+            offset,
+        )
+        .await
+        .map_err(storage_error)?;
+
+    Ok(Json(AuditEventsResponse { items, next_cursor }))
+}
+
+fn device_label(metadata: &AcquisitionMetadata) -> String {
+    let device = &metadata.device;
+    format!(
+        "{} {} (os {}, app {})",
+        device.platform,
+        device.model.as_deref().unwrap_or("?"),
+        device.os_version.as_deref().unwrap_or("?"),
+        device.app_version
+    )
+}
+
+fn storage_error(err: StorageError) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({"error":"storage_failure","details":err.to_string()})),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::{to_bytes, Body},
+        http::{Method, Request, StatusCode},
+        routing::post,
+        Router,
+    };
+    use chrono::Utc;
+    use std::{path::PathBuf, sync::Arc};
+
+    use tower::ServiceExt;
+
+    use super::AppState;
+    use crate::{
+        calibration::CalibrationScale, config::ServerConfig, exports::ExportSigner,
+        images::ImageStore, jobs, storage::AnalysisStore, timestamping::TsaClient,
+        verification::Verifier,
+    };
+
+    struct TestContext {
+        app: Router,
+        state: AppState,
+        image_dir: PathBuf,
+    }
+
+    impl TestContext {
+        /// Runs every due job, standing in for the background worker pool.
+        async fn drain_jobs(&self) {
+            while jobs::run_next_job(&self.state).await.unwrap().is_some() {}
+        }
+    }
+
+    async fn test_context_with(config: ServerConfig) -> TestContext {
+        let image_dir =
+            std::env::temp_dir().join(format!("peroxyde-images-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&image_dir).unwrap();
+
+        let export_dir =
+            std::env::temp_dir().join(format!("peroxyde-exports-{}", uuid::Uuid::new_v4()));
+        let config = ServerConfig {
+            export_dir: export_dir.to_string_lossy().into_owned(),
+            ..config
+        };
+
+        let store = AnalysisStore::new_in_memory().await.unwrap();
+        let scale = CalibrationScale::load("../data/calibration/reference-swatches.csv").unwrap();
+        let state = AppState {
+            store,
+            verifier: Arc::new(Verifier::new(scale, ImageStore::new(&image_dir))),
+            export_signer: Arc::new(ExportSigner::generate()),
+            tsa: TsaClient::from_config(&config).unwrap().map(Arc::new),
+            config: Arc::new(config),
+        };
+
+        TestContext {
+            app: super::router(state.clone()),
+            state,
+            image_dir,
+        }
+    }
+
+    async fn test_context() -> TestContext {
+        test_context_with(ServerConfig::default()).await
+    }
+
+    async fn test_app() -> Router {
+        test_context().await.app
+    }
+
+    /// Writes a uniform strip photo in the image store and returns its sha256.
+    fn store_strip_photo(image_dir: &std::path::Path, rgb: [u8; 3]) -> String {
+        let photo = image::RgbImage::from_pixel(64, 48, image::Rgb(rgb));
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        photo.write_to(&mut bytes, image::ImageFormat::Png).unwrap();
+        let bytes = bytes.into_inner();
+        let sha256 = crate::images::sha256_hex(&bytes);
+        std::fs::write(image_dir.join(&sha256), bytes).unwrap();
+        sha256
+    }
+
+    async fn post_analysis(app: &Router, payload: String) -> serde_json::Value {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/v1/analyses")
+                    .header("content-type", "application/json")
+                    .body(Body::from(payload))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    /// Requests an audit export, runs its job and downloads the file.
+    async fn export_audit(
+        ctx: &TestContext,
+        request: serde_json::Value,
+    ) -> axum::response::Response {
+        let (status, queued) = post_json(&ctx.app, "/v1/analyses/audit-export", request).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        ctx.drain_jobs().await;
+        ctx.app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!(
+                        "/v1/exports/{}",
+                        queued["export_id"].as_str().unwrap()
+                    ))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    async fn get_json(app: &Router, uri: &str) -> (StatusCode, serde_json::Value) {
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    async fn post_json(
+        app: &Router,
+        uri: &str,
+        body: serde_json::Value,
+    ) -> (StatusCode, serde_json::Value) {
+        send_json(app, Method::POST, uri, body).await
+    }
+
+    async fn send_json(
+        app: &Router,
+        method: Method,
+        uri: &str,
+        body: serde_json::Value,
+    ) -> (StatusCode, serde_json::Value) {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let parsed = if body.is_empty() {
+            serde_json::Value::Null
+        } else {
+            serde_json::from_slice(&body).unwrap()
+        };
+        (status, parsed)
+    }
+
+    fn valid_payload() -> String {
+        serde_json::json!({
+            "client_analysis_id": "a8e68c43-8fba-4cad-bb7a-3d6b5d9af2aa",
+            "sample_id": "SAMPLE-2026-0001",
+            "operator_id": "op-line-2",
+            "bath_id": "bac-p3-1",
+            "ppm_estime": 276.4,
+            "ppm_min": 255.0,
+            "ppm_max": 298.0,
+            "compliance_status": "conforme_production",
+            "analysis_result": "CONFORME POUR LA PRODUCTION",
+            "recommended_action": "Poursuivre la production normale.",
+            "confidence": 0.93,
+            "analysis_rules_version": "analysis-rules/v1",
+            "calibration_version": "calib-2026-02",
+            "captured_at": "2026-02-13T09:45:00Z",
+            "image": {
+                "uri": "s3://rochias-analyses/2026/02/13/a8e68c43.jpg",
+                "sha256": "9f0868d4d1f8ca0f4b9f2b3f575b8f71c8e7df4a6932f53f9fdd5d6a016d89cf",
+                "content_type": "image/jpeg"
+            },
+            "acquisition_metadata": {
+                "light_condition": "conforme",
+                "ambient_lux": 620.5,
+                "temperature_celsius": 21.3,
+                "humidity_percent": 44.0,
+                "camera_focus_score": 0.88,
+                "rejection_flags": {
+                    "low_light": false,
+                    "blur_detected": false,
+                    "framing_issue": false,
+                    "overexposed": false,
+                    "strip_not_detected": false
+                },
+                "device": {
+                    "platform": "android",
+                    "model": "SM-X910",
+                    "os_version": "14",
+                    "app_version": "0.3.0"
+                }
+            }
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn create_analysis_nominal_returns_202() {
+        let app = test_app().await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/v1/analyses")
+                    .header("content-type", "application/json")
+                    .body(Body::from(valid_payload()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let parsed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(parsed.get("server_analysis_id").is_some());
+        assert_eq!(parsed["server_lifecycle_status"], "recu");
+        assert!(parsed.get("received_at").is_some());
+    }
+
+    #[tokio::test]
+    async fn create_analysis_idempotent_returns_200() {
+        let app = test_app().await;
+
+        let first = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/v1/analyses")
+                    .header("content-type", "application/json")
+                    .body(Body::from(valid_payload()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(first.status(), StatusCode::ACCEPTED);
+
+        let second = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/v1/analyses")
+                    .header("content-type", "application/json")
+                    .body(Body::from(valid_payload()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(second.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn create_analysis_invalid_json_returns_400() {
+        let app = test_app().await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/v1/analyses")
+                    .header("content-type", "application/json")
+                    .body(Body::from("{invalid-json"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn create_analysis_business_validation_fails_returns_422() {
+        let app = test_app().await;
+
+        let mut payload: serde_json::Value = serde_json::from_str(&valid_payload()).unwrap();
+        payload["confidence"] = serde_json::json!(1.7);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/v1/analyses")
+                    .header("content-type", "application/json")
+                    .body(Body::from(payload.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    async fn register_user(app: &Router, user_id: &str, roles: serde_json::Value) {
+        let (status, _) = send_json(
+            app,
+            Method::PUT,
+            &format!("/v1/admin/users/{user_id}"),
+            serde_json::json!({
+                "display_name": user_id,
+                "roles": roles,
+                "password": signing_password(user_id),
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    fn signing_password(user_id: &str) -> String {
+        format!("{user_id}-signing")
+    }
+
+    fn ack_uri(created: &serde_json::Value) -> String {
+        format!(
+            "/v1/analyses/{}/ack",
+            created["server_analysis_id"].as_str().unwrap()
+        )
+    }
+
+    fn photo_payload(image_sha256: &str, ppm: f32, confidence: f32) -> String {
+        let mut payload: serde_json::Value = serde_json::from_str(&valid_payload()).unwrap();
+        payload["client_analysis_id"] = serde_json::json!(uuid::Uuid::new_v4());
+        payload["image"]["sha256"] = serde_json::json!(image_sha256);
+        payload["ppm_estime"] = serde_json::json!(ppm);
+        payload["ppm_min"] = serde_json::json!(ppm - 15.0);
+        payload["ppm_max"] = serde_json::json!(ppm + 15.0);
+        payload["confidence"] = serde_json::json!(confidence);
+        payload.to_string()
+    }
+
+    #[tokio::test]
+    async fn ack_is_pending_until_the_worker_verifies() {
+        let ctx = test_context().await;
+        let created = post_analysis(&ctx.app, valid_payload()).await;
+
+        let (status, ack) = get_json(&ctx.app, &ack_uri(&created)).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ack["server_lifecycle_status"], "recu");
+        assert_eq!(ack["validated_schema"], false);
+        assert_eq!(ack["validated_business_rules"], false);
+        assert!(ack["verified_at"].is_null());
+    }
+
+    #[tokio::test]
+    async fn ack_reports_failed_checks_when_photo_is_missing() {
+        let ctx = test_context().await;
+        let created = post_analysis(&ctx.app, valid_payload()).await;
+        ctx.drain_jobs().await;
+
+        let (_, ack) = get_json(&ctx.app, &ack_uri(&created)).await;
+
+        assert_eq!(ack["server_lifecycle_status"], "recu");
+        assert_eq!(ack["validated_schema"], true);
+        assert_eq!(ack["validated_business_rules"], false);
+        assert_eq!(ack["failed_checks"][0]["check"], "image_available");
+        assert!(ack["server_ppm_estime"].is_null());
+        assert_eq!(ack["queued_for_secondary_review"], false);
+
+        let (_, jobs) = get_json(&ctx.app, "/v1/admin/jobs?kind=verify_analysis").await;
+        assert_eq!(jobs["counts"]["queued"], 1);
+        assert_eq!(jobs["items"][0]["attempts"], 1);
+        assert!(jobs["items"][0]["last_error"]
+            .as_str()
+            .unwrap()
+            .contains("image not found"));
+    }
+
+    #[tokio::test]
+    async fn images_are_only_read_from_the_store_by_their_digest() {
+        let ctx = test_context().await;
+        let outside =
+            std::env::temp_dir().join(format!("peroxyde-secret-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&outside, b"not a strip photo").unwrap();
+        let outside_sha256 = crate::images::sha256_hex(b"not a strip photo");
+
+        for sha256 in [
+            "9F0868D4D1F8CA0F4B9F2B3F575B8F71C8E7DF4A6932F53F9FDD5D6A016D89CF",
+            "../keys/export-signing.key",
+        ] {
+            let mut payload: serde_json::Value = serde_json::from_str(&valid_payload()).unwrap();
+            payload["image"]["sha256"] = serde_json::json!(sha256);
+            let (status, body) = post_json(&ctx.app, "/v1/analyses", payload).await;
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+            assert!(body["error"].as_str().unwrap().contains("image.sha256"));
+        }
+
+        // A file:// URI is not followed: the photo is looked up by its digest.
+        let mut payload: serde_json::Value = serde_json::from_str(&valid_payload()).unwrap();
+        payload["image"]["uri"] = serde_json::json!(format!("file://{}", outside.display()));
+        payload["image"]["sha256"] = serde_json::json!(outside_sha256);
+        let created = post_analysis(&ctx.app, payload.to_string()).await;
+
+        // A stored file that does not match its digest is reported without
+        // revealing the digest actually computed.
+        let strip_sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        let declared = "0".repeat(64);
+        std::fs::copy(
+            ctx.image_dir.join(&strip_sha256),
+            ctx.image_dir.join(&declared),
+        )
+        .unwrap();
+        let mismatched = post_analysis(&ctx.app, photo_payload(&declared, 300.0, 0.93)).await;
+        ctx.drain_jobs().await;
+
+        let (_, ack) = get_json(&ctx.app, &ack_uri(&created)).await;
+        assert_eq!(ack["failed_checks"][0]["check"], "image_available");
+        assert!(!ack["failed_checks"]
+            .to_string()
+            .contains(&outside.display().to_string()));
+        let (_, ack) = get_json(&ctx.app, &ack_uri(&mismatched)).await;
+        assert_eq!(ack["failed_checks"][0]["check"], "image_integrity");
+        assert!(!ack.to_string().contains(&strip_sha256));
+    }
+
+    #[tokio::test]
+    async fn missing_photo_is_rejected_once_retries_are_exhausted() {
+        let ctx = test_context_with(ServerConfig {
+            job_max_attempts: 1,
+            ..ServerConfig::default()
+        })
+        .await;
+        let created = post_analysis(&ctx.app, valid_payload()).await;
+        ctx.drain_jobs().await;
+
+        let (_, ack) = get_json(&ctx.app, &ack_uri(&created)).await;
+        assert_eq!(ack["server_lifecycle_status"], "rejete");
+
+        let (_, jobs) = get_json(&ctx.app, "/v1/admin/jobs?status=succeeded").await;
+        assert_eq!(jobs["items"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn ack_reports_server_recomputed_ppm_and_decision() {
+        let ctx = test_context().await;
+        let sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        let created = post_analysis(&ctx.app, photo_payload(&sha256, 300.0, 0.62)).await;
+        ctx.drain_jobs().await;
+
+        let (_, ack) = get_json(&ctx.app, &ack_uri(&created)).await;
+
+        assert_eq!(ack["server_lifecycle_status"], "valide");
+        assert_eq!(ack["validated_business_rules"], true);
+        assert_eq!(ack["failed_checks"], serde_json::json!([]));
+        let server_ppm = ack["server_ppm_estime"].as_f64().unwrap();
+        assert!((server_ppm - 300.0).abs() <= 15.0, "got {server_ppm}");
+        assert_eq!(
+            ack["server_decision"]["analysis_result"],
+            "CONFORME POUR LA PRODUCTION"
+        );
+        assert_eq!(ack["queued_for_secondary_review"], true);
+        assert_eq!(ack["review_reasons"], serde_json::json!(["low_confidence"]));
+    }
+
+    #[tokio::test]
+    async fn ack_unknown_analysis_returns_404() {
+        let app = test_app().await;
+        let (status, _) = get_json(
+            &app,
+            "/v1/analyses/2f58c716-9707-4fd1-9f6f-1ba0990f6378/ack",
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn review_queue_lists_items_and_enforces_claims() {
+        let ctx = test_context().await;
+        let sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        let doubtful = post_analysis(&ctx.app, photo_payload(&sha256, 300.0, 0.62)).await;
+        post_analysis(&ctx.app, photo_payload(&sha256, 300.0, 0.93)).await;
+        ctx.drain_jobs().await;
+        let id = doubtful["server_analysis_id"].as_str().unwrap();
+        for reviewer in ["lab-tech-17", "lab-tech-18"] {
+            register_user(&ctx.app, reviewer, serde_json::json!(["qualite"])).await;
+        }
+
+        let (_, queue) = get_json(&ctx.app, "/v1/review-queue").await;
+        let items = queue["items"].as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["server_analysis_id"], id);
+        assert_eq!(
+            items[0]["review_reasons"],
+            serde_json::json!(["low_confidence"])
+        );
+
+        let (_, filtered) = get_json(&ctx.app, "/v1/review-queue?reason=non_compliant").await;
+        assert_eq!(filtered["items"], serde_json::json!([]));
+        let (_, too_recent) = get_json(&ctx.app, "/v1/review-queue?min_age_minutes=60").await;
+        assert_eq!(too_recent["items"], serde_json::json!([]));
+
+        let claim_uri = format!("/v1/review-queue/{id}/claim");
+        let (status, claim) = post_json(
+            &ctx.app,
+            &claim_uri,
+            serde_json::json!({"reviewer_id": "lab-tech-17"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(claim["reviewer_id"], "lab-tech-17");
+
+        let (status, _) = post_json(
+            &ctx.app,
+            &claim_uri,
+            serde_json::json!({"reviewer_id": "lab-tech-18"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (_, unclaimed) = get_json(&ctx.app, "/v1/review-queue?include_claimed=false").await;
+        assert_eq!(unclaimed["items"], serde_json::json!([]));
+
+        let review_uri = format!("/v1/analyses/{id}/secondary-review");
+        let review = |reviewer: &str| {
+            serde_json::json!({
+                "reviewer_id": reviewer,
+                "decision": "confirmed",
+                "comment": "Signal net, cohérent avec étalon.",
+                "reviewed_at": "2026-02-13T10:02:00Z",
+                "signature": {"password": signing_password(reviewer), "meaning": "reviewed"}
+            })
+        };
+        let (status, _) = post_json(&ctx.app, &review_uri, review("lab-tech-18")).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let (status, reviewed) = post_json(&ctx.app, &review_uri, review("lab-tech-17")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(reviewed["server_lifecycle_status"], "revu_secondairement");
+        assert_eq!(reviewed["secondary_review_status"], "completed");
+
+        let (_, queue) = get_json(&ctx.app, "/v1/review-queue").await;
+        assert_eq!(queue["items"], serde_json::json!([]));
+        let (status, _) = post_json(&ctx.app, &review_uri, review("lab-tech-17")).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn expired_claim_can_be_taken_over() {
+        let ctx = test_context_with(ServerConfig {
+            review_claim_timeout_minutes: 0,
+            ..ServerConfig::default()
+        })
+        .await;
+        let sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        let created = post_analysis(&ctx.app, photo_payload(&sha256, 300.0, 0.62)).await;
+        ctx.drain_jobs().await;
+        for reviewer in ["lab-tech-17", "lab-tech-18"] {
+            register_user(&ctx.app, reviewer, serde_json::json!(["qualite"])).await;
+        }
+        let claim_uri = format!(
+            "/v1/review-queue/{}/claim",
+            created["server_analysis_id"].as_str().unwrap()
+        );
+
+        for reviewer in ["lab-tech-17", "lab-tech-18"] {
+            let (status, claim) = post_json(
+                &ctx.app,
+                &claim_uri,
+                serde_json::json!({ "reviewer_id": reviewer }),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(claim["reviewer_id"], reviewer);
+        }
+    }
+
+    #[tokio::test]
+    async fn four_eyes_rule_covers_every_version_of_a_corrected_analysis() {
+        let ctx = test_context().await;
+        let sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        let mut payload: serde_json::Value =
+            serde_json::from_str(&photo_payload(&sha256, 300.0, 0.62)).unwrap();
+        payload["operator_id"] = serde_json::json!("qa-07");
+        let created = post_analysis(&ctx.app, payload.to_string()).await;
+        ctx.drain_jobs().await;
+        register_user(
+            &ctx.app,
+            "qa-07",
+            serde_json::json!(["operateur", "qualite"]),
+        )
+        .await;
+        register_user(&ctx.app, "qa-01", serde_json::json!(["qualite"])).await;
+        register_user(&ctx.app, "qa-02", serde_json::json!(["qualite"])).await;
+
+        // The scanner reassigns the scan to someone else, then tries to review it.
+        let (status, corrected) = post_json(
+            &ctx.app,
+            &format!(
+                "/v1/analyses/{}/corrections",
+                created["server_analysis_id"].as_str().unwrap()
+            ),
+            serde_json::json!({
+                "corrected_by": "qa-07",
+                "justification": "Opérateur mal saisi",
+                "operator_id": "op-line-9"
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        ctx.drain_jobs().await;
+        let id = corrected["server_analysis_id"].as_str().unwrap();
+
+        let review_uri = format!("/v1/analyses/{id}/secondary-review");
+        let review = |reviewer: &str| {
+            serde_json::json!({
+                "reviewer_id": reviewer,
+                "decision": "confirmed",
+                "reviewed_at": "2026-02-13T10:02:00Z",
+                "signature": {"password": signing_password(reviewer), "meaning": "reviewed"}
+            })
+        };
+        let (status, refused) = post_json(&ctx.app, &review_uri, review("qa-07")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(refused["reason"], "reviewer_is_operator");
+        let (status, refused) = post_json(
+            &ctx.app,
+            &format!("/v1/review-queue/{id}/claim"),
+            serde_json::json!({"reviewer_id": "qa-07"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(refused["reason"], "reviewer_is_operator");
+
+        let (status, corrected) = post_json(
+            &ctx.app,
+            &format!("/v1/analyses/{id}/corrections"),
+            serde_json::json!({
+                "corrected_by": "qa-01",
+                "justification": "Échantillon mal saisi",
+                "sample_id": "SAMPLE-2026-0009"
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        ctx.drain_jobs().await;
+        let review_uri = format!(
+            "/v1/analyses/{}/secondary-review",
+            corrected["server_analysis_id"].as_str().unwrap()
+        );
+        let (status, refused) = post_json(&ctx.app, &review_uri, review("qa-01")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(refused["reason"], "reviewer_is_corrector");
+        let (status, _) = post_json(&ctx.app, &review_uri, review("qa-02")).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn secondary_review_enforces_four_eyes_rule() {
+        let ctx = test_context().await;
+        let sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        let created = post_analysis(&ctx.app, photo_payload(&sha256, 300.0, 0.62)).await;
+        ctx.drain_jobs().await;
+        let id = created["server_analysis_id"].as_str().unwrap();
+        register_user(
+            &ctx.app,
+            "op-line-2",
+            serde_json::json!(["operateur", "qualite"]),
+        )
+        .await;
+        register_user(&ctx.app, "maint-04", serde_json::json!(["maintenance"])).await;
+        register_user(&ctx.app, "qa-01", serde_json::json!(["qualite"])).await;
+
+        let review_uri = format!("/v1/analyses/{id}/secondary-review");
+        let review = |reviewer: &str| {
+            serde_json::json!({
+                "reviewer_id": reviewer,
+                "decision": "confirmed",
+                "reviewed_at": "2026-02-13T10:02:00Z",
+                "signature": {"password": signing_password(reviewer), "meaning": "reviewed"}
+            })
+        };
+
+        let (status, refused) = post_json(&ctx.app, &review_uri, review("op-line-2")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(refused["reason"], "reviewer_is_operator");
+        let (status, refused) = post_json(&ctx.app, &review_uri, review("maint-04")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(refused["reason"], "reviewer_lacks_quality_role");
+        let (status, _) = post_json(
+            &ctx.app,
+            &format!("/v1/review-queue/{id}/claim"),
+            serde_json::json!({"reviewer_id": "unknown-user"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (events, _) = ctx
+            .state
+            .store
+            .list_audit_events(
+                crate::storage::AuditEventFilter {
+                    event_type: Some(crate::domain::AuditEventType::AnalysisSecondaryReviewRefused),
+                    analysis_id: Some(id.parse().unwrap()),
+                    ..Default::default()
+                },
+                100,
+                0,
+            )
+            .await
+            .unwrap();
+        let actors: Vec<_> = events.iter().filter_map(|e| e.actor.as_deref()).collect();
+        assert_eq!(actors, ["op-line-2", "maint-04", "unknown-user"]);
+
+        let mut wrong_password = review("qa-01");
+        wrong_password["signature"]["password"] = serde_json::json!("not-the-password");
+        let (status, _) = post_json(&ctx.app, &review_uri, wrong_password).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, reviewed) = post_json(&ctx.app, &review_uri, review("qa-01")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(reviewed["server_lifecycle_status"], "revu_secondairement");
+        assert_eq!(reviewed["signature"]["signer_id"], "qa-01");
+        assert_eq!(reviewed["signature"]["meaning"], "reviewed");
+
+        let (_, detail) = get_json(&ctx.app, &format!("/v1/analyses/{id}")).await;
+        assert_eq!(
+            detail["signatures"],
+            serde_json::json!([reviewed["signature"]])
+        );
+    }
+
+    fn recontrol_payload(
+        parent: Option<&serde_json::Value>,
+        bath_id: &str,
+        ppm: f32,
+        compliance_status: &str,
+        captured_at: &str,
+    ) -> serde_json::Value {
+        let mut payload: serde_json::Value = serde_json::from_str(&valid_payload()).unwrap();
+        payload["client_analysis_id"] = serde_json::json!(uuid::Uuid::new_v4());
+        payload["bath_id"] = serde_json::json!(bath_id);
+        payload["parent_analysis_id"] =
+            serde_json::json!(parent.map(|created| created["server_analysis_id"].clone()));
+        payload["ppm_estime"] = serde_json::json!(ppm);
+        payload["ppm_min"] = serde_json::json!(ppm - 15.0);
+        payload["ppm_max"] = serde_json::json!(ppm + 15.0);
+        payload["compliance_status"] = serde_json::json!(compliance_status);
+        payload["captured_at"] = serde_json::json!(captured_at);
+        payload
+    }
+
+    #[tokio::test]
+    async fn out_of_range_result_opens_deviation_closed_by_linked_recontrol() {
+        let ctx = test_context().await;
+        let alert = post_analysis(
+            &ctx.app,
+            recontrol_payload(
+                None,
+                "bac-p3-1",
+                620.0,
+                "seuil_depasse",
+                "2026-02-13T09:45:00Z",
+            )
+            .to_string(),
+        )
+        .await;
+
+        let (_, open) = get_json(&ctx.app, "/v1/deviations?status=open").await;
+        let items = open["items"].as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["opening_analysis_id"], alert["server_analysis_id"]);
+        assert_eq!(items[0]["opening_compliance_status"], "seuil_depasse");
+        let deviation_uri = format!(
+            "/v1/deviations/{}",
+            items[0]["deviation_id"].as_str().unwrap()
+        );
+
+        // Still above threshold: a second deviation, the first one stays open.
+        let second = post_analysis(
+            &ctx.app,
+            recontrol_payload(
+                Some(&alert),
+                "bac-p3-1",
+                540.0,
+                "seuil_depasse",
+                "2026-02-13T10:05:00Z",
+            )
+            .to_string(),
+        )
+        .await;
+        // In range but not linked to the alert.
+        post_analysis(
+            &ctx.app,
+            recontrol_payload(
+                None,
+                "bac-p3-1",
+                300.0,
+                "conforme_production",
+                "2026-02-13T10:10:00Z",
+            )
+            .to_string(),
+        )
+        .await;
+        let (_, open) = get_json(&ctx.app, "/v1/deviations?status=open&bath_id=bac-p3-1").await;
+        assert_eq!(open["items"].as_array().unwrap().len(), 2);
+
+        let (status, refused) = post_json(
+            &ctx.app,
+            "/v1/analyses",
+            recontrol_payload(
+                Some(&second),
+                "bac-p3-2",
+                300.0,
+                "conforme_production",
+                "2026-02-13T10:20:00Z",
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(refused["error"], "parent analysis belongs to another bath");
+
+        let sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        let mut recontrol = recontrol_payload(
+            Some(&second),
+            "bac-p3-1",
+            300.0,
+            "conforme_production",
+            "2026-02-13T10:30:00Z",
+        );
+        recontrol["image"]["sha256"] = serde_json::json!(sha256);
+        let recontrol = post_analysis(&ctx.app, recontrol.to_string()).await;
+        // The declared status does not close anything until the server agrees.
+        let (_, open) = get_json(&ctx.app, "/v1/deviations?status=open&bath_id=bac-p3-1").await;
+        assert_eq!(open["items"].as_array().unwrap().len(), 2);
+
+        ctx.drain_jobs().await;
+        let (_, open) = get_json(&ctx.app, "/v1/deviations?status=open").await;
+        assert_eq!(open["items"], serde_json::json!([]));
+        let (status, closed) = get_json(&ctx.app, &deviation_uri).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(closed["status"], "closed");
+        assert_eq!(
+            closed["closing_analysis_id"],
+            recontrol["server_analysis_id"]
+        );
+        assert_eq!(closed["elapsed_minutes"], 45);
+    }
+
+    #[tokio::test]
+    async fn rejected_in_range_recontrol_leaves_the_deviation_open() {
+        let ctx = test_context().await;
+        let sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        let alert = post_analysis(
+            &ctx.app,
+            recontrol_payload(
+                None,
+                "bac-p3-1",
+                620.0,
+                "seuil_depasse",
+                "2026-02-13T09:45:00Z",
+            )
+            .to_string(),
+        )
+        .await;
+        // The strip reads in range, but the capture was blurred.
+        let mut recontrol = recontrol_payload(
+            Some(&alert),
+            "bac-p3-1",
+            300.0,
+            "conforme_production",
+            "2026-02-13T10:30:00Z",
+        );
+        recontrol["image"]["sha256"] = serde_json::json!(sha256);
+        recontrol["acquisition_metadata"]["rejection_flags"]["blur_detected"] =
+            serde_json::json!(true);
+        let recontrol = post_analysis(&ctx.app, recontrol.to_string()).await;
+        ctx.drain_jobs().await;
+
+        let (_, ack) = get_json(&ctx.app, &ack_uri(&recontrol)).await;
+        assert_eq!(ack["server_lifecycle_status"], "rejete");
+        assert!(ack["server_ppm_estime"].as_f64().unwrap() < 331.0);
+        let (_, open) = get_json(&ctx.app, "/v1/deviations?status=open").await;
+        let items = open["items"].as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["opening_analysis_id"], alert["server_analysis_id"]);
+        assert!(items[0]["closing_analysis_id"].is_null());
+    }
+
+    #[tokio::test]
+    async fn bath_correction_moves_the_deviation_to_the_corrected_bath() {
+        let ctx = test_context().await;
+        register_user(&ctx.app, "qa-01", serde_json::json!(["qualite"])).await;
+        let sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        let alert = post_analysis(
+            &ctx.app,
+            recontrol_payload(
+                None,
+                "bac-p3-1",
+                620.0,
+                "seuil_depasse",
+                "2026-02-13T09:45:00Z",
+            )
+            .to_string(),
+        )
+        .await;
+        let (status, corrected) = post_json(
+            &ctx.app,
+            &format!(
+                "/v1/analyses/{}/corrections",
+                alert["server_analysis_id"].as_str().unwrap()
+            ),
+            serde_json::json!({
+                "corrected_by": "qa-01",
+                "justification": "Bain mal sélectionné",
+                "bath_id": "bac-p3-2"
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (_, open) = get_json(&ctx.app, "/v1/deviations?status=open&bath_id=bac-p3-1").await;
+        assert_eq!(open["items"], serde_json::json!([]));
+        let (_, open) = get_json(&ctx.app, "/v1/deviations?status=open&bath_id=bac-p3-2").await;
+        assert_eq!(
+            open["items"][0]["opening_analysis_id"],
+            alert["server_analysis_id"]
+        );
+
+        // Linked to the corrected version, the re-control still answers it.
+        let mut recontrol = recontrol_payload(
+            Some(&corrected),
+            "bac-p3-2",
+            300.0,
+            "conforme_production",
+            "2026-02-13T10:30:00Z",
+        );
+        recontrol["image"]["sha256"] = serde_json::json!(sha256);
+        let recontrol = post_analysis(&ctx.app, recontrol.to_string()).await;
+        ctx.drain_jobs().await;
+        let (_, deviations) = get_json(&ctx.app, "/v1/deviations?bath_id=bac-p3-2").await;
+        assert_eq!(deviations["items"][0]["status"], "closed");
+        assert_eq!(
+            deviations["items"][0]["closing_analysis_id"],
+            recontrol["server_analysis_id"]
+        );
+    }
+
+    #[tokio::test]
+    async fn voiding_withdraws_deviations_opened_or_closed_by_the_scan() {
+        let ctx = test_context().await;
+        register_user(&ctx.app, "qa-01", serde_json::json!(["qualite"])).await;
+        let sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        let void = |created: &serde_json::Value| {
+            let uri = format!(
+                "/v1/analyses/{}/corrections",
+                created["server_analysis_id"].as_str().unwrap()
+            );
+            let app = ctx.app.clone();
+            async move {
+                let (status, voided) = post_json(
+                    &app,
+                    &uri,
+                    serde_json::json!({
+                        "corrected_by": "qa-01",
+                        "justification": "Bandelette périmée",
+                        "void": true
+                    }),
+                )
+                .await;
+                assert_eq!(status, StatusCode::CREATED);
+                voided
+            }
+        };
+        let alert = post_analysis(
+            &ctx.app,
+            recontrol_payload(
+                None,
+                "bac-p3-1",
+                620.0,
+                "seuil_depasse",
+                "2026-02-13T09:45:00Z",
+            )
+            .to_string(),
+        )
+        .await;
+        let mut recontrol = recontrol_payload(
+            Some(&alert),
+            "bac-p3-1",
+            300.0,
+            "conforme_production",
+            "2026-02-13T10:30:00Z",
+        );
+        recontrol["image"]["sha256"] = serde_json::json!(sha256);
+        let recontrol = post_analysis(&ctx.app, recontrol.to_string()).await;
+        ctx.drain_jobs().await;
+        let (_, open) = get_json(&ctx.app, "/v1/deviations?status=open").await;
+        assert_eq!(open["items"], serde_json::json!([]));
+
+        // Without its re-control, the alert is open again.
+        void(&recontrol).await;
+        let (_, open) = get_json(&ctx.app, "/v1/deviations?status=open").await;
+        let items = open["items"].as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(items[0]["closing_analysis_id"].is_null());
+
+        let voided = void(&alert).await;
+        let (_, open) = get_json(&ctx.app, "/v1/deviations?status=open").await;
+        assert_eq!(open["items"], serde_json::json!([]));
+        let (_, withdrawn) = get_json(&ctx.app, "/v1/deviations?status=voided").await;
+        assert_eq!(
+            withdrawn["items"][0]["opening_analysis_id"],
+            alert["server_analysis_id"]
+        );
+        assert_eq!(
+            withdrawn["items"][0]["closing_analysis_id"],
+            voided["server_analysis_id"]
+        );
+    }
+
+    #[tokio::test]
+    async fn corrective_action_lifecycle_is_exported_in_audit_register() {
+        let ctx = test_context().await;
+        register_user(&ctx.app, "maint-04", serde_json::json!(["maintenance"])).await;
+        register_user(&ctx.app, "qa-01", serde_json::json!(["qualite"])).await;
+        let compliant = post_analysis(&ctx.app, valid_payload()).await;
+        let alert = post_analysis(
+            &ctx.app,
+            recontrol_payload(None, "bac-p3-1", 80.0, "taux_bas", "2026-02-13T11:00:00Z")
+                .to_string(),
+        )
+        .await;
+        let (_, deviations) = get_json(&ctx.app, "/v1/deviations").await;
+        let deviation_id = deviations["items"][0]["deviation_id"].clone();
+
+        let action = |link: serde_json::Value| {
+            let mut body = serde_json::json!({
+                "description": "Contrôler la pompe doseuse",
+                "owner_id": "maint-04",
+                "due_at": "2026-02-14T11:00:00Z",
+                "created_by": "qa-01"
+            });
+            body.as_object_mut()
+                .unwrap()
+                .extend(link.as_object().unwrap().clone());
+            body
+        };
+        let (status, _) = post_json(
+            &ctx.app,
+            "/v1/corrective-actions",
+            action(serde_json::json!({"analysis_id": compliant["server_analysis_id"]})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (status, created) = post_json(
+            &ctx.app,
+            "/v1/corrective-actions",
+            action(serde_json::json!({"deviation_id": deviation_id})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(created["analysis_id"], alert["server_analysis_id"]);
+        assert_eq!(created["status"], "open");
+        let (_, overdue) = get_json(&ctx.app, "/v1/corrective-actions?overdue=true").await;
+        assert_eq!(overdue["items"].as_array().unwrap().len(), 1);
+
+        let action_uri = format!("/v1/corrective-actions/{}", created["id"].as_str().unwrap());
+        let (status, _) = post_json(
+            &ctx.app,
+            &format!("{action_uri}/verify"),
+            serde_json::json!({"verified_by": "qa-01", "verification_comment": "ok"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let (status, completed) = post_json(
+            &ctx.app,
+            &format!("{action_uri}/complete"),
+            serde_json::json!({"completed_by": "maint-04", "action_taken": "Pompe réglée"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(completed["status"], "completed");
+
+        let (status, _) = post_json(
+            &ctx.app,
+            &format!("{action_uri}/verify"),
+            serde_json::json!({"verified_by": "maint-04", "verification_comment": "ok"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, verified) = post_json(
+            &ctx.app,
+            &format!("{action_uri}/verify"),
+            serde_json::json!({
+                "verified_by": "qa-01",
+                "verification_comment": "Recontrôle conforme à 300 ppm"
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(verified["status"], "verified");
+
+        let response = export_audit(&ctx, serde_json::json!({})).await;
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let mut reader = csv::Reader::from_reader(body.as_ref());
+        let column = reader
+            .headers()
+            .unwrap()
+            .iter()
+            .position(|header| header == "corrective_actions_json")
+            .unwrap();
+        let exported: Vec<serde_json::Value> = reader
+            .records()
+            .map(|record| serde_json::from_str(&record.unwrap()[column]).unwrap())
+            .collect();
+        assert_eq!(exported[0], serde_json::json!([]));
+        assert_eq!(exported[1][0]["action_taken"], "Pompe réglée");
+        assert_eq!(exported[1][0]["verified_by"], "qa-01");
+    }
+
+    #[tokio::test]
+    async fn restart_requires_compliant_recontrol_after_stop() {
+        let ctx = test_context().await;
+        let sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        let alert = post_analysis(
+            &ctx.app,
+            recontrol_payload(
+                None,
+                "bac-p3-1",
+                620.0,
+                "seuil_depasse",
+                "2026-02-13T09:45:00Z",
+            )
+            .to_string(),
+        )
+        .await;
+        let event = |kind: &str, occurred_at: &str, linked: serde_json::Value| {
+            serde_json::json!({
+                "bath_id": "bac-p3-1",
+                "kind": kind,
+                "recorded_by": "chef-equipe-2",
+                "occurred_at": occurred_at,
+                "reason": "Seuil P3 dépassé",
+                "linked_analysis_ids": linked
+            })
+        };
+
+        let (status, _) = post_json(
+            &ctx.app,
+            "/v1/production-events",
+            event("restart", "2026-02-13T09:40:00Z", serde_json::json!([])),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (status, stop) = post_json(
+            &ctx.app,
+            "/v1/production-events",
+            event(
+                "stop",
+                "2026-02-13T09:50:00Z",
+                serde_json::json!([alert["server_analysis_id"]]),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(stop["linked_analysis_ids"][0], alert["server_analysis_id"]);
+
+        let (status, refused) = post_json(
+            &ctx.app,
+            "/v1/production-events",
+            event("restart", "2026-02-13T10:00:00Z", serde_json::json!([])),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(refused["error"]
+            .as_str()
+            .unwrap()
+            .contains("compliant re-control"));
+
+        let mut recontrol = recontrol_payload(
+            Some(&alert),
+            "bac-p3-1",
+            300.0,
+            "conforme_production",
+            "2026-02-13T10:30:00Z",
+        );
+        recontrol["image"]["sha256"] = serde_json::json!(sha256);
+        let recontrol = post_analysis(&ctx.app, recontrol.to_string()).await;
+        // Still `recu`: the server has not verified the re-control yet.
+        let (status, _) = post_json(
+            &ctx.app,
+            "/v1/production-events",
+            event("restart", "2026-02-13T10:40:00Z", serde_json::json!([])),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        ctx.drain_jobs().await;
+        let (status, restart) = post_json(
+            &ctx.app,
+            "/v1/production-events",
+            event("restart", "2026-02-13T10:40:00Z", serde_json::json!([])),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(
+            restart["recontrol_analysis_id"],
+            recontrol["server_analysis_id"]
+        );
+
+        let (_, events) = get_json(&ctx.app, "/v1/production-events?bath_id=bac-p3-1").await;
+        let kinds: Vec<_> = events["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|event| event["kind"].as_str().unwrap())
+            .collect();
+        assert_eq!(kinds, ["stop", "restart"]);
+    }
+
+    #[tokio::test]
+    async fn correction_supersedes_without_touching_the_original() {
+        let ctx = test_context().await;
+        register_user(&ctx.app, "qa-01", serde_json::json!(["qualite"])).await;
+        register_user(&ctx.app, "op-line-3", serde_json::json!(["operateur"])).await;
+        let original = post_analysis(&ctx.app, valid_payload()).await;
+        let original_id = original["server_analysis_id"].as_str().unwrap();
+        let corrections_uri = format!("/v1/analyses/{original_id}/corrections");
+
+        let (status, _) = post_json(
+            &ctx.app,
+            &corrections_uri,
+            serde_json::json!({
+                "corrected_by": "op-line-3",
+                "justification": "Mauvais échantillon",
+                "sample_id": "SAMPLE-2026-0002"
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = post_json(
+            &ctx.app,
+            &corrections_uri,
+            serde_json::json!({"corrected_by": "qa-01", "justification": "Rien"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (status, corrected) = post_json(
+            &ctx.app,
+            &corrections_uri,
+            serde_json::json!({
+                "corrected_by": "qa-01",
+                "justification": "Mauvais échantillon saisi",
+                "sample_id": "SAMPLE-2026-0002"
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(corrected["version"], 2);
+        let corrected_id = corrected["server_analysis_id"].as_str().unwrap();
+
+        let (status, _) = post_json(
+            &ctx.app,
+            &corrections_uri,
+            serde_json::json!({"corrected_by": "qa-01", "justification": "x", "void": true}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (status, _) = post_json(
+            &ctx.app,
+            &format!("/v1/analyses/{corrected_id}/corrections"),
+            serde_json::json!({
+                "corrected_by": "qa-01",
+                "justification": "Scan réalisé sur bandelette périmée",
+                "void": true
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        ctx.drain_jobs().await;
+
+        let (_, detail) = get_json(&ctx.app, &format!("/v1/analyses/{original_id}")).await;
+        assert_eq!(detail["analysis"]["sample_id"], "SAMPLE-2026-0001");
+        assert_eq!(detail["superseded_by_analysis_id"], corrected_id);
+        let versions = detail["versions"].as_array().unwrap();
+        assert_eq!(versions.len(), 3);
+        assert_eq!(versions[1]["sample_id"], "SAMPLE-2026-0002");
+        assert_eq!(versions[2]["correction"]["voided"], true);
+        assert_eq!(versions[2]["server_lifecycle_status"], "rejete");
+
+        let (_, history) = get_json(&ctx.app, "/v1/analyses/history").await;
+        let items = history["items"].as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(
+            items[0]["server_analysis_id"],
+            versions[2]["server_analysis_id"]
+        );
+    }
+
+    #[tokio::test]
+    async fn audit_events_capture_request_metadata_and_filter() {
+        let app = test_app().await;
+        let payload = valid_payload();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/v1/analyses")
+                    .header("content-type", "application/json")
+                    .header("x-forwarded-for", "203.0.113.7, 10.0.0.1")
+                    .body(Body::from(payload.clone()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let id = created["server_analysis_id"].as_str().unwrap();
+
+        let (status, _) = get_json(&app, &format!("/v1/analyses/{id}/ack")).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, page) = get_json(&app, &format!("/v1/audit-events?analysis_id={id}")).await;
+        assert_eq!(status, StatusCode::OK);
+        let types: Vec<_> = page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|event| event["event_type"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                "analysis_received",
+                "analysis_schema_validated",
+                "analysis_ack_viewed"
+            ]
+        );
+
+        let received = &page["items"][0];
+        assert_eq!(received["actor"], "op-line-2");
+        assert_eq!(received["client_ip"], "203.0.113.7");
+        assert_eq!(
+            received["payload_sha256"],
+            crate::images::sha256_hex(payload.as_bytes())
+        );
+        assert!(received["device"].as_str().unwrap().contains("android"));
+        assert!(received["occurred_at"].is_string());
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/audit-events?event_type=audit_events_viewed&actor=qa-01")
+                    .header("x-actor-id", "qa-01")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let viewed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let items = viewed["items"].as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["actor"], "qa-01");
+        assert!(items[0]["payload_sha256"].is_null());
+    }
+
+    #[tokio::test]
+    async fn integrity_check_reports_first_broken_link_of_each_chain() {
+        let ctx = test_context().await;
+        let created = post_analysis(&ctx.app, valid_payload()).await;
+        let id = created["server_analysis_id"].as_str().unwrap();
+        let (status, _) = get_json(&ctx.app, &format!("/v1/analyses/{id}/ack")).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, report) = get_json(&ctx.app, "/v1/admin/integrity").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report["intact"], true);
+        assert_eq!(report["analyses"]["entries"], 1);
+        assert!(report["analyses"]["first_broken_link"].is_null());
+        assert!(report["audit_events"]["entries"].as_u64().unwrap() >= 3);
+
+        let response = export_audit(&ctx, serde_json::json!({})).await;
+        assert_eq!(
+            response.headers()["x-chain-head-analyses"],
+            report["analyses"]["head"].as_str().unwrap()
+        );
+        assert_eq!(
+            response.headers()["x-chain-head-audit-events"]
+                .to_str()
+                .unwrap()
+                .len(),
+            64
+        );
+
+        // Someone with the file can drop the append-only triggers; the chains
+        // still give them away.
+        ctx.state
+            .store
+            .execute_raw(
+                "DROP TRIGGER analyses_no_result_update;
+                 DROP TRIGGER audit_events_no_delete;
+                 UPDATE analyses SET ppm_estime = ppm_estime + 1;",
+            )
+            .await
+            .unwrap();
+        ctx.state
+            .store
+            .execute_raw(
+                "DELETE FROM audit_events WHERE rowid = (SELECT rowid FROM audit_events LIMIT 1 OFFSET 1)",
+            )
+            .await
+            .unwrap();
+
+        let (_, report) = get_json(&ctx.app, "/v1/admin/integrity").await;
+        assert_eq!(report["intact"], false);
+        assert_eq!(
+            report["analyses"]["first_broken_link"],
+            serde_json::json!({
+                "position": 1,
+                "entry_id": id,
+                "reason": "content_hash_mismatch",
+            })
+        );
+        assert_eq!(report["audit_events"]["first_broken_link"]["position"], 2);
+        assert_eq!(
+            report["audit_events"]["first_broken_link"]["reason"],
+            "previous_hash_mismatch"
+        );
+    }
+
+    #[tokio::test]
+    async fn analyses_and_audit_events_are_append_only() {
+        let ctx = test_context().await;
+        let created = post_analysis(&ctx.app, valid_payload()).await;
+        let id = created["server_analysis_id"].as_str().unwrap();
+
+        for sql in [
+            "UPDATE analyses SET ppm_estime = 12.0",
+            "UPDATE analyses SET server_lifecycle_status = '\"valide\"'",
+            "UPDATE analyses SET chain_hash = 'forged'",
+            "DELETE FROM analyses",
+            "UPDATE audit_events SET actor = 'someone-else'",
+            "DELETE FROM audit_events",
+            "INSERT INTO analysis_lifecycle_transitions (
+                analysis_id, from_status, to_status, reason, transitioned_at
+             ) SELECT id, '\"valide\"', '\"rejete\"', 'forged', '2026-01-01T00:00:00Z'
+               FROM analyses",
+        ] {
+            assert!(
+                ctx.state.store.execute_raw(sql).await.is_err(),
+                "accepted: {sql}"
+            );
+        }
+
+        let (status, ack) = get_json(&ctx.app, &format!("/v1/analyses/{id}/ack")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ack["server_lifecycle_status"], "recu");
+
+        // The sanctioned path still moves the status.
+        assert!(ctx
+            .state
+            .store
+            .transition_lifecycle(
+                id.parse().unwrap(),
+                crate::domain::ServerLifecycleStatus::Recu,
+                crate::domain::ServerLifecycleStatus::Rejete,
+                "manual check",
+            )
+            .await
+            .unwrap());
+        let (_, ack) = get_json(&ctx.app, &format!("/v1/analyses/{id}/ack")).await;
+        assert_eq!(ack["server_lifecycle_status"], "rejete");
+
+        let (_, report) = get_json(&ctx.app, "/v1/admin/integrity").await;
+        assert_eq!(report["intact"], true);
+    }
+
+    #[tokio::test]
+    async fn calibration_and_rules_signatures_require_reauthenticated_quality() {
+        let ctx = test_context().await;
+        register_user(&ctx.app, "qa-01", serde_json::json!(["qualite"])).await;
+        register_user(&ctx.app, "op-line-3", serde_json::json!(["operateur"])).await;
+        let sign = |record_type: &str, signer: &str, password: &str| {
+            serde_json::json!({
+                "record_type": record_type,
+                "signer_id": signer,
+                "password": password,
+                "meaning": "approved",
+            })
+        };
+
+        let (status, _) = post_json(
+            &ctx.app,
+            "/v1/signatures",
+            sign("calibration_activation", "qa-01", "wrong-password"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, refused) = post_json(
+            &ctx.app,
+            "/v1/signatures",
+            sign(
+                "calibration_activation",
+                "op-line-3",
+                &signing_password("op-line-3"),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(refused["reason"], "signer_lacks_quality_role");
+
+        let (status, calibration) = post_json(
+            &ctx.app,
+            "/v1/signatures",
+            sign(
+                "calibration_activation",
+                "qa-01",
+                &signing_password("qa-01"),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(calibration["record_id"], "calib-2026-02-14T09:30:00Z");
+        assert_eq!(calibration["record_hash"].as_str().unwrap().len(), 64);
+        assert_eq!(calibration["signer_display_name"], "qa-01");
+        assert_eq!(calibration["meaning"], "approved");
+
+        let (status, rules) = post_json(
+            &ctx.app,
+            "/v1/signatures",
+            sign("rules_change", "qa-01", &signing_password("qa-01")),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(rules["record_id"], "analysis-rules/v2");
+        assert_ne!(rules["signature_hash"], calibration["signature_hash"]);
+
+        let (status, listed) = get_json(
+            &ctx.app,
+            "/v1/signatures?record_type=calibration_activation",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(listed["items"], serde_json::json!([calibration]));
+
+        let (_, refusals) = get_json(
+            &ctx.app,
+            "/v1/audit-events?event_type=electronic_signature_refused",
+        )
+        .await;
+        assert_eq!(refusals["items"].as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn audit_export_is_accompanied_by_a_verifiable_signed_manifest() {
+        let ctx = test_context().await;
+        post_analysis(&ctx.app, valid_payload()).await;
+
+        let response = export_audit(&ctx, serde_json::json!({})).await;
+        let export_id = response.headers()["x-export-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let signature = response.headers()["x-export-signature"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let csv = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        let (status, manifest) =
+            get_json(&ctx.app, &format!("/v1/exports/{export_id}/manifest")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(manifest["signature"], signature);
+        assert_eq!(manifest["manifest"]["record_count"], 1);
+        assert_eq!(
+            manifest["manifest"]["file_sha256"],
+            crate::images::sha256_hex(&csv)
+        );
+        assert_eq!(
+            manifest["manifest"]["server_version"],
+            env!("CARGO_PKG_VERSION")
+        );
+
+        let signed: crate::domain::SignedExportManifest = serde_json::from_value(manifest).unwrap();
+        let trusted = ctx.state.export_signer.public_key_hex();
+        assert_eq!(
+            crate::exports::verify_export(&csv, &signed, &trusted),
+            Ok(())
+        );
+
+        let mut altered = csv.to_vec();
+        altered[0] ^= 1;
+        assert!(matches!(
+            crate::exports::verify_export(&altered, &signed, &trusted),
+            Err(crate::exports::ExportVerificationError::FileDigestMismatch { .. })
+        ));
+
+        let mut forged = signed.clone();
+        forged.manifest.record_count = 0;
+        assert_eq!(
+            crate::exports::verify_export(&csv, &forged, &trusted),
+            Err(crate::exports::ExportVerificationError::BadSignature)
+        );
+
+        let other_key = ExportSigner::generate().public_key_hex();
+        assert!(matches!(
+            crate::exports::verify_export(&csv, &signed, &other_key),
+            Err(crate::exports::ExportVerificationError::UntrustedKey { .. })
+        ));
+    }
+
+    /// Serves `reply` (`timestamping::stand_in_reply` or `forged_reply`) on
+    /// a local port, standing in for a real Time Stamping Authority.
+    async fn spawn_stand_in_tsa(reply: fn(&[u8], i64, chrono::DateTime<Utc>) -> Vec<u8>) -> String {
+        let serial = Arc::new(std::sync::atomic::AtomicI64::new(1));
+        let tsa = Router::new().route(
+            "/tsr",
+            post(move |body: axum::body::Bytes| {
+                let serial = serial.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                async move { reply(&body, serial, Utc::now()) }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, tsa).await.unwrap() });
+        format!("http://{address}/tsr")
+    }
+
+    /// Writes the stand-in TSA certificate to a temporary file.
+    fn stand_in_tsa_cert_path() -> String {
+        let path = std::env::temp_dir().join(format!("peroxyde-tsa-{}.der", uuid::Uuid::new_v4()));
+        std::fs::write(&path, crate::timestamping::stand_in_certificate()).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[tokio::test]
+    async fn exports_and_daily_chain_heads_are_timestamped_by_the_tsa() {
+        let ctx = test_context_with(ServerConfig {
+            tsa_url: Some(spawn_stand_in_tsa(crate::timestamping::stand_in_reply).await),
+            tsa_cert_path: Some(stand_in_tsa_cert_path()),
+            ..ServerConfig::default()
+        })
+        .await;
+        post_analysis(&ctx.app, valid_payload()).await;
+
+        let response = export_audit(&ctx, serde_json::json!({})).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let export_id = response.headers()["x-export-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let timestamp_id = response.headers()["x-export-timestamp-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let (status, listed) = get_json(
+            &ctx.app,
+            &format!("/v1/timestamps?subject_type=export_manifest&subject_id={export_id}"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(listed["items"].as_array().unwrap().len(), 1);
+        let item = &listed["items"][0];
+        assert_eq!(item["id"], timestamp_id);
+        let (_, manifest) = get_json(&ctx.app, &format!("/v1/exports/{export_id}/manifest")).await;
+        let timestamped: serde_json::Value =
+            serde_json::from_str(item["message"].as_str().unwrap()).unwrap();
+        assert_eq!(timestamped, manifest["manifest"]);
+
+        let response = ctx
+            .app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/v1/timestamps/{timestamp_id}/token"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let token = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let info = crate::timestamping::verify_token(
+            &token,
+            item["message"].as_str().unwrap().as_bytes(),
+            ctx.state.tsa.as_ref().unwrap().certificate(),
+        )
+        .unwrap();
+        assert_eq!(
+            info.message_sha256,
+            item["message_sha256"].as_str().unwrap()
+        );
+
+        let daily = jobs::timestamp_chain_heads_if_due(&ctx.state)
+            .await
+            .unwrap()
+            .expect("chain heads timestamped");
+        assert_eq!(daily.subject_id, Utc::now().date_naive().to_string());
+        assert!(jobs::timestamp_chain_heads_if_due(&ctx.state)
+            .await
+            .unwrap()
+            .is_none());
+
+        let (_, report) = get_json(&ctx.app, "/v1/admin/integrity").await;
+        assert_eq!(report["intact"], true);
+        assert_eq!(report["timestamps"]["tokens"], 2);
+        assert_eq!(
+            report["timestamps"]["first_invalid"],
+            serde_json::Value::Null
+        );
+        assert!(report["timestamps"]["last_chain_heads_gen_time"].is_string());
+
+        let (_, events) = get_json(
+            &ctx.app,
+            "/v1/audit-events?event_type=chain_heads_timestamped",
+        )
+        .await;
+        assert_eq!(
+            events["items"][0]["details"]["timestamp_id"],
+            daily.id.to_string()
+        );
+
+        ctx.state
+            .store
+            .execute_raw(
+                "DROP TRIGGER trusted_timestamps_no_update;
+                 UPDATE trusted_timestamps SET message = message || ' '
+                 WHERE subject_type = '\"export_manifest\"';",
+            )
+            .await
+            .unwrap();
+        let (_, report) = get_json(&ctx.app, "/v1/admin/integrity").await;
+        assert_eq!(report["intact"], false);
+        assert_eq!(
+            report["timestamps"]["first_invalid"]["timestamp_id"],
+            timestamp_id
+        );
+        assert_eq!(
+            report["timestamps"]["first_invalid"]["reason"],
+            "imprint_mismatch"
+        );
+    }
+
+    #[tokio::test]
+    async fn unsigned_timestamp_tokens_are_refused_on_receipt_and_flagged_when_stored() {
+        let cert_path = stand_in_tsa_cert_path();
+        let forging = test_context_with(ServerConfig {
+            tsa_url: Some(spawn_stand_in_tsa(crate::timestamping::forged_reply).await),
+            tsa_cert_path: Some(cert_path.clone()),
+            ..ServerConfig::default()
+        })
+        .await;
+        let response = export_audit(&forging, serde_json::json!({})).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("x-export-timestamp-id").is_none());
+        let (_, events) = get_json(
+            &forging.app,
+            "/v1/audit-events?event_type=timestamp_request_failed",
+        )
+        .await;
+        assert_eq!(
+            events["items"][0]["details"]["error"],
+            "timestamp token carries no signer"
+        );
+        let (_, listed) = get_json(&forging.app, "/v1/timestamps").await;
+        assert_eq!(listed["items"].as_array().unwrap().len(), 0);
+
+        let ctx = test_context_with(ServerConfig {
+            tsa_url: Some(spawn_stand_in_tsa(crate::timestamping::stand_in_reply).await),
+            tsa_cert_path: Some(cert_path),
+            ..ServerConfig::default()
+        })
+        .await;
+        let response = export_audit(&ctx, serde_json::json!({})).await;
+        assert!(response.headers().get("x-export-timestamp-id").is_some());
+        let genuine = ctx
+            .state
+            .store
+            .list_timestamps(None, None)
+            .await
+            .unwrap()
+            .remove(0);
+        let forged = crate::domain::TrustedTimestamp {
+            id: uuid::Uuid::new_v4(),
+            token: crate::timestamping::strip_signers(&genuine.token),
+            ..genuine.clone()
+        };
+        assert_eq!(
+            crate::timestamping::verify_token(
+                &forged.token,
+                forged.message.as_bytes(),
+                ctx.state.tsa.as_ref().unwrap().certificate(),
+            ),
+            Err(crate::timestamping::TimestampError::Unsigned)
+        );
+        ctx.state
+            .store
+            .record_timestamp(forged.clone())
+            .await
+            .unwrap();
+
+        let (_, report) = get_json(&ctx.app, "/v1/admin/integrity").await;
+        assert_eq!(report["intact"], false);
+        assert_eq!(
+            report["timestamps"]["first_invalid"]["timestamp_id"],
+            forged.id.to_string()
+        );
+        assert_eq!(
+            report["timestamps"]["first_invalid"]["reason"],
+            "signature_invalid"
+        );
+    }
+
+    #[tokio::test]
+    async fn unreachable_tsa_does_not_block_exports() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed = format!("http://{}/tsr", listener.local_addr().unwrap());
+        drop(listener);
+        let ctx = test_context_with(ServerConfig {
+            tsa_url: Some(closed),
+            tsa_cert_path: Some(stand_in_tsa_cert_path()),
+            ..ServerConfig::default()
+        })
+        .await;
 
-    Ok(Json(HistoryResponse { items, next_cursor }))
-}
+        let response = export_audit(&ctx, serde_json::json!({})).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("x-export-timestamp-id").is_none());
 
-pub async fn analysis_ack(
-    State(store): State<AnalysisStore>,
-    Path(server_analysis_id): Path<Uuid>,
-) -> impl IntoResponse {
-    match store.find_by_id(server_analysis_id).await {
-        Ok(Some(analysis)) => (
-            StatusCode::OK,
-            Json(AnalysisAckResponse {
-                server_analysis_id: analysis.id,
-                server_lifecycle_status: analysis.server_lifecycle_status,
-                validated_schema: true,
-                validated_business_rules: true,
-                queued_for_secondary_review: true,
-                ack_at: Utc::now(),
-            }),
-        )
-            .into_response(),
-        Ok(None) => (
-            StatusCode::NOT_FOUND,
-            Json(json!({"error":"analysis not found"})),
+        let (_, events) = get_json(
+            &ctx.app,
+            "/v1/audit-events?event_type=timestamp_request_failed",
         )
-            .into_response(),
-        Err(err) => storage_error(err).into_response(),
+        .await;
+        assert_eq!(events["items"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            events["items"][0]["details"]["subject_type"],
+            "export_manifest"
+        );
+        let (_, listed) = get_json(&ctx.app, "/v1/timestamps").await;
+        assert_eq!(listed["items"].as_array().unwrap().len(), 0);
     }
-}
 
-pub async fn export_audit_csv(
-    State(store): State<AnalysisStore>,
-    Json(payload): Json<AuditExportPayload>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let analyses = store
-        .list_for_audit_csv(payload.from, payload.to)
-        .await
-        .map_err(storage_error)?;
+    #[tokio::test]
+    async fn scheduled_integrity_check_rehashes_images_and_degrades_health() {
+        let ctx = test_context().await;
+        let sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        let created = post_analysis(&ctx.app, photo_payload(&sha256, 300.0, 0.93)).await;
+        // Photo never uploaded: not expected in the store.
+        post_analysis(&ctx.app, photo_payload(&"ab".repeat(32), 300.0, 0.93)).await;
+        ctx.drain_jobs().await;
 
-    let csv = reporting::audit_csv(&analyses).map_err(|err| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": err})),
-        )
-    })?;
+        let (status, health) = get_json(&ctx.app, "/health").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(health["status"], "ok");
+        assert_eq!(health["integrity"], serde_json::Value::Null);
 
-    Ok((
-        [
-            (
-                header::CONTENT_TYPE,
-                HeaderValue::from_static("text/csv; charset=utf-8"),
-            ),
-            (
-                header::CONTENT_DISPOSITION,
-                HeaderValue::from_static("attachment; filename=registre-audit.csv"),
-            ),
-        ],
-        csv,
-    ))
-}
+        let run = jobs::run_integrity_check(&ctx.state).await.unwrap();
+        assert!(run.healthy, "{:?}", run.failures);
+        assert_eq!(run.images.checked, 1);
+        assert_eq!(run.images.skipped, 1);
+        assert_eq!(run.sqlite_integrity, ["ok"]);
+        let (_, health) = get_json(&ctx.app, "/health").await;
+        assert_eq!(health["status"], "ok");
+        assert_eq!(health["integrity"]["healthy"], true);
 
-fn storage_error(err: StorageError) -> (StatusCode, Json<serde_json::Value>) {
-    (
-        StatusCode::INTERNAL_SERVER_ERROR,
-        Json(json!({"error":"storage_failure","details":err.to_string()})),
-    )
-}
+        std::fs::write(ctx.image_dir.join(&sha256), b"swapped photo").unwrap();
+        let run = jobs::run_integrity_check(&ctx.state).await.unwrap();
+        assert!(!run.healthy);
+        assert_eq!(run.failures, ["image_integrity"]);
+        assert_eq!(
+            run.images.issues[0].analysis_id.to_string(),
+            created["server_analysis_id"].as_str().unwrap()
+        );
 
-#[cfg(test)]
-mod tests {
-    use axum::{
-        body::{to_bytes, Body},
-        http::{Method, Request, StatusCode},
-        routing::{get, post},
-        Router,
-    };
-    use tower::ServiceExt;
+        let (_, health) = get_json(&ctx.app, "/health").await;
+        assert_eq!(health["status"], "degraded");
+        assert_eq!(
+            health["integrity"]["failures"],
+            serde_json::json!(["image_integrity"])
+        );
+        let (_, alerts) = get_json(
+            &ctx.app,
+            "/v1/audit-events?event_type=integrity_alert_raised",
+        )
+        .await;
+        let alerts = alerts["items"].as_array().unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(
+            alerts[0]["details"]["image_issues"][0]["reason"],
+            "hash_mismatch"
+        );
+        let (_, completed) = get_json(
+            &ctx.app,
+            "/v1/audit-events?event_type=integrity_check_completed",
+        )
+        .await;
+        assert_eq!(completed["items"].as_array().unwrap().len(), 2);
+    }
 
-    use crate::storage::AnalysisStore;
+    #[tokio::test]
+    async fn pdf_audit_register_is_paginated_with_photos_reviews_and_signed_manifest() {
+        let ctx = test_context().await;
+        let sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        let created = post_analysis(&ctx.app, photo_payload(&sha256, 300.0, 0.62)).await;
+        ctx.drain_jobs().await;
+        register_user(&ctx.app, "lab-tech-17", serde_json::json!(["qualite"])).await;
+        let id = created["server_analysis_id"].as_str().unwrap();
+        let (status, _) = post_json(
+            &ctx.app,
+            &format!("/v1/analyses/{id}/secondary-review"),
+            serde_json::json!({
+                "reviewer_id": "lab-tech-17",
+                "decision": "confirmed",
+                "reviewed_at": "2026-02-13T10:02:00Z",
+                "signature": {"password": signing_password("lab-tech-17"), "meaning": "reviewed"}
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
 
-    async fn test_app() -> Router {
-        let store = AnalysisStore::new_in_memory().await.unwrap();
-        Router::new()
-            .route("/v1/analyses", post(super::create_analysis))
-            .route("/v1/analyses/history", get(super::analyses_history))
-            .with_state(store)
-    }
+        let response = export_audit(&ctx, serde_json::json!({"format": "pdf"})).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "application/pdf");
+        let export_id = response.headers()["x-export-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let pdf = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let contains = |needle: &str| pdf.windows(needle.len()).any(|w| w == needle.as_bytes());
 
-    fn valid_payload() -> String {
-        serde_json::json!({
-            "client_analysis_id": "a8e68c43-8fba-4cad-bb7a-3d6b5d9af2aa",
-            "sample_id": "SAMPLE-2026-0001",
-            "ppm_estime": 276.4,
-            "ppm_min": 255.0,
-            "ppm_max": 298.0,
-            "compliance_status": "conforme_production",
-            "analysis_result": "CONFORME POUR LA PRODUCTION",
-            "recommended_action": "Poursuivre la production normale.",
-            "confidence": 0.93,
-            "analysis_rules_version": "analysis-rules/v1",
-            "calibration_version": "calib-2026-02",
-            "captured_at": "2026-02-13T09:45:00Z",
-            "image": {
-                "uri": "s3://rochias-analyses/2026/02/13/a8e68c43.jpg",
-                "sha256": "9f0868d4d1f8ca0f4b9f2b3f575b8f71c8e7df4a6932f53f9fdd5d6a016d89cf",
-                "content_type": "image/jpeg"
-            },
-            "acquisition_metadata": {
-                "light_condition": "conforme",
-                "ambient_lux": 620.5,
-                "temperature_celsius": 21.3,
-                "humidity_percent": 44.0,
-                "camera_focus_score": 0.88,
-                "rejection_flags": {
-                    "low_light": false,
-                    "blur_detected": false,
-                    "framing_issue": false,
-                    "overexposed": false,
-                    "strip_not_detected": false
-                },
-                "device": {
-                    "platform": "android",
-                    "model": "SM-X910",
-                    "os_version": "14",
-                    "app_version": "0.3.0"
-                }
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+        // Every cross-reference entry points at its object.
+        let tail = pdf
+            .windows(10)
+            .rposition(|w| w == b"startxref\n")
+            .map(|at| String::from_utf8_lossy(&pdf[at + 10..]).to_string())
+            .unwrap();
+        let startxref: usize = tail.lines().next().unwrap().parse().unwrap();
+        let xref = String::from_utf8_lossy(&pdf[startxref..]).to_string();
+        assert!(xref.starts_with("xref\n"));
+        for (number, entry) in xref.lines().skip(3).enumerate() {
+            if entry.starts_with("trailer") {
+                break;
             }
-        })
-        .to_string()
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(format!("{} 0 obj", number + 1).as_bytes()));
+        }
+
+        assert!(contains("/Count 2"));
+        assert!(contains("(Page 1 / 2)") && contains("(Page 2 / 2)"));
+        assert!(contains("(Registre des analyses)"));
+        assert!(contains("/Filter /DCTDecode"), "strip thumbnail embedded");
+        assert!(contains("(Confirm\\351e)"));
+
+        let (_, manifest) = get_json(&ctx.app, &format!("/v1/exports/{export_id}/manifest")).await;
+        assert_eq!(manifest["manifest"]["content_type"], "application/pdf");
+        assert_eq!(manifest["manifest"]["file_name"], "registre-audit.pdf");
+        assert_eq!(
+            manifest["manifest"]["file_sha256"],
+            crate::images::sha256_hex(&pdf)
+        );
+        let (_, events) = get_json(
+            &ctx.app,
+            "/v1/audit-events?event_type=analysis_exported_audit",
+        )
+        .await;
+        assert_eq!(events["items"][0]["details"]["format"], "pdf");
     }
 
     #[tokio::test]
-    async fn create_analysis_nominal_returns_202() {
-        let app = test_app().await;
+    async fn audit_export_job_moves_final_analyses_and_expires_its_file() {
+        let ctx = test_context_with(ServerConfig {
+            export_retention_hours: 0,
+            ..ServerConfig::default()
+        })
+        .await;
+        let sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        let settled = post_analysis(&ctx.app, photo_payload(&sha256, 300.0, 0.93)).await;
+        let flagged = post_analysis(&ctx.app, photo_payload(&sha256, 300.0, 0.62)).await;
+        // Photo never uploaded: still `recu` while the job retries.
+        let pending = post_analysis(&ctx.app, valid_payload()).await;
+        ctx.drain_jobs().await;
 
-        let response = app
+        let (status, queued) = post_json(
+            &ctx.app,
+            "/v1/analyses/audit-export",
+            serde_json::json!({"format": "csv"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(queued["status"], "queued");
+        assert!(queued["download_url"].is_null());
+        let export_id = queued["export_id"].as_str().unwrap().to_string();
+        let (status, refused) = get_json(&ctx.app, &format!("/v1/exports/{export_id}")).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(refused["status"], "queued");
+
+        ctx.drain_jobs().await;
+        let (status, ready) = get_json(&ctx.app, &format!("/v1/exports/{export_id}/status")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ready["status"], "ready");
+        assert_eq!(ready["record_count"], 3);
+        assert_eq!(ready["download_url"], format!("/v1/exports/{export_id}"));
+
+        let response = ctx
+            .app
+            .clone()
             .oneshot(
                 Request::builder()
-                    .method(Method::POST)
-                    .uri("/v1/analyses")
-                    .header("content-type", "application/json")
-                    .body(Body::from(valid_payload()))
+                    .uri(format!("/v1/exports/{export_id}"))
+                    .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-export-id"], export_id.as_str());
+        let csv = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let (_, manifest) = get_json(&ctx.app, &format!("/v1/exports/{export_id}/manifest")).await;
+        assert_eq!(
+            manifest["manifest"]["file_sha256"],
+            crate::images::sha256_hex(&csv)
+        );
 
-        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let (_, ack) = get_json(&ctx.app, &ack_uri(&settled)).await;
+        assert_eq!(ack["server_lifecycle_status"], "exporte_audit");
+        let (_, ack) = get_json(&ctx.app, &ack_uri(&flagged)).await;
+        assert_eq!(
+            ack["server_lifecycle_status"], "valide",
+            "awaits its review"
+        );
+        let (_, ack) = get_json(&ctx.app, &ack_uri(&pending)).await;
+        assert_eq!(ack["server_lifecycle_status"], "recu");
+        let (_, events) = get_json(
+            &ctx.app,
+            &format!(
+                "/v1/audit-events?event_type=analysis_exported_audit&analysis_id={}",
+                settled["server_analysis_id"].as_str().unwrap()
+            ),
+        )
+        .await;
+        let events = events["items"].as_array().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["details"]["export_id"], export_id.as_str());
+        assert_eq!(events[0]["details"]["previous_status"], "valide");
+
+        assert_eq!(jobs::expire_audit_exports(&ctx.state).await.unwrap(), 1);
+        let (status, _) = get_json(&ctx.app, &format!("/v1/exports/{export_id}")).await;
+        assert_eq!(status, StatusCode::GONE);
+        let (_, expired) = get_json(&ctx.app, &format!("/v1/exports/{export_id}/status")).await;
+        assert_eq!(expired["status"], "expired");
+        assert!(expired["download_url"].is_null());
+        let file =
+            std::path::Path::new(&ctx.state.config.export_dir).join(format!("{export_id}.csv"));
+        assert!(!file.exists());
+        let (status, _) = get_json(&ctx.app, &format!("/v1/exports/{export_id}/manifest")).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn zip_evidence_bundle_lists_signed_hashes_of_registers_photos_and_contracts() {
+        let ctx = test_context().await;
+        let sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        let photographed = post_analysis(&ctx.app, photo_payload(&sha256, 300.0, 0.93)).await;
+        let unphotographed = post_analysis(&ctx.app, valid_payload()).await;
+        ctx.drain_jobs().await;
+
+        let response = export_audit(&ctx, serde_json::json!({"format": "zip"})).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "application/zip");
+        let export_id = response.headers()["x-export-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let zip = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let (_, manifest) = get_json(&ctx.app, &format!("/v1/exports/{export_id}/manifest")).await;
+        let signed: crate::domain::SignedExportManifest = serde_json::from_value(manifest).unwrap();
+        assert_eq!(signed.manifest.file_name, "dossier-audit.zip");
+        let trusted = ctx.state.export_signer.public_key_hex();
+        assert_eq!(
+            crate::exports::verify_export(&zip, &signed, &trusted),
+            Ok(())
+        );
+
+        let bundle = crate::exports::verify_bundle(&zip, &trusted).unwrap();
+        let photo_path = format!(
+            "photos/{}.png",
+            photographed["server_analysis_id"].as_str().unwrap()
+        );
+        let paths: Vec<&str> = bundle
+            .manifest
+            .files
+            .iter()
+            .map(|file| file.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "registre-audit.csv",
+                "registre-audit.pdf",
+                "controles-manquants.csv",
+                "etalonnage/reference-swatches.csv",
+                "regles/analysis_rules_contract_v2.json",
+                photo_path.as_str(),
+            ]
+        );
+        let photo = bundle
+            .manifest
+            .files
+            .iter()
+            .find(|file| file.path == photo_path)
+            .unwrap();
+        assert_eq!(photo.sha256, sha256);
+        assert_eq!(
+            bundle.manifest.calibration_version,
+            ctx.state.verifier.calibration().calibration_version
+        );
+        assert_eq!(bundle.manifest.missing_images.len(), 1);
+        assert_eq!(
+            bundle.manifest.missing_images[0].analysis_id.to_string(),
+            unphotographed["server_analysis_id"].as_str().unwrap()
+        );
+
+        // Swapping a photo inside the archive is caught by the bundle manifest.
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(zip.to_vec())).unwrap();
+        let mut tampered = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index).unwrap();
+            let mut bytes = Vec::new();
+            std::io::Read::read_to_end(&mut entry, &mut bytes).unwrap();
+            if entry.name() == photo_path {
+                bytes.push(0);
+            }
+            tampered
+                .start_file(entry.name(), zip::write::SimpleFileOptions::default())
+                .unwrap();
+            std::io::Write::write_all(&mut tampered, &bytes).unwrap();
+        }
+        let tampered = tampered.finish().unwrap().into_inner();
+        assert_eq!(
+            crate::exports::verify_bundle(&tampered, &trusted),
+            Err(
+                crate::exports::ExportVerificationError::BundleFileMismatch {
+                    path: photo_path.clone()
+                }
+            )
+        );
+
+        // Rebuilds the archive with an extra entry written before the others.
+        let mut with_extra_entry = |name: &str, bytes: &[u8]| {
+            let stored = zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Stored);
+            let mut rebuilt = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+            rebuilt.start_file(name, stored).unwrap();
+            std::io::Write::write_all(&mut rebuilt, bytes).unwrap();
+            for index in 0..archive.len() {
+                let mut entry = archive.by_index(index).unwrap();
+                let mut bytes = Vec::new();
+                std::io::Read::read_to_end(&mut entry, &mut bytes).unwrap();
+                rebuilt.start_file(entry.name(), stored).unwrap();
+                std::io::Write::write_all(&mut rebuilt, &bytes).unwrap();
+            }
+            rebuilt.finish().unwrap().into_inner()
+        };
+        // An altered copy of the photo under the same name: the zip writer
+        // refuses duplicates, so the name is patched in the raw archive.
+        let decoy = format!("{}X", &photo_path[..photo_path.len() - 1]);
+        let mut duplicated = with_extra_entry(&decoy, b"altered photo");
+        let positions: Vec<usize> = duplicated
+            .windows(decoy.len())
+            .enumerate()
+            .filter(|(_, window)| *window == decoy.as_bytes())
+            .map(|(at, _)| at)
+            .collect();
+        assert_eq!(positions.len(), 2, "local header and central directory");
+        for at in positions {
+            duplicated[at..at + photo_path.len()].copy_from_slice(photo_path.as_bytes());
+        }
+        assert_eq!(
+            crate::exports::verify_bundle(&duplicated, &trusted),
+            Err(
+                crate::exports::ExportVerificationError::DuplicateBundleEntry {
+                    path: photo_path.clone()
+                }
+            )
+        );
+        let unlisted = with_extra_entry("photos/extra.png", b"unlisted photo");
+        assert_eq!(
+            crate::exports::verify_bundle(&unlisted, &trusted),
+            Err(
+                crate::exports::ExportVerificationError::UnlistedBundleFile {
+                    path: "photos/extra.png".to_string()
+                }
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn zip_evidence_bundle_never_includes_files_outside_the_image_store() {
+        let ctx = test_context().await;
+        let outside_dir =
+            std::env::temp_dir().join(format!("peroxyde-keys-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&outside_dir).unwrap();
+        let key_path = outside_dir.join("export-signing.key");
+        std::fs::write(&key_path, b"secret signing key").unwrap();
+        let key_sha256 = crate::images::sha256_hex(b"secret signing key");
+        // Referenced by URI, then planted in the store as a link.
+        let mut by_uri: serde_json::Value = serde_json::from_str(&valid_payload()).unwrap();
+        by_uri["image"]["uri"] = serde_json::json!(format!("file://{}", key_path.display()));
+        by_uri["image"]["sha256"] = serde_json::json!(key_sha256);
+        let by_uri = post_analysis(&ctx.app, by_uri.to_string()).await;
+        ctx.drain_jobs().await;
+
+        let response = export_audit(&ctx, serde_json::json!({"format": "zip"})).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let zip = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let trusted = ctx.state.export_signer.public_key_hex();
+        let bundle = crate::exports::verify_bundle(&zip, &trusted).unwrap();
+        assert!(bundle
+            .manifest
+            .files
+            .iter()
+            .all(|file| !file.path.starts_with("photos/")));
+        assert_eq!(bundle.manifest.missing_images.len(), 1);
+        assert_eq!(
+            bundle.manifest.missing_images[0].analysis_id.to_string(),
+            by_uri["server_analysis_id"].as_str().unwrap()
+        );
+        assert_eq!(
+            bundle.manifest.missing_images[0].reason,
+            crate::domain::ImageIssueReason::Missing
+        );
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&key_path, ctx.image_dir.join(&key_sha256)).unwrap();
+            let response = export_audit(&ctx, serde_json::json!({"format": "zip"})).await;
+            let zip = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let bundle = crate::exports::verify_bundle(&zip, &trusted).unwrap();
+            assert!(bundle
+                .manifest
+                .files
+                .iter()
+                .all(|file| !file.path.starts_with("photos/")));
+            assert_eq!(
+                bundle.manifest.missing_images[0].reason,
+                crate::domain::ImageIssueReason::Missing
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn excel_exports_type_register_cells_and_use_french_csv_conventions() {
+        let ctx = test_context().await;
+        post_analysis(&ctx.app, valid_payload()).await;
+        let mut summer: serde_json::Value = serde_json::from_str(&valid_payload()).unwrap();
+        summer["client_analysis_id"] = serde_json::json!(uuid::Uuid::new_v4());
+        summer["sample_id"] = serde_json::json!("SAMPLE-2026-0002");
+        summer["captured_at"] = serde_json::json!("2026-07-01T12:00:00Z");
+        post_analysis(&ctx.app, summer.to_string()).await;
+        ctx.drain_jobs().await;
+
+        let response = export_audit(&ctx, serde_json::json!({"csv_dialect": "excel_fr"})).await;
+        assert_eq!(response.status(), StatusCode::OK);
         let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
-        let parsed: serde_json::Value = serde_json::from_slice(&body).unwrap();
-        assert!(parsed.get("server_analysis_id").is_some());
-        assert_eq!(parsed["server_lifecycle_status"], "recu");
-        assert!(parsed.get("received_at").is_some());
+        let csv = std::str::from_utf8(&body).unwrap();
+        let csv = csv.strip_prefix('\u{feff}').expect("UTF-8 BOM for Excel");
+        assert!(csv.starts_with("server_analysis_id;client_analysis_id;sample_id;"));
+        assert!(csv.contains(";276,4;255;298;"));
+        assert!(csv.contains(";13/02/2026 10:45:00;"));
+        assert!(csv.contains(";01/07/2026 14:00:00;"));
+
+        let response = export_audit(&ctx, serde_json::json!({"format": "xlsx"})).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()["content-type"],
+            crate::xlsx::CONTENT_TYPE
+        );
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let mut workbook = zip::ZipArchive::new(std::io::Cursor::new(body.to_vec())).unwrap();
+        let mut sheet = String::new();
+        std::io::Read::read_to_string(
+            &mut workbook.by_name("xl/worksheets/sheet1.xml").unwrap(),
+            &mut sheet,
+        )
+        .unwrap();
+        assert!(sheet.contains("state=\"frozen\""));
+        assert!(sheet.contains("<autoFilter ref=\"A1:AQ3\"/>"));
+        assert!(sheet.contains("<c r=\"G2\"><v>276.4</v></c>"));
+        assert!(sheet.contains("<c r=\"AG2\" t=\"b\"><v>0</v></c>"));
+        // 2026-02-13 10:45 Paris time as an Excel serial date.
+        assert!(sheet.contains("<c r=\"P2\" s=\"2\"><v>46066.447917</v></c>"));
+        assert!(sheet.contains(
+            "<conditionalFormatting sqref=\"J2:J3\"><cfRule type=\"containsText\" dxfId=\"1\""
+        ));
+        assert!(sheet.contains("text=\"taux_bas\""));
+
+        // EU daylight saving time switches at 01:00 UTC on the last Sundays
+        // of March and October.
+        let paris = |at: &str| {
+            crate::reporting::paris_time(at.parse().unwrap())
+                .format("%d/%m %H:%M")
+                .to_string()
+        };
+        assert_eq!(paris("2026-03-29T00:59:00Z"), "29/03 01:59");
+        assert_eq!(paris("2026-03-29T01:00:00Z"), "29/03 03:00");
+        assert_eq!(paris("2026-10-25T00:59:00Z"), "25/10 02:59");
+        assert_eq!(paris("2026-10-25T01:00:00Z"), "25/10 02:00");
     }
 
     #[tokio::test]
-    async fn create_analysis_idempotent_returns_200() {
-        let app = test_app().await;
+    async fn csv_register_streamed_page_by_page_matches_the_in_memory_register() {
+        let ctx = test_context_with(ServerConfig {
+            export_page_size: 2,
+            ..ServerConfig::default()
+        })
+        .await;
+        register_user(&ctx.app, "qa-01", serde_json::json!(["qualite"])).await;
+        let mut ids = Vec::new();
+        for day in 10..14 {
+            let mut payload: serde_json::Value = serde_json::from_str(&valid_payload()).unwrap();
+            payload["client_analysis_id"] = serde_json::json!(uuid::Uuid::new_v4());
+            payload["captured_at"] = serde_json::json!(format!("2026-02-{day}T09:45:00Z"));
+            let created = post_analysis(&ctx.app, payload.to_string()).await;
+            ids.push(created["server_analysis_id"].as_str().unwrap().to_string());
+        }
+        let (status, corrected) = post_json(
+            &ctx.app,
+            &format!("/v1/analyses/{}/corrections", ids[0]),
+            serde_json::json!({
+                "corrected_by": "qa-01",
+                "justification": "Mauvais échantillon saisi",
+                "sample_id": "SAMPLE-2026-0002"
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        ctx.drain_jobs().await;
 
-        let first = app
+        let response = export_audit(&ctx, serde_json::json!({"format": "csv"})).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let export_id = response.headers()["x-export-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let streamed = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let (_, manifest) = get_json(&ctx.app, &format!("/v1/exports/{export_id}/manifest")).await;
+        let signed: crate::domain::SignedExportManifest = serde_json::from_value(manifest).unwrap();
+        assert_eq!(signed.manifest.record_count, 5);
+        assert_eq!(
+            crate::exports::verify_export(
+                &streamed,
+                &signed,
+                &ctx.state.export_signer.public_key_hex()
+            ),
+            Ok(())
+        );
+
+        let csv = std::str::from_utf8(&streamed).unwrap();
+        assert_eq!(csv.lines().count(), 6);
+        assert_eq!(csv.matches("server_analysis_id").count(), 1);
+        let original_row = csv.lines().find(|line| line.starts_with(&ids[0])).unwrap();
+        assert!(original_row.contains(corrected["server_analysis_id"].as_str().unwrap()));
+
+        // Same rows as the register built in one piece for the bundle.
+        let response = export_audit(&ctx, serde_json::json!({"format": "zip"})).await;
+        let zip = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let mut bundle = zip::ZipArchive::new(std::io::Cursor::new(zip.to_vec())).unwrap();
+        let mut bundled = Vec::new();
+        std::io::Read::read_to_end(
+            &mut bundle.by_name("registre-audit.csv").unwrap(),
+            &mut bundled,
+        )
+        .unwrap();
+        assert_eq!(streamed.to_vec(), bundled);
+    }
+
+    #[tokio::test]
+    async fn compliance_report_groups_checks_by_paris_calendar_period() {
+        let ctx = test_context_with(ServerConfig {
+            job_max_attempts: 1,
+            ..ServerConfig::default()
+        })
+        .await;
+        let sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        for (confidence, captured_at) in [
+            (0.93, "2026-02-09T08:00:00Z"),
+            // 00:30 on Tuesday in Paris.
+            (0.62, "2026-02-09T23:30:00Z"),
+            (0.93, "2026-02-16T08:00:00Z"),
+        ] {
+            let mut payload: serde_json::Value =
+                serde_json::from_str(&photo_payload(&sha256, 300.0, confidence)).unwrap();
+            payload["captured_at"] = serde_json::json!(captured_at);
+            post_analysis(&ctx.app, payload.to_string()).await;
+        }
+        // No photo: rejected by the server, and opens a deviation.
+        post_analysis(
+            &ctx.app,
+            recontrol_payload(
+                None,
+                "bac-p3-1",
+                620.0,
+                "seuil_depasse",
+                "2026-02-11T10:00:00Z",
+            )
+            .to_string(),
+        )
+        .await;
+        ctx.drain_jobs().await;
+
+        let range = "from=2026-02-08T23:00:00Z&to=2026-02-22T22:59:59Z";
+        let (status, weekly) = get_json(
+            &ctx.app,
+            &format!("/v1/reports/compliance?period=week&{range}"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let periods = weekly["periods"].as_array().unwrap();
+        assert_eq!(periods.len(), 2);
+        assert_eq!(periods[0]["period"], "2026-W07");
+        assert_eq!(periods[0]["period_start"], "2026-02-09");
+        assert_eq!(periods[0]["checks"], 3);
+        assert_eq!(periods[0]["conforme_production"], 2);
+        assert_eq!(periods[0]["seuil_depasse_percent"], 33.3);
+        assert_eq!(periods[0]["ppm_mean"], 406.7);
+        assert_eq!(periods[0]["ppm_min"], 300.0);
+        assert_eq!(periods[0]["ppm_max"], 620.0);
+        assert_eq!(periods[0]["rejected_captures"], 1);
+        // The low-confidence check and the out-of-range one.
+        assert_eq!(periods[0]["awaiting_review"], 2);
+        assert_eq!(periods[0]["deviations_opened"], 1);
+        assert_eq!(periods[0]["open_deviations"], 1);
+        assert_eq!(periods[1]["checks"], 1);
+        assert_eq!(periods[1]["deviations_opened"], 0);
+        assert_eq!(periods[1]["open_deviations"], 1);
+
+        let (_, daily) = get_json(&ctx.app, &format!("/v1/reports/compliance?{range}")).await;
+        let days = daily["periods"].as_array().unwrap();
+        assert_eq!(days.len(), 14);
+        assert_eq!(days[0]["checks"], 1);
+        assert_eq!(days[1]["period"], "2026-02-10");
+        assert_eq!(days[1]["checks"], 1);
+        assert!(days[3]["ppm_mean"].is_null());
+
+        let response = ctx
+            .app
             .clone()
             .oneshot(
                 Request::builder()
-                    .method(Method::POST)
-                    .uri("/v1/analyses")
-                    .header("content-type", "application/json")
-                    .body(Body::from(valid_payload()))
+                    .uri(format!(
+                        "/v1/reports/compliance?period=month&format=csv&{range}"
+                    ))
+                    .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
-        assert_eq!(first.status(), StatusCode::ACCEPTED);
+        assert_eq!(
+            response.headers()["content-type"],
+            "text/csv; charset=utf-8"
+        );
+        let csv = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let csv = std::str::from_utf8(&csv).unwrap();
+        assert!(csv.starts_with("period,period_start,checks,conforme_production,"));
+        assert!(csv
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("2026-02,2026-02-01,4,3,0,1,75.0,"));
+        let response = ctx
+            .app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!(
+                        "/v1/reports/compliance?period=month&format=csv&csv_dialect=excel_fr&{range}"
+                    ))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let csv = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let csv = std::str::from_utf8(&csv).unwrap();
+        assert!(csv.starts_with("\u{feff}period;period_start;checks;"));
+        assert!(csv
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("2026-02;2026-02-01;4;3;0;1;75,0;"));
 
-        let second = app
+        let response = ctx
+            .app
+            .clone()
             .oneshot(
                 Request::builder()
-                    .method(Method::POST)
-                    .uri("/v1/analyses")
-                    .header("content-type", "application/json")
-                    .body(Body::from(valid_payload()))
+                    .uri(format!("/v1/reports/compliance?format=pdf&{range}"))
+                    .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
-        assert_eq!(second.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "application/pdf");
+        let pdf = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+
+        let (status, _) = get_json(
+            &ctx.app,
+            "/v1/reports/compliance?from=2026-02-10T00:00:00Z&to=2026-02-01T00:00:00Z",
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let (status, _) = get_json(
+            &ctx.app,
+            "/v1/reports/compliance?from=2016-01-01T00:00:00Z&to=2026-01-01T00:00:00Z",
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
     }
 
     #[tokio::test]
-    async fn create_analysis_invalid_json_returns_400() {
-        let app = test_app().await;
+    async fn spc_chart_raises_a_trend_signal_before_the_control_limit() {
+        let ctx = test_context_with(ServerConfig {
+            job_max_attempts: 1,
+            spc_baseline_points: 4,
+            ..ServerConfig::default()
+        })
+        .await;
+        let sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        // Baseline 300/304, then a steady decline still inside the limits.
+        let readings = [
+            300.0, 304.0, 300.0, 304.0, 303.0, 302.0, 301.0, 300.0, 299.0,
+        ];
+        let mut ids = Vec::new();
+        for (hour, ppm) in readings.into_iter().enumerate() {
+            let mut payload: serde_json::Value =
+                serde_json::from_str(&photo_payload(&sha256, ppm, 0.93)).unwrap();
+            payload["captured_at"] = serde_json::json!(format!("2026-02-13T{hour:02}:00:00Z"));
+            ids.push(
+                post_analysis(&ctx.app, payload.to_string()).await["server_analysis_id"].clone(),
+            );
+        }
+        ctx.drain_jobs().await;
 
-        let response = app
+        let (status, chart) = get_json(&ctx.app, "/v1/spc/baths/bac-p3-1/chart").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(chart["limits"]["center"], 302.0);
+        assert_eq!(chart["limits"]["moving_range_center"], 4.0);
+        let lcl = chart["limits"]["lower_control_limit"].as_f64().unwrap();
+        assert!(299.0 > lcl);
+        let points = chart["points"].as_array().unwrap();
+        assert_eq!(points.len(), 9);
+        assert!(points[0]["moving_range"].is_null());
+        assert_eq!(points[8]["signals"], serde_json::json!(["six_trending"]));
+        assert!(points[..8]
+            .iter()
+            .all(|point| point["signals"].as_array().unwrap().is_empty()));
+
+        let (_, signals) = get_json(&ctx.app, "/v1/spc/signals?bath_id=bac-p3-1").await;
+        let items = signals["items"].as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["analysis_id"], ids[8]);
+        assert_eq!(items[0]["rule"], "six_trending");
+        assert_eq!(items[0]["severity"], "trend");
+        assert_eq!(items[0]["direction"], "down");
+        let (_, events) = get_json(&ctx.app, "/v1/audit-events?event_type=spc_signal_raised").await;
+        assert_eq!(events["items"].as_array().unwrap().len(), 1);
+
+        // Later checks leave the frozen limits alone, whatever the window.
+        for (hour, ppm) in [(9, 310.0), (10, 311.0)] {
+            let mut payload: serde_json::Value =
+                serde_json::from_str(&photo_payload(&sha256, ppm, 0.93)).unwrap();
+            payload["captured_at"] = serde_json::json!(format!("2026-02-13T{hour:02}:00:00Z"));
+            post_analysis(&ctx.app, payload.to_string()).await;
+        }
+        ctx.drain_jobs().await;
+        let (_, recent) = get_json(&ctx.app, "/v1/spc/baths/bac-p3-1/chart?limit=4").await;
+        assert_eq!(recent["limits"], chart["limits"]);
+        assert_eq!(recent["points"].as_array().unwrap().len(), 4);
+        let (_, events) = get_json(&ctx.app, "/v1/audit-events?event_type=spc_limits_frozen").await;
+        assert_eq!(events["items"].as_array().unwrap().len(), 1);
+
+        // Before its baseline is complete, a bath has no limits.
+        let (_, other) = get_json(&ctx.app, "/v1/spc/baths/bac-p3-2/chart").await;
+        assert!(other["limits"].is_null());
+    }
+
+    #[tokio::test]
+    async fn threshold_forecast_fits_the_decay_since_the_last_dosing() {
+        let ctx = test_context_with(ServerConfig {
+            job_max_attempts: 1,
+            check_interval_hours: 48,
+            ..ServerConfig::default()
+        })
+        .await;
+        let sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        // 280 before the dosing, then about -3.6 % per hour.
+        let readings = [280.0, 330.0, 318.0, 306.0, 295.0, 285.0];
+        let mut ids = Vec::new();
+        for (hour, ppm) in readings.into_iter().enumerate() {
+            let mut payload: serde_json::Value =
+                serde_json::from_str(&photo_payload(&sha256, ppm, 0.93)).unwrap();
+            payload["captured_at"] = serde_json::json!(format!("2026-02-13T{hour:02}:00:00Z"));
+            ids.push(
+                post_analysis(&ctx.app, payload.to_string()).await["server_analysis_id"].clone(),
+            );
+        }
+        ctx.drain_jobs().await;
+
+        let (status, forecast) = get_json(&ctx.app, "/v1/forecasts/baths/bac-p3-1").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(forecast["status"], "predicted");
+        assert_eq!(forecast["threshold_ppm"], 100.0);
+        assert_eq!(forecast["points_used"], 5);
+        assert_eq!(forecast["segment_start"], "2026-02-13T01:00:00Z");
+        assert_eq!(forecast["next_check_at"], "2026-02-15T05:00:00Z");
+        let rate = forecast["decay_rate_per_hour"].as_f64().unwrap();
+        assert!((rate - 0.037).abs() < 0.002, "{rate}");
+        let crossing: chrono::DateTime<chrono::Utc> =
+            serde_json::from_value(forecast["predicted_crossing_at"].clone()).unwrap();
+        let earliest: chrono::DateTime<chrono::Utc> =
+            serde_json::from_value(forecast["crossing_earliest_at"].clone()).unwrap();
+        let latest: chrono::DateTime<chrono::Utc> =
+            serde_json::from_value(forecast["crossing_latest_at"].clone()).unwrap();
+        assert!(earliest < crossing && crossing < latest);
+        assert_eq!(crossing.format("%Y-%m-%dT%H").to_string(), "2026-02-14T09");
+        assert_eq!(forecast["alert"], true);
+
+        let (_, later_check) = get_json(
+            &ctx.app,
+            "/v1/forecasts/baths/bac-p3-1?next_check_at=2026-02-13T13:00:00Z",
+        )
+        .await;
+        assert_eq!(later_check["alert"], false);
+
+        // Only the latest check of the bath raises the alert.
+        let (_, events) = get_json(
+            &ctx.app,
+            "/v1/audit-events?event_type=threshold_crossing_forecast",
+        )
+        .await;
+        let items = events["items"].as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["analysis_id"], ids[5]);
+
+        let (_, other_bath) = get_json(&ctx.app, "/v1/forecasts/baths/bac-p3-2").await;
+        assert_eq!(other_bath["status"], "insufficient_data");
+        assert!(other_bath["next_check_at"].is_null());
+    }
+
+    #[tokio::test]
+    async fn control_plan_gaps_list_late_and_missing_checks_and_alert_once() {
+        let ctx = test_context().await;
+        let start = Utc::now() - chrono::Duration::hours(10);
+        ctx.state
+            .store
+            .insert_control_plan(crate::domain::ControlPlan {
+                id: uuid::Uuid::new_v4(),
+                bath_id: "bac-p3-1".to_string(),
+                interval_minutes: 120,
+                grace_minutes: 15,
+                active: true,
+                effective_from: start,
+                updated_by: "qa-01".to_string(),
+            })
+            .await
+            .unwrap();
+        // On time, then 30 minutes late, then nothing for more than 5 hours.
+        let mut ids = Vec::new();
+        for minutes in [110, 260] {
+            let mut payload: serde_json::Value = serde_json::from_str(&valid_payload()).unwrap();
+            payload["client_analysis_id"] = serde_json::json!(uuid::Uuid::new_v4());
+            payload["captured_at"] = serde_json::json!(start + chrono::Duration::minutes(minutes));
+            ids.push(
+                post_analysis(&ctx.app, payload.to_string()).await["server_analysis_id"].clone(),
+            );
+        }
+
+        assert_eq!(jobs::run_control_gap_check(&ctx.state).await.unwrap(), 1);
+        assert_eq!(jobs::run_control_gap_check(&ctx.state).await.unwrap(), 0);
+        let (_, events) =
+            get_json(&ctx.app, "/v1/audit-events?event_type=control_check_missed").await;
+        let events = events["items"].as_array().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["analysis_id"], ids[1]);
+
+        let (status, gaps) = get_json(&ctx.app, "/v1/control-gaps?bath_id=bac-p3-1").await;
+        assert_eq!(status, StatusCode::OK);
+        let items = gaps["items"].as_array().unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0]["status"], "late");
+        assert_eq!(items[0]["previous_analysis_id"], ids[0]);
+        assert_eq!(items[0]["next_analysis_id"], ids[1]);
+        assert_eq!(items[0]["missed_checks"], 1);
+        assert_eq!(items[0]["overdue_minutes"], 30);
+        assert_eq!(items[1]["status"], "missing");
+        assert!(items[1]["next_check_at"].is_null());
+        // Due 6h20 and 8h20 after the plan start; the next one is still ahead.
+        assert_eq!(items[1]["missed_checks"], 2);
+        assert!((219..=220).contains(&items[1]["overdue_minutes"].as_i64().unwrap()));
+
+        let response = ctx
+            .app
+            .clone()
             .oneshot(
                 Request::builder()
-                    .method(Method::POST)
-                    .uri("/v1/analyses")
-                    .header("content-type", "application/json")
-                    .body(Body::from("{invalid-json"))
+                    .uri("/v1/control-gaps?format=csv")
+                    .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
+        assert_eq!(
+            response.headers()["content-type"],
+            "text/csv; charset=utf-8"
+        );
+        let csv = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let csv = std::str::from_utf8(&csv).unwrap();
+        assert!(csv.starts_with("bath_id,plan_id,status,"));
+        assert_eq!(csv.lines().count(), 3);
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = export_audit(&ctx, serde_json::json!({"format": "zip"})).await;
+        let zip = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let mut bundle = zip::ZipArchive::new(std::io::Cursor::new(zip.to_vec())).unwrap();
+        let mut bundled = String::new();
+        std::io::Read::read_to_string(
+            &mut bundle.by_name("controles-manquants.csv").unwrap(),
+            &mut bundled,
+        )
+        .unwrap();
+        assert_eq!(bundled.lines().count(), 3);
+
+        let (status, _) = send_json(
+            &ctx.app,
+            Method::PUT,
+            "/v1/control-plans/bac-p3-1",
+            serde_json::json!({"interval_minutes": 120, "updated_by": "op-17"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        register_user(&ctx.app, "qa-01", serde_json::json!(["qualite"])).await;
+        let (status, _) = send_json(
+            &ctx.app,
+            Method::PUT,
+            "/v1/control-plans/bac-p3-1",
+            serde_json::json!({"interval_minutes": 2, "updated_by": "qa-01"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let (status, plan) = send_json(
+            &ctx.app,
+            Method::PUT,
+            "/v1/control-plans/bac-p3-1",
+            serde_json::json!({"interval_minutes": 240, "grace_minutes": 30, "updated_by": "qa-01"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(plan["active"], true);
+        let (_, plans) = get_json(&ctx.app, "/v1/control-plans?bath_id=bac-p3-1").await;
+        assert_eq!(plans["items"].as_array().unwrap().len(), 2);
+        assert_eq!(plans["items"][1]["interval_minutes"], 240);
+        // Gaps of the former version stay listed; the new one starts fresh.
+        let (_, gaps) = get_json(&ctx.app, "/v1/control-gaps").await;
+        assert_eq!(gaps["items"].as_array().unwrap().len(), 2);
     }
 
     #[tokio::test]
-    async fn create_analysis_business_validation_fails_returns_422() {
-        let app = test_app().await;
+    async fn shift_report_is_generated_once_at_shift_end_and_downloadable_as_pdf() {
+        let ctx = test_context().await;
+        let sha256 = store_strip_photo(&ctx.image_dir, [178, 157, 140]);
+        // Morning shift of 13/02/2026, 06:00 to 14:00 in Paris.
+        let conforme = post_analysis(&ctx.app, photo_payload(&sha256, 300.0, 0.93)).await;
+        let doubtful = post_analysis(&ctx.app, photo_payload(&sha256, 300.0, 0.62)).await;
+        // The strip reads about 300 ppm: the server rejects the 620 ppm result.
+        let mut alert = recontrol_payload(
+            None,
+            "bac-p3-2",
+            620.0,
+            "seuil_depasse",
+            "2026-02-13T10:30:00Z",
+        );
+        alert["image"]["sha256"] = serde_json::json!(sha256);
+        let alert = post_analysis(&ctx.app, alert.to_string()).await;
+        let mut later: serde_json::Value =
+            serde_json::from_str(&photo_payload(&sha256, 300.0, 0.93)).unwrap();
+        later["captured_at"] = serde_json::json!("2026-02-13T13:30:00Z");
+        post_analysis(&ctx.app, later.to_string()).await;
+        ctx.drain_jobs().await;
+        ctx.state
+            .store
+            .insert_control_plan(crate::domain::ControlPlan {
+                id: uuid::Uuid::new_v4(),
+                bath_id: "bac-p3-3".to_string(),
+                interval_minutes: 60,
+                grace_minutes: 0,
+                active: true,
+                effective_from: "2026-02-13T05:00:00Z".parse().unwrap(),
+                updated_by: "qa-01".to_string(),
+            })
+            .await
+            .unwrap();
 
-        let mut payload: serde_json::Value = serde_json::from_str(&valid_payload()).unwrap();
-        payload["confidence"] = serde_json::json!(1.7);
+        let now: chrono::DateTime<chrono::Utc> = "2026-02-13T13:05:00Z".parse().unwrap();
+        let mut reports = jobs::generate_due_shift_reports(&ctx.state, now)
+            .await
+            .unwrap();
+        assert_eq!(reports.len(), 1, "report for the morning shift");
+        let report = reports.remove(0);
+        assert!(jobs::generate_due_shift_reports(&ctx.state, now)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(report.shift_start.to_rfc3339(), "2026-02-13T05:00:00+00:00");
+        assert_eq!(report.shift_end.to_rfc3339(), "2026-02-13T13:00:00+00:00");
+        let check_ids: Vec<String> = report
+            .checks
+            .iter()
+            .map(|check| check.analysis_id.to_string())
+            .collect();
+        assert_eq!(
+            check_ids,
+            [&conforme, &doubtful, &alert]
+                .map(|created| created["server_analysis_id"].as_str().unwrap().to_string())
+        );
+        assert_eq!(report.conforme_production, 2);
+        assert_eq!(report.seuil_depasse, 1);
+        assert_eq!(report.rejected_captures, 1);
+        assert_eq!(report.open_deviations.len(), 1);
+        assert_eq!(report.open_deviations[0].bath_id, "bac-p3-2");
+        assert!(report.pending_reviews.iter().any(|review| {
+            review.analysis_id.to_string() == doubtful["server_analysis_id"]
+                && review.review_reasons == [crate::domain::ReviewReason::LowConfidence]
+        }));
+        assert_eq!(report.missed_checks.len(), 1);
+        assert_eq!(report.missed_checks[0].bath_id, "bac-p3-3");
+        assert_eq!(report.missed_checks[0].missed_checks, 8);
 
-        let response = app
+        let (status, list) = get_json(&ctx.app, "/v1/shift-reports").await;
+        assert_eq!(status, StatusCode::OK);
+        let items = list["items"].as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["id"], report.id.to_string());
+        assert_eq!(items[0]["checks"], 3);
+        assert_eq!(items[0]["missed_checks"], 1);
+
+        let response = ctx
+            .app
+            .clone()
             .oneshot(
                 Request::builder()
-                    .method(Method::POST)
-                    .uri("/v1/analyses")
-                    .header("content-type", "application/json")
-                    .body(Body::from(payload.to_string()))
+                    .uri(format!("/v1/shift-reports/{}?format=pdf", report.id))
+                    .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "application/pdf");
+        assert_eq!(
+            response.headers()["content-disposition"],
+            "attachment; filename=passation-poste-20260213-06h.pdf"
+        );
+        let pdf = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let contains = |needle: &str| pdf.windows(needle.len()).any(|w| w == needle.as_bytes());
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        assert!(contains("(du 13/02/2026 06:00 au 13/02/2026 14:00)"));
+        assert!(contains("(Page 1 / 1)"));
 
-        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let (status, _) = get_json(
+            &ctx.app,
+            "/v1/shift-reports/2f58c716-9707-4fd1-9f6f-1ba0990f6378",
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (_, events) = get_json(
+            &ctx.app,
+            "/v1/audit-events?event_type=shift_report_generated",
+        )
+        .await;
+        assert_eq!(events["items"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn shift_reports_catch_up_every_shift_missed_while_down() {
+        let ctx = test_context().await;
+        let at = |value: &str| value.parse::<chrono::DateTime<chrono::Utc>>().unwrap();
+        let shift_starts = |reports: Vec<crate::domain::ShiftReport>| -> Vec<String> {
+            reports
+                .iter()
+                .map(|report| report.shift_start.to_rfc3339())
+                .collect()
+        };
+        assert_eq!(
+            shift_starts(
+                jobs::generate_due_shift_reports(&ctx.state, at("2026-02-13T13:05:00Z"))
+                    .await
+                    .unwrap()
+            ),
+            ["2026-02-13T05:00:00+00:00"]
+        );
+
+        // Down from 13:10 to 05:10 UTC: the afternoon and night shifts ended.
+        let now = at("2026-02-14T05:10:00Z");
+        assert_eq!(
+            shift_starts(
+                jobs::generate_due_shift_reports(&ctx.state, now)
+                    .await
+                    .unwrap()
+            ),
+            ["2026-02-13T13:00:00+00:00", "2026-02-13T21:00:00+00:00"]
+        );
+        assert!(jobs::generate_due_shift_reports(&ctx.state, now)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Beyond the look-back, older shifts are left out.
+        let reports = jobs::generate_due_shift_reports(&ctx.state, at("2026-03-02T05:10:00Z"))
+            .await
+            .unwrap();
+        assert_eq!(
+            reports.first().unwrap().shift_start.to_rfc3339(),
+            "2026-02-23T13:00:00+00:00"
+        );
+        assert_eq!(
+            reports.last().unwrap().shift_start.to_rfc3339(),
+            "2026-03-01T21:00:00+00:00"
+        );
     }
 }
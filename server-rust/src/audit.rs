@@ -0,0 +1,107 @@
+use std::net::SocketAddr;
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Request},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    domain::{AuditEvent, AuditEventType},
+    images::sha256_hex,
+    storage::{AnalysisStore, StorageError},
+};
+
+/// Same ceiling as axum's default `Json` body limit.
+const MAX_AUDITED_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Who called, from where and with which body. Captured once per request by
+/// [`capture_audit_context`] and copied into every audit event it produces.
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+    pub actor: Option<String>,
+    pub client_ip: Option<String>,
+    pub device: Option<String>,
+    pub payload_sha256: Option<String>,
+}
+
+/// Buffers the request body to digest it, then hands the request on
+/// unchanged with an [`AuditContext`] extension.
+pub async fn capture_audit_context(request: Request, next: Next) -> Result<Response, StatusCode> {
+    let (mut parts, body) = request.into_parts();
+    let bytes = axum::body::to_bytes(body, MAX_AUDITED_BODY_BYTES)
+        .await
+        .map_err(|_| StatusCode::PAYLOAD_TOO_LARGE)?;
+
+    let forwarded_for = header(&parts.headers, "x-forwarded-for")
+        .and_then(|value| value.split(',').next().map(|ip| ip.trim().to_string()));
+    let peer = parts
+        .extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string());
+
+    let context = AuditContext {
+        actor: header(&parts.headers, "x-actor-id"),
+        client_ip: forwarded_for.or(peer),
+        device: header(&parts.headers, "x-device-id")
+            .or_else(|| header(&parts.headers, "user-agent")),
+        payload_sha256: (!bytes.is_empty()).then(|| sha256_hex(&bytes)),
+    };
+    parts.extensions.insert(context);
+
+    Ok(next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await)
+}
+
+fn header(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.trim().is_empty())
+        .map(str::to_owned)
+}
+
+impl AuditContext {
+    /// Context for events raised outside a request, e.g. by the job workers.
+    pub fn server() -> Self {
+        Self {
+            actor: Some("server".to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_device(mut self, device: String) -> Self {
+        self.device = Some(device);
+        self
+    }
+
+    /// `actor` comes from the payload when the request names one (reviewer,
+    /// operator…); otherwise the `X-Actor-Id` header is used.
+    pub async fn record(
+        &self,
+        store: &AnalysisStore,
+        event_type: AuditEventType,
+        analysis_id: Option<Uuid>,
+        actor: Option<&str>,
+        details: serde_json::Value,
+    ) -> Result<(), StorageError> {
+        store
+            .record_audit_event(AuditEvent {
+                id: Uuid::new_v4(),
+                event_type,
+                analysis_id,
+                actor: actor.map(str::to_owned).or_else(|| self.actor.clone()),
+                client_ip: self.client_ip.clone(),
+                device: self.device.clone(),
+                payload_sha256: self.payload_sha256.clone(),
+                details,
+                occurred_at: Utc::now(),
+            })
+            .await
+    }
+}
@@ -1,7 +1,263 @@
+use std::{f64::consts::PI, fmt, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::images::sha256_hex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LabColor {
+    pub l: f64,
+    pub a: f64,
+    pub b: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferenceSwatch {
+    pub ppm: f64,
+    pub lab: LabColor,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PpmEstimate {
+    pub ppm_estime: f64,
+    pub ppm_min: f64,
+    pub ppm_max: f64,
+    pub delta_e00_to_lower: f64,
+    pub delta_e00_to_upper: f64,
+}
+
+/// Reference scale loaded from `reference-swatches.csv`, the same table the
+/// mobile app interpolates against.
+#[derive(Debug, Clone)]
+pub struct CalibrationScale {
+    pub calibration_version: String,
+    /// SHA-256 of the swatches file, the record signed on activation.
+    pub source_sha256: String,
+    swatches: Vec<ReferenceSwatch>,
+}
+
+#[derive(Debug)]
+pub enum CalibrationError {
+    Io(String),
+    Format(String),
+}
+
+impl fmt::Display for CalibrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "calibration io error: {err}"),
+            Self::Format(err) => write!(f, "calibration format error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for CalibrationError {}
+
+impl CalibrationScale {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, CalibrationError> {
+        let bytes = std::fs::read(path).map_err(|err| CalibrationError::Io(err.to_string()))?;
+        let mut reader = csv::Reader::from_reader(bytes.as_slice());
+
+        #[derive(Deserialize)]
+        struct Row {
+            calibration_version: String,
+            ppm: f64,
+            #[serde(rename = "L_star")]
+            l_star: f64,
+            a_star: f64,
+            b_star: f64,
+        }
+
+        let mut calibration_version = None;
+        let mut swatches = Vec::new();
+        for row in reader.deserialize::<Row>() {
+            let row = row.map_err(|err| CalibrationError::Format(err.to_string()))?;
+            match &calibration_version {
+                None => calibration_version = Some(row.calibration_version.clone()),
+                Some(version) if *version != row.calibration_version => {
+                    return Err(CalibrationError::Format(format!(
+                        "mixed calibration versions: {version} and {}",
+                        row.calibration_version
+                    )));
+                }
+                Some(_) => {}
+            }
+            swatches.push(ReferenceSwatch {
+                ppm: row.ppm,
+                lab: LabColor {
+                    l: row.l_star,
+                    a: row.a_star,
+                    b: row.b_star,
+                },
+            });
+        }
+
+        if swatches.len() < 2 {
+            return Err(CalibrationError::Format(
+                "at least two reference swatches are required".to_string(),
+            ));
+        }
+        swatches.sort_by(|a, b| a.ppm.total_cmp(&b.ppm));
+
+        Ok(Self {
+            calibration_version: calibration_version.unwrap_or_default(),
+            source_sha256: sha256_hex(&bytes),
+            swatches,
+        })
+    }
+
+    /// Interpolates between the two swatches closest in ΔE00, mirroring
+    /// `CoreAnalysisModule.estimatePpm` on the Android side.
+    pub fn estimate_ppm(&self, sample: LabColor) -> PpmEstimate {
+        let mut ranked: Vec<(&ReferenceSwatch, f64)> = self
+            .swatches
+            .iter()
+            .map(|swatch| (swatch, delta_e00(sample, swatch.lab)))
+            .collect();
+        ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let (lower, d_lower) = ranked[0];
+        let (upper, d_upper) = ranked[1];
+
+        let total = d_lower + d_upper;
+        let t = if total == 0.0 {
+            0.5
+        } else {
+            (d_lower / total).clamp(0.0, 1.0)
+        };
+
+        PpmEstimate {
+            ppm_estime: lower.ppm + t * (upper.ppm - lower.ppm),
+            ppm_min: lower.ppm.min(upper.ppm),
+            ppm_max: lower.ppm.max(upper.ppm),
+            delta_e00_to_lower: d_lower,
+            delta_e00_to_upper: d_upper,
+        }
+    }
+}
+
+pub fn srgb_to_lab(r: u8, g: u8, b: u8) -> LabColor {
+    let r = srgb_to_linear(f64::from(r) / 255.0);
+    let g = srgb_to_linear(f64::from(g) / 255.0);
+    let b = srgb_to_linear(f64::from(b) / 255.0);
+
+    let x = (0.4124564 * r + 0.3575761 * g + 0.1804375 * b) / 0.95047;
+    let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+    let z = (0.0193339 * r + 0.1191920 * g + 0.9503041 * b) / 1.08883;
+
+    let fx = lab_f(x);
+    let fy = lab_f(y);
+    let fz = lab_f(z);
+
+    LabColor {
+        l: 116.0 * fy - 16.0,
+        a: 500.0 * (fx - fy),
+        b: 200.0 * (fy - fz),
+    }
+}
+
+pub fn delta_e00(first: LabColor, second: LabColor) -> f64 {
+    let l_bar = (first.l + second.l) / 2.0;
+    let c1 = first.a.hypot(first.b);
+    let c2 = second.a.hypot(second.b);
+    let c_bar = (c1 + c2) / 2.0;
+
+    let g = 0.5 * (1.0 - (c_bar.powi(7) / (c_bar.powi(7) + 25f64.powi(7))).sqrt());
+    let a1_prime = (1.0 + g) * first.a;
+    let a2_prime = (1.0 + g) * second.a;
+    let c1_prime = a1_prime.hypot(first.b);
+    let c2_prime = a2_prime.hypot(second.b);
+
+    let h1_prime = hue_radians(a1_prime, first.b);
+    let h2_prime = hue_radians(a2_prime, second.b);
+
+    let delta_l_prime = second.l - first.l;
+    let delta_c_prime = c2_prime - c1_prime;
+    let delta_h_prime = delta_hue_prime(h1_prime, h2_prime, c1_prime, c2_prime);
+
+    let s_l = 1.0 + (0.015 * (l_bar - 50.0).powi(2)) / (20.0 + (l_bar - 50.0).powi(2)).sqrt();
+    let c_bar_prime = (c1_prime + c2_prime) / 2.0;
+    let h_bar_prime = average_hue_prime(h1_prime, h2_prime, c1_prime, c2_prime);
+    let t = 1.0 - 0.17 * (h_bar_prime - PI / 6.0).cos()
+        + 0.24 * (2.0 * h_bar_prime).cos()
+        + 0.32 * (3.0 * h_bar_prime + PI / 30.0).cos()
+        - 0.20 * (4.0 * h_bar_prime - 63.0 * PI / 180.0).cos();
+    let s_c = 1.0 + 0.045 * c_bar_prime;
+    let s_h = 1.0 + 0.015 * c_bar_prime * t;
+
+    let delta_theta =
+        30.0 * PI / 180.0 * (-((h_bar_prime * 180.0 / PI - 275.0) / 25.0).powi(2)).exp();
+    let r_c = 2.0 * (c_bar_prime.powi(7) / (c_bar_prime.powi(7) + 25f64.powi(7))).sqrt();
+    let r_t = -r_c * (2.0 * delta_theta).sin();
+
+    let l_term = delta_l_prime / s_l;
+    let c_term = delta_c_prime / s_c;
+    let h_term = delta_h_prime / s_h;
+
+    (l_term.powi(2) + c_term.powi(2) + h_term.powi(2) + r_t * c_term * h_term).sqrt()
+}
+
+fn srgb_to_linear(value: f64) -> f64 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn lab_f(value: f64) -> f64 {
+    if value > 216.0 / 24389.0 {
+        value.cbrt()
+    } else {
+        (24389.0 / 27.0 * value + 16.0) / 116.0
+    }
+}
+
+fn hue_radians(a: f64, b: f64) -> f64 {
+    if a == 0.0 && b == 0.0 {
+        return 0.0;
+    }
+    let hue = b.atan2(a);
+    if hue >= 0.0 {
+        hue
+    } else {
+        hue + 2.0 * PI
+    }
+}
+
+fn delta_hue_prime(h1: f64, h2: f64, c1_prime: f64, c2_prime: f64) -> f64 {
+    if c1_prime == 0.0 || c2_prime == 0.0 {
+        return 0.0;
+    }
+    let mut delta = h2 - h1;
+    if delta > PI {
+        delta -= 2.0 * PI;
+    }
+    if delta < -PI {
+        delta += 2.0 * PI;
+    }
+    2.0 * (c1_prime * c2_prime).sqrt() * (delta / 2.0).sin()
+}
+
+fn average_hue_prime(h1: f64, h2: f64, c1_prime: f64, c2_prime: f64) -> f64 {
+    if c1_prime == 0.0 || c2_prime == 0.0 {
+        return h1 + h2;
+    }
+    if (h1 - h2).abs() <= PI {
+        (h1 + h2) / 2.0
+    } else if h1 + h2 < 2.0 * PI {
+        (h1 + h2 + 2.0 * PI) / 2.0
+    } else {
+        (h1 + h2 - 2.0 * PI) / 2.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::error::Error;
 
+    use super::{srgb_to_lab, CalibrationScale};
+
     #[derive(Debug)]
     struct SwatchRow {
         ppm: u32,
@@ -16,7 +272,10 @@ mod tests {
         let header = lines.next().ok_or("missing header")?;
 
         let columns: Vec<&str> = header.split(',').collect();
-        let ppm_idx = columns.iter().position(|c| *c == "ppm").ok_or("missing ppm")?;
+        let ppm_idx = columns
+            .iter()
+            .position(|c| *c == "ppm")
+            .ok_or("missing ppm")?;
         let l_idx = columns
             .iter()
             .position(|c| *c == "L_star")
@@ -83,4 +342,25 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn server_scale_recovers_reference_ppm_from_srgb() {
+        let scale = CalibrationScale::load("../data/calibration/reference-swatches.csv")
+            .expect("load reference swatches");
+        assert_eq!(scale.calibration_version, "calib-2026-02-14T09:30:00Z");
+
+        for (rgb, expected_ppm) in [
+            ((241, 221, 140), 0.0),
+            ((213, 191, 145), 100.0),
+            ((178, 157, 140), 300.0),
+            ((148, 124, 121), 500.0),
+        ] {
+            let estimate = scale.estimate_ppm(srgb_to_lab(rgb.0, rgb.1, rgb.2));
+            assert!(
+                (estimate.ppm_estime - expected_ppm).abs() <= 15.0,
+                "expected ~{expected_ppm} ppm, got {}",
+                estimate.ppm_estime
+            );
+        }
+    }
 }
@@ -0,0 +1,162 @@
+use std::str::FromStr;
+
+/// Runtime settings, read once from the environment at startup.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub database_url: String,
+    pub calibration_swatches_path: String,
+    /// Rules contract of `ANALYSIS_RULES_VERSION`, shipped in evidence bundles.
+    pub rules_contract_path: String,
+    pub image_store_dir: String,
+    pub verification_workers: usize,
+    pub job_max_attempts: u32,
+    pub job_retry_base_seconds: i64,
+    pub job_poll_interval_ms: u64,
+    pub review_claim_timeout_minutes: i64,
+    /// Hex Ed25519 seed signing export manifests; created on first start.
+    pub export_signing_key_path: String,
+    /// Where generated audit exports are kept until they expire.
+    pub export_dir: String,
+    pub export_retention_hours: i64,
+    /// Analyses read from SQLite per batch while streaming a CSV register.
+    pub export_page_size: usize,
+    /// Oldest validated checks of a bath used to compute its control limits.
+    pub spc_baseline_points: usize,
+    /// Most recent validated checks kept on a bath control chart.
+    pub spc_window_points: usize,
+    /// Most recent validated checks considered by the threshold forecast.
+    pub forecast_window_points: usize,
+    /// Rise between two checks read as a dosing, starting a new decay.
+    pub forecast_dosing_jump_ppm: f64,
+    /// Delay between two scheduled checks of a bath.
+    pub check_interval_hours: i64,
+    /// Period of the missed check detection; 0 disables it.
+    pub control_gap_check_interval_minutes: u64,
+    /// Paris local hours at which shifts start, sorted.
+    pub shift_start_hours: Vec<u32>,
+    /// RFC 3161 Time Stamping Authority (`http://` only); timestamping is
+    /// disabled when unset.
+    pub tsa_url: Option<String>,
+    /// PEM or DER certificate of the TSA; its key must have signed every
+    /// token. Required when `tsa_url` is set.
+    pub tsa_cert_path: Option<String>,
+    pub tsa_timeout_seconds: u64,
+    /// Period of the scheduled integrity verification; 0 disables it.
+    pub integrity_check_interval_minutes: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            database_url: "data/analyses.sqlite".to_string(),
+            calibration_swatches_path: "../data/calibration/reference-swatches.csv".to_string(),
+            rules_contract_path: "../data/validation/analysis_rules_contract_v2.json".to_string(),
+            image_store_dir: "data/images".to_string(),
+            verification_workers: 2,
+            job_max_attempts: 5,
+            job_retry_base_seconds: 30,
+            job_poll_interval_ms: 500,
+            review_claim_timeout_minutes: 30,
+            export_signing_key_path: "data/keys/export-signing.key".to_string(),
+            export_dir: "data/exports".to_string(),
+            export_retention_hours: 24 * 7,
+            export_page_size: 500,
+            spc_baseline_points: 20,
+            spc_window_points: 100,
+            forecast_window_points: 12,
+            forecast_dosing_jump_ppm: 20.0,
+            check_interval_hours: 8,
+            control_gap_check_interval_minutes: 5,
+            shift_start_hours: vec![6, 14, 22],
+            tsa_url: None,
+            tsa_cert_path: None,
+            tsa_timeout_seconds: 10,
+            integrity_check_interval_minutes: 60,
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            database_url: env_parse("DATABASE_URL", defaults.database_url),
+            calibration_swatches_path: env_parse(
+                "CALIBRATION_SWATCHES_PATH",
+                defaults.calibration_swatches_path,
+            ),
+            rules_contract_path: env_parse("RULES_CONTRACT_PATH", defaults.rules_contract_path),
+            image_store_dir: env_parse("IMAGE_STORE_DIR", defaults.image_store_dir),
+            verification_workers: env_parse("VERIFICATION_WORKERS", defaults.verification_workers),
+            job_max_attempts: env_parse("JOB_MAX_ATTEMPTS", defaults.job_max_attempts),
+            job_retry_base_seconds: env_parse(
+                "JOB_RETRY_BASE_SECONDS",
+                defaults.job_retry_base_seconds,
+            ),
+            job_poll_interval_ms: env_parse("JOB_POLL_INTERVAL_MS", defaults.job_poll_interval_ms),
+            review_claim_timeout_minutes: env_parse(
+                "REVIEW_CLAIM_TIMEOUT_MINUTES",
+                defaults.review_claim_timeout_minutes,
+            ),
+            export_signing_key_path: env_parse(
+                "EXPORT_SIGNING_KEY_PATH",
+                defaults.export_signing_key_path,
+            ),
+            export_dir: env_parse("EXPORT_DIR", defaults.export_dir),
+            export_retention_hours: env_parse(
+                "EXPORT_RETENTION_HOURS",
+                defaults.export_retention_hours,
+            ),
+            export_page_size: env_parse("EXPORT_PAGE_SIZE", defaults.export_page_size).max(1),
+            spc_baseline_points: env_parse("SPC_BASELINE_POINTS", defaults.spc_baseline_points)
+                .max(2),
+            spc_window_points: env_parse("SPC_WINDOW_POINTS", defaults.spc_window_points),
+            forecast_window_points: env_parse(
+                "FORECAST_WINDOW_POINTS",
+                defaults.forecast_window_points,
+            ),
+            forecast_dosing_jump_ppm: env_parse(
+                "FORECAST_DOSING_JUMP_PPM",
+                defaults.forecast_dosing_jump_ppm,
+            ),
+            check_interval_hours: env_parse("CHECK_INTERVAL_HOURS", defaults.check_interval_hours),
+            control_gap_check_interval_minutes: env_parse(
+                "CONTROL_GAP_CHECK_INTERVAL_MINUTES",
+                defaults.control_gap_check_interval_minutes,
+            ),
+            shift_start_hours: std::env::var("SHIFT_START_HOURS")
+                .ok()
+                .and_then(|hours| parse_hours(&hours))
+                .unwrap_or(defaults.shift_start_hours),
+            tsa_url: std::env::var("TSA_URL")
+                .ok()
+                .filter(|url| !url.trim().is_empty()),
+            tsa_cert_path: std::env::var("TSA_CERT_PATH")
+                .ok()
+                .filter(|path| !path.trim().is_empty()),
+            tsa_timeout_seconds: env_parse("TSA_TIMEOUT_SECONDS", defaults.tsa_timeout_seconds),
+            integrity_check_interval_minutes: env_parse(
+                "INTEGRITY_CHECK_INTERVAL_MINUTES",
+                defaults.integrity_check_interval_minutes,
+            ),
+        }
+    }
+}
+
+/// `6,14,22`: distinct hours of the day, returned sorted.
+fn parse_hours(value: &str) -> Option<Vec<u32>> {
+    let mut hours = value
+        .split(',')
+        .map(|hour| hour.trim().parse::<u32>().ok().filter(|hour| *hour < 24))
+        .collect::<Option<Vec<_>>>()?;
+    hours.sort_unstable();
+    hours.dedup();
+    (!hours.is_empty()).then_some(hours)
+}
+
+fn env_parse<T: FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
@@ -0,0 +1,204 @@
+//! Control plan: how often each bath must be checked while its production
+//! runs, and the missed or late checks against it.
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::{
+    domain::{ControlGap, ControlGapStatus, ControlPlan, ProductionEvent, ProductionEventKind},
+    storage::{AnalysisStore, StorageError},
+};
+
+/// Production stops of a bath: from a stop to the following restart, or
+/// still running when the line has not restarted.
+fn stop_periods(events: &[ProductionEvent]) -> Vec<(DateTime<Utc>, Option<DateTime<Utc>>)> {
+    let mut periods = Vec::new();
+    let mut stopped_at = None;
+    for event in events {
+        match (event.kind, stopped_at) {
+            (ProductionEventKind::Stop, None) => stopped_at = Some(event.occurred_at),
+            (ProductionEventKind::Restart, Some(start)) => {
+                periods.push((start, Some(event.occurred_at)));
+                stopped_at = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = stopped_at {
+        periods.push((start, None));
+    }
+    periods
+}
+
+/// Time reached after `minutes` of production from `start`, or `None` when
+/// the line stops before and never restarts.
+fn advance(
+    start: DateTime<Utc>,
+    minutes: i64,
+    stops: &[(DateTime<Utc>, Option<DateTime<Utc>>)],
+) -> Option<DateTime<Utc>> {
+    let mut at = start;
+    let mut remaining = Duration::minutes(minutes);
+    for &(stop, restart) in stops {
+        if restart.is_some_and(|restart| restart <= at) {
+            continue;
+        }
+        if stop > at {
+            if stop - at >= remaining {
+                return Some(at + remaining);
+            }
+            remaining -= stop - at;
+        }
+        at = restart?;
+    }
+    Some(at + remaining)
+}
+
+/// Gaps of one plan version, in force until `until` (the next version or
+/// the evaluation time). `checks` are in capture order.
+fn plan_gaps(
+    plan: &ControlPlan,
+    until: DateTime<Utc>,
+    checks: &[(Uuid, DateTime<Utc>)],
+    stops: &[(DateTime<Utc>, Option<DateTime<Utc>>)],
+) -> Vec<ControlGap> {
+    let interval = i64::from(plan.interval_minutes.max(1));
+    let grace = Duration::minutes(plan.grace_minutes.into());
+    // Due times from `base` whose grace delay ran out before `end`.
+    let missed = |base: DateTime<Utc>, end: DateTime<Utc>| {
+        (1..)
+            .map_while(|k| advance(base, k * interval, stops))
+            .take_while(|due| *due + grace < end)
+            .count() as u32
+    };
+
+    let mut gaps = Vec::new();
+    let mut checks = checks
+        .iter()
+        .filter(|(_, at)| *at >= plan.effective_from && *at < until);
+    let mut previous: Option<(Uuid, DateTime<Utc>)> = None;
+    loop {
+        let base = previous.map_or(plan.effective_from, |(_, at)| at);
+        let Some(due_at) = advance(base, interval, stops) else {
+            break;
+        };
+        if due_at >= until {
+            break;
+        }
+        let next = checks.next().copied();
+        let end = next.map_or(until, |(_, at)| at);
+        if end > due_at + grace {
+            gaps.push(ControlGap {
+                bath_id: plan.bath_id.clone(),
+                plan_id: plan.id,
+                status: if next.is_some() {
+                    ControlGapStatus::Late
+                } else {
+                    ControlGapStatus::Missing
+                },
+                previous_analysis_id: previous.map(|(id, _)| id),
+                previous_check_at: previous.map(|(_, at)| at),
+                due_at,
+                next_analysis_id: next.map(|(id, _)| id),
+                next_check_at: next.map(|(_, at)| at),
+                missed_checks: missed(base, end),
+                overdue_minutes: (end - due_at).num_minutes(),
+            });
+        }
+        match next {
+            Some(check) => previous = Some(check),
+            None => break,
+        }
+    }
+    gaps
+}
+
+/// Missed or late checks of the active plans up to `now`, whose due time
+/// falls within `[from, to]`, in due order.
+pub async fn list_gaps(
+    store: &AnalysisStore,
+    bath_id: Option<String>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<Vec<ControlGap>, StorageError> {
+    let plans = store.list_control_plans(bath_id).await?;
+    let mut gaps = Vec::new();
+    for versions in plans.chunk_by(|a, b| a.bath_id == b.bath_id) {
+        let bath_id = versions[0].bath_id.clone();
+        let events = store
+            .list_production_events(Some(bath_id.clone()), None, None)
+            .await?;
+        let stops = stop_periods(&events);
+        let checks = store
+            .list_check_times(bath_id, versions[0].effective_from)
+            .await?;
+        for (index, plan) in versions.iter().enumerate() {
+            let until = versions
+                .get(index + 1)
+                .map_or(now, |next| next.effective_from)
+                .min(now);
+            if plan.active {
+                gaps.extend(plan_gaps(plan, until, &checks, &stops));
+            }
+        }
+    }
+    gaps.retain(|gap| {
+        from.is_none_or(|from| gap.due_at >= from) && to.is_none_or(|to| gap.due_at <= to)
+    });
+    gaps.sort_by(|a, b| a.due_at.cmp(&b.due_at).then(a.bath_id.cmp(&b.bath_id)));
+    Ok(gaps)
+}
+
+/// Interval of the active plan of the bath, `default` without one.
+pub async fn check_interval(
+    store: &AnalysisStore,
+    bath_id: &str,
+    default: Duration,
+) -> Result<Duration, StorageError> {
+    Ok(store
+        .list_control_plans(Some(bath_id.to_string()))
+        .await?
+        .last()
+        .filter(|plan| plan.active)
+        .map_or(default, |plan| {
+            Duration::minutes(plan.interval_minutes.into())
+        }))
+}
+
+pub fn gaps_csv(gaps: &[ControlGap]) -> Result<Vec<u8>, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for gap in gaps {
+        writer.serialize(gap).map_err(|err| err.to_string())?;
+    }
+    writer.flush().map_err(|err| err.to_string())?;
+    writer.into_inner().map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+
+    use super::advance;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn production_stops_pause_the_control_clock() {
+        let stops = [
+            (at("2026-02-13T09:00:00Z"), Some(at("2026-02-13T10:30:00Z"))),
+            (at("2026-02-13T12:00:00Z"), None),
+        ];
+        assert_eq!(
+            advance(at("2026-02-13T08:00:00Z"), 120, &stops),
+            Some(at("2026-02-13T11:30:00Z"))
+        );
+        assert_eq!(
+            advance(at("2026-02-13T09:30:00Z"), 60, &stops),
+            Some(at("2026-02-13T11:30:00Z"))
+        );
+        assert_eq!(advance(at("2026-02-13T11:00:00Z"), 120, &stops), None);
+    }
+}
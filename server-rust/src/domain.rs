@@ -1,7 +1,9 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::rules::VersionedAnalysisDecision;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum ComplianceStatus {
@@ -38,6 +40,21 @@ pub struct RejectionFlags {
     pub strip_not_detected: bool,
 }
 
+impl RejectionFlags {
+    pub fn raised(&self) -> Vec<&'static str> {
+        [
+            ("low_light", self.low_light),
+            ("blur_detected", self.blur_detected),
+            ("framing_issue", self.framing_issue),
+            ("overexposed", self.overexposed),
+            ("strip_not_detected", self.strip_not_detected),
+        ]
+        .into_iter()
+        .filter_map(|(name, raised)| raised.then_some(name))
+        .collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct DeviceMetadata {
     pub platform: String,
@@ -69,6 +86,9 @@ pub struct Analysis {
     pub id: Uuid,
     pub client_analysis_id: Uuid,
     pub sample_id: String,
+    pub operator_id: String,
+    pub bath_id: String,
+    pub parent_analysis_id: Option<Uuid>,
     pub ppm_estime: f32,
     pub ppm_min: f32,
     pub ppm_max: f32,
@@ -83,12 +103,28 @@ pub struct Analysis {
     pub image: ImageReference,
     pub acquisition_metadata: AcquisitionMetadata,
     pub server_lifecycle_status: ServerLifecycleStatus,
+    /// 1 for a phone submission, incremented by each correction.
+    pub version: u32,
+    pub supersedes_analysis_id: Option<Uuid>,
+    pub correction: Option<AnalysisCorrection>,
+}
+
+/// Justification of a superseding version. The superseded row is never
+/// modified; a voided version withdraws the scan from production records.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AnalysisCorrection {
+    pub justification: String,
+    pub corrected_by: String,
+    pub voided: bool,
 }
 
 impl Analysis {
     pub fn is_same_submission(&self, other: &Self) -> bool {
         self.client_analysis_id == other.client_analysis_id
             && self.sample_id == other.sample_id
+            && self.operator_id == other.operator_id
+            && self.bath_id == other.bath_id
+            && self.parent_analysis_id == other.parent_analysis_id
             && self.ppm_estime == other.ppm_estime
             && self.ppm_min == other.ppm_min
             && self.ppm_max == other.ppm_max
@@ -103,3 +139,926 @@ impl Analysis {
             && self.acquisition_metadata == other.acquisition_metadata
     }
 }
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationCheck {
+    PayloadRules,
+    DecisionConsistency,
+    CaptureConditions,
+    ImageAvailable,
+    ImageIntegrity,
+    ImageDecodable,
+    ImageQuality,
+    ServerPpmAgreement,
+    ServerDecisionAgreement,
+}
+
+impl VerificationCheck {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::PayloadRules => "payload_rules",
+            Self::DecisionConsistency => "decision_consistency",
+            Self::CaptureConditions => "capture_conditions",
+            Self::ImageAvailable => "image_available",
+            Self::ImageIntegrity => "image_integrity",
+            Self::ImageDecodable => "image_decodable",
+            Self::ImageQuality => "image_quality",
+            Self::ServerPpmAgreement => "server_ppm_agreement",
+            Self::ServerDecisionAgreement => "server_decision_agreement",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FailedCheck {
+    pub check: VerificationCheck,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewReason {
+    LowConfidence,
+    ClientServerDiscrepancy,
+    NonCompliant,
+}
+
+/// Outcome of the server-side re-verification of an analysis, persisted so the
+/// acknowledgement reflects what was actually checked.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AnalysisVerification {
+    pub analysis_id: Uuid,
+    pub validated_schema: bool,
+    pub validated_business_rules: bool,
+    pub failed_checks: Vec<FailedCheck>,
+    pub review_reasons: Vec<ReviewReason>,
+    pub server_ppm_estime: Option<f32>,
+    pub server_decision: Option<VersionedAnalysisDecision>,
+    pub verified_at: DateTime<Utc>,
+}
+
+impl AnalysisVerification {
+    pub fn queued_for_secondary_review(&self) -> bool {
+        !self.review_reasons.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    VerifyAnalysis,
+    /// Produces the audit export whose id is the job subject.
+    GenerateAuditExport,
+    /// Applies the control chart rules to the bath of the validated analysis
+    /// whose id is the job subject.
+    EvaluateSpc,
+    /// Forecasts when the bath of the validated analysis whose id is the job
+    /// subject falls below the low threshold.
+    ForecastThreshold,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Job {
+    pub id: Uuid,
+    pub kind: JobKind,
+    pub subject_id: Uuid,
+    pub status: JobStatus,
+    pub attempts: u32,
+    pub max_attempts: u32,
+    pub run_after: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewDecision {
+    Confirmed,
+    Rejected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SecondaryReview {
+    pub id: Uuid,
+    pub analysis_id: Uuid,
+    pub reviewer_id: String,
+    pub decision: ReviewDecision,
+    pub comment: Option<String>,
+    pub reviewed_at: DateTime<Utc>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Temporary assignment of a review-queue item to one reviewer.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReviewClaim {
+    pub analysis_id: Uuid,
+    pub reviewer_id: String,
+    pub claimed_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl ReviewClaim {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at > now
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UserRole {
+    Operateur,
+    ChefEquipe,
+    Qualite,
+    Maintenance,
+    Administrateur,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct User {
+    pub user_id: String,
+    pub display_name: String,
+    pub roles: Vec<UserRole>,
+    pub active: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl User {
+    pub fn has_role(&self, role: UserRole) -> bool {
+        self.active && self.roles.contains(&role)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEventType {
+    AnalysisReceived,
+    AnalysisSchemaValidated,
+    AnalysisBusinessValidated,
+    AnalysisRejected,
+    AnalysisCorrected,
+    AnalysisViewed,
+    AnalysisAckViewed,
+    AnalysisHistoryViewed,
+    AnalysisSecondaryReviewCompleted,
+    AnalysisSecondaryReviewRefused,
+    AnalysisExportedAudit,
+    AuditExportRequested,
+    AuditExportFailed,
+    AuditExportViewed,
+    AuditExportDownloaded,
+    AuditExportExpired,
+    ReviewQueueViewed,
+    ReviewClaimed,
+    ReviewClaimReleased,
+    DeviationsViewed,
+    CorrectiveActionCreated,
+    CorrectiveActionCompleted,
+    CorrectiveActionVerified,
+    CorrectiveActionsViewed,
+    ProductionEventRecorded,
+    ProductionEventRefused,
+    ProductionEventsViewed,
+    IntegrityVerified,
+    ElectronicSignatureApplied,
+    ElectronicSignatureRefused,
+    SignaturesViewed,
+    ExportManifestViewed,
+    ChainHeadsTimestamped,
+    ExportTimestamped,
+    TimestampRequestFailed,
+    TimestampsViewed,
+    IntegrityCheckCompleted,
+    IntegrityAlertRaised,
+    UserUpserted,
+    UsersViewed,
+    JobsViewed,
+    AuditEventsViewed,
+    ComplianceReportViewed,
+    SpcLimitsFrozen,
+    SpcSignalRaised,
+    SpcChartViewed,
+    SpcSignalsViewed,
+    ThresholdCrossingForecast,
+    ThresholdForecastViewed,
+    ControlPlanUpdated,
+    ControlPlanRefused,
+    ControlPlansViewed,
+    ControlGapsViewed,
+    ControlCheckMissed,
+    ShiftReportGenerated,
+    ShiftReportsViewed,
+    ShiftReportViewed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditEvent {
+    pub id: Uuid,
+    pub event_type: AuditEventType,
+    pub analysis_id: Option<Uuid>,
+    pub actor: Option<String>,
+    pub client_ip: Option<String>,
+    pub device: Option<String>,
+    /// SHA-256 of the raw request body, when there was one.
+    pub payload_sha256: Option<String>,
+    pub details: serde_json::Value,
+    pub occurred_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviationStatus {
+    Open,
+    Closed,
+    /// The opening result was withdrawn by a voiding correction.
+    Voided,
+}
+
+/// Out-of-range result awaiting its re-control. Times are capture times of
+/// the opening and closing analyses, not reception times.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Deviation {
+    pub id: Uuid,
+    pub bath_id: String,
+    pub opening_analysis_id: Uuid,
+    pub opening_compliance_status: ComplianceStatus,
+    pub opened_at: DateTime<Utc>,
+    pub status: DeviationStatus,
+    pub closing_analysis_id: Option<Uuid>,
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+impl Deviation {
+    pub fn elapsed(&self, now: DateTime<Utc>) -> chrono::Duration {
+        self.closed_at.unwrap_or(now) - self.opened_at
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CorrectiveActionStatus {
+    Open,
+    Completed,
+    Verified,
+}
+
+/// CAPA record. Always attached to the analysis that triggered it; actions
+/// raised from a deviation also keep the deviation id.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CorrectiveAction {
+    pub id: Uuid,
+    pub analysis_id: Uuid,
+    pub deviation_id: Option<Uuid>,
+    pub description: String,
+    pub owner_id: String,
+    pub due_at: DateTime<Utc>,
+    pub status: CorrectiveActionStatus,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub action_taken: Option<String>,
+    pub completed_by: Option<String>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub verified_by: Option<String>,
+    pub verification_comment: Option<String>,
+    pub verified_at: Option<DateTime<Utc>>,
+}
+
+impl CorrectiveAction {
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        self.status == CorrectiveActionStatus::Open && self.due_at < now
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProductionEventKind {
+    Stop,
+    Restart,
+}
+
+/// Line stop or restart on the production fed by a P3 bath. A restart
+/// carries the compliant re-control analysis that allowed it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProductionEvent {
+    pub id: Uuid,
+    pub bath_id: String,
+    pub kind: ProductionEventKind,
+    pub recorded_by: String,
+    pub occurred_at: DateTime<Utc>,
+    pub reason: String,
+    pub linked_analysis_ids: Vec<Uuid>,
+    pub recontrol_analysis_id: Option<Uuid>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BrokenLinkReason {
+    /// The entry does not point at the hash of the entry stored before it:
+    /// something was deleted, inserted or reordered.
+    PreviousHashMismatch,
+    /// The entry content no longer matches its own hash: it was edited.
+    ContentHashMismatch,
+    /// The row cannot be read back at all.
+    Unreadable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BrokenLink {
+    /// 1-based position of the entry in insertion order.
+    pub position: usize,
+    pub entry_id: String,
+    pub reason: BrokenLinkReason,
+}
+
+/// Result of walking one hash chain (`analyses` or `audit_events`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChainVerification {
+    pub entries: usize,
+    /// Hash of the last stored entry, as exported with every audit register
+    /// (the genesis hash, 64 zeros, while the chain is empty).
+    pub head: String,
+    pub first_broken_link: Option<BrokenLink>,
+}
+
+impl ChainVerification {
+    pub fn is_intact(&self) -> bool {
+        self.first_broken_link.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IntegrityReport {
+    pub intact: bool,
+    pub analyses: ChainVerification,
+    pub audit_events: ChainVerification,
+    pub timestamps: TimestampsVerification,
+    pub verified_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampSubject {
+    /// Daily timestamp of both chain heads; the subject id is the UTC date.
+    ChainHeads,
+    /// Signed export manifest; the subject id is the export id.
+    ExportManifest,
+}
+
+/// RFC 3161 token obtained from the configured Time Stamping Authority.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TrustedTimestamp {
+    pub id: Uuid,
+    pub subject_type: TimestampSubject,
+    pub subject_id: String,
+    /// Exact bytes whose SHA-256 was timestamped: the two chain heads
+    /// separated by a newline, or the compact JSON of an export manifest.
+    pub message: String,
+    pub message_sha256: String,
+    pub tsa_url: String,
+    pub policy: String,
+    pub serial_number: String,
+    pub nonce: i64,
+    /// Time asserted by the TSA.
+    pub gen_time: DateTime<Utc>,
+    pub requested_at: DateTime<Utc>,
+    /// DER token, served by `GET /v1/timestamps/{id}/token`.
+    #[serde(skip)]
+    pub token: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampFailure {
+    /// The stored token cannot be decoded.
+    Unreadable,
+    /// The token is unsigned, or not signed by the configured TSA
+    /// certificate over its TSTInfo.
+    SignatureInvalid,
+    /// No TSA certificate is configured to check the token against.
+    TsaCertificateMissing,
+    /// The token does not cover the stored message.
+    ImprintMismatch,
+    /// Nonce or generation time differ from what was recorded on receipt.
+    TokenMismatch,
+    /// The message no longer matches its subject: a timestamped chain head
+    /// vanished from its chain, or the export manifest changed.
+    SubjectMismatch,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InvalidTimestamp {
+    pub timestamp_id: Uuid,
+    pub subject_type: TimestampSubject,
+    pub subject_id: String,
+    pub reason: TimestampFailure,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TimestampsVerification {
+    pub tokens: usize,
+    /// Latest TSA time covering the chain heads, if any.
+    pub last_chain_heads_gen_time: Option<DateTime<Utc>>,
+    pub first_invalid: Option<InvalidTimestamp>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageIssueReason {
+    /// The photo was verified on receipt but is no longer in the store.
+    Missing,
+    /// The stored bytes no longer hash to `image_sha256`.
+    HashMismatch,
+    Unreadable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImageIntegrityIssue {
+    pub analysis_id: Uuid,
+    pub image_sha256: String,
+    pub reason: ImageIssueReason,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImageIntegrityScan {
+    /// Photos re-hashed: those that passed the image checks on receipt.
+    pub checked: usize,
+    /// Photos never verified intact (not yet verified, never uploaded, or
+    /// already rejected for integrity), left out of the scan.
+    pub skipped: usize,
+    pub issues: Vec<ImageIntegrityIssue>,
+}
+
+/// Outcome of one scheduled integrity verification, recorded as an
+/// `integrity_check_completed` event and summarised on `/health`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IntegrityCheckRun {
+    pub healthy: bool,
+    /// Codes of the failed checks: `image_integrity`, `analyses_chain`,
+    /// `audit_events_chain`, `timestamps`, `sqlite_integrity`.
+    pub failures: Vec<String>,
+    pub images: ImageIntegrityScan,
+    pub chains: IntegrityReport,
+    /// Output of `PRAGMA integrity_check`: `["ok"]` when sound.
+    pub sqlite_integrity: Vec<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureMeaning {
+    Reviewed,
+    Approved,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SignedRecordType {
+    SecondaryReview,
+    CalibrationActivation,
+    RulesChange,
+}
+
+/// Electronic signature applied after re-authentication of the signer. The
+/// manifestation (who, what meaning, when) is bound to the hash of the record
+/// as it was at signing time.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ElectronicSignature {
+    pub id: Uuid,
+    pub record_type: SignedRecordType,
+    /// Analysis id, calibration version or rules version.
+    pub record_id: String,
+    /// Analysis chain hash, calibration file digest or rules digest.
+    pub record_hash: String,
+    pub signer_id: String,
+    pub signer_display_name: String,
+    pub meaning: SignatureMeaning,
+    pub signed_at: DateTime<Utc>,
+    /// SHA-256 over every field above.
+    pub signature_hash: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditExportFormat {
+    #[default]
+    Csv,
+    Pdf,
+    /// Evidence bundle: both registers, the photos, the calibration and
+    /// rules files in force and a signed list of file hashes.
+    Zip,
+    /// Register as an Excel workbook with typed columns.
+    Xlsx,
+}
+
+impl AuditExportFormat {
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Csv => "registre-audit.csv",
+            Self::Pdf => "registre-audit.pdf",
+            Self::Zip => "dossier-audit.zip",
+            Self::Xlsx => "registre-audit.xlsx",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Pdf => "application/pdf",
+            Self::Zip => "application/zip",
+            Self::Xlsx => crate::xlsx::CONTENT_TYPE,
+        }
+    }
+}
+
+/// How the CSV register (alone or inside the ZIP bundle) is written.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvDialect {
+    /// Comma separator, `.` decimals, RFC 3339 UTC times.
+    #[default]
+    Standard,
+    /// For French Excel: UTF-8 BOM, `;` separator, `,` decimals and
+    /// Europe/Paris local times.
+    ExcelFr,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditExportStatus {
+    Queued,
+    Running,
+    Ready,
+    Failed,
+    /// File deleted after the retention period; the manifest is kept.
+    Expired,
+}
+
+/// Audit register requested through `POST /v1/analyses/audit-export` and
+/// produced by a `generate_audit_export` job. The file lives in
+/// `EXPORT_DIR` until `expires_at`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditExport {
+    pub id: Uuid,
+    pub format: AuditExportFormat,
+    pub csv_dialect: CsvDialect,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub status: AuditExportStatus,
+    pub requested_by: Option<String>,
+    pub requested_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub record_count: Option<usize>,
+    pub file_sha256: Option<String>,
+    pub error: Option<String>,
+}
+
+impl AuditExport {
+    /// Name of the file under `EXPORT_DIR`.
+    pub fn stored_file_name(&self) -> String {
+        let extension = match self.format {
+            AuditExportFormat::Csv => "csv",
+            AuditExportFormat::Pdf => "pdf",
+            AuditExportFormat::Zip => "zip",
+            AuditExportFormat::Xlsx => "xlsx",
+        };
+        format!("{}.{extension}", self.id)
+    }
+}
+
+/// What an audit export certifies. Signed by the server key so an auditor
+/// can check the file they were handed is the one the server produced.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExportManifest {
+    pub export_id: Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub record_count: usize,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub file_sha256: String,
+    pub chain_head_analyses: String,
+    pub chain_head_audit_events: String,
+    pub server_version: String,
+    pub generated_at: DateTime<Utc>,
+    /// Hex Ed25519 public key of the signing server.
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SignedExportManifest {
+    pub manifest: ExportManifest,
+    /// Hex Ed25519 signature over the compact JSON of `manifest`.
+    pub signature: String,
+}
+
+/// Entry of an evidence bundle, listed in its `manifest.json`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BundleFile {
+    pub path: String,
+    pub sha256: String,
+    pub size: u64,
+}
+
+/// Content of the `manifest.json` at the root of a ZIP evidence bundle.
+/// Photos that could not be included (missing or altered since receipt) are
+/// listed in `missing_images` rather than silently left out.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BundleManifest {
+    pub export_id: Uuid,
+    pub generated_at: DateTime<Utc>,
+    pub calibration_version: String,
+    pub analysis_rules_version: String,
+    pub files: Vec<BundleFile>,
+    pub missing_images: Vec<ImageIntegrityIssue>,
+    /// Hex Ed25519 public key of the signing server.
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SignedBundleManifest {
+    pub manifest: BundleManifest,
+    /// Hex Ed25519 signature over the compact JSON of `manifest`.
+    pub signature: String,
+}
+
+/// Granularity of the compliance statistics, on the Europe/Paris calendar.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportPeriod {
+    #[default]
+    Day,
+    /// ISO week, Monday to Sunday.
+    Week,
+    Month,
+}
+
+/// Compliance figures of one day, week or month. Counts cover the current
+/// version of each analysis; voided analyses are left out.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CompliancePeriodStatistics {
+    /// `2026-02-13`, `2026-W07` or `2026-02`.
+    pub period: String,
+    pub period_start: NaiveDate,
+    pub checks: usize,
+    pub conforme_production: usize,
+    pub taux_bas: usize,
+    pub seuil_depasse: usize,
+    /// Shares of `checks`, in percent rounded to 0.1; null without checks.
+    pub conforme_production_percent: Option<f64>,
+    pub taux_bas_percent: Option<f64>,
+    pub seuil_depasse_percent: Option<f64>,
+    pub ppm_mean: Option<f64>,
+    pub ppm_min: Option<f32>,
+    pub ppm_max: Option<f32>,
+    /// Analyses the server verification rejected.
+    pub rejected_captures: usize,
+    pub reviews_confirmed: usize,
+    pub reviews_rejected: usize,
+    /// Still in the secondary review queue.
+    pub awaiting_review: usize,
+    pub deviations_opened: usize,
+    /// Deviations still open at the end of the period.
+    pub open_deviations: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ComplianceReport {
+    pub period: ReportPeriod,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub bath_id: Option<String>,
+    pub generated_at: DateTime<Utc>,
+    pub periods: Vec<CompliancePeriodStatistics>,
+}
+
+/// Western Electric / Nelson rules applied to the individuals chart, plus
+/// the moving range limit.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SpcRule {
+    /// Nelson 1: one point beyond 3 sigma.
+    BeyondControlLimit,
+    /// Nelson 2: nine points in a row on the same side of the centre line.
+    NineSameSide,
+    /// Nelson 3: six points in a row steadily increasing or decreasing.
+    SixTrending,
+    /// Nelson 5: two of three points beyond 2 sigma on the same side.
+    TwoOfThreeBeyondTwoSigma,
+    /// Nelson 6: four of five points beyond 1 sigma on the same side.
+    FourOfFiveBeyondOneSigma,
+    /// Jump between two consecutive checks beyond the moving range limit.
+    MovingRangeBeyondLimit,
+}
+
+impl SpcRule {
+    pub fn severity(self) -> SpcSeverity {
+        match self {
+            Self::BeyondControlLimit | Self::MovingRangeBeyondLimit => SpcSeverity::OutOfControl,
+            _ => SpcSeverity::Trend,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SpcSeverity {
+    /// Early warning: the process drifts while still inside the limits.
+    Trend,
+    OutOfControl,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SpcDirection {
+    Up,
+    Down,
+}
+
+/// Individuals / moving range limits, frozen from the first validated checks
+/// of the bath.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ControlLimits {
+    pub center: f64,
+    /// Estimated as the mean moving range / 1.128.
+    pub sigma: f64,
+    pub upper_control_limit: f64,
+    pub lower_control_limit: f64,
+    pub moving_range_center: f64,
+    pub moving_range_upper_limit: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ControlChartPoint {
+    pub analysis_id: Uuid,
+    pub captured_at: DateTime<Utc>,
+    pub ppm_estime: f32,
+    /// Absolute difference with the previous point; null for the first one.
+    pub moving_range: Option<f64>,
+    pub signals: Vec<SpcRule>,
+}
+
+/// I-MR chart of the validated checks of one bath, oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ControlChart {
+    pub bath_id: String,
+    pub baseline_points: usize,
+    /// Null until the bath has `baseline_points` validated checks and its
+    /// limits are frozen.
+    pub limits: Option<ControlLimits>,
+    pub points: Vec<ControlChartPoint>,
+}
+
+/// Rule violation raised by the `evaluate_spc` job, at most once per
+/// analysis and rule.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SpcSignal {
+    pub id: Uuid,
+    pub bath_id: String,
+    /// Point on which the rule fired.
+    pub analysis_id: Uuid,
+    pub captured_at: DateTime<Utc>,
+    pub rule: SpcRule,
+    pub severity: SpcSeverity,
+    pub direction: SpcDirection,
+    pub ppm_estime: f32,
+    pub limits: ControlLimits,
+    pub raised_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ForecastStatus {
+    /// A crossing time was fitted.
+    Predicted,
+    /// Fewer than three checks since the last dosing.
+    InsufficientData,
+    /// The checks since the last dosing do not decrease.
+    NotDecaying,
+    /// The latest check is already below the threshold.
+    AlreadyBelow,
+}
+
+/// When the P3 concentration of a bath is expected to fall below the low
+/// threshold, from an exponential decay fitted since the last dosing.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ThresholdForecast {
+    pub bath_id: String,
+    pub status: ForecastStatus,
+    pub threshold_ppm: f64,
+    /// Checks of the decay segment used for the fit.
+    pub points_used: usize,
+    pub segment_start: Option<DateTime<Utc>>,
+    pub last_check_at: Option<DateTime<Utc>>,
+    pub last_ppm: Option<f32>,
+    /// Fitted `k` of `ppm(t) = ppm0 * exp(-k * t)`, per hour.
+    pub decay_rate_per_hour: Option<f64>,
+    pub half_life_hours: Option<f64>,
+    pub predicted_crossing_at: Option<DateTime<Utc>>,
+    /// 95 % interval of the crossing from the uncertainty of the decay rate;
+    /// `crossing_latest_at` is null when the decay could be flat.
+    pub crossing_earliest_at: Option<DateTime<Utc>>,
+    pub crossing_latest_at: Option<DateTime<Utc>>,
+    pub next_check_at: Option<DateTime<Utc>>,
+    /// The predicted crossing comes before the next scheduled check.
+    pub alert: bool,
+}
+
+/// How often a bath must be checked while its production runs. Each update
+/// is a new version, in force from `effective_from`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ControlPlan {
+    pub id: Uuid,
+    pub bath_id: String,
+    pub interval_minutes: u32,
+    /// Delay after the due time before a check counts as late.
+    pub grace_minutes: u32,
+    /// An inactive version suspends the plan.
+    pub active: bool,
+    pub effective_from: DateTime<Utc>,
+    pub updated_by: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlGapStatus {
+    /// A check eventually came, after the grace delay.
+    Late,
+    /// No check since the due time.
+    Missing,
+}
+
+/// Period of production without the check required by the control plan.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ControlGap {
+    pub bath_id: String,
+    pub plan_id: Uuid,
+    pub status: ControlGapStatus,
+    /// Last check before the gap; null when counted from the plan start.
+    pub previous_analysis_id: Option<Uuid>,
+    pub previous_check_at: Option<DateTime<Utc>>,
+    pub due_at: DateTime<Utc>,
+    pub next_analysis_id: Option<Uuid>,
+    pub next_check_at: Option<DateTime<Utc>>,
+    /// Due times passed without a check.
+    pub missed_checks: u32,
+    /// From the due time to the next check, or to the evaluation time.
+    pub overdue_minutes: i64,
+}
+
+/// Check captured during a shift, as it stood when the report was generated.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ShiftCheck {
+    pub analysis_id: Uuid,
+    pub captured_at: DateTime<Utc>,
+    pub bath_id: String,
+    pub sample_id: String,
+    pub operator_id: String,
+    pub ppm_estime: f32,
+    pub compliance_status: ComplianceStatus,
+    pub server_lifecycle_status: ServerLifecycleStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PendingReview {
+    pub analysis_id: Uuid,
+    pub bath_id: String,
+    pub captured_at: DateTime<Utc>,
+    pub review_reasons: Vec<ReviewReason>,
+    /// Reviewer holding the claim, if any.
+    pub claimed_by: Option<String>,
+}
+
+/// Handover report of one shift, generated once the shift is over and kept
+/// as it was then.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ShiftReport {
+    pub id: Uuid,
+    pub shift_start: DateTime<Utc>,
+    pub shift_end: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+    pub checks: Vec<ShiftCheck>,
+    pub conforme_production: usize,
+    pub taux_bas: usize,
+    pub seuil_depasse: usize,
+    /// Checks of the shift rejected by the server verification.
+    pub rejected_captures: usize,
+    /// Deviations still open at the end of the shift.
+    pub open_deviations: Vec<Deviation>,
+    /// Secondary review queue when the report was generated.
+    pub pending_reviews: Vec<PendingReview>,
+    /// Missed or late checks due during the shift.
+    pub missed_checks: Vec<ControlGap>,
+}
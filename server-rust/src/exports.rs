@@ -0,0 +1,312 @@
+use std::{
+    fmt, fs,
+    io::{Cursor, Read, Write},
+    path::Path,
+};
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand_core::OsRng;
+use serde::Serialize;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    domain::{
+        BundleFile, BundleManifest, ExportManifest, SignedBundleManifest, SignedExportManifest,
+    },
+    images::sha256_hex,
+};
+
+/// Signed list of file hashes at the root of an evidence bundle.
+pub const BUNDLE_MANIFEST_PATH: &str = "manifest.json";
+
+/// Holds the server Ed25519 key that signs export manifests.
+pub struct ExportSigner {
+    key: SigningKey,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ExportVerificationError {
+    UntrustedKey { manifest_key: String },
+    MalformedSignature,
+    BadSignature,
+    FileDigestMismatch { expected: String, actual: String },
+    UnreadableBundle(String),
+    BundleFileMismatch { path: String },
+    UnlistedBundleFile { path: String },
+    DuplicateBundleEntry { path: String },
+}
+
+impl fmt::Display for ExportVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UntrustedKey { manifest_key } => {
+                write!(f, "manifest signed by an untrusted key: {manifest_key}")
+            }
+            Self::MalformedSignature => write!(f, "manifest signature is malformed"),
+            Self::BadSignature => write!(f, "manifest signature does not match its content"),
+            Self::FileDigestMismatch { expected, actual } => write!(
+                f,
+                "file sha256 {actual} does not match the manifest ({expected})"
+            ),
+            Self::UnreadableBundle(err) => write!(f, "evidence bundle is unreadable: {err}"),
+            Self::BundleFileMismatch { path } => {
+                write!(f, "{path} is missing or differs from the bundle manifest")
+            }
+            Self::UnlistedBundleFile { path } => {
+                write!(f, "{path} is not listed in the bundle manifest")
+            }
+            Self::DuplicateBundleEntry { path } => {
+                write!(f, "{path} appears more than once in the bundle")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExportVerificationError {}
+
+impl ExportSigner {
+    /// Reads the hex seed stored at `path`, creating a fresh key on first
+    /// start. The file must be kept private and backed up: losing it means
+    /// past exports can only be checked against their recorded public key.
+    pub fn load_or_create<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        if path.exists() {
+            return Self::load(path);
+        }
+
+        let signer = Self::generate();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        fs::write(path, hex::encode(signer.key.to_bytes())).map_err(|err| err.to_string())?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+                .map_err(|err| err.to_string())?;
+        }
+        Ok(signer)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let seed = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let seed: [u8; 32] = hex::decode(seed.trim())
+            .map_err(|err| err.to_string())?
+            .try_into()
+            .map_err(|_| "export signing key must be a 32-byte hex seed".to_string())?;
+        Ok(Self {
+            key: SigningKey::from_bytes(&seed),
+        })
+    }
+
+    pub fn generate() -> Self {
+        Self {
+            key: SigningKey::generate(&mut OsRng),
+        }
+    }
+
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(self.key.verifying_key().to_bytes())
+    }
+
+    pub fn sign(&self, manifest: ExportManifest) -> SignedExportManifest {
+        SignedExportManifest {
+            signature: self.signature_hex(&manifest),
+            manifest,
+        }
+    }
+
+    pub fn sign_bundle(&self, manifest: BundleManifest) -> SignedBundleManifest {
+        SignedBundleManifest {
+            signature: self.signature_hex(&manifest),
+            manifest,
+        }
+    }
+
+    fn signature_hex<T: Serialize>(&self, manifest: &T) -> String {
+        hex::encode(self.key.sign(&manifest_bytes(manifest)).to_bytes())
+    }
+}
+
+/// Zips `contents` with a signed `manifest.json` listing the SHA-256 of each
+/// entry. `manifest.files` is filled in from `contents`.
+pub fn evidence_bundle(
+    signer: &ExportSigner,
+    mut manifest: BundleManifest,
+    contents: &[(String, Vec<u8>)],
+) -> Result<Vec<u8>, String> {
+    manifest.files = contents
+        .iter()
+        .map(|(path, bytes)| BundleFile {
+            path: path.clone(),
+            sha256: sha256_hex(bytes),
+            size: bytes.len() as u64,
+        })
+        .collect();
+    manifest.public_key = signer.public_key_hex();
+    let signed = signer.sign_bundle(manifest);
+    let signed_json = serde_json::to_vec_pretty(&signed).map_err(|err| err.to_string())?;
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (path, bytes) in contents
+        .iter()
+        .map(|(path, bytes)| (path.as_str(), bytes.as_slice()))
+        .chain([(BUNDLE_MANIFEST_PATH, signed_json.as_slice())])
+    {
+        zip.start_file(path, options)
+            .map_err(|err| err.to_string())?;
+        zip.write_all(bytes).map_err(|err| err.to_string())?;
+    }
+    let zip = zip.finish().map_err(|err| err.to_string())?;
+    Ok(zip.into_inner())
+}
+
+/// Checks that `file` is the one described by `signed`, and that the
+/// manifest was signed by `trusted_public_key`.
+pub fn verify_export(
+    file: &[u8],
+    signed: &SignedExportManifest,
+    trusted_public_key: &str,
+) -> Result<(), ExportVerificationError> {
+    let manifest = &signed.manifest;
+    verify_signature(
+        manifest,
+        &manifest.public_key,
+        &signed.signature,
+        trusted_public_key,
+    )?;
+
+    let actual = sha256_hex(file);
+    if actual != manifest.file_sha256 {
+        return Err(ExportVerificationError::FileDigestMismatch {
+            expected: manifest.file_sha256.clone(),
+            actual,
+        });
+    }
+    Ok(())
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Option<usize> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?).into())
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<usize> {
+    usize::try_from(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?)).ok()
+}
+
+/// Entry names as listed in the central directory, duplicates included:
+/// `ZipArchive` keeps a single entry per name and would hide them.
+fn central_directory_names(zip: &[u8]) -> Option<Vec<String>> {
+    const END_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x05, 0x06];
+    const ENTRY_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x01, 0x02];
+    // The end record is 22 bytes followed by a comment of at most 64 KiB.
+    let last = zip.len().checked_sub(22)?;
+    let end = (last.saturating_sub(u16::MAX as usize)..=last)
+        .rev()
+        .find(|at| zip[*at..*at + 4] == END_SIGNATURE)?;
+    let count = read_u16(zip, end + 10)?;
+    let mut at = read_u32(zip, end + 16)?;
+    let mut names = Vec::with_capacity(count);
+    for _ in 0..count {
+        if zip.get(at..at + 4)? != ENTRY_SIGNATURE {
+            return None;
+        }
+        let name_len = read_u16(zip, at + 28)?;
+        let extra_len = read_u16(zip, at + 30)?;
+        let comment_len = read_u16(zip, at + 32)?;
+        let name = zip.get(at + 46..at + 46 + name_len)?;
+        names.push(String::from_utf8_lossy(name).into_owned());
+        at += 46 + name_len + extra_len + comment_len;
+    }
+    Some(names)
+}
+
+/// Checks the signed `manifest.json` of an evidence bundle, then that every
+/// entry name appears once and is listed, and that every listed file is
+/// present with its hash.
+pub fn verify_bundle(
+    zip: &[u8],
+    trusted_public_key: &str,
+) -> Result<SignedBundleManifest, ExportVerificationError> {
+    let unreadable =
+        |err: &dyn fmt::Display| ExportVerificationError::UnreadableBundle(err.to_string());
+    let names = central_directory_names(zip)
+        .ok_or_else(|| unreadable(&"central directory is malformed"))?;
+    for (index, name) in names.iter().enumerate() {
+        if names[..index].contains(name) {
+            return Err(ExportVerificationError::DuplicateBundleEntry { path: name.clone() });
+        }
+    }
+    let mut archive = ZipArchive::new(Cursor::new(zip)).map_err(|err| unreadable(&err))?;
+    let mut entries = Vec::new();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(|err| unreadable(&err))?;
+        let mut bytes = Vec::new();
+        entry
+            .read_to_end(&mut bytes)
+            .map_err(|err| unreadable(&err))?;
+        entries.push((entry.name().to_string(), bytes));
+    }
+    let manifest_json = entries
+        .iter()
+        .find(|(path, _)| path == BUNDLE_MANIFEST_PATH)
+        .map(|(_, bytes)| bytes)
+        .ok_or_else(|| unreadable(&format!("{BUNDLE_MANIFEST_PATH} is missing")))?;
+    let signed: SignedBundleManifest =
+        serde_json::from_slice(manifest_json).map_err(|err| unreadable(&err))?;
+    let manifest = &signed.manifest;
+    verify_signature(
+        manifest,
+        &manifest.public_key,
+        &signed.signature,
+        trusted_public_key,
+    )?;
+
+    if let Some(path) = names.iter().find(|path| {
+        *path != BUNDLE_MANIFEST_PATH && !manifest.files.iter().any(|file| file.path == **path)
+    }) {
+        return Err(ExportVerificationError::UnlistedBundleFile { path: path.clone() });
+    }
+    for listed in &manifest.files {
+        let intact = entries
+            .iter()
+            .find(|(path, _)| *path == listed.path)
+            .is_some_and(|(_, bytes)| sha256_hex(bytes) == listed.sha256);
+        if !intact {
+            return Err(ExportVerificationError::BundleFileMismatch {
+                path: listed.path.clone(),
+            });
+        }
+    }
+    Ok(signed)
+}
+
+fn verify_signature<T: Serialize>(
+    manifest: &T,
+    manifest_key: &str,
+    signature: &str,
+    trusted_public_key: &str,
+) -> Result<(), ExportVerificationError> {
+    let untrusted = || ExportVerificationError::UntrustedKey {
+        manifest_key: manifest_key.to_string(),
+    };
+    if !manifest_key.eq_ignore_ascii_case(trusted_public_key) {
+        return Err(untrusted());
+    }
+    let key = hex::decode(trusted_public_key)
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+        .ok_or_else(untrusted)?;
+    let signature = hex::decode(signature)
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+        .ok_or(ExportVerificationError::MalformedSignature)?;
+    key.verify(&manifest_bytes(manifest), &signature)
+        .map_err(|_| ExportVerificationError::BadSignature)
+}
+
+fn manifest_bytes<T: Serialize>(manifest: &T) -> Vec<u8> {
+    serde_json::to_vec(manifest).expect("export manifest serializes")
+}
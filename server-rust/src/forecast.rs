@@ -0,0 +1,248 @@
+//! Time-to-threshold forecast: exponential decay of the P3 concentration
+//! fitted on the checks of a bath since its last dosing.
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    domain::{ForecastStatus, ThresholdForecast},
+    rules::LOW_PPM_THRESHOLD,
+    storage::SpcPoint,
+};
+
+/// Two-sided 95 % Student quantiles for 1 to 10 degrees of freedom.
+const T_QUANTILES_95: [f64; 10] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+];
+/// Used beyond ten degrees of freedom.
+const T_QUANTILE_95_LARGE: f64 = 2.0;
+
+/// Trailing checks since the last rise of more than `dosing_jump_ppm`.
+fn decay_segment(points: &[SpcPoint], dosing_jump_ppm: f64) -> &[SpcPoint] {
+    let start = points
+        .windows(2)
+        .rposition(|pair| (pair[1].ppm_estime - pair[0].ppm_estime) as f64 > dosing_jump_ppm)
+        .map_or(0, |index| index + 1);
+    &points[start..]
+}
+
+fn hours_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    (to - from).num_milliseconds() as f64 / 3_600_000.0
+}
+
+fn at_hours(origin: DateTime<Utc>, hours: f64) -> Option<DateTime<Utc>> {
+    // Beyond about a century the crossing is meaningless.
+    (hours.is_finite() && hours.abs() < 1e6)
+        .then(|| origin + Duration::milliseconds((hours * 3_600_000.0) as i64))
+}
+
+/// Forecast for `points`, the latest validated checks of the bath oldest
+/// first. `next_check_at` defaults to the last check plus `check_interval`.
+pub fn threshold_forecast(
+    bath_id: String,
+    points: &[SpcPoint],
+    dosing_jump_ppm: f64,
+    check_interval: Duration,
+    next_check_at: Option<DateTime<Utc>>,
+) -> ThresholdForecast {
+    let threshold = LOW_PPM_THRESHOLD as f64;
+    let segment = decay_segment(points, dosing_jump_ppm);
+    let last = points.last();
+    let mut forecast = ThresholdForecast {
+        bath_id,
+        status: ForecastStatus::InsufficientData,
+        threshold_ppm: threshold,
+        points_used: segment.len(),
+        segment_start: segment.first().map(|point| point.captured_at),
+        last_check_at: last.map(|point| point.captured_at),
+        last_ppm: last.map(|point| point.ppm_estime),
+        decay_rate_per_hour: None,
+        half_life_hours: None,
+        predicted_crossing_at: None,
+        crossing_earliest_at: None,
+        crossing_latest_at: None,
+        next_check_at: next_check_at
+            .or_else(|| last.map(|point| point.captured_at + check_interval)),
+        alert: false,
+    };
+
+    if last.is_some_and(|point| (point.ppm_estime as f64) < threshold) {
+        forecast.status = ForecastStatus::AlreadyBelow;
+        return forecast;
+    }
+    if segment.len() < 3 {
+        return forecast;
+    }
+
+    // Least squares on ln(ppm) = a - k * t, t in hours since the segment start.
+    let origin = segment[0].captured_at;
+    let samples: Vec<(f64, f64)> = segment
+        .iter()
+        .map(|point| {
+            (
+                hours_between(origin, point.captured_at),
+                (point.ppm_estime as f64).max(1.0).ln(),
+            )
+        })
+        .collect();
+    let n = samples.len() as f64;
+    let mean_t = samples.iter().map(|(t, _)| t).sum::<f64>() / n;
+    let mean_y = samples.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let sxx: f64 = samples.iter().map(|(t, _)| (t - mean_t).powi(2)).sum();
+    if sxx <= 0.0 {
+        return forecast;
+    }
+    let slope = samples
+        .iter()
+        .map(|(t, y)| (t - mean_t) * (y - mean_y))
+        .sum::<f64>()
+        / sxx;
+    let decay_rate = -slope;
+    if decay_rate <= 0.0 {
+        forecast.status = ForecastStatus::NotDecaying;
+        return forecast;
+    }
+
+    let residuals: f64 = samples
+        .iter()
+        .map(|(t, y)| (y - (mean_y + slope * (t - mean_t))).powi(2))
+        .sum();
+    let degrees = samples.len() - 2;
+    let slope_error = (residuals / degrees as f64 / sxx).sqrt();
+    let quantile = T_QUANTILES_95
+        .get(degrees - 1)
+        .copied()
+        .unwrap_or(T_QUANTILE_95_LARGE);
+
+    // The fitted line goes through (mean_t, mean_y); the crossing only moves
+    // with the rate around that point.
+    let crossing_after = |rate: f64| mean_t + (mean_y - threshold.ln()) / rate;
+    let fastest = decay_rate + quantile * slope_error;
+    let slowest = decay_rate - quantile * slope_error;
+
+    forecast.status = ForecastStatus::Predicted;
+    forecast.decay_rate_per_hour = Some(decay_rate);
+    forecast.half_life_hours = Some(std::f64::consts::LN_2 / decay_rate);
+    forecast.predicted_crossing_at = at_hours(origin, crossing_after(decay_rate));
+    forecast.crossing_earliest_at = at_hours(origin, crossing_after(fastest));
+    forecast.crossing_latest_at = (slowest > 0.0)
+        .then(|| at_hours(origin, crossing_after(slowest)))
+        .flatten();
+    forecast.alert = matches!(
+        (forecast.predicted_crossing_at, forecast.next_check_at),
+        (Some(crossing), Some(next_check)) if crossing <= next_check
+    );
+    forecast
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, Utc};
+    use uuid::Uuid;
+
+    use super::{decay_segment, threshold_forecast};
+    use crate::{
+        domain::{ForecastStatus, ThresholdForecast},
+        storage::SpcPoint,
+    };
+
+    fn origin() -> DateTime<Utc> {
+        "2026-02-13T06:00:00Z".parse().unwrap()
+    }
+
+    /// One check every 8 hours from `origin()`.
+    fn points(ppm: &[f32]) -> Vec<SpcPoint> {
+        ppm.iter()
+            .enumerate()
+            .map(|(index, ppm)| SpcPoint {
+                analysis_id: Uuid::new_v4(),
+                captured_at: origin() + Duration::hours(8 * index as i64),
+                ppm_estime: *ppm,
+            })
+            .collect()
+    }
+
+    fn forecast(ppm: &[f32], next_check_at: Option<DateTime<Utc>>) -> ThresholdForecast {
+        threshold_forecast(
+            "bac-p3-1".to_string(),
+            &points(ppm),
+            20.0,
+            Duration::hours(8),
+            next_check_at,
+        )
+    }
+
+    #[test]
+    fn segment_starts_after_the_last_rise_beyond_the_dosing_jump() {
+        let checks = points(&[300.0, 250.0, 400.0, 350.0, 300.0]);
+        let segment = decay_segment(&checks, 20.0);
+        assert_eq!(segment.len(), 3);
+        assert_eq!(segment[0].ppm_estime, 400.0);
+        // A rise of exactly the jump is measurement noise, not a dosing.
+        assert_eq!(
+            decay_segment(&points(&[300.0, 280.0, 300.0]), 20.0).len(),
+            3
+        );
+        assert_eq!(
+            decay_segment(&points(&[300.0, 280.0, 301.0]), 20.0).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn exact_decay_gives_its_half_life_and_a_collapsed_band() {
+        // 24 h half-life from 400 ppm: 100 ppm is reached after 48 h.
+        let ppm: Vec<f32> = (0..4)
+            .map(|index| 400.0 * 0.5f32.powf(8.0 * index as f32 / 24.0))
+            .collect();
+        let forecast = forecast(&ppm, None);
+        assert_eq!(forecast.status, ForecastStatus::Predicted);
+        assert_eq!(forecast.points_used, 4);
+        assert!((forecast.half_life_hours.unwrap() - 24.0).abs() < 1e-3);
+        let expected = origin() + Duration::hours(48);
+        for crossing in [
+            forecast.predicted_crossing_at,
+            forecast.crossing_earliest_at,
+            forecast.crossing_latest_at,
+        ] {
+            assert!((crossing.unwrap() - expected).num_seconds().abs() < 60);
+        }
+        assert_eq!(forecast.next_check_at, Some(origin() + Duration::hours(32)));
+        assert!(!forecast.alert);
+    }
+
+    #[test]
+    fn scattered_checks_widen_the_band_around_the_prediction() {
+        let forecast = forecast(&[400.0, 310.0, 265.0, 195.0, 170.0], None);
+        assert_eq!(forecast.status, ForecastStatus::Predicted);
+        let predicted = forecast.predicted_crossing_at.unwrap();
+        let earliest = forecast.crossing_earliest_at.unwrap();
+        let latest = forecast.crossing_latest_at.unwrap();
+        assert!(earliest < predicted && predicted < latest);
+        assert!(latest - earliest > Duration::hours(1));
+    }
+
+    #[test]
+    fn rising_short_or_low_series_give_no_prediction() {
+        let rising = forecast(&[300.0, 310.0, 320.0], None);
+        assert_eq!(rising.status, ForecastStatus::NotDecaying);
+        assert!(rising.predicted_crossing_at.is_none());
+
+        let short = forecast(&[300.0, 280.0], None);
+        assert_eq!(short.status, ForecastStatus::InsufficientData);
+        // Two checks since the last dosing are not enough either.
+        let dosed = forecast(&[300.0, 250.0, 200.0, 400.0, 380.0], None);
+        assert_eq!(dosed.status, ForecastStatus::InsufficientData);
+        assert_eq!(dosed.points_used, 2);
+
+        let below = forecast(&[200.0, 150.0, 90.0], None);
+        assert_eq!(below.status, ForecastStatus::AlreadyBelow);
+    }
+
+    #[test]
+    fn alert_fires_when_the_crossing_is_due_by_the_next_check() {
+        let ppm = [400.0, 310.0, 265.0, 195.0, 170.0];
+        let crossing = forecast(&ppm, None).predicted_crossing_at.unwrap();
+        assert!(forecast(&ppm, Some(crossing)).alert);
+        assert!(!forecast(&ppm, Some(crossing - Duration::milliseconds(1))).alert);
+    }
+}
@@ -0,0 +1,77 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::domain::ImageReference;
+
+/// Local copy of the strip photos sent by the phones. Images are addressed by
+/// their SHA-256 (`<root>/<sha256>`) only; `image.uri` is never used to locate
+/// a file on the server.
+#[derive(Debug, Clone)]
+pub struct ImageStore {
+    root: PathBuf,
+}
+
+#[derive(Debug)]
+pub enum ImageError {
+    NotFound(String),
+    Io(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(path) => write!(f, "image not found: {path}"),
+            Self::Io(err) => write!(f, "image io error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl ImageStore {
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    /// `None` when the reference does not hold a SHA-256 digest.
+    pub fn resolve(&self, image: &ImageReference) -> Option<PathBuf> {
+        is_sha256_hex(&image.sha256).then(|| self.root.join(&image.sha256))
+    }
+
+    /// Reads the photo, reported as not found when its file, once links are
+    /// followed, lies outside the store.
+    pub fn read(&self, image: &ImageReference) -> Result<Vec<u8>, ImageError> {
+        let path = self
+            .resolve(image)
+            .ok_or_else(|| ImageError::NotFound(image.sha256.clone()))?;
+        let not_found = || ImageError::NotFound(path.display().to_string());
+        let io_error = |err: std::io::Error| match err.kind() {
+            std::io::ErrorKind::NotFound => not_found(),
+            _ => ImageError::Io(err.to_string()),
+        };
+        let real_path = path.canonicalize().map_err(io_error)?;
+        let root = self.root.canonicalize().map_err(io_error)?;
+        if !real_path.starts_with(root) {
+            return Err(not_found());
+        }
+        std::fs::read(&real_path).map_err(io_error)
+    }
+}
+
+/// 64 lowercase hexadecimal characters.
+pub fn is_sha256_hex(value: &str) -> bool {
+    value.len() == 64
+        && value
+            .bytes()
+            .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
@@ -0,0 +1,1098 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+use crate::{
+    api::AppState,
+    audit::AuditContext,
+    control_plan,
+    domain::{
+        AuditEventType, AuditExport, AuditExportFormat, AuditExportStatus, BundleManifest,
+        ComplianceStatus, ControlGapStatus, CsvDialect, ExportManifest, ImageIntegrityIssue,
+        ImageIntegrityScan, ImageIssueReason, ImageReference, IntegrityCheckRun, Job, JobKind,
+        JobStatus, ServerLifecycleStatus, ShiftReport, SpcSignal, TimestampSubject,
+        TrustedTimestamp, VerificationCheck,
+    },
+    exports, forecast,
+    images::{sha256_hex, ImageError, ImageStore},
+    reporting, rules, shift_report, spc,
+    storage::{AuditRegisterScope, ChainHeads, SpcBaseline, StorageError, StoredImage},
+    timestamping,
+};
+
+const MAX_RETRY_DELAY_SECONDS: i64 = 3600;
+/// How often the chain heads timestamper checks whether today's token is
+/// still missing (first start of the day, or the TSA was unreachable).
+const CHAIN_TIMESTAMP_CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const EXPORT_EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// How often the shift report generator looks for a shift that just ended.
+const SHIFT_REPORT_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// How far back the shift report generator catches up on missed shifts
+/// (server down over a shift end).
+const SHIFT_REPORT_CATCH_UP_DAYS: i64 = 7;
+
+#[derive(Debug)]
+enum JobError {
+    /// Worth another attempt later (photo not uploaded yet, storage hiccup).
+    Transient(String),
+    Permanent(String),
+}
+
+impl From<StorageError> for JobError {
+    fn from(err: StorageError) -> Self {
+        Self::Transient(err.to_string())
+    }
+}
+
+/// Starts the in-process worker pool. Each worker polls the persistent `jobs`
+/// table, so queued work survives restarts.
+pub fn spawn_workers(state: AppState) {
+    for worker in 0..state.config.verification_workers.max(1) {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let idle = Duration::from_millis(state.config.job_poll_interval_ms);
+            loop {
+                match run_next_job(&state).await {
+                    Ok(Some(_)) => continue,
+                    Ok(None) => {}
+                    Err(err) => eprintln!("job worker {worker}: {err}"),
+                }
+                tokio::time::sleep(idle).await;
+            }
+        });
+    }
+}
+
+/// Timestamps the chain heads once per UTC day when a TSA is configured.
+pub fn spawn_chain_timestamping(state: AppState) {
+    if state.tsa.is_none() {
+        return;
+    }
+    tokio::spawn(async move {
+        loop {
+            if let Err(err) = timestamp_chain_heads_if_due(&state).await {
+                eprintln!("chain heads timestamping: {err}");
+            }
+            tokio::time::sleep(CHAIN_TIMESTAMP_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Requests today's chain heads token unless one is already stored.
+pub async fn timestamp_chain_heads_if_due(
+    state: &AppState,
+) -> Result<Option<TrustedTimestamp>, StorageError> {
+    let Some(tsa) = &state.tsa else {
+        return Ok(None);
+    };
+    let today = Utc::now().date_naive().to_string();
+    let existing = state
+        .store
+        .list_timestamps(Some(TimestampSubject::ChainHeads), Some(today.clone()))
+        .await?;
+    if !existing.is_empty() {
+        return Ok(None);
+    }
+
+    let heads = state.store.chain_heads().await?;
+    timestamping::timestamp_and_store(
+        &state.store,
+        tsa,
+        &AuditContext::server(),
+        TimestampSubject::ChainHeads,
+        today,
+        heads.timestamp_message(),
+    )
+    .await
+}
+
+/// Deletes audit export files once their retention period is over.
+pub fn spawn_export_expiry(state: AppState) {
+    tokio::spawn(async move {
+        loop {
+            if let Err(err) = expire_audit_exports(&state).await {
+                eprintln!("audit export expiry: {err}");
+            }
+            tokio::time::sleep(EXPORT_EXPIRY_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Removes the files of ready exports past `expires_at` and marks them
+/// `expired`. Their signed manifests stay in the store.
+pub async fn expire_audit_exports(state: &AppState) -> Result<usize, StorageError> {
+    let expired = state.store.list_expired_audit_exports(Utc::now()).await?;
+    for export in &expired {
+        let path = std::path::Path::new(&state.config.export_dir).join(export.stored_file_name());
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(StorageError::Sqlite(err.to_string())),
+        }
+        state
+            .store
+            .set_audit_export_status(export.id, AuditExportStatus::Expired, None)
+            .await?;
+        AuditContext::server()
+            .record(
+                &state.store,
+                AuditEventType::AuditExportExpired,
+                None,
+                None,
+                json!({"export_id": export.id, "expires_at": export.expires_at}),
+            )
+            .await?;
+    }
+    Ok(expired.len())
+}
+
+/// Looks for missed checks every `control_gap_check_interval_minutes`.
+pub fn spawn_control_gap_checks(state: AppState) {
+    let minutes = state.config.control_gap_check_interval_minutes;
+    if minutes == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        loop {
+            if let Err(err) = run_control_gap_check(&state).await {
+                eprintln!("control gap check: {err}");
+            }
+            tokio::time::sleep(Duration::from_secs(minutes * 60)).await;
+        }
+    });
+}
+
+/// Raises a `control_check_missed` event, once per due time, for each bath
+/// whose current control plan waits for a check past its grace delay.
+pub async fn run_control_gap_check(state: &AppState) -> Result<usize, StorageError> {
+    let now = Utc::now();
+    let plans = state.store.list_control_plans(None).await?;
+    let current_plans: Vec<Uuid> = plans
+        .chunk_by(|a, b| a.bath_id == b.bath_id)
+        .filter_map(|versions| versions.last())
+        .map(|plan| plan.id)
+        .collect();
+    let mut raised = 0;
+    for gap in control_plan::list_gaps(&state.store, None, None, None, now).await? {
+        if gap.status != ControlGapStatus::Missing || !current_plans.contains(&gap.plan_id) {
+            continue;
+        }
+        if !state
+            .store
+            .mark_control_gap_alerted(gap.bath_id.clone(), gap.due_at)
+            .await?
+        {
+            continue;
+        }
+        AuditContext::server()
+            .record(
+                &state.store,
+                AuditEventType::ControlCheckMissed,
+                gap.previous_analysis_id,
+                None,
+                json!({
+                    "bath_id": gap.bath_id,
+                    "plan_id": gap.plan_id,
+                    "previous_check_at": gap.previous_check_at,
+                    "due_at": gap.due_at,
+                    "missed_checks": gap.missed_checks,
+                    "overdue_minutes": gap.overdue_minutes,
+                }),
+            )
+            .await?;
+        raised += 1;
+    }
+    Ok(raised)
+}
+
+/// Generates the handover report of each shift once it is over.
+pub fn spawn_shift_reports(state: AppState) {
+    tokio::spawn(async move {
+        loop {
+            if let Err(err) = generate_due_shift_reports(&state, Utc::now()).await {
+                eprintln!("shift report: {err}");
+            }
+            tokio::time::sleep(SHIFT_REPORT_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Reports of the shifts over at `now` that have none yet, oldest first.
+/// Every shift since the latest stored report is caught up, at most
+/// `SHIFT_REPORT_CATCH_UP_DAYS` back; a store without any report starts with
+/// the latest shift.
+pub async fn generate_due_shift_reports(
+    state: &AppState,
+    now: DateTime<Utc>,
+) -> Result<Vec<ShiftReport>, StorageError> {
+    let start_hours = &state.config.shift_start_hours;
+    let shifts = match state.store.latest_shift_start().await? {
+        Some(latest) => shift_report::completed_shifts_since(
+            start_hours,
+            latest.max(now - chrono::Duration::days(SHIFT_REPORT_CATCH_UP_DAYS)),
+            now,
+        ),
+        None => shift_report::last_completed_shift(start_hours, now)
+            .into_iter()
+            .collect(),
+    };
+    let mut reports = Vec::new();
+    for (shift_start, shift_end) in shifts {
+        if let Some(report) = generate_shift_report(state, shift_start, shift_end, now).await? {
+            reports.push(report);
+        }
+    }
+    Ok(reports)
+}
+
+/// Report of `[shift_start, shift_end)`, `None` when it already has one.
+async fn generate_shift_report(
+    state: &AppState,
+    shift_start: DateTime<Utc>,
+    shift_end: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<Option<ShiftReport>, StorageError> {
+    if state.store.shift_report_exists(shift_start).await? {
+        return Ok(None);
+    }
+    let analyses = state
+        .store
+        .list_shift_checks(shift_start, shift_end)
+        .await?;
+    let deviations = state.store.list_deviations(None, None).await?;
+    let review_queue = state.store.list_review_queue(None, None).await?;
+    let missed_checks =
+        control_plan::list_gaps(&state.store, None, Some(shift_start), Some(shift_end), now)
+            .await?;
+    let report = shift_report::shift_report(
+        shift_start,
+        shift_end,
+        now,
+        &analyses,
+        deviations,
+        &review_queue,
+        missed_checks,
+    );
+    if !state.store.insert_shift_report(&report).await? {
+        return Ok(None);
+    }
+    AuditContext::server()
+        .record(
+            &state.store,
+            AuditEventType::ShiftReportGenerated,
+            None,
+            None,
+            json!({
+                "shift_report_id": report.id,
+                "shift_start": report.shift_start,
+                "shift_end": report.shift_end,
+                "checks": report.checks.len(),
+                "open_deviations": report.open_deviations.len(),
+                "pending_reviews": report.pending_reviews.len(),
+                "missed_checks": report.missed_checks.len(),
+            }),
+        )
+        .await?;
+    Ok(Some(report))
+}
+
+/// Runs the integrity verification every `integrity_check_interval_minutes`,
+/// starting right away.
+pub fn spawn_integrity_checks(state: AppState) {
+    let minutes = state.config.integrity_check_interval_minutes;
+    if minutes == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        loop {
+            if let Err(err) = run_integrity_check(&state).await {
+                eprintln!("integrity check: {err}");
+            }
+            tokio::time::sleep(Duration::from_secs(minutes * 60)).await;
+        }
+    });
+}
+
+/// Re-hashes every photo verified on receipt, walks the hash chains and runs
+/// SQLite's own consistency check. The outcome is recorded as an
+/// `integrity_check_completed` event; any failure also raises an
+/// `integrity_alert_raised` event and is reported on `/health`.
+pub async fn run_integrity_check(state: &AppState) -> Result<IntegrityCheckRun, StorageError> {
+    let started_at = Utc::now();
+    let stored_images = state.store.list_stored_images().await?;
+    let verifier = state.verifier.clone();
+    let images =
+        tokio::task::spawn_blocking(move || scan_images(verifier.images(), &stored_images))
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))?;
+    let tsa_certificate = state.tsa.as_ref().map(|tsa| tsa.certificate().clone());
+    let chains = state.store.verify_chains(tsa_certificate).await?;
+    let sqlite_integrity = state.store.sqlite_integrity_check().await?;
+
+    let mut failures = Vec::new();
+    if !images.issues.is_empty() {
+        failures.push("image_integrity".to_string());
+    }
+    if !chains.analyses.is_intact() {
+        failures.push("analyses_chain".to_string());
+    }
+    if !chains.audit_events.is_intact() {
+        failures.push("audit_events_chain".to_string());
+    }
+    if chains.timestamps.first_invalid.is_some() {
+        failures.push("timestamps".to_string());
+    }
+    if sqlite_integrity != ["ok"] {
+        failures.push("sqlite_integrity".to_string());
+    }
+
+    let run = IntegrityCheckRun {
+        healthy: failures.is_empty(),
+        failures,
+        images,
+        chains,
+        sqlite_integrity,
+        started_at,
+        finished_at: Utc::now(),
+    };
+    let audit = AuditContext::server();
+    audit
+        .record(
+            &state.store,
+            AuditEventType::IntegrityCheckCompleted,
+            None,
+            None,
+            serde_json::to_value(&run).map_err(|err| StorageError::Serde(err.to_string()))?,
+        )
+        .await?;
+    if !run.healthy {
+        eprintln!("ALERT: integrity check failed: {}", run.failures.join(", "));
+        audit
+            .record(
+                &state.store,
+                AuditEventType::IntegrityAlertRaised,
+                None,
+                None,
+                json!({
+                    "failures": run.failures,
+                    "image_issues": run.images.issues,
+                    "analyses_first_broken_link": run.chains.analyses.first_broken_link,
+                    "audit_events_first_broken_link": run.chains.audit_events.first_broken_link,
+                    "first_invalid_timestamp": run.chains.timestamps.first_invalid,
+                    "sqlite_integrity": run.sqlite_integrity,
+                }),
+            )
+            .await?;
+    }
+    Ok(run)
+}
+
+fn scan_images(store: &ImageStore, images: &[StoredImage]) -> ImageIntegrityScan {
+    let mut scan = ImageIntegrityScan {
+        checked: 0,
+        skipped: 0,
+        issues: Vec::new(),
+    };
+    for stored in images {
+        if !stored.verified_intact {
+            scan.skipped += 1;
+            continue;
+        }
+        scan.checked += 1;
+        let reason = match store.read(&stored.image) {
+            Err(ImageError::NotFound(_)) => Some(ImageIssueReason::Missing),
+            Err(ImageError::Io(_)) => Some(ImageIssueReason::Unreadable),
+            Ok(bytes) if !sha256_hex(&bytes).eq_ignore_ascii_case(&stored.image.sha256) => {
+                Some(ImageIssueReason::HashMismatch)
+            }
+            Ok(_) => None,
+        };
+        if let Some(reason) = reason {
+            scan.issues.push(ImageIntegrityIssue {
+                analysis_id: stored.analysis_id,
+                image_sha256: stored.image.sha256.clone(),
+                reason,
+            });
+        }
+    }
+    scan
+}
+
+/// Claims and runs a single due job. Returns `None` when the queue is idle.
+pub async fn run_next_job(state: &AppState) -> Result<Option<Job>, StorageError> {
+    let Some(job) = state.store.claim_next_job().await? else {
+        return Ok(None);
+    };
+
+    let outcome = match job.kind {
+        JobKind::VerifyAnalysis => verify_analysis(state, &job).await,
+        JobKind::GenerateAuditExport => generate_audit_export(state, &job).await,
+        JobKind::EvaluateSpc => evaluate_spc(state, &job).await,
+        JobKind::ForecastThreshold => forecast_threshold(state, &job).await,
+    };
+
+    match outcome {
+        Ok(()) => {
+            state
+                .store
+                .finish_job(job.id, JobStatus::Succeeded, None)
+                .await?
+        }
+        Err(JobError::Transient(error)) if job.attempts < job.max_attempts => {
+            let delay = (state.config.job_retry_base_seconds
+                << (job.attempts.saturating_sub(1)).min(16))
+            .min(MAX_RETRY_DELAY_SECONDS);
+            let run_after = Utc::now() + chrono::Duration::seconds(delay);
+            state.store.retry_job(job.id, run_after, error).await?
+        }
+        Err(JobError::Transient(error)) | Err(JobError::Permanent(error)) => {
+            state
+                .store
+                .finish_job(job.id, JobStatus::Failed, Some(error))
+                .await?
+        }
+    }
+
+    Ok(Some(job))
+}
+
+async fn verify_analysis(state: &AppState, job: &Job) -> Result<(), JobError> {
+    let analysis = state
+        .store
+        .find_by_id(job.subject_id)
+        .await?
+        .ok_or_else(|| JobError::Permanent("analysis not found".to_string()))?;
+    if analysis.server_lifecycle_status != ServerLifecycleStatus::Recu {
+        return Ok(());
+    }
+    if let Some(correction) = analysis.correction.as_ref().filter(|c| c.voided) {
+        let reason = format!("voided by correction: {}", correction.justification);
+        let transitioned = state
+            .store
+            .transition_lifecycle(
+                analysis.id,
+                ServerLifecycleStatus::Recu,
+                ServerLifecycleStatus::Rejete,
+                &reason,
+            )
+            .await?;
+        if transitioned {
+            record_verdict(state, analysis.id, ServerLifecycleStatus::Rejete, &reason).await?;
+        }
+        return Ok(());
+    }
+
+    let verifier = state.verifier.clone();
+    let subject = analysis.clone();
+    let verification = tokio::task::spawn_blocking(move || verifier.verify(&subject))
+        .await
+        .map_err(|err| JobError::Permanent(err.to_string()))?;
+
+    let image_pending = verification
+        .failed_checks
+        .iter()
+        .find(|failed| failed.check == VerificationCheck::ImageAvailable)
+        .map(|failed| failed.detail.clone());
+    let validated = verification.validated_business_rules;
+    let server_in_range = verification
+        .server_decision
+        .as_ref()
+        .is_some_and(|decision| decision.compliance() == ComplianceStatus::ConformeProduction);
+    let reason = if validated {
+        "server verification passed".to_string()
+    } else {
+        let checks: Vec<&str> = verification
+            .failed_checks
+            .iter()
+            .map(|failed| failed.check.code())
+            .collect();
+        format!("failed checks: {}", checks.join(", "))
+    };
+
+    // Persisted even when the photo is still missing so the acknowledgement
+    // tells the phone why the verdict is pending.
+    state.store.save_verification(verification).await?;
+
+    if let Some(detail) = image_pending {
+        if job.attempts < job.max_attempts {
+            return Err(JobError::Transient(detail));
+        }
+    }
+
+    let target = if validated {
+        ServerLifecycleStatus::Valide
+    } else {
+        ServerLifecycleStatus::Rejete
+    };
+    let transitioned = state
+        .store
+        .transition_lifecycle(
+            job.subject_id,
+            ServerLifecycleStatus::Recu,
+            target.clone(),
+            &reason,
+        )
+        .await?;
+    if transitioned {
+        record_verdict(state, job.subject_id, target.clone(), &reason).await?;
+        if target == ServerLifecycleStatus::Valide {
+            // Only a re-control the server itself read in range answers a
+            // deviation; the phone's declared status is not enough.
+            if server_in_range {
+                state.store.close_deviations(analysis.clone()).await?;
+            }
+            for kind in [JobKind::EvaluateSpc, JobKind::ForecastThreshold] {
+                state
+                    .store
+                    .enqueue_job(kind, job.subject_id, state.config.job_max_attempts)
+                    .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Raises an alert when, as of the validated analysis, its bath is expected
+/// to fall below the low threshold before the next scheduled check.
+async fn forecast_threshold(state: &AppState, job: &Job) -> Result<(), JobError> {
+    let analysis = state
+        .store
+        .find_by_id(job.subject_id)
+        .await?
+        .ok_or_else(|| JobError::Permanent("analysis not found".to_string()))?;
+    let points = state
+        .store
+        .list_spc_points(
+            analysis.bath_id.clone(),
+            state.config.forecast_window_points,
+        )
+        .await?;
+    // A later check of the bath supersedes this forecast.
+    if points.last().map(|point| point.analysis_id) != Some(analysis.id) {
+        return Ok(());
+    }
+    let check_interval = control_plan::check_interval(
+        &state.store,
+        &analysis.bath_id,
+        chrono::Duration::hours(state.config.check_interval_hours),
+    )
+    .await?;
+    let forecast = forecast::threshold_forecast(
+        analysis.bath_id.clone(),
+        &points,
+        state.config.forecast_dosing_jump_ppm,
+        check_interval,
+        None,
+    );
+    if forecast.alert {
+        AuditContext::server()
+            .record(
+                &state.store,
+                AuditEventType::ThresholdCrossingForecast,
+                Some(analysis.id),
+                None,
+                json!({
+                    "bath_id": forecast.bath_id,
+                    "threshold_ppm": forecast.threshold_ppm,
+                    "predicted_crossing_at": forecast.predicted_crossing_at,
+                    "crossing_earliest_at": forecast.crossing_earliest_at,
+                    "crossing_latest_at": forecast.crossing_latest_at,
+                    "next_check_at": forecast.next_check_at,
+                }),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Places the validated analysis on its bath control chart and records the
+/// rules it triggers.
+async fn evaluate_spc(state: &AppState, job: &Job) -> Result<(), JobError> {
+    let analysis = state
+        .store
+        .find_by_id(job.subject_id)
+        .await?
+        .ok_or_else(|| JobError::Permanent("analysis not found".to_string()))?;
+    let points = state
+        .store
+        .list_spc_points(analysis.bath_id.clone(), state.config.spc_window_points)
+        .await?;
+    let Some(index) = points
+        .iter()
+        .position(|point| point.analysis_id == analysis.id)
+    else {
+        // Voided, superseded or out of the window since it was validated.
+        return Ok(());
+    };
+    let Some(baseline) = freeze_spc_baseline(state, &analysis.bath_id).await? else {
+        return Ok(());
+    };
+    let limits = baseline.limits;
+    let values: Vec<f64> = points.iter().map(|point| point.ppm_estime as f64).collect();
+
+    let raised_at = Utc::now();
+    let signals = spc::rules_at(&values, index, &limits)
+        .into_iter()
+        .map(|(rule, direction)| SpcSignal {
+            id: Uuid::new_v4(),
+            bath_id: analysis.bath_id.clone(),
+            analysis_id: analysis.id,
+            captured_at: analysis.captured_at,
+            rule,
+            severity: rule.severity(),
+            direction,
+            ppm_estime: analysis.ppm_estime,
+            limits,
+            raised_at,
+        })
+        .collect();
+    for signal in state.store.insert_spc_signals(signals).await? {
+        AuditContext::server()
+            .record(
+                &state.store,
+                AuditEventType::SpcSignalRaised,
+                Some(signal.analysis_id),
+                None,
+                json!({
+                    "signal_id": signal.id,
+                    "bath_id": signal.bath_id,
+                    "rule": signal.rule,
+                    "severity": signal.severity,
+                    "direction": signal.direction,
+                    "ppm_estime": signal.ppm_estime,
+                }),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Frozen Phase I limits of the bath, computed and stored from its first
+/// `spc_baseline_points` validated checks once it has that many.
+async fn freeze_spc_baseline(
+    state: &AppState,
+    bath_id: &str,
+) -> Result<Option<SpcBaseline>, StorageError> {
+    if let Some(baseline) = state.store.find_spc_baseline(bath_id.to_string()).await? {
+        return Ok(Some(baseline));
+    }
+    let baseline_points = state.config.spc_baseline_points.max(2);
+    let values: Vec<f64> = state
+        .store
+        .list_spc_baseline_points(bath_id.to_string(), baseline_points)
+        .await?
+        .iter()
+        .map(|point| point.ppm_estime as f64)
+        .collect();
+    let Some(limits) = spc::control_limits(&values, baseline_points) else {
+        return Ok(None);
+    };
+    let baseline = SpcBaseline {
+        bath_id: bath_id.to_string(),
+        baseline_points,
+        limits,
+        frozen_at: Utc::now(),
+    };
+    if state.store.freeze_spc_baseline(baseline.clone()).await? {
+        AuditContext::server()
+            .record(
+                &state.store,
+                AuditEventType::SpcLimitsFrozen,
+                None,
+                None,
+                json!({
+                    "bath_id": baseline.bath_id,
+                    "baseline_points": baseline.baseline_points,
+                    "limits": baseline.limits,
+                }),
+            )
+            .await?;
+    }
+    state.store.find_spc_baseline(bath_id.to_string()).await
+}
+
+async fn generate_audit_export(state: &AppState, job: &Job) -> Result<(), JobError> {
+    let export = state
+        .store
+        .find_audit_export(job.subject_id)
+        .await?
+        .ok_or_else(|| JobError::Permanent("audit export not found".to_string()))?;
+    if export.status != AuditExportStatus::Queued && export.status != AuditExportStatus::Running {
+        return Ok(());
+    }
+    state
+        .store
+        .set_audit_export_status(export.id, AuditExportStatus::Running, None)
+        .await?;
+
+    let outcome = write_audit_export(state, &export).await;
+    if let Err(JobError::Transient(error) | JobError::Permanent(error)) = &outcome {
+        let last_attempt =
+            matches!(outcome, Err(JobError::Permanent(_))) || job.attempts >= job.max_attempts;
+        if last_attempt {
+            state
+                .store
+                .set_audit_export_status(export.id, AuditExportStatus::Failed, Some(error.clone()))
+                .await?;
+            AuditContext::server()
+                .record(
+                    &state.store,
+                    AuditEventType::AuditExportFailed,
+                    None,
+                    export.requested_by.as_deref(),
+                    json!({"export_id": export.id, "error": error}),
+                )
+                .await?;
+        }
+    }
+    outcome
+}
+
+/// Builds the register, writes it under `EXPORT_DIR`, signs its manifest,
+/// then moves the exported analyses to `exporte_audit`.
+async fn write_audit_export(state: &AppState, export: &AuditExport) -> Result<(), JobError> {
+    let store = &state.store;
+    let generated_at = Utc::now();
+    // Taken before the export events are appended, so the heads match the
+    // state the register was produced from.
+    let heads = store.chain_heads().await?;
+    let scope = AuditRegisterScope {
+        from: export.from,
+        to: export.to,
+        received_until: generated_at,
+    };
+
+    let dir = std::path::Path::new(&state.config.export_dir);
+    let path = dir.join(export.stored_file_name());
+    let partial = dir.join(format!("{}.partial", export.stored_file_name()));
+    let io_error = |err: std::io::Error| JobError::Transient(format!("{}: {err}", path.display()));
+    tokio::fs::create_dir_all(dir).await.map_err(io_error)?;
+    let (file_sha256, analysis_ids) = match export.format {
+        AuditExportFormat::Csv => stream_register_csv(state, export, scope, &partial).await?,
+        _ => {
+            let (file, analysis_ids) =
+                build_register_file(state, export, scope, generated_at, &heads).await?;
+            tokio::fs::write(&partial, &file).await.map_err(io_error)?;
+            (sha256_hex(&file), analysis_ids)
+        }
+    };
+    tokio::fs::rename(&partial, &path).await.map_err(io_error)?;
+    let record_count = analysis_ids.len();
+
+    let signed = state.export_signer.sign(ExportManifest {
+        export_id: export.id,
+        file_name: export.format.file_name().to_string(),
+        content_type: export.format.content_type().to_string(),
+        record_count,
+        from: export.from,
+        to: export.to,
+        file_sha256,
+        chain_head_analyses: heads.analyses.clone(),
+        chain_head_audit_events: heads.audit_events.clone(),
+        server_version: env!("CARGO_PKG_VERSION").to_string(),
+        generated_at,
+        public_key: state.export_signer.public_key_hex(),
+    });
+    let expires_at = generated_at + chrono::Duration::hours(state.config.export_retention_hours);
+    store
+        .complete_audit_export(signed.clone(), expires_at)
+        .await?;
+
+    let audit = AuditContext::server();
+    let requested_by = export.requested_by.as_deref();
+    audit
+        .record(
+            store,
+            AuditEventType::AnalysisExportedAudit,
+            None,
+            requested_by,
+            json!({
+                "from": export.from,
+                "to": export.to,
+                "format": export.format,
+                "analyses": record_count,
+                "chain_head_analyses": heads.analyses,
+                "chain_head_audit_events": heads.audit_events,
+                "export_id": export.id,
+                "file_sha256": signed.manifest.file_sha256,
+            }),
+        )
+        .await?;
+    let moved = store
+        .mark_exported_audit(analysis_ids, &format!("audit export {}", export.id))
+        .await?;
+    for (analysis_id, previous_status) in moved {
+        audit
+            .record(
+                store,
+                AuditEventType::AnalysisExportedAudit,
+                Some(analysis_id),
+                requested_by,
+                json!({
+                    "export_id": export.id,
+                    "format": export.format,
+                    "previous_status": previous_status,
+                    "server_lifecycle_status": ServerLifecycleStatus::ExporteAudit,
+                }),
+            )
+            .await?;
+    }
+
+    if let Some(tsa) = &state.tsa {
+        // Same bytes as stored, hence as signed.
+        let manifest_json = serde_json::to_string(&signed.manifest)
+            .map_err(|err| JobError::Permanent(err.to_string()))?;
+        timestamping::timestamp_and_store(
+            store,
+            tsa,
+            &audit,
+            TimestampSubject::ExportManifest,
+            export.id.to_string(),
+            manifest_json,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Writes the CSV register to `partial` one page of `EXPORT_PAGE_SIZE`
+/// analyses at a time, so memory does not grow with the period. Returns the
+/// file SHA-256 and the exported analysis ids.
+async fn stream_register_csv(
+    state: &AppState,
+    export: &AuditExport,
+    scope: AuditRegisterScope,
+    partial: &std::path::Path,
+) -> Result<(String, Vec<Uuid>), JobError> {
+    let io_error =
+        |err: std::io::Error| JobError::Transient(format!("{}: {err}", partial.display()));
+    let mut file = tokio::fs::File::create(partial).await.map_err(io_error)?;
+    let mut hasher = Sha256::new();
+    let mut analysis_ids = Vec::new();
+    let mut after = None;
+    loop {
+        let page = state
+            .store
+            .list_audit_register_page(scope, after, Some(state.config.export_page_size))
+            .await?;
+        let page_ids: Vec<Uuid> = page.iter().map(|analysis| analysis.id).collect();
+        let links = state
+            .store
+            .audit_register_links(scope, page_ids.clone())
+            .await?;
+        let rows = reporting::register_rows(&page, &links).map_err(JobError::Permanent)?;
+        let chunk = reporting::register_csv_part(&rows, export.csv_dialect, after.is_none())
+            .map_err(JobError::Permanent)?;
+        hasher.update(&chunk);
+        file.write_all(&chunk).await.map_err(io_error)?;
+
+        analysis_ids.extend(page_ids);
+        match page.last() {
+            Some(last) if page.len() == state.config.export_page_size => {
+                after = Some((last.captured_at, last.id));
+            }
+            _ => break,
+        }
+    }
+    file.sync_all().await.map_err(io_error)?;
+    Ok((format!("{:x}", hasher.finalize()), analysis_ids))
+}
+
+/// XLSX, PDF and ZIP registers, built in memory. Returns the file and the
+/// exported analysis ids.
+async fn build_register_file(
+    state: &AppState,
+    export: &AuditExport,
+    scope: AuditRegisterScope,
+    generated_at: DateTime<Utc>,
+    heads: &ChainHeads,
+) -> Result<(Vec<u8>, Vec<Uuid>), JobError> {
+    let store = &state.store;
+    let analyses = store.list_audit_register_page(scope, None, None).await?;
+    let analysis_ids: Vec<Uuid> = analyses.iter().map(|analysis| analysis.id).collect();
+    let links = store
+        .audit_register_links(scope, analysis_ids.clone())
+        .await?;
+    let rows = reporting::register_rows(&analyses, &links).map_err(JobError::Permanent)?;
+    if export.format == AuditExportFormat::Xlsx {
+        let file = reporting::register_xlsx(&rows).map_err(JobError::Permanent)?;
+        return Ok((file, analysis_ids));
+    }
+
+    // The PDF certifies the standard CSV whatever dialect was requested.
+    let standard_csv =
+        reporting::register_csv(&rows, CsvDialect::Standard).map_err(JobError::Permanent)?;
+    let photos: Vec<(Uuid, ImageReference)> = analyses
+        .iter()
+        .map(|analysis| (analysis.id, analysis.image.clone()))
+        .collect();
+    let reviews = store
+        .list_secondary_reviews_for_audit(export.from, export.to)
+        .await?;
+    let deviations = store
+        .list_deviations_for_audit(export.from, export.to)
+        .await?;
+    let control_gaps =
+        control_plan::list_gaps(store, None, export.from, export.to, generated_at).await?;
+    let gaps_csv = control_plan::gaps_csv(&control_gaps).map_err(JobError::Permanent)?;
+    let period_signatures = store
+        .list_signatures(None, None)
+        .await?
+        .into_iter()
+        .filter(|signature| {
+            export.from.is_none_or(|from| signature.signed_at >= from)
+                && export.to.is_none_or(|to| signature.signed_at <= to)
+        })
+        .collect();
+    let register = reporting::AuditRegister {
+        export_id: export.id,
+        from: export.from,
+        to: export.to,
+        generated_at,
+        analyses,
+        corrective_actions: links.corrective_actions,
+        reviews,
+        deviations,
+        control_gaps,
+        review_signatures: links.review_signatures,
+        period_signatures,
+        chain_head_analyses: heads.analyses.clone(),
+        chain_head_audit_events: heads.audit_events.clone(),
+        content_sha256: sha256_hex(&standard_csv),
+    };
+    let verifier = state.verifier.clone();
+    let pdf =
+        tokio::task::spawn_blocking(move || reporting::audit_pdf(&register, verifier.images()))
+            .await
+            .map_err(|err| JobError::Permanent(err.to_string()))?;
+    if export.format == AuditExportFormat::Pdf {
+        return Ok((pdf, analysis_ids));
+    }
+
+    let csv = match export.csv_dialect {
+        CsvDialect::Standard => standard_csv,
+        dialect => reporting::register_csv(&rows, dialect).map_err(JobError::Permanent)?,
+    };
+    let (state, export) = (state.clone(), export.clone());
+    let bundle = tokio::task::spawn_blocking(move || {
+        evidence_bundle(&state, &export, generated_at, csv, pdf, gaps_csv, &photos)
+    })
+    .await
+    .map_err(|err| JobError::Permanent(err.to_string()))??;
+    Ok((bundle, analysis_ids))
+}
+
+/// ZIP of both registers, the photo of each analysis named after its id,
+/// and the calibration and rules contract files the server runs with.
+fn evidence_bundle(
+    state: &AppState,
+    export: &AuditExport,
+    generated_at: DateTime<Utc>,
+    csv: Vec<u8>,
+    pdf: Vec<u8>,
+    gaps_csv: Vec<u8>,
+    photos: &[(Uuid, ImageReference)],
+) -> Result<Vec<u8>, JobError> {
+    let calibration = state.verifier.calibration();
+    let calibration_path = std::path::Path::new(&state.config.calibration_swatches_path);
+    let swatches = std::fs::read(calibration_path)
+        .map_err(|err| JobError::Transient(format!("{}: {err}", calibration_path.display())))?;
+    if sha256_hex(&swatches) != calibration.source_sha256 {
+        return Err(JobError::Permanent(format!(
+            "{} changed since calibration {} was loaded",
+            calibration_path.display(),
+            calibration.calibration_version
+        )));
+    }
+    let rules_path = std::path::Path::new(&state.config.rules_contract_path);
+    let rules_contract = std::fs::read(rules_path)
+        .map_err(|err| JobError::Transient(format!("{}: {err}", rules_path.display())))?;
+    let contract_version = serde_json::from_slice::<serde_json::Value>(&rules_contract)
+        .ok()
+        .and_then(|contract| contract["contract_version"].as_str().map(str::to_owned));
+    if contract_version.as_deref() != Some(rules::ANALYSIS_RULES_VERSION) {
+        return Err(JobError::Permanent(format!(
+            "{} is not the contract of {}",
+            rules_path.display(),
+            rules::ANALYSIS_RULES_VERSION
+        )));
+    }
+
+    let file_name = |path: &std::path::Path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let mut contents = vec![
+        (AuditExportFormat::Csv.file_name().to_string(), csv),
+        (AuditExportFormat::Pdf.file_name().to_string(), pdf),
+        ("controles-manquants.csv".to_string(), gaps_csv),
+        (
+            format!("etalonnage/{}", file_name(calibration_path)),
+            swatches,
+        ),
+        (format!("regles/{}", file_name(rules_path)), rules_contract),
+    ];
+    let mut missing_images = Vec::new();
+    for (analysis_id, image) in photos {
+        let reason = match state.verifier.images().read(image) {
+            Err(ImageError::NotFound(_)) => ImageIssueReason::Missing,
+            Err(ImageError::Io(_)) => ImageIssueReason::Unreadable,
+            Ok(bytes) if !sha256_hex(&bytes).eq_ignore_ascii_case(&image.sha256) => {
+                ImageIssueReason::HashMismatch
+            }
+            Ok(bytes) => {
+                let extension = match image::guess_format(&bytes) {
+                    Ok(image::ImageFormat::Jpeg) => "jpg",
+                    Ok(image::ImageFormat::Png) => "png",
+                    _ => "bin",
+                };
+                contents.push((format!("photos/{analysis_id}.{extension}"), bytes));
+                continue;
+            }
+        };
+        missing_images.push(ImageIntegrityIssue {
+            analysis_id: *analysis_id,
+            image_sha256: image.sha256.clone(),
+            reason,
+        });
+    }
+
+    let manifest = BundleManifest {
+        export_id: export.id,
+        generated_at,
+        calibration_version: calibration.calibration_version.clone(),
+        analysis_rules_version: rules::ANALYSIS_RULES_VERSION.to_string(),
+        files: Vec::new(),
+        missing_images,
+        public_key: String::new(),
+    };
+    exports::evidence_bundle(&state.export_signer, manifest, &contents).map_err(JobError::Permanent)
+}
+
+async fn record_verdict(
+    state: &AppState,
+    analysis_id: Uuid,
+    status: ServerLifecycleStatus,
+    reason: &str,
+) -> Result<(), StorageError> {
+    let event_type = if status == ServerLifecycleStatus::Valide {
+        AuditEventType::AnalysisBusinessValidated
+    } else {
+        AuditEventType::AnalysisRejected
+    };
+    AuditContext::server()
+        .record(
+            &state.store,
+            event_type,
+            Some(analysis_id),
+            None,
+            json!({"server_lifecycle_status": status, "reason": reason}),
+        )
+        .await
+}
@@ -1,38 +1,186 @@
 mod api;
+mod audit;
 mod calibration;
+mod config;
+mod control_plan;
 mod domain;
+mod exports;
+mod forecast;
+mod images;
+mod jobs;
+mod pdf;
 mod reporting;
 mod rules;
+mod shift_report;
+mod signatures;
+mod spc;
+mod statistics;
 mod storage;
+mod timestamping;
 mod validation;
+mod verification;
+mod xlsx;
 
-use axum::{
-    routing::{get, post},
-    Router,
+use std::sync::Arc;
+
+use crate::{
+    api::AppState,
+    calibration::CalibrationScale,
+    config::ServerConfig,
+    domain::{AuditExportFormat, SignedExportManifest},
+    exports::ExportSigner,
+    images::ImageStore,
+    storage::AnalysisStore,
+    timestamping::{TsaCertificate, TsaClient},
+    verification::Verifier,
 };
 
-use crate::storage::AnalysisStore;
+/// `server-rust verify-export <file> <manifest.json> [public-key-hex]`: checks
+/// an exported file against its signed manifest, and for an evidence bundle
+/// every file against the bundle's own `manifest.json`. The trusted key
+/// defaults to the one at `EXPORT_SIGNING_KEY_PATH`.
+fn verify_export_file(config: &ServerConfig, args: &[String]) {
+    let [file_path, manifest_path, rest @ ..] = args else {
+        eprintln!("usage: server-rust verify-export <file> <manifest.json> [public-key-hex]");
+        std::process::exit(2);
+    };
+    let trusted_key = match rest.first() {
+        Some(key) => key.clone(),
+        None => ExportSigner::load(&config.export_signing_key_path)
+            .expect("export signing key loading")
+            .public_key_hex(),
+    };
+    let file = std::fs::read(file_path).expect("export file reading");
+    let signed: SignedExportManifest =
+        serde_json::from_slice(&std::fs::read(manifest_path).expect("export manifest reading"))
+            .expect("export manifest parsing");
+
+    let verified = exports::verify_export(&file, &signed, &trusted_key).and_then(|()| {
+        if signed.manifest.content_type == AuditExportFormat::Zip.content_type() {
+            let bundle = exports::verify_bundle(&file, &trusted_key)?;
+            println!(
+                "OK: {} bundled files match manifest.json ({} missing photos)",
+                bundle.manifest.files.len(),
+                bundle.manifest.missing_images.len()
+            );
+        }
+        Ok(())
+    });
+    match verified {
+        Ok(()) => println!(
+            "OK: {} matches export {} ({} records, signed by {})",
+            file_path,
+            signed.manifest.export_id,
+            signed.manifest.record_count,
+            signed.manifest.public_key
+        ),
+        Err(err) => {
+            println!("ALTERED: {file_path}: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// `server-rust verify`: walks the hash chains of `DATABASE_URL`, prints the
+/// report and exits non-zero on the first broken link. Timestamp tokens are
+/// checked against the certificate at `TSA_CERT_PATH`.
+async fn verify_integrity(config: &ServerConfig, store: &AnalysisStore) {
+    let tsa_certificate = config
+        .tsa_cert_path
+        .as_ref()
+        .map(|path| TsaCertificate::load(path).expect("TSA certificate loading"));
+    let report = store
+        .verify_chains(tsa_certificate)
+        .await
+        .expect("hash chain verification");
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&report).expect("integrity report serialization")
+    );
+    if !report.intact {
+        std::process::exit(1);
+    }
+}
 
 #[tokio::main]
 async fn main() {
-    let database_url =
-        std::env::var("DATABASE_URL").unwrap_or_else(|_| "data/analyses.sqlite".to_string());
-    let store = AnalysisStore::new(&database_url)
+    let config = ServerConfig::from_env();
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("verify-export") {
+        verify_export_file(&config, &args[2..]);
+        return;
+    }
+
+    let store = AnalysisStore::new(&config.database_url)
         .await
         .expect("sqlite store initialization");
 
-    let app = Router::new()
-        .route("/health", get(api::health))
-        .route("/v1/analyses", post(api::create_analysis))
-        .route("/v1/analyses/history", get(api::analyses_history))
-        .route("/v1/analyses/:id/ack", get(api::analysis_ack))
-        .route("/v1/analyses/audit-export", post(api::export_audit_csv))
-        .with_state(store);
+    if args.get(1).map(String::as_str) == Some("verify") {
+        verify_integrity(&config, &store).await;
+        return;
+    }
+
+    let scale = CalibrationScale::load(&config.calibration_swatches_path)
+        .expect("calibration scale loading");
+    println!(
+        "calibration {} loaded from {}",
+        scale.calibration_version, config.calibration_swatches_path
+    );
+    let verifier = Arc::new(Verifier::new(
+        scale,
+        ImageStore::new(&config.image_store_dir),
+    ));
+
+    let recovered = store
+        .recover_jobs(config.job_max_attempts)
+        .await
+        .expect("job queue recovery");
+    if recovered > 0 {
+        println!("requeued verification for {recovered} received analyses");
+    }
+
+    let export_signer = Arc::new(
+        ExportSigner::load_or_create(&config.export_signing_key_path)
+            .expect("export signing key loading"),
+    );
+    println!(
+        "export manifests signed with key {}",
+        export_signer.public_key_hex()
+    );
+    let tsa = TsaClient::from_config(&config).expect("TSA configuration");
+    match &tsa {
+        Some(tsa) => println!(
+            "trusted timestamps requested from {}, signed by {}",
+            tsa.url(),
+            config.tsa_cert_path.as_deref().unwrap_or_default()
+        ),
+        None => println!("trusted timestamping disabled (TSA_URL not set)"),
+    }
+
+    let state = AppState {
+        store,
+        verifier,
+        export_signer,
+        tsa: tsa.map(Arc::new),
+        config: Arc::new(config),
+    };
+    jobs::spawn_workers(state.clone());
+    jobs::spawn_chain_timestamping(state.clone());
+    jobs::spawn_integrity_checks(state.clone());
+    jobs::spawn_export_expiry(state.clone());
+    jobs::spawn_control_gap_checks(state.clone());
+    jobs::spawn_shift_reports(state.clone());
+    let app = api::router(state);
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8080")
         .await
         .expect("bind 0.0.0.0:8080");
 
     println!("server-rust listening on http://0.0.0.0:8080");
-    axum::serve(listener, app).await.expect("server start");
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await
+    .expect("server start");
 }
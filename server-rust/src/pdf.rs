@@ -0,0 +1,336 @@
+//! Minimal PDF 1.4 writer for the audit register: standard Helvetica fonts
+//! in WinAnsi encoding (enough for French), filled rectangles, lines and
+//! JPEG images. Coordinates are PDF points from the bottom-left corner.
+
+use std::fmt::Write;
+
+/// A4 landscape.
+pub const PAGE_WIDTH: f32 = 842.0;
+pub const PAGE_HEIGHT: f32 = 595.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Self::Regular => "F1",
+            Self::Bold => "F2",
+        }
+    }
+}
+
+pub type Rgb = [u8; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageId(usize);
+
+struct Image {
+    jpeg: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+#[derive(Default)]
+pub struct Page {
+    content: String,
+    images: Vec<ImageId>,
+}
+
+impl Page {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&mut self, x: f32, y: f32, size: f32, font: Font, color: Rgb, text: &str) {
+        let [r, g, b] = unit(color);
+        let _ = writeln!(
+            self.content,
+            "BT /{} {size:.1} Tf {r:.3} {g:.3} {b:.3} rg {x:.2} {y:.2} Td ({}) Tj ET",
+            font.resource(),
+            escape(&win_ansi(text))
+        );
+    }
+
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Rgb) {
+        let [r, g, b] = unit(color);
+        let _ = writeln!(
+            self.content,
+            "{r:.3} {g:.3} {b:.3} rg {x:.2} {y:.2} {width:.2} {height:.2} re f"
+        );
+    }
+
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, width: f32, color: Rgb) {
+        let [r, g, b] = unit(color);
+        let _ = writeln!(
+            self.content,
+            "{r:.3} {g:.3} {b:.3} RG {width:.2} w {x1:.2} {y1:.2} m {x2:.2} {y2:.2} l S"
+        );
+    }
+
+    pub fn image(&mut self, image: ImageId, x: f32, y: f32, width: f32, height: f32) {
+        if !self.images.contains(&image) {
+            self.images.push(image);
+        }
+        let _ = writeln!(
+            self.content,
+            "q {width:.2} 0 0 {height:.2} {x:.2} {y:.2} cm /Im{} Do Q",
+            image.0
+        );
+    }
+}
+
+#[derive(Default)]
+pub struct Document {
+    pages: Vec<Page>,
+    images: Vec<Image>,
+}
+
+impl Document {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a baseline JPEG (RGB) once; it can then be drawn on any page.
+    pub fn add_jpeg(&mut self, jpeg: Vec<u8>, width: u32, height: u32) -> ImageId {
+        self.images.push(Image {
+            jpeg,
+            width,
+            height,
+        });
+        ImageId(self.images.len() - 1)
+    }
+
+    pub fn add_page(&mut self, page: Page) {
+        self.pages.push(page);
+    }
+
+    pub fn to_bytes(&self, title: &str) -> Vec<u8> {
+        // 1 catalog, 2 page tree, 3-4 fonts, 5 info, then images, then one
+        // page and one content stream per page.
+        let image_base = 6;
+        let page_base = image_base + self.images.len();
+        let object_count = page_base + 2 * self.pages.len();
+
+        let mut out: Vec<u8> = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = vec![0usize; object_count];
+        let mut object = |out: &mut Vec<u8>, number: usize, body: &[u8]| {
+            offsets[number] = out.len();
+            out.extend_from_slice(format!("{number} 0 obj\n").as_bytes());
+            out.extend_from_slice(body);
+            out.extend_from_slice(b"\nendobj\n");
+        };
+
+        object(&mut out, 1, b"<< /Type /Catalog /Pages 2 0 R >>");
+        let kids: Vec<String> = (0..self.pages.len())
+            .map(|index| format!("{} 0 R", page_base + 2 * index))
+            .collect();
+        object(
+            &mut out,
+            2,
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                self.pages.len()
+            )
+            .as_bytes(),
+        );
+        object(
+            &mut out,
+            3,
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>",
+        );
+        object(
+            &mut out,
+            4,
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>",
+        );
+        object(
+            &mut out,
+            5,
+            format!(
+                "<< /Title ({}) /Producer (peroxyde-server) >>",
+                escape(&win_ansi(title))
+            )
+            .as_bytes(),
+        );
+
+        for (index, image) in self.images.iter().enumerate() {
+            let mut body = format!(
+                "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /DCTDecode /Length {} >>\nstream\n",
+                image.width,
+                image.height,
+                image.jpeg.len()
+            )
+            .into_bytes();
+            body.extend_from_slice(&image.jpeg);
+            body.extend_from_slice(b"\nendstream");
+            object(&mut out, image_base + index, &body);
+        }
+
+        for (index, page) in self.pages.iter().enumerate() {
+            let number = page_base + 2 * index;
+            let x_objects: String = page
+                .images
+                .iter()
+                .map(|image| format!("/Im{} {} 0 R ", image.0, image_base + image.0))
+                .collect();
+            object(
+                &mut out,
+                number,
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> /XObject << {x_objects}>> >> /Contents {} 0 R >>",
+                    number + 1
+                )
+                .as_bytes(),
+            );
+            let mut body = format!("<< /Length {} >>\nstream\n", page.content.len()).into_bytes();
+            body.extend_from_slice(page.content.as_bytes());
+            body.extend_from_slice(b"endstream");
+            object(&mut out, number + 1, &body);
+        }
+
+        let xref = out.len();
+        out.extend_from_slice(format!("xref\n0 {object_count}\n0000000000 65535 f \n").as_bytes());
+        for offset in &offsets[1..] {
+            out.extend_from_slice(format!("{offset:010} 00000 n \n").as_bytes());
+        }
+        out.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {object_count} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{xref}\n%%EOF\n"
+            )
+            .as_bytes(),
+        );
+        out
+    }
+}
+
+/// Width of `text` in points, from the Helvetica AFM metrics. Accented
+/// letters take the width of their base letter.
+pub fn text_width(text: &str, size: f32, font: Font) -> f32 {
+    let units: u32 = text.chars().map(char_width).sum();
+    let bold_factor = if font == Font::Bold { 1.06 } else { 1.0 };
+    units as f32 * size / 1000.0 * bold_factor
+}
+
+/// Cuts `text` with an ellipsis so that it fits in `max_width`.
+pub fn truncate(text: &str, max_width: f32, size: f32, font: Font) -> String {
+    if text_width(text, size, font) <= max_width {
+        return text.to_string();
+    }
+    let mut kept = String::new();
+    for ch in text.chars() {
+        let candidate = format!("{kept}{ch}…");
+        if text_width(&candidate, size, font) > max_width {
+            break;
+        }
+        kept.push(ch);
+    }
+    format!("{kept}…")
+}
+
+/// Greedy word wrap; words wider than a line are truncated.
+pub fn wrap(text: &str, max_width: f32, size: f32, font: Font) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if current.is_empty() {
+            word.to_string()
+        } else {
+            format!("{current} {word}")
+        };
+        if text_width(&candidate, size, font) <= max_width {
+            current = candidate;
+        } else {
+            if !current.is_empty() {
+                lines.push(current);
+            }
+            current = truncate(word, max_width, size, font);
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+fn char_width(ch: char) -> u32 {
+    const ASCII: [u16; 95] = [
+        278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556,
+        556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722,
+        722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722,
+        667, 944, 667, 667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556,
+        556, 222, 222, 500, 222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500,
+        500, 334, 260, 334, 584,
+    ];
+    let base = match ch {
+        'à' | 'â' | 'ä' => 'a',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'î' | 'ï' => 'i',
+        'ô' | 'ö' => 'o',
+        'ù' | 'û' | 'ü' => 'u',
+        'ç' => 'c',
+        'À' | 'Â' => 'A',
+        'É' | 'È' | 'Ê' => 'E',
+        'Ô' => 'O',
+        'Ç' => 'C',
+        '—' => return 1000,
+        '–' | '…' | '°' | '€' => return 556,
+        '’' | '‘' => return 222,
+        _ => ch,
+    };
+    match base as u32 {
+        code @ 32..=126 => ASCII[(code - 32) as usize] as u32,
+        _ => 556,
+    }
+}
+
+/// WinAnsiEncoding bytes; characters outside it become `?`.
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|ch| match ch {
+            '\u{20}'..='\u{7e}' | '\u{a0}'..='\u{ff}' => ch as u8,
+            '€' => 0x80,
+            '‚' => 0x82,
+            '„' => 0x84,
+            '…' => 0x85,
+            'Œ' => 0x8c,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            '™' => 0x99,
+            'œ' => 0x9c,
+            _ => b'?',
+        })
+        .collect()
+}
+
+/// Literal string body: bytes as-is, except delimiters and non-ASCII which
+/// are octal-escaped so the content stream stays plain ASCII.
+fn escape(bytes: &[u8]) -> String {
+    let mut escaped = String::with_capacity(bytes.len());
+    for &byte in bytes {
+        match byte {
+            b'(' | b')' | b'\\' => {
+                escaped.push('\\');
+                escaped.push(byte as char);
+            }
+            0x20..=0x7e => escaped.push(byte as char),
+            _ => {
+                let _ = write!(escaped, "\\{byte:03o}");
+            }
+        }
+    }
+    escaped
+}
+
+fn unit(color: Rgb) -> [f32; 3] {
+    color.map(|channel| channel as f32 / 255.0)
+}
@@ -1,13 +1,33 @@
-use crate::domain::Analysis;
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Utc, Weekday};
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        Analysis, ComplianceStatus, ControlGap, ControlGapStatus, CorrectiveAction,
+        CorrectiveActionStatus, CsvDialect, Deviation, DeviationStatus, ElectronicSignature,
+        ReviewDecision, SecondaryReview, ServerLifecycleStatus, SignatureMeaning, SignedRecordType,
+    },
+    images::{sha256_hex, ImageStore},
+    pdf::{text_width, truncate, wrap, Document, Font, Page, Rgb, PAGE_HEIGHT, PAGE_WIDTH},
+    storage::AuditRegisterLinks,
+    xlsx,
+};
 
 pub fn audit_line(analysis: &Analysis) -> String {
+    let analysis_label = format!(
+        "{} — {}",
+        analysis.analysis_rules_version, analysis.analysis_result
+    );
     format!(
-        "analysis={} sample={} ppm_estime={} compliance_status={:?} analysis_label={} decision_version={} decision_message={} recommended_action={} lifecycle_status={:?}",
+        "analysis={} sample={} operator={} ppm_estime={} compliance_status={:?} analysis_label={} decision_version={} decision_message={} recommended_action={} lifecycle_status={:?}",
         analysis.id,
         analysis.sample_id,
+        analysis.operator_id,
         analysis.ppm_estime,
         analysis.compliance_status,
-        format!("{} — {}", analysis.analysis_rules_version, analysis.analysis_result),
+        analysis_label,
         analysis.analysis_rules_version,
         analysis.analysis_result,
         analysis.recommended_action,
@@ -15,134 +35,1045 @@ pub fn audit_line(analysis: &Analysis) -> String {
     )
 }
 
-pub fn audit_csv(analyses: &[Analysis]) -> Result<String, String> {
-    let mut writer = csv::Writer::from_writer(vec![]);
-    writer
-        .write_record([
-            "server_analysis_id",
-            "client_analysis_id",
-            "sample_id",
-            "ppm_estime",
-            "ppm_min",
-            "ppm_max",
-            "compliance_status",
-            "analysis_result",
-            "recommended_action",
-            "confidence",
-            "analysis_rules_version",
-            "calibration_version",
-            "captured_at",
-            "received_at",
-            "server_lifecycle_status",
-            "image.uri",
-            "image.sha256",
-            "image.content_type",
-            "acquisition.light_condition",
-            "acquisition.ambient_lux",
-            "acquisition.temperature_celsius",
-            "acquisition.humidity_percent",
-            "acquisition.camera_focus_score",
-            "acquisition.rejection_flags.low_light",
-            "acquisition.rejection_flags.blur_detected",
-            "acquisition.rejection_flags.framing_issue",
-            "acquisition.rejection_flags.overexposed",
-            "acquisition.rejection_flags.strip_not_detected",
-            "device.platform",
-            "device.model",
-            "device.os_version",
-            "device.app_version",
-        ])
-        .map_err(|err| err.to_string())?;
-
-    for analysis in analyses {
-        writer
-            .write_record([
-                analysis.id.to_string(),
-                analysis.client_analysis_id.to_string(),
-                analysis.sample_id.clone(),
-                analysis.ppm_estime.to_string(),
-                analysis.ppm_min.to_string(),
-                analysis.ppm_max.to_string(),
-                serde_json::to_string(&analysis.compliance_status)
-                    .map_err(|err| err.to_string())?,
-                analysis.analysis_result.clone(),
-                analysis.recommended_action.clone(),
-                analysis.confidence.to_string(),
-                analysis.analysis_rules_version.clone(),
-                analysis.calibration_version.clone(),
-                analysis.captured_at.to_rfc3339(),
-                analysis.received_at.to_rfc3339(),
-                serde_json::to_string(&analysis.server_lifecycle_status)
-                    .map_err(|err| err.to_string())?,
-                analysis.image.uri.clone(),
-                analysis.image.sha256.clone(),
-                analysis.image.content_type.clone(),
-                serde_json::to_string(&analysis.acquisition_metadata.light_condition)
-                    .map_err(|err| err.to_string())?,
-                analysis
-                    .acquisition_metadata
-                    .ambient_lux
-                    .map(|v| v.to_string())
-                    .unwrap_or_default(),
-                analysis
-                    .acquisition_metadata
-                    .temperature_celsius
-                    .map(|v| v.to_string())
-                    .unwrap_or_default(),
-                analysis
-                    .acquisition_metadata
-                    .humidity_percent
-                    .map(|v| v.to_string())
-                    .unwrap_or_default(),
-                analysis
-                    .acquisition_metadata
-                    .camera_focus_score
-                    .map(|v| v.to_string())
-                    .unwrap_or_default(),
-                analysis
-                    .acquisition_metadata
-                    .rejection_flags
-                    .low_light
-                    .to_string(),
-                analysis
-                    .acquisition_metadata
-                    .rejection_flags
-                    .blur_detected
-                    .to_string(),
-                analysis
-                    .acquisition_metadata
-                    .rejection_flags
-                    .framing_issue
-                    .to_string(),
-                analysis
-                    .acquisition_metadata
-                    .rejection_flags
-                    .overexposed
-                    .to_string(),
-                analysis
-                    .acquisition_metadata
-                    .rejection_flags
-                    .strip_not_detected
-                    .to_string(),
-                analysis.acquisition_metadata.device.platform.clone(),
-                analysis
-                    .acquisition_metadata
-                    .device
-                    .model
-                    .clone()
-                    .unwrap_or_default(),
-                analysis
-                    .acquisition_metadata
-                    .device
-                    .os_version
-                    .clone()
-                    .unwrap_or_default(),
-                analysis.acquisition_metadata.device.app_version.clone(),
+/// Value of a register cell, typed so that each output format can render it:
+/// CSV dialects differ on decimals and dates, XLSX stores numbers and dates.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegisterCell {
+    Text(String),
+    /// Decimal as printed by Rust, with a `.` separator.
+    Number(String),
+    Boolean(bool),
+    Time(DateTime<Utc>),
+}
+
+impl RegisterCell {
+    pub fn optional_number<T: ToString>(value: Option<T>) -> Self {
+        value
+            .map(|value| Self::Number(value.to_string()))
+            .unwrap_or_else(|| Self::Text(String::new()))
+    }
+
+    fn optional_text<T: ToString>(value: Option<T>) -> Self {
+        Self::Text(value.map(|value| value.to_string()).unwrap_or_default())
+    }
+
+    fn json<T: serde::Serialize + ?Sized>(value: &T) -> Result<Self, String> {
+        serde_json::to_string(value)
+            .map(Self::Text)
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// Columns of the audit register, in export order.
+pub const REGISTER_COLUMNS: [&str; 43] = [
+    "server_analysis_id",
+    "client_analysis_id",
+    "sample_id",
+    "operator_id",
+    "bath_id",
+    "parent_analysis_id",
+    "ppm_estime",
+    "ppm_min",
+    "ppm_max",
+    "compliance_status",
+    "analysis_result",
+    "recommended_action",
+    "confidence",
+    "analysis_rules_version",
+    "calibration_version",
+    "captured_at",
+    "received_at",
+    "server_lifecycle_status",
+    "version",
+    "supersedes_analysis_id",
+    "superseded_by_analysis_id",
+    "correction.justification",
+    "correction.corrected_by",
+    "correction.voided",
+    "image.uri",
+    "image.sha256",
+    "image.content_type",
+    "acquisition.light_condition",
+    "acquisition.ambient_lux",
+    "acquisition.temperature_celsius",
+    "acquisition.humidity_percent",
+    "acquisition.camera_focus_score",
+    "acquisition.rejection_flags.low_light",
+    "acquisition.rejection_flags.blur_detected",
+    "acquisition.rejection_flags.framing_issue",
+    "acquisition.rejection_flags.overexposed",
+    "acquisition.rejection_flags.strip_not_detected",
+    "device.platform",
+    "device.model",
+    "device.os_version",
+    "device.app_version",
+    "corrective_actions_json",
+    "signatures_json",
+];
+
+/// One row per analysis, in the order of [`REGISTER_COLUMNS`]. `links`
+/// only needs to cover `analyses`, so the register can be built page by page.
+pub fn register_rows(
+    analyses: &[Analysis],
+    links: &AuditRegisterLinks,
+) -> Result<Vec<Vec<RegisterCell>>, String> {
+    use RegisterCell::{Boolean, Number, Text, Time};
+
+    let AuditRegisterLinks {
+        superseded_by,
+        corrective_actions,
+        review_signatures,
+    } = links;
+    let mut actions_by_analysis: HashMap<Uuid, Vec<&CorrectiveAction>> = HashMap::new();
+    for action in corrective_actions {
+        actions_by_analysis
+            .entry(action.analysis_id)
+            .or_default()
+            .push(action);
+    }
+
+    let mut signatures_by_analysis: HashMap<&str, Vec<&ElectronicSignature>> = HashMap::new();
+    for signature in review_signatures {
+        signatures_by_analysis
+            .entry(signature.record_id.as_str())
+            .or_default()
+            .push(signature);
+    }
+
+    analyses
+        .iter()
+        .map(|analysis| {
+            let acquisition = &analysis.acquisition_metadata;
+            let flags = &acquisition.rejection_flags;
+            let correction = analysis.correction.as_ref();
+            Ok(vec![
+                Text(analysis.id.to_string()),
+                Text(analysis.client_analysis_id.to_string()),
+                Text(analysis.sample_id.clone()),
+                Text(analysis.operator_id.clone()),
+                Text(analysis.bath_id.clone()),
+                RegisterCell::optional_text(analysis.parent_analysis_id),
+                Number(analysis.ppm_estime.to_string()),
+                Number(analysis.ppm_min.to_string()),
+                Number(analysis.ppm_max.to_string()),
+                RegisterCell::json(&analysis.compliance_status)?,
+                Text(analysis.analysis_result.clone()),
+                Text(analysis.recommended_action.clone()),
+                Number(analysis.confidence.to_string()),
+                Text(analysis.analysis_rules_version.clone()),
+                Text(analysis.calibration_version.clone()),
+                Time(analysis.captured_at),
+                Time(analysis.received_at),
+                RegisterCell::json(&analysis.server_lifecycle_status)?,
+                Number(analysis.version.to_string()),
+                RegisterCell::optional_text(analysis.supersedes_analysis_id),
+                RegisterCell::optional_text(superseded_by.get(&analysis.id)),
+                RegisterCell::optional_text(correction.map(|c| &c.justification)),
+                RegisterCell::optional_text(correction.map(|c| &c.corrected_by)),
+                correction
+                    .map(|c| Boolean(c.voided))
+                    .unwrap_or_else(|| Text(String::new())),
+                Text(analysis.image.uri.clone()),
+                Text(analysis.image.sha256.clone()),
+                Text(analysis.image.content_type.clone()),
+                RegisterCell::json(&acquisition.light_condition)?,
+                RegisterCell::optional_number(acquisition.ambient_lux),
+                RegisterCell::optional_number(acquisition.temperature_celsius),
+                RegisterCell::optional_number(acquisition.humidity_percent),
+                RegisterCell::optional_number(acquisition.camera_focus_score),
+                Boolean(flags.low_light),
+                Boolean(flags.blur_detected),
+                Boolean(flags.framing_issue),
+                Boolean(flags.overexposed),
+                Boolean(flags.strip_not_detected),
+                Text(acquisition.device.platform.clone()),
+                RegisterCell::optional_text(acquisition.device.model.as_ref()),
+                RegisterCell::optional_text(acquisition.device.os_version.as_ref()),
+                Text(acquisition.device.app_version.clone()),
+                RegisterCell::json(
+                    actions_by_analysis
+                        .get(&analysis.id)
+                        .map(Vec::as_slice)
+                        .unwrap_or_default(),
+                )?,
+                RegisterCell::json(
+                    signatures_by_analysis
+                        .get(analysis.id.to_string().as_str())
+                        .map(Vec::as_slice)
+                        .unwrap_or_default(),
+                )?,
             ])
+        })
+        .collect()
+}
+
+/// `standard`: comma separator, RFC 3339 UTC times. `excel_fr`: what French
+/// Excel opens as is, i.e. BOM, semicolons, decimal commas and
+/// Europe/Paris local times.
+pub fn register_csv(rows: &[Vec<RegisterCell>], dialect: CsvDialect) -> Result<Vec<u8>, String> {
+    register_csv_part(rows, dialect, true)
+}
+
+/// Part of a CSV register; the concatenation of a part with `header` and
+/// parts without is the whole register.
+pub fn register_csv_part(
+    rows: &[Vec<RegisterCell>],
+    dialect: CsvDialect,
+    header: bool,
+) -> Result<Vec<u8>, String> {
+    csv_part(&REGISTER_COLUMNS, rows, dialect, header)
+}
+
+/// CSV of `rows` under `columns` in `dialect`, as [`register_csv`] writes it.
+pub fn csv_part(
+    columns: &[&str],
+    rows: &[Vec<RegisterCell>],
+    dialect: CsvDialect,
+    header: bool,
+) -> Result<Vec<u8>, String> {
+    let excel_fr = dialect == CsvDialect::ExcelFr;
+    let mut out = Vec::new();
+    if excel_fr && header {
+        out.extend_from_slice("\u{feff}".as_bytes());
+    }
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(if excel_fr { b';' } else { b',' })
+        .from_writer(out);
+    if header {
+        writer
+            .write_record(columns)
+            .map_err(|err| err.to_string())?;
+    }
+    for row in rows {
+        writer
+            .write_record(row.iter().map(|cell| match cell {
+                RegisterCell::Text(text) => text.clone(),
+                RegisterCell::Number(number) if excel_fr => number.replace('.', ","),
+                RegisterCell::Number(number) => number.clone(),
+                RegisterCell::Boolean(value) => value.to_string(),
+                RegisterCell::Time(at) if excel_fr => {
+                    paris_time(*at).format("%d/%m/%Y %H:%M:%S").to_string()
+                }
+                RegisterCell::Time(at) => at.to_rfc3339(),
+            }))
             .map_err(|err| err.to_string())?;
     }
-
     writer.flush().map_err(|err| err.to_string())?;
-    let bytes = writer.into_inner().map_err(|err| err.to_string())?;
-    String::from_utf8(bytes).map_err(|err| err.to_string())
+    writer.into_inner().map_err(|err| err.to_string())
+}
+
+/// Single-sheet workbook with typed cells, times in Europe/Paris, and the
+/// compliance column coloured like the PDF register.
+pub fn register_xlsx(rows: &[Vec<RegisterCell>]) -> Result<Vec<u8>, String> {
+    let compliance_column = REGISTER_COLUMNS
+        .iter()
+        .position(|column| *column == "compliance_status")
+        .expect("compliance column");
+    let highlights = [
+        ComplianceStatus::ConformeProduction,
+        ComplianceStatus::TauxBas,
+        ComplianceStatus::SeuilDepasse,
+    ]
+    .iter()
+    .map(|status| xlsx::Highlight {
+        column: compliance_column,
+        text: serde_json::to_value(status)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_owned))
+            .unwrap_or_default(),
+        fill: compliance_color(status),
+    })
+    .collect();
+    let sheet = xlsx::Sheet {
+        name: "Registre".to_string(),
+        header: REGISTER_COLUMNS
+            .iter()
+            .map(|column| column.to_string())
+            .collect(),
+        rows: rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| match cell {
+                        RegisterCell::Text(text) if text.is_empty() => xlsx::Cell::Empty,
+                        RegisterCell::Text(text) => xlsx::Cell::Text(text.clone()),
+                        RegisterCell::Number(number) => xlsx::Cell::Number(number.clone()),
+                        RegisterCell::Boolean(value) => xlsx::Cell::Boolean(*value),
+                        RegisterCell::Time(at) => xlsx::Cell::DateTime(paris_time(*at)),
+                    })
+                    .collect()
+            })
+            .collect(),
+        highlights,
+    };
+    sheet.to_xlsx()
+}
+
+/// Europe/Paris wall-clock time: UTC+1, or UTC+2 from the last Sunday of
+/// March to the last Sunday of October at 01:00 UTC (EU rule since 1996).
+pub fn paris_time(at: DateTime<Utc>) -> NaiveDateTime {
+    let last_sunday_at_one = |month: u32| {
+        let mut day = NaiveDate::from_ymd_opt(at.year(), month, 31).expect("31-day month");
+        while day.weekday() != Weekday::Sun {
+            day = day.pred_opt().expect("date in range");
+        }
+        day.and_hms_opt(1, 0, 0).expect("valid time").and_utc()
+    };
+    let summer = at >= last_sunday_at_one(3) && at < last_sunday_at_one(10);
+    let offset = chrono::Duration::hours(if summer { 2 } else { 1 });
+    (at + offset).naive_utc()
+}
+
+/// UTC instant of a Paris wall-clock time. A time skipped by the spring
+/// change is read as winter time.
+pub fn paris_to_utc(local: NaiveDateTime) -> DateTime<Utc> {
+    [2, 1]
+        .into_iter()
+        .map(|hours| local.and_utc() - chrono::Duration::hours(hours))
+        .find(|at| paris_time(*at) == local)
+        .unwrap_or_else(|| local.and_utc() - chrono::Duration::hours(1))
+}
+
+/// Everything the PDF register shows, gathered before rendering.
+pub struct AuditRegister {
+    pub export_id: Uuid,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub generated_at: DateTime<Utc>,
+    pub analyses: Vec<Analysis>,
+    pub corrective_actions: Vec<CorrectiveAction>,
+    pub reviews: Vec<SecondaryReview>,
+    pub deviations: Vec<Deviation>,
+    /// Missed or late checks against the control plans, due in the period.
+    pub control_gaps: Vec<ControlGap>,
+    /// Secondary review signatures, matched to rows by analysis id.
+    pub review_signatures: Vec<ElectronicSignature>,
+    /// Every electronic signature applied during the period, for the cover.
+    pub period_signatures: Vec<ElectronicSignature>,
+    pub chain_head_analyses: String,
+    pub chain_head_audit_events: String,
+    /// SHA-256 of the CSV register built from the same rows, printed in
+    /// every footer so the two formats can be cross-checked.
+    pub content_sha256: String,
+}
+
+const MARGIN: f32 = 30.0;
+const FOOTER_Y: f32 = 18.0;
+const ROW_HEIGHT: f32 = 50.0;
+const CELL_FONT_SIZE: f32 = 7.0;
+const CELL_LINE_HEIGHT: f32 = 8.5;
+const BLACK: Rgb = [0, 0, 0];
+const GREY: Rgb = [110, 110, 110];
+const RULE: Rgb = [190, 190, 190];
+const HEADER_FILL: Rgb = [225, 230, 238];
+/// Register columns: title and width in points (782 in total).
+const COLUMNS: [(&str, f32); 8] = [
+    ("Photo", 58.0),
+    ("Capture (UTC)", 78.0),
+    ("Échantillon", 118.0),
+    ("PPM", 70.0),
+    ("Résultat", 120.0),
+    ("Statut serveur", 70.0),
+    ("Relecture secondaire", 130.0),
+    ("Écarts et CAPA", 138.0),
+];
+
+/// Paginated French register: cover page with the period and signatures,
+/// then one row per analysis with its photo, review, deviations and CAPA.
+pub fn audit_pdf(register: &AuditRegister, images: &ImageStore) -> Vec<u8> {
+    let mut document = Document::new();
+    let mut pages = cover_pages(register);
+
+    let mut reviews: HashMap<Uuid, &SecondaryReview> = HashMap::new();
+    for review in &register.reviews {
+        reviews.insert(review.analysis_id, review);
+    }
+    let mut signatures: HashMap<&str, &ElectronicSignature> = HashMap::new();
+    for signature in &register.review_signatures {
+        signatures.insert(signature.record_id.as_str(), signature);
+    }
+
+    let rows_top = PAGE_HEIGHT - MARGIN - 40.0;
+    let rows_per_page = ((rows_top - FOOTER_Y - 20.0) / ROW_HEIGHT) as usize;
+    let chunks: Vec<&[Analysis]> = if register.analyses.is_empty() {
+        vec![&[]]
+    } else {
+        register.analyses.chunks(rows_per_page).collect()
+    };
+    for chunk in chunks {
+        let mut page = Page::new();
+        page.text(
+            MARGIN,
+            PAGE_HEIGHT - MARGIN - 4.0,
+            13.0,
+            Font::Bold,
+            BLACK,
+            "Registre des analyses",
+        );
+        table_header(&mut page, rows_top);
+        if chunk.is_empty() {
+            page.text(
+                MARGIN,
+                rows_top - 24.0,
+                9.0,
+                Font::Regular,
+                GREY,
+                "Aucune analyse sur la période.",
+            );
+        }
+        for (index, analysis) in chunk.iter().enumerate() {
+            let top = rows_top - index as f32 * ROW_HEIGHT;
+            analysis_row(
+                &mut page,
+                &mut document,
+                top,
+                analysis,
+                register,
+                reviews.get(&analysis.id).copied(),
+                signatures.get(analysis.id.to_string().as_str()).copied(),
+                images,
+            );
+        }
+        pages.push(page);
+    }
+
+    let total = pages.len();
+    for (index, mut page) in pages.into_iter().enumerate() {
+        footer(&mut page, register, index + 1, total);
+        document.add_page(page);
+    }
+    document.to_bytes("Registre d'audit — analyses de peroxyde")
+}
+
+fn cover_pages(register: &AuditRegister) -> Vec<Page> {
+    let mut page = Page::new();
+    let mut y = PAGE_HEIGHT - MARGIN - 30.0;
+    page.text(
+        MARGIN,
+        y,
+        20.0,
+        Font::Bold,
+        BLACK,
+        "Registre d'audit — analyses de peroxyde",
+    );
+    y -= 30.0;
+
+    let period = format!(
+        "du {} au {}",
+        register
+            .from
+            .map(french_datetime)
+            .unwrap_or_else(|| "premier enregistrement".to_string()),
+        register
+            .to
+            .map(french_datetime)
+            .unwrap_or_else(|| "dernier enregistrement".to_string())
+    );
+    for (label, value) in [
+        ("Période", period),
+        ("Généré le", french_datetime(register.generated_at)),
+        ("Export", register.export_id.to_string()),
+        ("Analyses", register.analyses.len().to_string()),
+        (
+            "Contrôles manquants ou en retard",
+            register.control_gaps.len().to_string(),
+        ),
+        (
+            "Empreinte du registre",
+            format!("{} (SHA-256 du CSV équivalent)", register.content_sha256),
+        ),
+        (
+            "Tête de chaîne analyses",
+            register.chain_head_analyses.clone(),
+        ),
+        (
+            "Tête de chaîne événements",
+            register.chain_head_audit_events.clone(),
+        ),
+        ("Version serveur", env!("CARGO_PKG_VERSION").to_string()),
+    ] {
+        page.text(MARGIN, y, 10.0, Font::Bold, BLACK, label);
+        page.text(MARGIN + 150.0, y, 10.0, Font::Regular, BLACK, &value);
+        y -= 15.0;
+    }
+
+    y -= 12.0;
+    page.text(
+        MARGIN,
+        y,
+        12.0,
+        Font::Bold,
+        BLACK,
+        "Signatures électroniques de la période",
+    );
+    y -= 16.0;
+
+    let columns = [
+        ("Signée le (UTC)", 95.0),
+        ("Signataire", 150.0),
+        ("Signification", 75.0),
+        ("Objet", 250.0),
+        ("Empreinte de signature", 212.0),
+    ];
+    let mut pages = Vec::new();
+    if register.period_signatures.is_empty() {
+        page.text(
+            MARGIN,
+            y,
+            9.0,
+            Font::Regular,
+            GREY,
+            "Aucune signature électronique sur la période.",
+        );
+        y -= 14.0;
+    }
+    let mut header_drawn = false;
+    for signature in &register.period_signatures {
+        // Room for the visa boxes is kept at the bottom of the last page.
+        if y < FOOTER_Y + 110.0 {
+            pages.push(std::mem::take(&mut page));
+            y = PAGE_HEIGHT - MARGIN - 10.0;
+            page.text(
+                MARGIN,
+                y,
+                12.0,
+                Font::Bold,
+                BLACK,
+                "Signatures électroniques de la période (suite)",
+            );
+            y -= 16.0;
+            header_drawn = false;
+        }
+        if !header_drawn {
+            page.fill_rect(
+                MARGIN,
+                y - 3.0,
+                PAGE_WIDTH - 2.0 * MARGIN,
+                12.0,
+                HEADER_FILL,
+            );
+            let mut x = MARGIN + 2.0;
+            for (title, width) in columns {
+                page.text(x, y, 7.5, Font::Bold, BLACK, title);
+                x += width;
+            }
+            y -= 13.0;
+            header_drawn = true;
+        }
+        let values = [
+            french_datetime(signature.signed_at),
+            format!(
+                "{} ({})",
+                signature.signer_display_name, signature.signer_id
+            ),
+            signature_meaning_label(signature.meaning).to_string(),
+            format!(
+                "{} {}",
+                signed_record_label(signature.record_type),
+                signature.record_id
+            ),
+            signature.signature_hash.clone(),
+        ];
+        let mut x = MARGIN + 2.0;
+        for ((_, width), value) in columns.iter().zip(values) {
+            page.text(
+                x,
+                y,
+                7.0,
+                Font::Regular,
+                BLACK,
+                &truncate(&value, width - 4.0, 7.0, Font::Regular),
+            );
+            x += width;
+        }
+        y -= 11.0;
+    }
+
+    y -= 12.0;
+    page.text(
+        MARGIN,
+        y,
+        12.0,
+        Font::Bold,
+        BLACK,
+        "Contrôles manquants ou en retard (plan de contrôle)",
+    );
+    y -= 16.0;
+    if register.control_gaps.is_empty() {
+        page.text(
+            MARGIN,
+            y,
+            9.0,
+            Font::Regular,
+            GREY,
+            "Aucun contrôle manquant sur la période.",
+        );
+    }
+    let gap_columns = [
+        ("Bain", 120.0),
+        ("Contrôle précédent", 120.0),
+        ("Attendu le", 120.0),
+        ("Contrôle suivant", 120.0),
+        ("Contrôles manqués", 90.0),
+        ("Retard", 212.0),
+    ];
+    let mut gap_header_drawn = false;
+    for gap in &register.control_gaps {
+        if y < FOOTER_Y + 110.0 {
+            pages.push(std::mem::take(&mut page));
+            y = PAGE_HEIGHT - MARGIN - 10.0;
+            page.text(
+                MARGIN,
+                y,
+                12.0,
+                Font::Bold,
+                BLACK,
+                "Contrôles manquants ou en retard (suite)",
+            );
+            y -= 16.0;
+            gap_header_drawn = false;
+        }
+        if !gap_header_drawn {
+            page.fill_rect(
+                MARGIN,
+                y - 3.0,
+                PAGE_WIDTH - 2.0 * MARGIN,
+                12.0,
+                HEADER_FILL,
+            );
+            let mut x = MARGIN + 2.0;
+            for (title, width) in gap_columns {
+                page.text(x, y, 7.5, Font::Bold, BLACK, title);
+                x += width;
+            }
+            y -= 13.0;
+            gap_header_drawn = true;
+        }
+        let values = [
+            gap.bath_id.clone(),
+            gap.previous_check_at
+                .map(french_datetime)
+                .unwrap_or_else(|| "début du plan".to_string()),
+            french_datetime(gap.due_at),
+            gap.next_check_at
+                .map(french_datetime)
+                .unwrap_or_else(|| "aucun".to_string()),
+            gap.missed_checks.to_string(),
+            format!(
+                "{} h {:02} min{}",
+                gap.overdue_minutes / 60,
+                gap.overdue_minutes % 60,
+                if gap.status == ControlGapStatus::Missing {
+                    " (toujours manquant)"
+                } else {
+                    ""
+                }
+            ),
+        ];
+        let mut x = MARGIN + 2.0;
+        for ((_, width), value) in gap_columns.iter().zip(values) {
+            page.text(
+                x,
+                y,
+                7.0,
+                Font::Regular,
+                BLACK,
+                &truncate(&value, width - 4.0, 7.0, Font::Regular),
+            );
+            x += width;
+        }
+        y -= 11.0;
+    }
+
+    let box_width = (PAGE_WIDTH - 2.0 * MARGIN - 20.0) / 2.0;
+    for (index, title) in ["Visa responsable qualité", "Visa direction"]
+        .into_iter()
+        .enumerate()
+    {
+        let x = MARGIN + index as f32 * (box_width + 20.0);
+        let top = FOOTER_Y + 95.0;
+        page.text(x, top, 10.0, Font::Bold, BLACK, title);
+        for (offset, label) in [(20.0, "Nom :"), (40.0, "Date :"), (60.0, "Signature :")] {
+            page.text(x, top - offset, 9.0, Font::Regular, BLACK, label);
+            page.line(
+                x + 55.0,
+                top - offset - 2.0,
+                x + box_width,
+                top - offset - 2.0,
+                0.5,
+                RULE,
+            );
+        }
+    }
+    pages.push(page);
+    pages
+}
+
+fn table_header(page: &mut Page, top: f32) {
+    page.fill_rect(MARGIN, top, PAGE_WIDTH - 2.0 * MARGIN, 14.0, HEADER_FILL);
+    let mut x = MARGIN;
+    for (title, width) in COLUMNS {
+        page.text(x + 3.0, top + 4.0, 7.5, Font::Bold, BLACK, title);
+        x += width;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn analysis_row(
+    page: &mut Page,
+    document: &mut Document,
+    top: f32,
+    analysis: &Analysis,
+    register: &AuditRegister,
+    review: Option<&SecondaryReview>,
+    signature: Option<&ElectronicSignature>,
+    images: &ImageStore,
+) {
+    let bottom = top - ROW_HEIGHT;
+    let mut x = MARGIN;
+    let column_x: Vec<f32> = COLUMNS
+        .iter()
+        .map(|(_, width)| {
+            let start = x;
+            x += width;
+            start
+        })
+        .collect();
+
+    page.fill_rect(
+        column_x[4],
+        bottom,
+        COLUMNS[4].1,
+        ROW_HEIGHT,
+        compliance_color(&analysis.compliance_status),
+    );
+    page.fill_rect(
+        column_x[5],
+        bottom,
+        COLUMNS[5].1,
+        ROW_HEIGHT,
+        lifecycle_color(&analysis.server_lifecycle_status),
+    );
+
+    match strip_thumbnail(images, analysis) {
+        Some((jpeg, width, height)) => {
+            let image = document.add_jpeg(jpeg, width, height);
+            let scale =
+                ((COLUMNS[0].1 - 6.0) / width as f32).min((ROW_HEIGHT - 6.0) / height as f32);
+            page.image(
+                image,
+                column_x[0] + 3.0,
+                bottom + 3.0,
+                width as f32 * scale,
+                height as f32 * scale,
+            );
+        }
+        None => page.text(
+            column_x[0] + 3.0,
+            top - 12.0,
+            CELL_FONT_SIZE,
+            Font::Regular,
+            GREY,
+            "indisponible",
+        ),
+    }
+
+    let mut sample = vec![
+        (Font::Bold, analysis.sample_id.clone()),
+        (Font::Regular, format!("Bain {}", analysis.bath_id)),
+        (Font::Regular, format!("Opérateur {}", analysis.operator_id)),
+    ];
+    let version = match &analysis.correction {
+        Some(correction) if correction.voided => {
+            format!(
+                "v{} annulée par {}",
+                analysis.version, correction.corrected_by
+            )
+        }
+        Some(correction) => {
+            format!(
+                "v{} corrigée par {}",
+                analysis.version, correction.corrected_by
+            )
+        }
+        None => format!("v{}", analysis.version),
+    };
+    sample.push((Font::Regular, version));
+
+    let mut result = vec![(
+        Font::Bold,
+        compliance_label(&analysis.compliance_status).to_string(),
+    )];
+    result.extend(
+        wrap(
+            &analysis.analysis_result,
+            COLUMNS[4].1 - 6.0,
+            CELL_FONT_SIZE,
+            Font::Regular,
+        )
+        .into_iter()
+        .take(4)
+        .map(|line| (Font::Regular, line)),
+    );
+
+    let mut review_lines = Vec::new();
+    match review {
+        Some(review) => {
+            review_lines.push((
+                Font::Bold,
+                match review.decision {
+                    ReviewDecision::Confirmed => "Confirmée".to_string(),
+                    ReviewDecision::Rejected => "Rejetée".to_string(),
+                },
+            ));
+            review_lines.push((
+                Font::Regular,
+                format!(
+                    "par {} le {}",
+                    review.reviewer_id,
+                    french_datetime(review.reviewed_at)
+                ),
+            ));
+            if let Some(comment) = &review.comment {
+                review_lines.push((Font::Regular, comment.clone()));
+            }
+        }
+        None => review_lines.push((Font::Regular, "Aucune".to_string())),
+    }
+    if let Some(signature) = signature {
+        review_lines.push((
+            Font::Regular,
+            format!(
+                "Signé : {} ({})",
+                signature.signer_display_name,
+                signature_meaning_label(signature.meaning)
+            ),
+        ));
+        review_lines.push((
+            Font::Regular,
+            format!(
+                "Empreinte {}",
+                &signature.signature_hash[..16.min(signature.signature_hash.len())]
+            ),
+        ));
+    }
+
+    let mut follow_up = Vec::new();
+    for deviation in &register.deviations {
+        if deviation.opening_analysis_id == analysis.id {
+            follow_up.push((
+                Font::Bold,
+                match deviation.status {
+                    DeviationStatus::Open => "Écart ouvert".to_string(),
+                    DeviationStatus::Closed => format!(
+                        "Écart clos le {}",
+                        deviation.closed_at.map(french_datetime).unwrap_or_default()
+                    ),
+                    DeviationStatus::Voided => format!(
+                        "Écart annulé par correction le {}",
+                        deviation.closed_at.map(french_datetime).unwrap_or_default()
+                    ),
+                },
+            ));
+        } else if deviation.status == DeviationStatus::Closed
+            && deviation.closing_analysis_id == Some(analysis.id)
+        {
+            follow_up.push((
+                Font::Regular,
+                format!(
+                    "Recontrôle : clôt l'écart du {}",
+                    french_datetime(deviation.opened_at)
+                ),
+            ));
+        }
+    }
+    for action in register
+        .corrective_actions
+        .iter()
+        .filter(|action| action.analysis_id == analysis.id)
+    {
+        follow_up.push((
+            Font::Regular,
+            format!(
+                "CAPA {} : {}",
+                corrective_action_label(action.status),
+                action.description
+            ),
+        ));
+    }
+    if follow_up.is_empty() {
+        follow_up.push((Font::Regular, "—".to_string()));
+    }
+
+    let cells = [
+        (
+            1,
+            vec![
+                (
+                    Font::Regular,
+                    analysis.captured_at.format("%d/%m/%Y").to_string(),
+                ),
+                (
+                    Font::Regular,
+                    analysis.captured_at.format("%H:%M:%S").to_string(),
+                ),
+                (
+                    Font::Regular,
+                    format!("reçue {}", analysis.received_at.format("%d/%m %H:%M")),
+                ),
+            ],
+        ),
+        (2, sample),
+        (
+            3,
+            vec![
+                (Font::Bold, format!("{:.0} ppm", analysis.ppm_estime)),
+                (
+                    Font::Regular,
+                    format!("[{:.0} – {:.0}]", analysis.ppm_min, analysis.ppm_max),
+                ),
+                (
+                    Font::Regular,
+                    format!("confiance {:.0} %", analysis.confidence * 100.0),
+                ),
+            ],
+        ),
+        (4, result),
+        (
+            5,
+            vec![(
+                Font::Bold,
+                lifecycle_label(&analysis.server_lifecycle_status).to_string(),
+            )],
+        ),
+        (6, review_lines),
+        (7, follow_up),
+    ];
+    for (column, lines) in cells {
+        let width = COLUMNS[column].1 - 6.0;
+        for (index, (font, line)) in lines.iter().take(5).enumerate() {
+            page.text(
+                column_x[column] + 3.0,
+                top - 10.0 - index as f32 * CELL_LINE_HEIGHT,
+                CELL_FONT_SIZE,
+                *font,
+                BLACK,
+                &truncate(line, width, CELL_FONT_SIZE, *font),
+            );
+        }
+    }
+    page.line(MARGIN, bottom, PAGE_WIDTH - MARGIN, bottom, 0.4, RULE);
+}
+
+fn footer(page: &mut Page, register: &AuditRegister, number: usize, total: usize) {
+    page.line(
+        MARGIN,
+        FOOTER_Y + 10.0,
+        PAGE_WIDTH - MARGIN,
+        FOOTER_Y + 10.0,
+        0.5,
+        RULE,
+    );
+    page.text(
+        MARGIN,
+        FOOTER_Y,
+        7.0,
+        Font::Regular,
+        GREY,
+        &format!(
+            "Export {} — empreinte du registre SHA-256 {}",
+            register.export_id, register.content_sha256
+        ),
+    );
+    let label = format!("Page {number} / {total}");
+    page.text(
+        PAGE_WIDTH - MARGIN - text_width(&label, 7.0, Font::Regular),
+        FOOTER_Y,
+        7.0,
+        Font::Regular,
+        GREY,
+        &label,
+    );
+}
+
+/// JPEG thumbnail of the strip photo, only when the stored file still
+/// matches its declared hash.
+fn strip_thumbnail(images: &ImageStore, analysis: &Analysis) -> Option<(Vec<u8>, u32, u32)> {
+    let bytes = images.read(&analysis.image).ok()?;
+    if !sha256_hex(&bytes).eq_ignore_ascii_case(&analysis.image.sha256) {
+        return None;
+    }
+    let thumbnail = image::load_from_memory(&bytes)
+        .ok()?
+        .thumbnail(160, 120)
+        .to_rgb8();
+    let mut jpeg = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, 75)
+        .encode_image(&thumbnail)
+        .ok()?;
+    Some((jpeg, thumbnail.width(), thumbnail.height()))
+}
+
+pub fn french_datetime(at: DateTime<Utc>) -> String {
+    at.format("%d/%m/%Y %H:%M").to_string()
+}
+
+pub fn compliance_label(status: &ComplianceStatus) -> &'static str {
+    match status {
+        ComplianceStatus::TauxBas => "Taux bas",
+        ComplianceStatus::ConformeProduction => "Conforme production",
+        ComplianceStatus::SeuilDepasse => "Seuil dépassé",
+    }
+}
+
+pub fn compliance_color(status: &ComplianceStatus) -> Rgb {
+    match status {
+        ComplianceStatus::TauxBas => [255, 229, 180],
+        ComplianceStatus::ConformeProduction => [212, 237, 218],
+        ComplianceStatus::SeuilDepasse => [248, 215, 218],
+    }
+}
+
+pub fn lifecycle_label(status: &ServerLifecycleStatus) -> &'static str {
+    match status {
+        ServerLifecycleStatus::Recu => "Reçu",
+        ServerLifecycleStatus::Valide => "Validé",
+        ServerLifecycleStatus::Rejete => "Rejeté",
+        ServerLifecycleStatus::RevuSecondairement => "Revu",
+        ServerLifecycleStatus::ExporteAudit => "Exporté audit",
+    }
+}
+
+fn lifecycle_color(status: &ServerLifecycleStatus) -> Rgb {
+    match status {
+        ServerLifecycleStatus::Recu => [255, 243, 205],
+        ServerLifecycleStatus::Valide | ServerLifecycleStatus::RevuSecondairement => {
+            [212, 237, 218]
+        }
+        ServerLifecycleStatus::Rejete => [220, 220, 220],
+        ServerLifecycleStatus::ExporteAudit => [209, 231, 245],
+    }
+}
+
+fn signature_meaning_label(meaning: SignatureMeaning) -> &'static str {
+    match meaning {
+        SignatureMeaning::Reviewed => "relu",
+        SignatureMeaning::Approved => "approuvé",
+    }
+}
+
+fn signed_record_label(record_type: SignedRecordType) -> &'static str {
+    match record_type {
+        SignedRecordType::SecondaryReview => "Relecture de l'analyse",
+        SignedRecordType::CalibrationActivation => "Étalonnage",
+        SignedRecordType::RulesChange => "Règles",
+    }
+}
+
+fn corrective_action_label(status: CorrectiveActionStatus) -> &'static str {
+    match status {
+        CorrectiveActionStatus::Open => "ouverte",
+        CorrectiveActionStatus::Completed => "réalisée",
+        CorrectiveActionStatus::Verified => "vérifiée",
+    }
 }
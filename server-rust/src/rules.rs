@@ -1,7 +1,12 @@
 use serde::{Deserialize, Serialize};
 
+use crate::{domain::ComplianceStatus, images::sha256_hex};
+
 pub const ANALYSIS_RULES_VERSION: &str = "analysis-rules/v2";
 
+/// Below this concentration the bath is `taux_bas`.
+pub const LOW_PPM_THRESHOLD: u32 = 100;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct VersionedAnalysisDecision {
     pub contract_version: String,
@@ -10,8 +15,38 @@ pub struct VersionedAnalysisDecision {
     pub recommended_action: String,
 }
 
+impl VersionedAnalysisDecision {
+    pub fn compliance(&self) -> ComplianceStatus {
+        match self.compliance_status.as_str() {
+            "CONFORME" => ComplianceStatus::ConformeProduction,
+            "SEUIL_DEPASSE" => ComplianceStatus::SeuilDepasse,
+            _ => ComplianceStatus::TauxBas,
+        }
+    }
+}
+
+/// Rounds a measured concentration the same way the mobile app does before
+/// applying the thresholds.
+pub fn evaluate_ppm_reading(ppm: f32) -> VersionedAnalysisDecision {
+    evaluate_ppm(ppm.max(0.0).round() as u32)
+}
+
+/// Fingerprint of the thresholds in force: the decisions on each side of
+/// every frontier. Signed by quality when the rules change.
+pub fn rules_digest() -> String {
+    let frontiers: Vec<(u32, VersionedAnalysisDecision)> = [99, 100, 500, 501]
+        .into_iter()
+        .map(|ppm| (ppm, evaluate_ppm(ppm)))
+        .collect();
+    sha256_hex(
+        serde_json::to_string(&frontiers)
+            .expect("rules decisions serialize")
+            .as_bytes(),
+    )
+}
+
 pub fn evaluate_ppm(ppm: u32) -> VersionedAnalysisDecision {
-    if ppm < 100 {
+    if ppm < LOW_PPM_THRESHOLD {
         return VersionedAnalysisDecision {
             contract_version: ANALYSIS_RULES_VERSION.to_string(),
             analysis_result: "ATTENTION TAUX BAS".to_string(),
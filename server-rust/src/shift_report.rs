@@ -0,0 +1,539 @@
+//! Shift handover report: checks, results, open deviations, pending reviews
+//! and missed checks of one shift, for the incoming team leader.
+
+use chrono::{DateTime, Days, Utc};
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        Analysis, ComplianceStatus, ControlGap, ControlGapStatus, Deviation, DeviationStatus,
+        PendingReview, ReviewReason, ServerLifecycleStatus, ShiftCheck, ShiftReport,
+    },
+    pdf::{text_width, truncate, Document, Font, Page, Rgb, PAGE_HEIGHT, PAGE_WIDTH},
+    reporting::{compliance_color, compliance_label, lifecycle_label, paris_time, paris_to_utc},
+    storage::ReviewQueueEntry,
+};
+
+/// Latest shift over at `now`, from the Paris local start hours.
+pub fn last_completed_shift(
+    start_hours: &[u32],
+    now: DateTime<Utc>,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let today = paris_time(now).date();
+    // With a single start hour, the previous shift began two days ago.
+    let starts: Vec<DateTime<Utc>> = (0..4)
+        .map(|offset| today - Days::new(2) + Days::new(offset))
+        .flat_map(|day| {
+            start_hours
+                .iter()
+                .filter_map(move |hour| day.and_hms_opt(*hour, 0, 0))
+        })
+        .map(paris_to_utc)
+        .collect();
+    starts
+        .windows(2)
+        .map(|pair| (pair[0], pair[1]))
+        .rfind(|(_, end)| *end <= now)
+}
+
+/// Shifts over at `now` that started at or after `from`, oldest first.
+pub fn completed_shifts_since(
+    start_hours: &[u32],
+    from: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let last_day = paris_time(now).date() + Days::new(1);
+    let starts: Vec<DateTime<Utc>> = (paris_time(from).date() - Days::new(1))
+        .iter_days()
+        .take_while(|day| *day <= last_day)
+        .flat_map(|day| {
+            start_hours
+                .iter()
+                .filter_map(move |hour| day.and_hms_opt(*hour, 0, 0))
+        })
+        .map(paris_to_utc)
+        .collect();
+    starts
+        .windows(2)
+        .map(|pair| (pair[0], pair[1]))
+        .filter(|(start, end)| *start >= from && *end <= now)
+        .collect()
+}
+
+/// Report of the shift `[shift_start, shift_end)` from the checks captured in
+/// it and the state of deviations, reviews and control gaps.
+pub fn shift_report(
+    shift_start: DateTime<Utc>,
+    shift_end: DateTime<Utc>,
+    generated_at: DateTime<Utc>,
+    analyses: &[Analysis],
+    deviations: Vec<Deviation>,
+    review_queue: &[ReviewQueueEntry],
+    missed_checks: Vec<ControlGap>,
+) -> ShiftReport {
+    let count = |status: ComplianceStatus| {
+        analyses
+            .iter()
+            .filter(|analysis| analysis.compliance_status == status)
+            .count()
+    };
+    ShiftReport {
+        id: Uuid::new_v4(),
+        shift_start,
+        shift_end,
+        generated_at,
+        checks: analyses
+            .iter()
+            .map(|analysis| ShiftCheck {
+                analysis_id: analysis.id,
+                captured_at: analysis.captured_at,
+                bath_id: analysis.bath_id.clone(),
+                sample_id: analysis.sample_id.clone(),
+                operator_id: analysis.operator_id.clone(),
+                ppm_estime: analysis.ppm_estime,
+                compliance_status: analysis.compliance_status.clone(),
+                server_lifecycle_status: analysis.server_lifecycle_status.clone(),
+            })
+            .collect(),
+        conforme_production: count(ComplianceStatus::ConformeProduction),
+        taux_bas: count(ComplianceStatus::TauxBas),
+        seuil_depasse: count(ComplianceStatus::SeuilDepasse),
+        rejected_captures: analyses
+            .iter()
+            .filter(|analysis| analysis.server_lifecycle_status == ServerLifecycleStatus::Rejete)
+            .count(),
+        open_deviations: deviations
+            .into_iter()
+            .filter(|deviation| {
+                deviation.status != DeviationStatus::Voided
+                    && deviation.opened_at < shift_end
+                    && deviation
+                        .closed_at
+                        .is_none_or(|closed_at| closed_at >= shift_end)
+            })
+            .collect(),
+        pending_reviews: review_queue
+            .iter()
+            .map(|entry| PendingReview {
+                analysis_id: entry.analysis.id,
+                bath_id: entry.analysis.bath_id.clone(),
+                captured_at: entry.analysis.captured_at,
+                review_reasons: entry.verification.review_reasons.clone(),
+                claimed_by: entry.claim.as_ref().map(|claim| claim.reviewer_id.clone()),
+            })
+            .collect(),
+        missed_checks,
+    }
+}
+
+const MARGIN: f32 = 30.0;
+const FOOTER_Y: f32 = 18.0;
+const ROW_HEIGHT: f32 = 13.0;
+const BLACK: Rgb = [0, 0, 0];
+const GREY: Rgb = [110, 110, 110];
+const RULE: Rgb = [190, 190, 190];
+const HEADER_FILL: Rgb = [225, 230, 238];
+
+/// `13/02/2026 10:45` in Paris time.
+fn paris(at: DateTime<Utc>) -> String {
+    paris_time(at).format("%d/%m/%Y %H:%M").to_string()
+}
+
+fn review_reason_label(reason: ReviewReason) -> &'static str {
+    match reason {
+        ReviewReason::LowConfidence => "confiance faible",
+        ReviewReason::ClientServerDiscrepancy => "écart téléphone / serveur",
+        ReviewReason::NonCompliant => "non conforme",
+    }
+}
+
+/// Pages filled top to bottom, a new page starting when a line no longer
+/// fits above the footer.
+struct Layout {
+    pages: Vec<Page>,
+    page: Page,
+    y: f32,
+}
+
+impl Layout {
+    fn new() -> Self {
+        Self {
+            pages: Vec::new(),
+            page: Page::new(),
+            y: PAGE_HEIGHT - MARGIN - 20.0,
+        }
+    }
+
+    fn reserve(&mut self, height: f32) {
+        if self.y - height < FOOTER_Y + 20.0 {
+            self.pages.push(std::mem::take(&mut self.page));
+            self.y = PAGE_HEIGHT - MARGIN - 10.0;
+        }
+    }
+
+    fn heading(&mut self, text: &str) {
+        self.reserve(40.0);
+        self.y -= 14.0;
+        self.page
+            .text(MARGIN, self.y, 12.0, Font::Bold, BLACK, text);
+        self.y -= 18.0;
+    }
+
+    fn note(&mut self, text: &str) {
+        self.reserve(ROW_HEIGHT);
+        self.page
+            .text(MARGIN, self.y, 9.0, Font::Regular, GREY, text);
+        self.y -= ROW_HEIGHT;
+    }
+
+    fn row(&mut self, columns: &[(&str, f32)], values: &[String], fills: &[Option<Rgb>]) {
+        self.reserve(ROW_HEIGHT);
+        let mut x = MARGIN;
+        for (index, ((_, width), value)) in columns.iter().zip(values).enumerate() {
+            if let Some(fill) = fills.get(index).copied().flatten() {
+                self.page
+                    .fill_rect(x, self.y - 4.0, *width, ROW_HEIGHT, fill);
+            }
+            self.page.text(
+                x + 3.0,
+                self.y,
+                7.5,
+                Font::Regular,
+                BLACK,
+                &truncate(value, width - 6.0, 7.5, Font::Regular),
+            );
+            x += width;
+        }
+        self.page.line(
+            MARGIN,
+            self.y - 4.0,
+            PAGE_WIDTH - MARGIN,
+            self.y - 4.0,
+            0.3,
+            RULE,
+        );
+        self.y -= ROW_HEIGHT;
+    }
+
+    fn header(&mut self, columns: &[(&str, f32)]) {
+        self.reserve(2.0 * ROW_HEIGHT);
+        self.page.fill_rect(
+            MARGIN,
+            self.y - 4.0,
+            PAGE_WIDTH - 2.0 * MARGIN,
+            ROW_HEIGHT,
+            HEADER_FILL,
+        );
+        let mut x = MARGIN;
+        for (title, width) in columns {
+            self.page
+                .text(x + 3.0, self.y, 7.5, Font::Bold, BLACK, title);
+            x += width;
+        }
+        self.y -= ROW_HEIGHT;
+    }
+
+    fn table(&mut self, columns: &[(&str, f32)], rows: Vec<(Vec<String>, Vec<Option<Rgb>>)>) {
+        self.header(columns);
+        for (values, fills) in rows {
+            if self.y - ROW_HEIGHT < FOOTER_Y + 20.0 {
+                self.reserve(ROW_HEIGHT);
+                self.header(columns);
+            }
+            self.row(columns, &values, &fills);
+        }
+    }
+}
+
+/// French handover report, A4 landscape, times in Paris time.
+pub fn shift_report_pdf(report: &ShiftReport) -> Vec<u8> {
+    let title = "Passation de poste — analyses de peroxyde";
+    let mut layout = Layout::new();
+    layout
+        .page
+        .text(MARGIN, layout.y, 18.0, Font::Bold, BLACK, title);
+    layout.y -= 28.0;
+    for (label, value) in [
+        (
+            "Poste (heure de Paris)",
+            format!(
+                "du {} au {}",
+                paris(report.shift_start),
+                paris(report.shift_end)
+            ),
+        ),
+        ("Généré le", paris(report.generated_at)),
+        (
+            "Contrôles",
+            format!(
+                "{} dont {} conformes, {} taux bas, {} seuil dépassé ; {} rejetés par le serveur",
+                report.checks.len(),
+                report.conforme_production,
+                report.taux_bas,
+                report.seuil_depasse,
+                report.rejected_captures
+            ),
+        ),
+        (
+            "À suivre",
+            format!(
+                "{} écart(s) ouvert(s), {} relecture(s) en attente, {} contrôle(s) manquant(s) ou en retard",
+                report.open_deviations.len(),
+                report.pending_reviews.len(),
+                report.missed_checks.len()
+            ),
+        ),
+    ] {
+        layout
+            .page
+            .text(MARGIN, layout.y, 10.0, Font::Bold, BLACK, label);
+        layout
+            .page
+            .text(MARGIN + 150.0, layout.y, 10.0, Font::Regular, BLACK, &value);
+        layout.y -= 15.0;
+    }
+
+    layout.heading("Contrôles du poste");
+    if report.checks.is_empty() {
+        layout.note("Aucun contrôle pendant le poste.");
+    } else {
+        let columns = [
+            ("Capture", 90.0),
+            ("Bain", 100.0),
+            ("Échantillon", 150.0),
+            ("Opérateur", 100.0),
+            ("PPM", 60.0),
+            ("Résultat", 150.0),
+            ("Statut serveur", 132.0),
+        ];
+        let rows = report
+            .checks
+            .iter()
+            .map(|check| {
+                (
+                    vec![
+                        paris(check.captured_at),
+                        check.bath_id.clone(),
+                        check.sample_id.clone(),
+                        check.operator_id.clone(),
+                        format!("{:.0}", check.ppm_estime),
+                        compliance_label(&check.compliance_status).to_string(),
+                        lifecycle_label(&check.server_lifecycle_status).to_string(),
+                    ],
+                    vec![
+                        None,
+                        None,
+                        None,
+                        None,
+                        None,
+                        Some(compliance_color(&check.compliance_status)),
+                    ],
+                )
+            })
+            .collect();
+        layout.table(&columns, rows);
+    }
+
+    layout.heading("Écarts ouverts en fin de poste");
+    if report.open_deviations.is_empty() {
+        layout.note("Aucun écart ouvert.");
+    } else {
+        let columns = [
+            ("Bain", 150.0),
+            ("Ouvert le", 120.0),
+            ("Motif", 200.0),
+            ("Statut", 312.0),
+        ];
+        let rows = report
+            .open_deviations
+            .iter()
+            .map(|deviation| {
+                (
+                    vec![
+                        deviation.bath_id.clone(),
+                        paris(deviation.opened_at),
+                        compliance_label(&deviation.opening_compliance_status).to_string(),
+                        match deviation.status {
+                            DeviationStatus::Open => "Ouvert, recontrôle conforme attendu",
+                            DeviationStatus::Closed => "Clos après la fin du poste",
+                            DeviationStatus::Voided => "Annulé par correction",
+                        }
+                        .to_string(),
+                    ],
+                    vec![
+                        None,
+                        None,
+                        Some(compliance_color(&deviation.opening_compliance_status)),
+                    ],
+                )
+            })
+            .collect();
+        layout.table(&columns, rows);
+    }
+
+    layout.heading("Relectures secondaires en attente");
+    if report.pending_reviews.is_empty() {
+        layout.note("Aucune relecture en attente.");
+    } else {
+        let columns = [
+            ("Bain", 150.0),
+            ("Capture", 120.0),
+            ("Motifs", 312.0),
+            ("Prise en charge par", 200.0),
+        ];
+        let rows = report
+            .pending_reviews
+            .iter()
+            .map(|review| {
+                (
+                    vec![
+                        review.bath_id.clone(),
+                        paris(review.captured_at),
+                        review
+                            .review_reasons
+                            .iter()
+                            .map(|reason| review_reason_label(*reason))
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        review.claimed_by.clone().unwrap_or_else(|| "—".to_string()),
+                    ],
+                    Vec::new(),
+                )
+            })
+            .collect();
+        layout.table(&columns, rows);
+    }
+
+    layout.heading("Contrôles manquants ou en retard");
+    if report.missed_checks.is_empty() {
+        layout.note("Aucun contrôle manquant pendant le poste.");
+    } else {
+        let columns = [
+            ("Bain", 150.0),
+            ("Attendu le", 120.0),
+            ("Contrôle suivant", 120.0),
+            ("Contrôles manqués", 100.0),
+            ("Retard", 292.0),
+        ];
+        let rows = report
+            .missed_checks
+            .iter()
+            .map(|gap| {
+                (
+                    vec![
+                        gap.bath_id.clone(),
+                        paris(gap.due_at),
+                        gap.next_check_at
+                            .map(paris)
+                            .unwrap_or_else(|| "aucun".to_string()),
+                        gap.missed_checks.to_string(),
+                        format!(
+                            "{} h {:02} min{}",
+                            gap.overdue_minutes / 60,
+                            gap.overdue_minutes % 60,
+                            if gap.status == ControlGapStatus::Missing {
+                                " (toujours manquant)"
+                            } else {
+                                ""
+                            }
+                        ),
+                    ],
+                    Vec::new(),
+                )
+            })
+            .collect();
+        layout.table(&columns, rows);
+    }
+
+    // Handover visas, kept together at the end.
+    layout.reserve(80.0);
+    layout.y -= 20.0;
+    let box_width = (PAGE_WIDTH - 2.0 * MARGIN - 20.0) / 2.0;
+    for (index, title) in ["Chef d'équipe sortant", "Chef d'équipe entrant"]
+        .into_iter()
+        .enumerate()
+    {
+        let x = MARGIN + index as f32 * (box_width + 20.0);
+        layout
+            .page
+            .text(x, layout.y, 10.0, Font::Bold, BLACK, title);
+        for (offset, label) in [(20.0, "Nom :"), (40.0, "Signature :")] {
+            layout
+                .page
+                .text(x, layout.y - offset, 9.0, Font::Regular, BLACK, label);
+            layout.page.line(
+                x + 55.0,
+                layout.y - offset - 2.0,
+                x + box_width,
+                layout.y - offset - 2.0,
+                0.5,
+                RULE,
+            );
+        }
+    }
+    layout.pages.push(layout.page);
+
+    let total = layout.pages.len();
+    let mut document = Document::new();
+    for (number, mut page) in layout.pages.into_iter().enumerate() {
+        footer(&mut page, report, number + 1, total);
+        document.add_page(page);
+    }
+    document.to_bytes(title)
+}
+
+fn footer(page: &mut Page, report: &ShiftReport, number: usize, total: usize) {
+    page.line(
+        MARGIN,
+        FOOTER_Y + 10.0,
+        PAGE_WIDTH - MARGIN,
+        FOOTER_Y + 10.0,
+        0.5,
+        RULE,
+    );
+    page.text(
+        MARGIN,
+        FOOTER_Y,
+        7.0,
+        Font::Regular,
+        GREY,
+        &format!("Passation de poste {}", report.id),
+    );
+    let label = format!("Page {number} / {total}");
+    page.text(
+        PAGE_WIDTH - MARGIN - text_width(&label, 7.0, Font::Regular),
+        FOOTER_Y,
+        7.0,
+        Font::Regular,
+        GREY,
+        &label,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+
+    use super::last_completed_shift;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn shifts_follow_paris_hours_across_midnight_and_summer_time() {
+        let hours = [6, 14, 22];
+        assert_eq!(
+            last_completed_shift(&hours, at("2026-02-13T05:30:00Z")),
+            Some((at("2026-02-12T21:00:00Z"), at("2026-02-13T05:00:00Z")))
+        );
+        assert_eq!(
+            last_completed_shift(&hours, at("2026-07-01T12:30:00Z")),
+            Some((at("2026-07-01T04:00:00Z"), at("2026-07-01T12:00:00Z")))
+        );
+        assert_eq!(
+            last_completed_shift(&[6], at("2026-02-13T04:00:00Z")),
+            Some((at("2026-02-11T05:00:00Z"), at("2026-02-12T05:00:00Z")))
+        );
+    }
+}
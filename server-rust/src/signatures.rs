@@ -0,0 +1,75 @@
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    domain::{ElectronicSignature, SignatureMeaning, SignedRecordType, User},
+    images::sha256_hex,
+};
+
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// PHC string (`$argon2id$…`) stored in `users.password_hash`.
+pub fn hash_password(password: &str) -> Result<String, String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|err| err.to_string())
+}
+
+pub fn verify_password(password_hash: &str, password: &str) -> bool {
+    PasswordHash::new(password_hash)
+        .map(|parsed| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &parsed)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+/// Builds the signature of `signer` over `record_hash`; the caller has
+/// already re-authenticated the signer.
+pub fn sign(
+    record_type: SignedRecordType,
+    record_id: String,
+    record_hash: String,
+    signer: &User,
+    meaning: SignatureMeaning,
+    signed_at: DateTime<Utc>,
+) -> ElectronicSignature {
+    let mut signature = ElectronicSignature {
+        id: Uuid::new_v4(),
+        record_type,
+        record_id,
+        record_hash,
+        signer_id: signer.user_id.clone(),
+        signer_display_name: signer.display_name.clone(),
+        meaning,
+        signed_at,
+        signature_hash: String::new(),
+    };
+    signature.signature_hash = manifestation_hash(&signature);
+    signature
+}
+
+/// Hash binding the manifestation to the record.
+fn manifestation_hash(signature: &ElectronicSignature) -> String {
+    let fields = [
+        enum_text(&signature.record_type),
+        signature.record_id.clone(),
+        signature.record_hash.clone(),
+        signature.signer_id.clone(),
+        signature.signer_display_name.clone(),
+        enum_text(&signature.meaning),
+        signature.signed_at.to_rfc3339(),
+    ];
+    sha256_hex(fields.join("\n").as_bytes())
+}
+
+fn enum_text<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap()
+}
@@ -0,0 +1,216 @@
+//! Statistical process control on the P3 concentration: individuals / moving
+//! range chart per bath and the Western Electric / Nelson rules.
+
+use crate::{
+    domain::{ControlChart, ControlChartPoint, ControlLimits, SpcDirection, SpcRule},
+    storage::SpcPoint,
+};
+
+/// d2 constant for moving ranges of two consecutive points.
+const D2: f64 = 1.128;
+/// D4 constant: upper limit of the moving range chart.
+const D4: f64 = 3.267;
+
+/// Phase I limits from the first `baseline` points; `None` with fewer points.
+pub fn control_limits(values: &[f64], baseline: usize) -> Option<ControlLimits> {
+    let baseline = baseline.max(2);
+    if values.len() < baseline {
+        return None;
+    }
+    let sample = &values[..baseline];
+    let center = sample.iter().sum::<f64>() / baseline as f64;
+    let moving_range_center = sample
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).abs())
+        .sum::<f64>()
+        / (baseline - 1) as f64;
+    let sigma = moving_range_center / D2;
+    Some(ControlLimits {
+        center,
+        sigma,
+        upper_control_limit: center + 3.0 * sigma,
+        lower_control_limit: center - 3.0 * sigma,
+        moving_range_center,
+        moving_range_upper_limit: D4 * moving_range_center,
+    })
+}
+
+/// Rules firing on point `index`. Run rules (nine on one side, six trending)
+/// fire on the point completing the run, not again while it goes on. A
+/// constant baseline (`sigma == 0`) has no zones: only the run rules apply.
+pub fn rules_at(
+    values: &[f64],
+    index: usize,
+    limits: &ControlLimits,
+) -> Vec<(SpcRule, SpcDirection)> {
+    let mut fired = Vec::new();
+    let value = values[index];
+    let side = |x: f64| {
+        if x > limits.center {
+            Some(SpcDirection::Up)
+        } else if x < limits.center {
+            Some(SpcDirection::Down)
+        } else {
+            None
+        }
+    };
+    let beyond = |x: f64, sigmas: f64, direction: SpcDirection| match direction {
+        SpcDirection::Up => x > limits.center + sigmas * limits.sigma,
+        SpcDirection::Down => x < limits.center - sigmas * limits.sigma,
+    };
+
+    let Some(direction) = side(value) else {
+        return fired;
+    };
+
+    let zoned = limits.sigma > 0.0;
+    if zoned && beyond(value, 3.0, direction) {
+        fired.push((SpcRule::BeyondControlLimit, direction));
+    }
+
+    let same_side = values[..=index]
+        .iter()
+        .rev()
+        .take_while(|x| side(**x) == Some(direction))
+        .count();
+    if same_side == 9 {
+        fired.push((SpcRule::NineSameSide, direction));
+    }
+
+    if index > 0 && values[index] != values[index - 1] {
+        let trend = if values[index] > values[index - 1] {
+            SpcDirection::Up
+        } else {
+            SpcDirection::Down
+        };
+        let steps = values[..=index]
+            .windows(2)
+            .rev()
+            .take_while(|pair| match trend {
+                SpcDirection::Up => pair[1] > pair[0],
+                SpcDirection::Down => pair[1] < pair[0],
+            })
+            .count();
+        // Six points steadily moving make five steps.
+        if steps == 5 {
+            fired.push((SpcRule::SixTrending, trend));
+        }
+    }
+
+    let recent_beyond = |count: usize, sigmas: f64| {
+        values[index.saturating_sub(count - 1)..=index]
+            .iter()
+            .filter(|x| beyond(**x, sigmas, direction))
+            .count()
+    };
+    if zoned && beyond(value, 2.0, direction) && recent_beyond(3, 2.0) >= 2 {
+        fired.push((SpcRule::TwoOfThreeBeyondTwoSigma, direction));
+    }
+    if zoned && beyond(value, 1.0, direction) && recent_beyond(5, 1.0) >= 4 {
+        fired.push((SpcRule::FourOfFiveBeyondOneSigma, direction));
+    }
+
+    if zoned && index > 0 {
+        let moving_range = (value - values[index - 1]).abs();
+        if moving_range > limits.moving_range_upper_limit {
+            let jump = if value > values[index - 1] {
+                SpcDirection::Up
+            } else {
+                SpcDirection::Down
+            };
+            fired.push((SpcRule::MovingRangeBeyondLimit, jump));
+        }
+    }
+
+    fired
+}
+
+/// Chart of `points`, oldest first, with the rules firing on each point
+/// against the frozen limits of the bath.
+pub fn control_chart(
+    bath_id: String,
+    baseline: usize,
+    limits: Option<ControlLimits>,
+    points: &[SpcPoint],
+) -> ControlChart {
+    let values: Vec<f64> = points.iter().map(|point| point.ppm_estime as f64).collect();
+    let points = points
+        .iter()
+        .enumerate()
+        .map(|(index, point)| ControlChartPoint {
+            analysis_id: point.analysis_id,
+            captured_at: point.captured_at,
+            ppm_estime: point.ppm_estime,
+            moving_range: (index > 0).then(|| (values[index] - values[index - 1]).abs()),
+            signals: limits
+                .as_ref()
+                .map(|limits| {
+                    rules_at(&values, index, limits)
+                        .into_iter()
+                        .map(|(rule, _)| rule)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+        .collect();
+    ControlChart {
+        bath_id,
+        baseline_points: baseline.max(2),
+        limits,
+        points,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{control_limits, rules_at};
+    use crate::domain::{SpcDirection, SpcRule};
+
+    #[test]
+    fn limits_follow_the_individuals_moving_range_constants() {
+        let limits = control_limits(&[270.0, 272.0, 270.0, 272.0], 4).expect("limits");
+        assert_eq!(limits.center, 271.0);
+        assert_eq!(limits.moving_range_center, 2.0);
+        assert!((limits.sigma - 2.0 / 1.128).abs() < 1e-9);
+        assert!((limits.moving_range_upper_limit - 6.534).abs() < 1e-9);
+        assert!(control_limits(&[270.0, 272.0], 4).is_none());
+    }
+
+    #[test]
+    fn run_rules_fire_once_on_the_point_completing_the_run() {
+        let mut values = vec![270.0, 272.0, 270.0, 272.0];
+        let limits = control_limits(&values, 4).expect("limits");
+        values.extend([271.9, 271.8, 271.7, 271.6, 271.5]);
+
+        let fired = |values: &[f64], index| rules_at(values, index, &limits);
+        assert!(fired(&values, 7).is_empty());
+        assert_eq!(
+            fired(&values, 8),
+            vec![(SpcRule::SixTrending, SpcDirection::Down)]
+        );
+        values.push(271.4);
+        assert!(fired(&values, 9).is_empty());
+    }
+
+    #[test]
+    fn a_constant_baseline_only_runs_the_run_rules() {
+        let mut values = vec![300.0; 4];
+        let limits = control_limits(&values, 4).expect("limits");
+        assert_eq!(limits.sigma, 0.0);
+        values.extend([301.0, 301.0, 301.0, 301.0, 301.0]);
+
+        // Any move would be beyond a zero-width zone or moving range limit.
+        for index in 4..9 {
+            assert!(rules_at(&values, index, &limits).is_empty());
+        }
+        values.extend([302.0, 303.0, 304.0, 305.0, 306.0]);
+        assert_eq!(
+            rules_at(&values, 12, &limits),
+            vec![(SpcRule::NineSameSide, SpcDirection::Up)]
+        );
+        assert_eq!(
+            rules_at(&values, 13, &limits),
+            vec![(SpcRule::SixTrending, SpcDirection::Up)]
+        );
+    }
+}
@@ -0,0 +1,648 @@
+//! Periodic compliance statistics for management review: analyses grouped
+//! by day, ISO week or month of the Europe/Paris calendar.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Months, NaiveDate, NaiveDateTime, Utc};
+
+use crate::{
+    domain::{
+        CompliancePeriodStatistics, ComplianceReport, ComplianceStatus, CsvDialect, Deviation,
+        DeviationStatus, ReportPeriod, ReviewDecision,
+    },
+    pdf::{text_width, Document, Font, Page, Rgb, PAGE_HEIGHT, PAGE_WIDTH},
+    reporting::{compliance_color, csv_part, french_datetime, paris_time, RegisterCell},
+    storage::ComplianceObservation,
+};
+
+/// Upper bound on the periods of one report (about three years of days).
+pub const MAX_REPORT_PERIODS: usize = 1100;
+
+impl ReportPeriod {
+    /// First day of the period containing `date`.
+    fn start_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Day => date,
+            Self::Week => {
+                date - chrono::Duration::days(date.weekday().num_days_from_monday().into())
+            }
+            Self::Month => date.with_day(1).expect("first day of month"),
+        }
+    }
+
+    fn next_start(self, start: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Day => start + chrono::Duration::days(1),
+            Self::Week => start + chrono::Duration::days(7),
+            Self::Month => start + Months::new(1),
+        }
+    }
+
+    fn label(self, start: NaiveDate) -> String {
+        match self {
+            Self::Day => start.format("%Y-%m-%d").to_string(),
+            Self::Week => {
+                let week = start.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Self::Month => start.format("%Y-%m").to_string(),
+        }
+    }
+
+    /// Start of every period overlapping `[from, to]`, Paris time. Stops one
+    /// past [`MAX_REPORT_PERIODS`] so callers can refuse longer ranges.
+    pub fn starts(self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<NaiveDate> {
+        let last = paris_time(to).date();
+        let mut starts = Vec::new();
+        let mut start = self.start_of(paris_time(from).date());
+        while start <= last && starts.len() <= MAX_REPORT_PERIODS {
+            starts.push(start);
+            start = self.next_start(start);
+        }
+        starts
+    }
+}
+
+/// One row per period of `[from, to]`, empty periods included.
+/// `deviations` may extend beyond the range: those opened before `from` and
+/// still open count in `open_deviations`. Voided deviations never count.
+pub fn compliance_report(
+    period: ReportPeriod,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    bath_id: Option<String>,
+    observations: &[ComplianceObservation],
+    deviations: &[Deviation],
+) -> ComplianceReport {
+    let starts = period.starts(from, to);
+    let index: HashMap<NaiveDate, usize> = starts
+        .iter()
+        .enumerate()
+        .map(|(position, start)| (*start, position))
+        .collect();
+    let bucket = |at: DateTime<Utc>| index.get(&period.start_of(paris_time(at).date())).copied();
+
+    let mut by_period: Vec<Vec<&ComplianceObservation>> = vec![Vec::new(); starts.len()];
+    for observation in observations {
+        if let Some(position) = bucket(observation.captured_at) {
+            by_period[position].push(observation);
+        }
+    }
+    let mut opened = vec![0; starts.len()];
+    let deviations: Vec<&Deviation> = deviations
+        .iter()
+        .filter(|deviation| deviation.status != DeviationStatus::Voided)
+        .collect();
+    for deviation in &deviations {
+        if (from..=to).contains(&deviation.opened_at) {
+            if let Some(position) = bucket(deviation.opened_at) {
+                opened[position] += 1;
+            }
+        }
+    }
+
+    let to_local = paris_time(to);
+    let periods = starts
+        .iter()
+        .zip(by_period)
+        .zip(opened)
+        .map(|((start, observations), deviations_opened)| {
+            let end = period
+                .next_start(*start)
+                .and_hms_opt(0, 0, 0)
+                .expect("midnight")
+                .min(to_local);
+            period_statistics(
+                period.label(*start),
+                *start,
+                &observations,
+                deviations_opened,
+                open_deviations_at(&deviations, end),
+            )
+        })
+        .collect();
+
+    ComplianceReport {
+        period,
+        from,
+        to,
+        bath_id,
+        generated_at: Utc::now(),
+        periods,
+    }
+}
+
+fn period_statistics(
+    period: String,
+    period_start: NaiveDate,
+    observations: &[&ComplianceObservation],
+    deviations_opened: usize,
+    open_deviations: usize,
+) -> CompliancePeriodStatistics {
+    let checks = observations.len();
+    let count = |status: ComplianceStatus| {
+        observations
+            .iter()
+            .filter(|observation| observation.compliance_status == status)
+            .count()
+    };
+    let percent =
+        |count: usize| (checks > 0).then(|| round_tenth(count as f64 * 100.0 / checks as f64));
+    let reviewed = |decision: ReviewDecision| {
+        observations
+            .iter()
+            .filter(|observation| observation.review_decision == Some(decision))
+            .count()
+    };
+    let ppm = observations
+        .iter()
+        .map(|observation| observation.ppm_estime);
+    let (conforme_production, taux_bas, seuil_depasse) = (
+        count(ComplianceStatus::ConformeProduction),
+        count(ComplianceStatus::TauxBas),
+        count(ComplianceStatus::SeuilDepasse),
+    );
+
+    CompliancePeriodStatistics {
+        period,
+        period_start,
+        checks,
+        conforme_production,
+        taux_bas,
+        seuil_depasse,
+        conforme_production_percent: percent(conforme_production),
+        taux_bas_percent: percent(taux_bas),
+        seuil_depasse_percent: percent(seuil_depasse),
+        ppm_mean: (checks > 0)
+            .then(|| round_tenth(ppm.clone().map(f64::from).sum::<f64>() / checks as f64)),
+        ppm_min: ppm.clone().reduce(f32::min),
+        ppm_max: ppm.reduce(f32::max),
+        rejected_captures: observations
+            .iter()
+            .filter(|observation| observation.rejected)
+            .count(),
+        reviews_confirmed: reviewed(ReviewDecision::Confirmed),
+        reviews_rejected: reviewed(ReviewDecision::Rejected),
+        awaiting_review: observations
+            .iter()
+            .filter(|observation| observation.awaiting_review)
+            .count(),
+        deviations_opened,
+        open_deviations,
+    }
+}
+
+/// Deviations opened before `end` (Paris time) and not closed by then.
+fn open_deviations_at(deviations: &[&Deviation], end: NaiveDateTime) -> usize {
+    deviations
+        .iter()
+        .filter(|deviation| {
+            paris_time(deviation.opened_at) < end
+                && deviation
+                    .closed_at
+                    .is_none_or(|closed_at| paris_time(closed_at) >= end)
+        })
+        .count()
+}
+
+fn round_tenth(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+/// CSV columns, named after the JSON fields of a period.
+const REPORT_COLUMNS: [&str; 18] = [
+    "period",
+    "period_start",
+    "checks",
+    "conforme_production",
+    "taux_bas",
+    "seuil_depasse",
+    "conforme_production_percent",
+    "taux_bas_percent",
+    "seuil_depasse_percent",
+    "ppm_mean",
+    "ppm_min",
+    "ppm_max",
+    "rejected_captures",
+    "reviews_confirmed",
+    "reviews_rejected",
+    "awaiting_review",
+    "deviations_opened",
+    "open_deviations",
+];
+
+/// One row per period, in the CSV dialect of the audit register: shares and
+/// means keep their single decimal.
+pub fn report_csv(report: &ComplianceReport, dialect: CsvDialect) -> Result<Vec<u8>, String> {
+    let tenth =
+        |value: Option<f64>| RegisterCell::optional_number(value.map(|v| format!("{v:.1}")));
+    let count = |value: usize| RegisterCell::Number(value.to_string());
+    let rows: Vec<Vec<RegisterCell>> = report
+        .periods
+        .iter()
+        .map(|period| {
+            vec![
+                RegisterCell::Text(period.period.clone()),
+                RegisterCell::Text(period.period_start.to_string()),
+                count(period.checks),
+                count(period.conforme_production),
+                count(period.taux_bas),
+                count(period.seuil_depasse),
+                tenth(period.conforme_production_percent),
+                tenth(period.taux_bas_percent),
+                tenth(period.seuil_depasse_percent),
+                tenth(period.ppm_mean),
+                RegisterCell::optional_number(period.ppm_min),
+                RegisterCell::optional_number(period.ppm_max),
+                count(period.rejected_captures),
+                count(period.reviews_confirmed),
+                count(period.reviews_rejected),
+                count(period.awaiting_review),
+                count(period.deviations_opened),
+                count(period.open_deviations),
+            ]
+        })
+        .collect();
+    csv_part(&REPORT_COLUMNS, &rows, dialect, true)
+}
+
+const MARGIN: f32 = 30.0;
+const FOOTER_Y: f32 = 18.0;
+const ROW_HEIGHT: f32 = 16.0;
+const BLACK: Rgb = [0, 0, 0];
+const GREY: Rgb = [110, 110, 110];
+const RULE: Rgb = [190, 190, 190];
+const HEADER_FILL: Rgb = [225, 230, 238];
+/// Table columns: title and width in points (782 in total).
+const COLUMNS: [(&str, f32); 10] = [
+    ("Période", 80.0),
+    ("Contrôles", 55.0),
+    ("Conforme", 70.0),
+    ("Taux bas", 70.0),
+    ("Seuil dépassé", 80.0),
+    ("PPM moyen", 60.0),
+    ("PPM min – max", 85.0),
+    ("Captures rejetées", 72.0),
+    ("Relectures conf. / rej. / att.", 120.0),
+    ("Écarts ouverts / en cours", 90.0),
+];
+
+/// French management review report: scope, then one table row per period.
+pub fn report_pdf(report: &ComplianceReport) -> Vec<u8> {
+    let title = "Rapport de conformité — analyses de peroxyde";
+    let granularity = match report.period {
+        ReportPeriod::Day => "jour",
+        ReportPeriod::Week => "semaine ISO",
+        ReportPeriod::Month => "mois",
+    };
+    let scope = [
+        (
+            "Période (UTC)",
+            format!(
+                "du {} au {}",
+                french_datetime(report.from),
+                french_datetime(report.to)
+            ),
+        ),
+        (
+            "Regroupement",
+            format!("par {granularity} (heure de Paris)"),
+        ),
+        (
+            "Bain",
+            report
+                .bath_id
+                .clone()
+                .unwrap_or_else(|| "tous les bains".to_string()),
+        ),
+        ("Généré le (UTC)", french_datetime(report.generated_at)),
+    ];
+
+    let top = PAGE_HEIGHT - MARGIN - 30.0;
+    let first_rows_top = top - 30.0 - 15.0 * scope.len() as f32;
+    let rows_on = |rows_top: f32| ((rows_top - FOOTER_Y - 20.0) / ROW_HEIGHT) as usize;
+    let first_page_rows = rows_on(first_rows_top).min(report.periods.len());
+    let mut chunks = vec![&report.periods[..first_page_rows]];
+    chunks.extend(report.periods[first_page_rows..].chunks(rows_on(top)));
+
+    let total = chunks.len();
+    let mut document = Document::new();
+    for (number, chunk) in chunks.into_iter().enumerate() {
+        let mut page = Page::new();
+        let mut y = top;
+        if number == 0 {
+            page.text(MARGIN, y, 18.0, Font::Bold, BLACK, title);
+            y -= 30.0;
+            for (label, value) in &scope {
+                page.text(MARGIN, y, 10.0, Font::Bold, BLACK, label);
+                page.text(MARGIN + 150.0, y, 10.0, Font::Regular, BLACK, value);
+                y -= 15.0;
+            }
+        }
+        table_header(&mut page, y);
+        y -= ROW_HEIGHT;
+        for period in chunk {
+            period_row(&mut page, y, period);
+            y -= ROW_HEIGHT;
+        }
+        footer(&mut page, report, number + 1, total);
+        document.add_page(page);
+    }
+    document.to_bytes(title)
+}
+
+fn table_header(page: &mut Page, top: f32) {
+    page.fill_rect(MARGIN, top, PAGE_WIDTH - 2.0 * MARGIN, 14.0, HEADER_FILL);
+    let mut x = MARGIN;
+    for (title, width) in COLUMNS {
+        page.text(x + 3.0, top + 4.0, 7.5, Font::Bold, BLACK, title);
+        x += width;
+    }
+}
+
+fn period_row(page: &mut Page, top: f32, period: &CompliancePeriodStatistics) {
+    let share = |count: usize, percent: Option<f64>| match percent {
+        Some(percent) => format!("{count} ({} %)", french_decimal(percent)),
+        None => "—".to_string(),
+    };
+    let cells = [
+        (period.period.clone(), None),
+        (period.checks.to_string(), None),
+        (
+            share(
+                period.conforme_production,
+                period.conforme_production_percent,
+            ),
+            None,
+        ),
+        (
+            share(period.taux_bas, period.taux_bas_percent),
+            (period.taux_bas > 0).then(|| compliance_color(&ComplianceStatus::TauxBas)),
+        ),
+        (
+            share(period.seuil_depasse, period.seuil_depasse_percent),
+            (period.seuil_depasse > 0).then(|| compliance_color(&ComplianceStatus::SeuilDepasse)),
+        ),
+        (
+            period
+                .ppm_mean
+                .map(french_decimal)
+                .unwrap_or_else(|| "—".to_string()),
+            None,
+        ),
+        (
+            match (period.ppm_min, period.ppm_max) {
+                (Some(min), Some(max)) => format!(
+                    "{} – {}",
+                    french_decimal(min.into()),
+                    french_decimal(max.into())
+                ),
+                _ => "—".to_string(),
+            },
+            None,
+        ),
+        (period.rejected_captures.to_string(), None),
+        (
+            format!(
+                "{} / {} / {}",
+                period.reviews_confirmed, period.reviews_rejected, period.awaiting_review
+            ),
+            None,
+        ),
+        (
+            format!("{} / {}", period.deviations_opened, period.open_deviations),
+            None,
+        ),
+    ];
+    let mut x = MARGIN;
+    for ((_, width), (text, fill)) in COLUMNS.iter().zip(cells) {
+        if let Some(fill) = fill {
+            page.fill_rect(x, top, *width, ROW_HEIGHT, fill);
+        }
+        page.text(x + 3.0, top + 5.0, 7.5, Font::Regular, BLACK, &text);
+        x += width;
+    }
+    page.line(MARGIN, top, PAGE_WIDTH - MARGIN, top, 0.3, RULE);
+}
+
+fn footer(page: &mut Page, report: &ComplianceReport, number: usize, total: usize) {
+    page.line(
+        MARGIN,
+        FOOTER_Y + 10.0,
+        PAGE_WIDTH - MARGIN,
+        FOOTER_Y + 10.0,
+        0.5,
+        RULE,
+    );
+    page.text(
+        MARGIN,
+        FOOTER_Y,
+        7.0,
+        Font::Regular,
+        GREY,
+        &format!(
+            "Rapport de conformité généré le {} (UTC)",
+            french_datetime(report.generated_at)
+        ),
+    );
+    let label = format!("Page {number} / {total}");
+    page.text(
+        PAGE_WIDTH - MARGIN - text_width(&label, 7.0, Font::Regular),
+        FOOTER_Y,
+        7.0,
+        Font::Regular,
+        GREY,
+        &label,
+    );
+}
+
+/// `93,3`: one decimal with a comma.
+fn french_decimal(value: f64) -> String {
+    format!("{value:.1}").replace('.', ",")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+
+    use super::{compliance_report, report_csv};
+    use crate::{
+        domain::{ComplianceStatus, CsvDialect, Deviation, DeviationStatus, ReportPeriod},
+        storage::ComplianceObservation,
+    };
+
+    fn at(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    fn observation(captured_at: &str, ppm: f32, status: ComplianceStatus) -> ComplianceObservation {
+        ComplianceObservation {
+            captured_at: at(captured_at),
+            ppm_estime: ppm,
+            compliance_status: status,
+            rejected: false,
+            review_decision: None,
+            awaiting_review: false,
+        }
+    }
+
+    /// `(period, checks)` of every non-empty period.
+    fn counts(
+        period: ReportPeriod,
+        from: &str,
+        to: &str,
+        observations: &[ComplianceObservation],
+    ) -> Vec<(String, usize)> {
+        compliance_report(period, at(from), at(to), None, observations, &[])
+            .periods
+            .into_iter()
+            .filter(|stats| stats.checks > 0)
+            .map(|stats| (stats.period, stats.checks))
+            .collect()
+    }
+
+    #[test]
+    fn iso_weeks_start_on_paris_monday_midnight_across_time_changes() {
+        let conforme = ComplianceStatus::ConformeProduction;
+        let observations = [
+            // Sunday 23:30 and Monday 00:30 in Paris, winter time.
+            observation("2026-03-22T22:30:00Z", 300.0, conforme.clone()),
+            observation("2026-03-22T23:30:00Z", 300.0, conforme.clone()),
+            // The night of the spring change: Monday 00:30 is 22:30 UTC.
+            observation("2026-03-29T21:30:00Z", 300.0, conforme.clone()),
+            observation("2026-03-29T22:30:00Z", 300.0, conforme.clone()),
+            // The night of the autumn change: back to 23:00 UTC.
+            observation("2026-10-25T22:30:00Z", 300.0, conforme.clone()),
+            observation("2026-10-25T23:30:00Z", 300.0, conforme.clone()),
+            // 1 January 2027 is a Friday of the 53rd ISO week of 2026.
+            observation("2027-01-01T12:00:00Z", 300.0, conforme.clone()),
+            observation("2027-01-04T00:30:00Z", 300.0, conforme),
+        ];
+        assert_eq!(
+            counts(
+                ReportPeriod::Week,
+                "2026-03-01T00:00:00Z",
+                "2027-01-10T00:00:00Z",
+                &observations,
+            ),
+            [
+                ("2026-W12".to_string(), 1),
+                ("2026-W13".to_string(), 2),
+                ("2026-W14".to_string(), 1),
+                ("2026-W43".to_string(), 1),
+                ("2026-W44".to_string(), 1),
+                ("2026-W53".to_string(), 1),
+                ("2027-W01".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn months_follow_the_paris_calendar() {
+        let conforme = ComplianceStatus::ConformeProduction;
+        let observations = [
+            // 1 March 00:30 in Paris (winter time).
+            observation("2026-02-28T23:30:00Z", 300.0, conforme.clone()),
+            // 30 June 23:30 and 1 July 00:30 in Paris (summer time).
+            observation("2026-06-30T21:30:00Z", 300.0, conforme.clone()),
+            observation("2026-06-30T22:30:00Z", 300.0, conforme.clone()),
+            // 31 October 23:59 and 1 November 00:00, just after the autumn change.
+            observation("2026-10-31T22:59:00Z", 300.0, conforme.clone()),
+            observation("2026-10-31T23:00:00Z", 300.0, conforme),
+        ];
+        assert_eq!(
+            counts(
+                ReportPeriod::Month,
+                "2026-02-01T00:00:00Z",
+                "2026-11-30T00:00:00Z",
+                &observations,
+            ),
+            [
+                ("2026-03".to_string(), 1),
+                ("2026-06".to_string(), 1),
+                ("2026-07".to_string(), 1),
+                ("2026-10".to_string(), 1),
+                ("2026-11".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn shares_and_means_are_rounded_to_a_tenth_in_every_csv_dialect() {
+        let observations = [
+            observation(
+                "2026-02-09T08:00:00Z",
+                300.0,
+                ComplianceStatus::ConformeProduction,
+            ),
+            observation(
+                "2026-02-09T09:00:00Z",
+                300.0,
+                ComplianceStatus::ConformeProduction,
+            ),
+            observation(
+                "2026-02-09T10:00:00Z",
+                620.5,
+                ComplianceStatus::SeuilDepasse,
+            ),
+        ];
+        let report = compliance_report(
+            ReportPeriod::Day,
+            at("2026-02-09T00:00:00Z"),
+            at("2026-02-09T20:00:00Z"),
+            None,
+            &observations,
+            &[],
+        );
+        let day = &report.periods[0];
+        assert_eq!(day.conforme_production_percent, Some(66.7));
+        assert_eq!(day.seuil_depasse_percent, Some(33.3));
+        assert_eq!(day.taux_bas_percent, Some(0.0));
+        assert_eq!(day.ppm_mean, Some(406.8));
+
+        let standard =
+            String::from_utf8(report_csv(&report, CsvDialect::Standard).unwrap()).unwrap();
+        assert_eq!(
+            standard.lines().nth(1).unwrap(),
+            "2026-02-09,2026-02-09,3,2,0,1,66.7,0.0,33.3,406.8,300,620.5,0,0,0,0,0,0"
+        );
+        let excel = String::from_utf8(report_csv(&report, CsvDialect::ExcelFr).unwrap()).unwrap();
+        let excel = excel.strip_prefix('\u{feff}').expect("UTF-8 BOM for Excel");
+        assert!(excel.starts_with("period;period_start;checks;"));
+        assert_eq!(
+            excel.lines().nth(1).unwrap(),
+            "2026-02-09;2026-02-09;3;2;0;1;66,7;0,0;33,3;406,8;300;620,5;0;0;0;0;0;0"
+        );
+    }
+
+    #[test]
+    fn voided_deviations_are_neither_opened_nor_open() {
+        let deviation = |opened_at: &str, status: DeviationStatus| Deviation {
+            id: uuid::Uuid::new_v4(),
+            bath_id: "bac-p3-1".to_string(),
+            opening_analysis_id: uuid::Uuid::new_v4(),
+            opening_compliance_status: ComplianceStatus::SeuilDepasse,
+            opened_at: at(opened_at),
+            status,
+            closing_analysis_id: None,
+            closed_at: None,
+        };
+        let mut voided = deviation("2026-02-09T10:00:00Z", DeviationStatus::Voided);
+        voided.closed_at = Some(at("2026-02-10T08:00:00Z"));
+        let deviations = [
+            deviation("2026-02-09T09:00:00Z", DeviationStatus::Open),
+            voided,
+        ];
+        let report = compliance_report(
+            ReportPeriod::Day,
+            at("2026-02-09T00:00:00Z"),
+            at("2026-02-09T20:00:00Z"),
+            None,
+            &[],
+            &deviations,
+        );
+        let day = &report.periods[0];
+        assert_eq!(day.deviations_opened, 1);
+        assert_eq!(day.open_deviations, 1);
+    }
+}
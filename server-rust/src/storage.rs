@@ -1,14 +1,163 @@
-use std::{fmt, path::Path};
+use std::{collections::HashMap, fmt, path::Path};
 
 use chrono::{DateTime, Utc};
 use rusqlite::{params, OptionalExtension};
 use tokio_rusqlite::Connection;
 use uuid::Uuid;
 
-use crate::domain::{
-    AcquisitionMetadata, Analysis, ComplianceStatus, ImageReference, ServerLifecycleStatus,
+use crate::{
+    domain::{
+        AcquisitionMetadata, Analysis, AnalysisVerification, AuditEvent, AuditEventType,
+        AuditExport, AuditExportStatus, BrokenLink, BrokenLinkReason, ChainVerification,
+        ComplianceStatus, ControlLimits, ControlPlan, CorrectiveAction, CorrectiveActionStatus,
+        Deviation, DeviationStatus, ElectronicSignature, FailedCheck, ImageReference,
+        IntegrityReport, InvalidTimestamp, Job, JobKind, JobStatus, ProductionEvent,
+        ProductionEventKind, ReviewClaim, ReviewDecision, SecondaryReview, ServerLifecycleStatus,
+        ShiftReport, SignedExportManifest, SignedRecordType, SpcRule, SpcSignal, TimestampFailure,
+        TimestampSubject, TimestampsVerification, TrustedTimestamp, User, VerificationCheck,
+    },
+    images::sha256_hex,
+    timestamping::{self, TsaCertificate},
 };
 
+/// Installed last by `init_schema`. Analyses and audit events are
+/// append-only: recorded content can never be updated nor deleted, the hash
+/// chain columns can only be sealed once, and the lifecycle status only moves
+/// when a row is appended to `analysis_lifecycle_transitions`.
+const APPEND_ONLY_TRIGGERS: &str = "
+    CREATE TRIGGER IF NOT EXISTS analyses_no_result_update
+    BEFORE UPDATE OF
+        id, client_analysis_id, sample_id, operator_id, bath_id, parent_analysis_id,
+        ppm_estime, ppm_min, ppm_max, compliance_status, analysis_result,
+        recommended_action, confidence, analysis_rules_version, calibration_version,
+        captured_at, received_at, image_uri, image_sha256, image_content_type,
+        acquisition_metadata_json, version, supersedes_analysis_id, correction_json
+    ON analyses
+    BEGIN
+        SELECT RAISE(ABORT, 'analyses are append-only: record a correction instead');
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS analyses_chain_sealed
+    BEFORE UPDATE OF chain_prev_hash, chain_hash ON analyses
+    WHEN OLD.chain_hash IS NOT NULL
+    BEGIN
+        SELECT RAISE(ABORT, 'analyses hash chain entries are sealed');
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS analyses_lifecycle_via_transitions
+    BEFORE UPDATE OF server_lifecycle_status ON analyses
+    WHEN NEW.server_lifecycle_status IS NOT (
+        SELECT to_status FROM analysis_lifecycle_transitions
+        WHERE analysis_id = NEW.id ORDER BY id DESC LIMIT 1
+    )
+    BEGIN
+        SELECT RAISE(ABORT, 'lifecycle status changes go through analysis_lifecycle_transitions');
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS analyses_no_delete
+    BEFORE DELETE ON analyses
+    BEGIN
+        SELECT RAISE(ABORT, 'analyses are append-only');
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS lifecycle_transitions_from_current
+    BEFORE INSERT ON analysis_lifecycle_transitions
+    WHEN NEW.from_status IS NOT (
+        SELECT server_lifecycle_status FROM analyses WHERE id = NEW.analysis_id
+    )
+    BEGIN
+        SELECT RAISE(ABORT, 'transition does not start from the current lifecycle status');
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS lifecycle_transitions_apply
+    AFTER INSERT ON analysis_lifecycle_transitions
+    BEGIN
+        UPDATE analyses SET server_lifecycle_status = NEW.to_status WHERE id = NEW.analysis_id;
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS lifecycle_transitions_no_update
+    BEFORE UPDATE ON analysis_lifecycle_transitions
+    BEGIN
+        SELECT RAISE(ABORT, 'lifecycle transitions are append-only');
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS lifecycle_transitions_no_delete
+    BEFORE DELETE ON analysis_lifecycle_transitions
+    BEGIN
+        SELECT RAISE(ABORT, 'lifecycle transitions are append-only');
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS audit_events_no_update
+    BEFORE UPDATE OF
+        id, event_type, analysis_id, actor, client_ip, device, payload_sha256,
+        details_json, occurred_at
+    ON audit_events
+    BEGIN
+        SELECT RAISE(ABORT, 'audit events are append-only');
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS audit_events_chain_sealed
+    BEFORE UPDATE OF chain_prev_hash, chain_hash ON audit_events
+    WHEN OLD.chain_hash IS NOT NULL
+    BEGIN
+        SELECT RAISE(ABORT, 'audit events hash chain entries are sealed');
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS electronic_signatures_no_update
+    BEFORE UPDATE ON electronic_signatures
+    BEGIN
+        SELECT RAISE(ABORT, 'electronic signatures are append-only');
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS electronic_signatures_no_delete
+    BEFORE DELETE ON electronic_signatures
+    BEGIN
+        SELECT RAISE(ABORT, 'electronic signatures are append-only');
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS export_manifests_no_update
+    BEFORE UPDATE ON export_manifests
+    BEGIN
+        SELECT RAISE(ABORT, 'export manifests are append-only');
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS export_manifests_no_delete
+    BEFORE DELETE ON export_manifests
+    BEGIN
+        SELECT RAISE(ABORT, 'export manifests are append-only');
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS trusted_timestamps_no_update
+    BEFORE UPDATE ON trusted_timestamps
+    BEGIN
+        SELECT RAISE(ABORT, 'trusted timestamps are append-only');
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS trusted_timestamps_no_delete
+    BEFORE DELETE ON trusted_timestamps
+    BEGIN
+        SELECT RAISE(ABORT, 'trusted timestamps are append-only');
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS audit_events_no_delete
+    BEFORE DELETE ON audit_events
+    BEGIN
+        SELECT RAISE(ABORT, 'audit events are append-only');
+    END;
+";
+
+/// Verified analyses with at least one review reason and no secondary review
+/// yet. Expects the analyses table aliased as `a` and verifications as `v`.
+const REVIEW_QUEUE_PREDICATE: &str = "
+    a.server_lifecycle_status IN ('\"valide\"', '\"rejete\"')
+    AND v.review_reasons_json <> '[]'
+    AND NOT EXISTS (SELECT 1 FROM secondary_reviews r WHERE r.analysis_id = a.id)
+    AND NOT EXISTS (SELECT 1 FROM analyses s WHERE s.supersedes_analysis_id = a.id)";
+
+/// Latest version of a correction chain, with the analyses table aliased `a`.
+const CURRENT_VERSION_PREDICATE: &str =
+    "NOT EXISTS (SELECT 1 FROM analyses s WHERE s.supersedes_analysis_id = a.id)";
+
 #[derive(Clone)]
 pub struct AnalysisStore {
     conn: Connection,
@@ -21,6 +170,146 @@ pub enum UpsertResult {
     Conflict,
 }
 
+#[derive(Debug)]
+pub struct ReviewQueueEntry {
+    pub analysis: Analysis,
+    pub verification: AnalysisVerification,
+    pub claim: Option<ReviewClaim>,
+}
+
+#[derive(Debug)]
+pub enum ClaimOutcome {
+    Claimed(ReviewClaim),
+    HeldBy(ReviewClaim),
+    NotQueued,
+}
+
+#[derive(Debug)]
+pub enum ReviewOutcome {
+    Recorded(ServerLifecycleStatus),
+    NotFound,
+    NotReviewable(ServerLifecycleStatus),
+    AlreadyReviewed,
+    ClaimedByOther(ReviewClaim),
+}
+
+#[derive(Debug)]
+pub enum CorrectiveActionOutcome {
+    Updated(Box<CorrectiveAction>),
+    NotFound,
+    InvalidStatus(CorrectiveActionStatus),
+}
+
+#[derive(Debug)]
+pub enum ProductionEventOutcome {
+    Recorded(Box<ProductionEvent>),
+    UnknownAnalysis(Uuid),
+    /// The event is dated before the last recorded event of the bath.
+    OutOfOrder(DateTime<Utc>),
+    AlreadyStopped,
+    NotStopped,
+    NoCompliantRecontrol {
+        stopped_at: DateTime<Utc>,
+    },
+}
+
+#[derive(Debug)]
+pub enum CorrectionOutcome {
+    Recorded(Box<Analysis>),
+    NotFound,
+    /// Only the latest version of a chain can be corrected.
+    AlreadySuperseded(Uuid),
+}
+
+/// Last hash of each chain, i.e. what an export certifies.
+#[derive(Debug, Clone)]
+pub struct ChainHeads {
+    pub analyses: String,
+    pub audit_events: String,
+}
+
+impl ChainHeads {
+    /// Message submitted to the TSA by the daily chain heads timestamp.
+    pub fn timestamp_message(&self) -> String {
+        format!("{}\n{}", self.analyses, self.audit_events)
+    }
+}
+
+/// Photo referenced by an analysis, as scanned by the integrity job.
+#[derive(Debug, Clone)]
+pub struct StoredImage {
+    pub analysis_id: Uuid,
+    pub image: ImageReference,
+    /// The server verification found the photo present and matching its
+    /// declared hash.
+    pub verified_intact: bool,
+}
+
+/// Rows of an audit register: analyses captured within `[from, to]` and
+/// received by `received_until`. Paging a register over several queries
+/// then sees the same rows as the chain heads taken when it started.
+#[derive(Debug, Clone, Copy)]
+pub struct AuditRegisterScope {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub received_until: DateTime<Utc>,
+}
+
+/// What the register shows next to a set of its analyses.
+/// Phase I limits of a bath, frozen once its baseline was complete.
+#[derive(Debug, Clone)]
+pub struct SpcBaseline {
+    pub bath_id: String,
+    pub baseline_points: usize,
+    pub limits: ControlLimits,
+    pub frozen_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+pub struct AuditRegisterLinks {
+    /// Original analysis id to the id of the correction replacing it.
+    pub superseded_by: HashMap<Uuid, Uuid>,
+    pub corrective_actions: Vec<CorrectiveAction>,
+    pub review_signatures: Vec<ElectronicSignature>,
+}
+
+/// What the compliance statistics need of one analysis.
+#[derive(Debug, Clone)]
+pub struct ComplianceObservation {
+    pub captured_at: DateTime<Utc>,
+    pub ppm_estime: f32,
+    pub compliance_status: ComplianceStatus,
+    /// The server verification moved it to `rejete` at some point.
+    pub rejected: bool,
+    pub review_decision: Option<ReviewDecision>,
+    pub awaiting_review: bool,
+}
+
+/// Validated check plotted on a bath control chart.
+#[derive(Debug, Clone)]
+pub struct SpcPoint {
+    pub analysis_id: Uuid,
+    pub captured_at: DateTime<Utc>,
+    pub ppm_estime: f32,
+}
+
+#[derive(Debug, Default)]
+pub struct SpcSignalFilter {
+    pub bath_id: Option<String>,
+    pub rule: Option<SpcRule>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default)]
+pub struct AuditEventFilter {
+    pub event_type: Option<AuditEventType>,
+    pub analysis_id: Option<Uuid>,
+    pub actor: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug)]
 pub enum StorageError {
     Sqlite(String),
@@ -48,6 +337,7 @@ impl AnalysisStore {
         Ok(store)
     }
 
+    #[cfg(test)]
     pub async fn new_in_memory() -> Result<Self, StorageError> {
         let conn = Connection::open_in_memory()
             .await
@@ -57,6 +347,15 @@ impl AnalysisStore {
         Ok(store)
     }
 
+    /// Raw SQL access so tests can simulate someone editing the file.
+    #[cfg(test)]
+    pub async fn execute_raw(&self, sql: &'static str) -> Result<(), StorageError> {
+        self.conn
+            .call(move |conn| Ok(conn.execute_batch(sql)?))
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
     async fn init_schema(&self) -> Result<(), StorageError> {
         self.conn
             .call(|conn| {
@@ -88,8 +387,269 @@ impl AnalysisStore {
                     CREATE INDEX IF NOT EXISTS idx_analyses_received_at ON analyses (received_at);
                     CREATE INDEX IF NOT EXISTS idx_analyses_sample_id ON analyses (sample_id);
                     CREATE INDEX IF NOT EXISTS idx_analyses_server_lifecycle_status ON analyses (server_lifecycle_status);
+
+                    CREATE TABLE IF NOT EXISTS analysis_verifications (
+                        analysis_id TEXT PRIMARY KEY REFERENCES analyses (id),
+                        validated_schema INTEGER NOT NULL,
+                        validated_business_rules INTEGER NOT NULL,
+                        failed_checks_json TEXT NOT NULL,
+                        review_reasons_json TEXT NOT NULL,
+                        server_ppm_estime REAL,
+                        server_decision_json TEXT,
+                        verified_at TEXT NOT NULL
+                    );
+
+                    CREATE TABLE IF NOT EXISTS analysis_lifecycle_transitions (
+                        id INTEGER PRIMARY KEY AUTOINCREMENT,
+                        analysis_id TEXT NOT NULL REFERENCES analyses (id),
+                        from_status TEXT NOT NULL,
+                        to_status TEXT NOT NULL,
+                        reason TEXT NOT NULL,
+                        transitioned_at TEXT NOT NULL
+                    );
+
+                    CREATE INDEX IF NOT EXISTS idx_lifecycle_transitions_analysis_id ON analysis_lifecycle_transitions (analysis_id);
+
+                    CREATE TABLE IF NOT EXISTS jobs (
+                        id TEXT PRIMARY KEY,
+                        kind TEXT NOT NULL,
+                        subject_id TEXT NOT NULL,
+                        status TEXT NOT NULL,
+                        attempts INTEGER NOT NULL,
+                        max_attempts INTEGER NOT NULL,
+                        run_after TEXT NOT NULL,
+                        last_error TEXT,
+                        created_at TEXT NOT NULL,
+                        updated_at TEXT NOT NULL
+                    );
+
+                    CREATE INDEX IF NOT EXISTS idx_jobs_status_run_after ON jobs (status, run_after);
+                    CREATE INDEX IF NOT EXISTS idx_jobs_subject_id ON jobs (subject_id);
+
+                    CREATE TABLE IF NOT EXISTS review_claims (
+                        analysis_id TEXT PRIMARY KEY REFERENCES analyses (id),
+                        reviewer_id TEXT NOT NULL,
+                        claimed_at TEXT NOT NULL,
+                        expires_at TEXT NOT NULL
+                    );
+
+                    CREATE TABLE IF NOT EXISTS secondary_reviews (
+                        id TEXT PRIMARY KEY,
+                        analysis_id TEXT NOT NULL UNIQUE REFERENCES analyses (id),
+                        reviewer_id TEXT NOT NULL,
+                        decision TEXT NOT NULL,
+                        comment TEXT,
+                        reviewed_at TEXT NOT NULL,
+                        recorded_at TEXT NOT NULL
+                    );
+
+                    CREATE TABLE IF NOT EXISTS electronic_signatures (
+                        id TEXT PRIMARY KEY,
+                        record_type TEXT NOT NULL,
+                        record_id TEXT NOT NULL,
+                        record_hash TEXT NOT NULL,
+                        signer_id TEXT NOT NULL,
+                        signer_display_name TEXT NOT NULL,
+                        meaning TEXT NOT NULL,
+                        signed_at TEXT NOT NULL,
+                        signature_hash TEXT NOT NULL
+                    );
+
+                    CREATE INDEX IF NOT EXISTS idx_electronic_signatures_record ON electronic_signatures (record_type, record_id);
+
+                    CREATE TABLE IF NOT EXISTS export_manifests (
+                        id TEXT PRIMARY KEY,
+                        manifest_json TEXT NOT NULL,
+                        signature TEXT NOT NULL,
+                        generated_at TEXT NOT NULL
+                    );
+
+                    CREATE TABLE IF NOT EXISTS audit_exports (
+                        id TEXT PRIMARY KEY,
+                        format TEXT NOT NULL,
+                        from_at TEXT,
+                        to_at TEXT,
+                        status TEXT NOT NULL,
+                        requested_by TEXT,
+                        requested_at TEXT NOT NULL,
+                        completed_at TEXT,
+                        expires_at TEXT,
+                        record_count INTEGER,
+                        file_sha256 TEXT,
+                        error TEXT
+                    );
+
+                    CREATE INDEX IF NOT EXISTS idx_audit_exports_status_expires_at ON audit_exports (status, expires_at);
+
+                    CREATE TABLE IF NOT EXISTS trusted_timestamps (
+                        id TEXT PRIMARY KEY,
+                        subject_type TEXT NOT NULL,
+                        subject_id TEXT NOT NULL,
+                        message TEXT NOT NULL,
+                        message_sha256 TEXT NOT NULL,
+                        tsa_url TEXT NOT NULL,
+                        policy TEXT NOT NULL,
+                        serial_number TEXT NOT NULL,
+                        nonce INTEGER NOT NULL,
+                        gen_time TEXT NOT NULL,
+                        requested_at TEXT NOT NULL,
+                        token BLOB NOT NULL
+                    );
+
+                    CREATE INDEX IF NOT EXISTS idx_trusted_timestamps_subject ON trusted_timestamps (subject_type, subject_id);
+
+                    CREATE TABLE IF NOT EXISTS users (
+                        user_id TEXT PRIMARY KEY,
+                        display_name TEXT NOT NULL,
+                        roles_json TEXT NOT NULL,
+                        active INTEGER NOT NULL,
+                        updated_at TEXT NOT NULL
+                    );
+
+                    CREATE TABLE IF NOT EXISTS audit_events (
+                        id TEXT PRIMARY KEY,
+                        event_type TEXT NOT NULL,
+                        analysis_id TEXT,
+                        actor TEXT,
+                        details_json TEXT NOT NULL,
+                        occurred_at TEXT NOT NULL
+                    );
+
+                    CREATE INDEX IF NOT EXISTS idx_audit_events_analysis_id ON audit_events (analysis_id);
+
+                    CREATE TABLE IF NOT EXISTS deviations (
+                        id TEXT PRIMARY KEY,
+                        bath_id TEXT NOT NULL,
+                        opening_analysis_id TEXT NOT NULL UNIQUE,
+                        opening_compliance_status TEXT NOT NULL,
+                        opened_at TEXT NOT NULL,
+                        status TEXT NOT NULL,
+                        closing_analysis_id TEXT,
+                        closed_at TEXT
+                    );
+
+                    CREATE INDEX IF NOT EXISTS idx_deviations_bath_status ON deviations (bath_id, status);
+
+                    CREATE TABLE IF NOT EXISTS corrective_actions (
+                        id TEXT PRIMARY KEY,
+                        analysis_id TEXT NOT NULL,
+                        deviation_id TEXT,
+                        description TEXT NOT NULL,
+                        owner_id TEXT NOT NULL,
+                        due_at TEXT NOT NULL,
+                        status TEXT NOT NULL,
+                        created_by TEXT NOT NULL,
+                        created_at TEXT NOT NULL,
+                        action_taken TEXT,
+                        completed_by TEXT,
+                        completed_at TEXT,
+                        verified_by TEXT,
+                        verification_comment TEXT,
+                        verified_at TEXT
+                    );
+
+                    CREATE INDEX IF NOT EXISTS idx_corrective_actions_analysis_id ON corrective_actions (analysis_id);
+                    CREATE INDEX IF NOT EXISTS idx_corrective_actions_status ON corrective_actions (status);
+
+                    CREATE TABLE IF NOT EXISTS production_events (
+                        id TEXT PRIMARY KEY,
+                        bath_id TEXT NOT NULL,
+                        kind TEXT NOT NULL,
+                        recorded_by TEXT NOT NULL,
+                        occurred_at TEXT NOT NULL,
+                        reason TEXT NOT NULL,
+                        linked_analysis_ids_json TEXT NOT NULL,
+                        recontrol_analysis_id TEXT,
+                        recorded_at TEXT NOT NULL
+                    );
+
+                    CREATE INDEX IF NOT EXISTS idx_production_events_bath ON production_events (bath_id, occurred_at);
+
+                    CREATE TABLE IF NOT EXISTS spc_baselines (
+                        bath_id TEXT PRIMARY KEY,
+                        baseline_points INTEGER NOT NULL,
+                        limits_json TEXT NOT NULL,
+                        frozen_at TEXT NOT NULL
+                    );
+
+                    CREATE TABLE IF NOT EXISTS spc_signals (
+                        id TEXT PRIMARY KEY,
+                        bath_id TEXT NOT NULL,
+                        analysis_id TEXT NOT NULL,
+                        captured_at TEXT NOT NULL,
+                        rule TEXT NOT NULL,
+                        severity TEXT NOT NULL,
+                        direction TEXT NOT NULL,
+                        ppm_estime REAL NOT NULL,
+                        limits_json TEXT NOT NULL,
+                        raised_at TEXT NOT NULL,
+                        UNIQUE (analysis_id, rule)
+                    );
+
+                    CREATE INDEX IF NOT EXISTS idx_spc_signals_bath ON spc_signals (bath_id, captured_at);
+
+                    CREATE TABLE IF NOT EXISTS control_plans (
+                        id TEXT PRIMARY KEY,
+                        bath_id TEXT NOT NULL,
+                        interval_minutes INTEGER NOT NULL,
+                        grace_minutes INTEGER NOT NULL,
+                        active INTEGER NOT NULL,
+                        effective_from TEXT NOT NULL,
+                        updated_by TEXT NOT NULL
+                    );
+
+                    CREATE INDEX IF NOT EXISTS idx_control_plans_bath ON control_plans (bath_id, effective_from);
+
+                    CREATE TABLE IF NOT EXISTS shift_reports (
+                        id TEXT PRIMARY KEY,
+                        shift_start TEXT NOT NULL UNIQUE,
+                        shift_end TEXT NOT NULL,
+                        generated_at TEXT NOT NULL,
+                        report_json TEXT NOT NULL
+                    );
+
+                    CREATE TABLE IF NOT EXISTS control_gap_alerts (
+                        bath_id TEXT NOT NULL,
+                        due_at TEXT NOT NULL,
+                        alerted_at TEXT NOT NULL,
+                        PRIMARY KEY (bath_id, due_at)
+                    );
                     ",
                 )?;
+
+                // Columns added after the first deployments.
+                add_column_if_missing(conn, "analyses", "operator_id", "TEXT NOT NULL DEFAULT ''")?;
+                add_column_if_missing(conn, "analyses", "bath_id", "TEXT NOT NULL DEFAULT ''")?;
+                add_column_if_missing(conn, "analyses", "parent_analysis_id", "TEXT")?;
+                add_column_if_missing(conn, "analyses", "version", "INTEGER NOT NULL DEFAULT 1")?;
+                add_column_if_missing(conn, "analyses", "supersedes_analysis_id", "TEXT")?;
+                add_column_if_missing(conn, "analyses", "correction_json", "TEXT")?;
+                add_column_if_missing(conn, "audit_events", "client_ip", "TEXT")?;
+                add_column_if_missing(conn, "audit_events", "device", "TEXT")?;
+                add_column_if_missing(conn, "audit_events", "payload_sha256", "TEXT")?;
+                add_column_if_missing(conn, "users", "password_hash", "TEXT")?;
+                // One-time migration: rows stored before the chains existed
+                // are sealed in their insertion order, in the transaction that
+                // adds the chain columns. From then on an unsealed row is a
+                // broken link, never something to seal.
+                add_chain_columns(conn, "analyses", analysis_row_chain_content)?;
+                add_chain_columns(conn, "audit_events", audit_event_row_chain_content)?;
+                add_column_if_missing(
+                    conn,
+                    "audit_exports",
+                    "csv_dialect",
+                    "TEXT NOT NULL DEFAULT '\"standard\"'",
+                )?;
+                conn.execute_batch(APPEND_ONLY_TRIGGERS)?;
+                conn.execute_batch(
+                    "CREATE INDEX IF NOT EXISTS idx_audit_events_occurred_at
+                        ON audit_events (occurred_at);",
+                )?;
+                conn.execute_batch(
+                    "CREATE UNIQUE INDEX IF NOT EXISTS idx_analyses_supersedes
+                        ON analyses (supersedes_analysis_id)
+                        WHERE supersedes_analysis_id IS NOT NULL;",
+                )?;
                 Ok(())
             })
             .await
@@ -113,36 +673,16 @@ impl AnalysisStore {
         let analysis_for_insert = analysis.clone();
         self.conn
             .call(move |conn| {
-                conn.execute(
-                    "INSERT INTO analyses (
-                        id, client_analysis_id, sample_id, ppm_estime, ppm_min, ppm_max,
-                        compliance_status, analysis_result, recommended_action, confidence,
-                        analysis_rules_version, calibration_version, captured_at, received_at,
-                        image_uri, image_sha256, image_content_type, acquisition_metadata_json,
-                        server_lifecycle_status
-                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)",
-                    params![
-                        analysis_for_insert.id.to_string(),
-                        analysis_for_insert.client_analysis_id.to_string(),
-                        analysis_for_insert.sample_id,
-                        analysis_for_insert.ppm_estime,
-                        analysis_for_insert.ppm_min,
-                        analysis_for_insert.ppm_max,
-                        serde_json::to_string(&analysis_for_insert.compliance_status).unwrap(),
-                        analysis_for_insert.analysis_result,
-                        analysis_for_insert.recommended_action,
-                        analysis_for_insert.confidence,
-                        analysis_for_insert.analysis_rules_version,
-                        analysis_for_insert.calibration_version,
-                        analysis_for_insert.captured_at.to_rfc3339(),
-                        analysis_for_insert.received_at.to_rfc3339(),
-                        analysis_for_insert.image.uri,
-                        analysis_for_insert.image.sha256,
-                        analysis_for_insert.image.content_type,
-                        acquisition_json,
-                        serde_json::to_string(&analysis_for_insert.server_lifecycle_status).unwrap(),
-                    ],
+                let tx = conn.transaction()?;
+                insert_analysis_row(&tx, &analysis_for_insert, &acquisition_json)?;
+                append_to_chain(
+                    &tx,
+                    "analyses",
+                    &analysis_for_insert.id.to_string(),
+                    &analysis_chain_content(&analysis_for_insert)?,
                 )?;
+                open_deviation(&tx, &analysis_for_insert)?;
+                tx.commit()?;
                 Ok(())
             })
             .await
@@ -151,6 +691,85 @@ impl AnalysisStore {
         Ok(UpsertResult::Inserted(analysis))
     }
 
+    /// Inserts `correction` as the next version of `supersedes_analysis_id`,
+    /// provided that analysis is still the latest version of its chain, and
+    /// carries it over to the deviations the chain opened or closed.
+    pub async fn record_correction(
+        &self,
+        correction: Analysis,
+    ) -> Result<CorrectionOutcome, StorageError> {
+        let Some(original_id) = correction.supersedes_analysis_id else {
+            return Ok(CorrectionOutcome::NotFound);
+        };
+        let acquisition_json = serde_json::to_string(&correction.acquisition_metadata)
+            .map_err(|err| StorageError::Serde(err.to_string()))?;
+
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+                let exists: bool = tx.query_row(
+                    "SELECT EXISTS (SELECT 1 FROM analyses WHERE id = ?1)",
+                    [original_id.to_string()],
+                    |row| row.get(0),
+                )?;
+                if !exists {
+                    return Ok(CorrectionOutcome::NotFound);
+                }
+                if let Some(successor) = find_successor_id(&tx, original_id)? {
+                    return Ok(CorrectionOutcome::AlreadySuperseded(successor));
+                }
+
+                insert_analysis_row(&tx, &correction, &acquisition_json)?;
+                append_to_chain(
+                    &tx,
+                    "analyses",
+                    &correction.id.to_string(),
+                    &analysis_chain_content(&correction)?,
+                )?;
+                correct_deviations(&tx, &correction)?;
+                tx.commit()?;
+                Ok(CorrectionOutcome::Recorded(Box::new(correction)))
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn find_successor(&self, id: Uuid) -> Result<Option<Uuid>, StorageError> {
+        self.conn
+            .call(move |conn| Ok(find_successor_id(conn, id)?))
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Every version of the chain `id` belongs to, oldest first.
+    pub async fn list_version_chain(&self, id: Uuid) -> Result<Vec<Analysis>, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(
+                    "WITH RECURSIVE
+                        up(id, supersedes_id) AS (
+                            SELECT id, supersedes_analysis_id FROM analyses WHERE id = ?1
+                            UNION
+                            SELECT a.id, a.supersedes_analysis_id
+                            FROM analyses a JOIN up ON a.id = up.supersedes_id
+                        ),
+                        down(id) AS (
+                            SELECT id FROM up WHERE supersedes_id IS NULL
+                            UNION
+                            SELECT a.id FROM analyses a JOIN down ON a.supersedes_analysis_id = down.id
+                        )
+                     SELECT a.* FROM analyses a JOIN down ON a.id = down.id
+                     ORDER BY a.version ASC",
+                )?;
+                let versions = stmt
+                    .query_map([id.to_string()], parse_analysis_row)?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(versions)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
     pub async fn find_by_id(&self, id: Uuid) -> Result<Option<Analysis>, StorageError> {
         self.find_one("SELECT * FROM analyses WHERE id = ?1", vec![id.to_string()])
             .await
@@ -185,89 +804,2218 @@ impl AnalysisStore {
             .map_err(|err| StorageError::Sqlite(err.to_string()))
     }
 
-    pub async fn list_paginated(
+    pub async fn save_verification(
         &self,
-        limit: usize,
-        offset: usize,
-        sample_id: Option<&str>,
-    ) -> Result<(Vec<Analysis>, Option<String>), StorageError> {
-        let sample_filter = sample_id.map(str::to_owned);
-        let limit_i64 = limit as i64;
-        let offset_i64 = offset as i64;
+        verification: AnalysisVerification,
+    ) -> Result<(), StorageError> {
+        let failed_checks_json = serde_json::to_string(&verification.failed_checks)
+            .map_err(|err| StorageError::Serde(err.to_string()))?;
+        let review_reasons_json = serde_json::to_string(&verification.review_reasons)
+            .map_err(|err| StorageError::Serde(err.to_string()))?;
+        let server_decision_json = verification
+            .server_decision
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|err| StorageError::Serde(err.to_string()))?;
 
-        let (items, total): (Vec<Analysis>, i64) = self
-            .conn
+        self.conn
             .call(move |conn| {
-                let total: i64 = if let Some(ref sample) = sample_filter {
-                    conn.query_row(
-                        "SELECT COUNT(*) FROM analyses WHERE sample_id = ?1",
-                        [sample],
-                        |row| row.get(0),
-                    )?
-                } else {
-                    conn.query_row("SELECT COUNT(*) FROM analyses", [], |row| row.get(0))?
-                };
-
-                let mut stmt = if sample_filter.is_some() {
-                    conn.prepare(
-                        "SELECT * FROM analyses WHERE sample_id = ?1 ORDER BY received_at DESC LIMIT ?2 OFFSET ?3",
-                    )?
-                } else {
-                    conn.prepare("SELECT * FROM analyses ORDER BY received_at DESC LIMIT ?1 OFFSET ?2")?
-                };
-
-                let analyses = if let Some(ref sample) = sample_filter {
-                    stmt.query_map(params![sample, limit_i64, offset_i64], parse_analysis_row)?
-                        .collect::<Result<Vec<_>, _>>()?
-                } else {
-                    stmt.query_map(params![limit_i64, offset_i64], parse_analysis_row)?
-                        .collect::<Result<Vec<_>, _>>()?
-                };
+                conn.execute(
+                    "INSERT INTO analysis_verifications (
+                        analysis_id, validated_schema, validated_business_rules,
+                        failed_checks_json, review_reasons_json, server_ppm_estime,
+                        server_decision_json, verified_at
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                    ON CONFLICT (analysis_id) DO UPDATE SET
+                        validated_schema = excluded.validated_schema,
+                        validated_business_rules = excluded.validated_business_rules,
+                        failed_checks_json = excluded.failed_checks_json,
+                        review_reasons_json = excluded.review_reasons_json,
+                        server_ppm_estime = excluded.server_ppm_estime,
+                        server_decision_json = excluded.server_decision_json,
+                        verified_at = excluded.verified_at",
+                    params![
+                        verification.analysis_id.to_string(),
+                        verification.validated_schema,
+                        verification.validated_business_rules,
+                        failed_checks_json,
+                        review_reasons_json,
+                        verification.server_ppm_estime,
+                        server_decision_json,
+                        verification.verified_at.to_rfc3339(),
+                    ],
+                )?;
+                Ok(())
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
 
-                Ok((analyses, total))
+    pub async fn find_verification(
+        &self,
+        analysis_id: Uuid,
+    ) -> Result<Option<AnalysisVerification>, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let found = conn
+                    .query_row(
+                        "SELECT * FROM analysis_verifications WHERE analysis_id = ?1",
+                        [analysis_id.to_string()],
+                        parse_verification_row,
+                    )
+                    .optional()?;
+                Ok(found)
             })
             .await
-            .map_err(|err| StorageError::Sqlite(err.to_string()))?;
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
 
-        let next_offset = offset + items.len();
-        let next_cursor = (next_offset < total as usize).then(|| next_offset.to_string());
+    /// Moves an analysis to `to` only if it is still in `from`, recording the
+    /// transition. Returns `false` when another writer got there first.
+    pub async fn transition_lifecycle(
+        &self,
+        analysis_id: Uuid,
+        from: ServerLifecycleStatus,
+        to: ServerLifecycleStatus,
+        reason: &str,
+    ) -> Result<bool, StorageError> {
+        let from_text = enum_text(&from);
+        let to_text = enum_text(&to);
+        let reason = reason.to_string();
 
-        Ok((items, next_cursor))
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+                let applied = apply_transition(&tx, analysis_id, &from_text, &to_text, &reason)?;
+                tx.commit()?;
+                Ok(applied)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
     }
 
-    pub async fn list_for_audit_csv(
+    pub async fn enqueue_job(
         &self,
-        from: Option<DateTime<Utc>>,
-        to: Option<DateTime<Utc>>,
-    ) -> Result<Vec<Analysis>, StorageError> {
-        let from_s = from.map(|dt| dt.to_rfc3339());
-        let to_s = to.map(|dt| dt.to_rfc3339());
+        kind: JobKind,
+        subject_id: Uuid,
+        max_attempts: u32,
+    ) -> Result<Job, StorageError> {
+        let now = Utc::now();
+        let job = Job {
+            id: Uuid::new_v4(),
+            kind,
+            subject_id,
+            status: JobStatus::Queued,
+            attempts: 0,
+            max_attempts,
+            run_after: now,
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+        };
 
+        let job_for_insert = job.clone();
         self.conn
             .call(move |conn| {
-                let mut stmt = conn.prepare(
-                    "SELECT * FROM analyses
-                     WHERE (?1 IS NULL OR captured_at >= ?1)
-                       AND (?2 IS NULL OR captured_at <= ?2)
-                     ORDER BY captured_at ASC",
+                conn.execute(
+                    "INSERT INTO jobs (
+                        id, kind, subject_id, status, attempts, max_attempts, run_after,
+                        last_error, created_at, updated_at
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    params![
+                        job_for_insert.id.to_string(),
+                        enum_text(&job_for_insert.kind),
+                        job_for_insert.subject_id.to_string(),
+                        enum_text(&job_for_insert.status),
+                        job_for_insert.attempts,
+                        job_for_insert.max_attempts,
+                        job_for_insert.run_after.to_rfc3339(),
+                        job_for_insert.last_error,
+                        job_for_insert.created_at.to_rfc3339(),
+                        job_for_insert.updated_at.to_rfc3339(),
+                    ],
                 )?;
+                Ok(())
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))?;
 
-                let analyses = stmt
-                    .query_map(params![from_s, to_s], parse_analysis_row)?
-                    .collect::<Result<Vec<_>, _>>()?;
-                Ok(analyses)
+        Ok(job)
+    }
+
+    /// Atomically takes the oldest due job and marks it running.
+    pub async fn claim_next_job(&self) -> Result<Option<Job>, StorageError> {
+        let queued = enum_text(&JobStatus::Queued);
+        let running = enum_text(&JobStatus::Running);
+        self.conn
+            .call(move |conn| {
+                let now = Utc::now().to_rfc3339();
+                let claimed = conn
+                    .query_row(
+                        "UPDATE jobs
+                         SET status = ?1, attempts = attempts + 1, updated_at = ?2
+                         WHERE id = (
+                             SELECT id FROM jobs
+                             WHERE status = ?3 AND run_after <= ?2
+                             ORDER BY run_after ASC, created_at ASC
+                             LIMIT 1
+                         )
+                         RETURNING *",
+                        params![running, now, queued],
+                        parse_job_row,
+                    )
+                    .optional()?;
+                Ok(claimed)
             })
             .await
             .map_err(|err| StorageError::Sqlite(err.to_string()))
     }
-}
-
-fn parse_analysis_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Analysis> {
-    let compliance_status_json: String = row.get("compliance_status")?;
-    let lifecycle_status_json: String = row.get("server_lifecycle_status")?;
-    let acquisition_json: String = row.get("acquisition_metadata_json")?;
 
-    let compliance_status = serde_json::from_str::<ComplianceStatus>(&compliance_status_json)
+    pub async fn finish_job(
+        &self,
+        job_id: Uuid,
+        status: JobStatus,
+        error: Option<String>,
+    ) -> Result<(), StorageError> {
+        let status_text = enum_text(&status);
+        self.conn
+            .call(move |conn| {
+                conn.execute(
+                    "UPDATE jobs SET status = ?1, last_error = ?2, updated_at = ?3 WHERE id = ?4",
+                    params![
+                        status_text,
+                        error,
+                        Utc::now().to_rfc3339(),
+                        job_id.to_string()
+                    ],
+                )?;
+                Ok(())
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn retry_job(
+        &self,
+        job_id: Uuid,
+        run_after: DateTime<Utc>,
+        error: String,
+    ) -> Result<(), StorageError> {
+        let queued = enum_text(&JobStatus::Queued);
+        self.conn
+            .call(move |conn| {
+                conn.execute(
+                    "UPDATE jobs SET status = ?1, run_after = ?2, last_error = ?3, updated_at = ?4
+                     WHERE id = ?5",
+                    params![
+                        queued,
+                        run_after.to_rfc3339(),
+                        error,
+                        Utc::now().to_rfc3339(),
+                        job_id.to_string()
+                    ],
+                )?;
+                Ok(())
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Startup recovery: jobs left running by a previous process go back to the
+    /// queue, and `recu` analyses without any pending job get one.
+    pub async fn recover_jobs(&self, max_attempts: u32) -> Result<usize, StorageError> {
+        let queued = enum_text(&JobStatus::Queued);
+        let running = enum_text(&JobStatus::Running);
+        let verify = enum_text(&JobKind::VerifyAnalysis);
+        let recu = enum_text(&ServerLifecycleStatus::Recu);
+
+        self.conn
+            .call(move |conn| {
+                let now = Utc::now().to_rfc3339();
+                let tx = conn.transaction()?;
+                tx.execute(
+                    "UPDATE jobs SET status = ?1, updated_at = ?2 WHERE status = ?3",
+                    params![queued, now, running],
+                )?;
+
+                let orphans = {
+                    let mut stmt = tx.prepare(
+                        "SELECT id FROM analyses a
+                         WHERE a.server_lifecycle_status = ?1
+                           AND NOT EXISTS (
+                               SELECT 1 FROM jobs j
+                               WHERE j.subject_id = a.id AND j.kind = ?2 AND j.status = ?3
+                           )",
+                    )?;
+                    let ids = stmt
+                        .query_map(params![recu, verify, queued], |row| row.get::<_, String>(0))?
+                        .collect::<Result<Vec<_>, _>>()?;
+                    ids
+                };
+
+                for analysis_id in &orphans {
+                    tx.execute(
+                        "INSERT INTO jobs (
+                            id, kind, subject_id, status, attempts, max_attempts, run_after,
+                            last_error, created_at, updated_at
+                        ) VALUES (?1, ?2, ?3, ?4, 0, ?5, ?6, NULL, ?6, ?6)",
+                        params![
+                            Uuid::new_v4().to_string(),
+                            verify,
+                            analysis_id,
+                            queued,
+                            max_attempts,
+                            now
+                        ],
+                    )?;
+                }
+                tx.commit()?;
+                Ok(orphans.len())
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn list_jobs(
+        &self,
+        status: Option<JobStatus>,
+        kind: Option<JobKind>,
+        limit: usize,
+    ) -> Result<Vec<Job>, StorageError> {
+        let status_text = status.map(|status| enum_text(&status));
+        let kind_text = kind.map(|kind| enum_text(&kind));
+        let limit_i64 = limit as i64;
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT * FROM jobs
+                     WHERE (?1 IS NULL OR status = ?1)
+                       AND (?2 IS NULL OR kind = ?2)
+                     ORDER BY created_at DESC
+                     LIMIT ?3",
+                )?;
+                let jobs = stmt
+                    .query_map(params![status_text, kind_text, limit_i64], parse_job_row)?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(jobs)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn count_jobs_by_status(&self) -> Result<Vec<(JobStatus, i64)>, StorageError> {
+        self.conn
+            .call(|conn| {
+                let mut stmt = conn
+                    .prepare("SELECT status, COUNT(*) FROM jobs GROUP BY status ORDER BY status")?;
+                let counts = stmt
+                    .query_map([], |row| {
+                        Ok((
+                            json_column::<JobStatus>(&row.get::<_, String>(0)?)?,
+                            row.get::<_, i64>(1)?,
+                        ))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(counts)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn list_review_queue(
+        &self,
+        received_after: Option<DateTime<Utc>>,
+        received_before: Option<DateTime<Utc>>,
+    ) -> Result<Vec<ReviewQueueEntry>, StorageError> {
+        let after = received_after.map(|dt| dt.to_rfc3339());
+        let before = received_before.map(|dt| dt.to_rfc3339());
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT a.*, v.*,
+                            c.reviewer_id AS claim_reviewer_id,
+                            c.claimed_at AS claim_claimed_at,
+                            c.expires_at AS claim_expires_at
+                     FROM analyses a
+                     JOIN analysis_verifications v ON v.analysis_id = a.id
+                     LEFT JOIN review_claims c ON c.analysis_id = a.id
+                     WHERE {REVIEW_QUEUE_PREDICATE}
+                       AND (?1 IS NULL OR a.received_at >= ?1)
+                       AND (?2 IS NULL OR a.received_at <= ?2)
+                     ORDER BY a.received_at ASC"
+                ))?;
+                let entries = stmt
+                    .query_map(params![after, before], |row| {
+                        let analysis = parse_analysis_row(row)?;
+                        let claim = match row.get::<_, Option<String>>("claim_reviewer_id")? {
+                            Some(reviewer_id) => Some(ReviewClaim {
+                                analysis_id: analysis.id,
+                                reviewer_id,
+                                claimed_at: parse_timestamp(row.get("claim_claimed_at")?)?,
+                                expires_at: parse_timestamp(row.get("claim_expires_at")?)?,
+                            }),
+                            None => None,
+                        };
+                        Ok(ReviewQueueEntry {
+                            verification: parse_verification_row(row)?,
+                            analysis,
+                            claim,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(entries)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Assigns a queued analysis to `reviewer_id` until `expires_at`. A claim
+    /// held by someone else is only taken over once it has expired.
+    pub async fn claim_review(
+        &self,
+        analysis_id: Uuid,
+        reviewer_id: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<ClaimOutcome, StorageError> {
+        let reviewer_id = reviewer_id.to_string();
+        self.conn
+            .call(move |conn| {
+                let now = Utc::now();
+                let tx = conn.transaction()?;
+                let queued: bool = tx.query_row(
+                    &format!(
+                        "SELECT EXISTS (
+                            SELECT 1 FROM analyses a
+                            JOIN analysis_verifications v ON v.analysis_id = a.id
+                            WHERE a.id = ?1 AND {REVIEW_QUEUE_PREDICATE}
+                        )"
+                    ),
+                    [analysis_id.to_string()],
+                    |row| row.get(0),
+                )?;
+                if !queued {
+                    return Ok(ClaimOutcome::NotQueued);
+                }
+
+                if let Some(existing) = find_claim(&tx, analysis_id)? {
+                    if existing.is_active(now) && existing.reviewer_id != reviewer_id {
+                        return Ok(ClaimOutcome::HeldBy(existing));
+                    }
+                }
+
+                let claim = ReviewClaim {
+                    analysis_id,
+                    reviewer_id,
+                    claimed_at: now,
+                    expires_at,
+                };
+                tx.execute(
+                    "INSERT INTO review_claims (analysis_id, reviewer_id, claimed_at, expires_at)
+                     VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT (analysis_id) DO UPDATE SET
+                        reviewer_id = excluded.reviewer_id,
+                        claimed_at = excluded.claimed_at,
+                        expires_at = excluded.expires_at",
+                    params![
+                        claim.analysis_id.to_string(),
+                        claim.reviewer_id,
+                        claim.claimed_at.to_rfc3339(),
+                        claim.expires_at.to_rfc3339()
+                    ],
+                )?;
+                tx.commit()?;
+                Ok(ClaimOutcome::Claimed(claim))
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn release_claim(
+        &self,
+        analysis_id: Uuid,
+        reviewer_id: &str,
+    ) -> Result<bool, StorageError> {
+        let reviewer_id = reviewer_id.to_string();
+        self.conn
+            .call(move |conn| {
+                let deleted = conn.execute(
+                    "DELETE FROM review_claims WHERE analysis_id = ?1 AND reviewer_id = ?2",
+                    params![analysis_id.to_string(), reviewer_id],
+                )?;
+                Ok(deleted == 1)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Stores the second reading with the reviewer's electronic signature,
+    /// moves the analysis to `revu_secondairement` and clears its claim, all
+    /// in one transaction.
+    pub async fn record_secondary_review(
+        &self,
+        review: SecondaryReview,
+        signature: ElectronicSignature,
+    ) -> Result<ReviewOutcome, StorageError> {
+        let reviewed_status = ServerLifecycleStatus::RevuSecondairement;
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+                let status_text: Option<String> = tx
+                    .query_row(
+                        "SELECT server_lifecycle_status FROM analyses WHERE id = ?1",
+                        [review.analysis_id.to_string()],
+                        |row| row.get(0),
+                    )
+                    .optional()?;
+                let Some(status_text) = status_text else {
+                    return Ok(ReviewOutcome::NotFound);
+                };
+                let status: ServerLifecycleStatus = json_column(&status_text)?;
+
+                let already_reviewed: bool = tx.query_row(
+                    "SELECT EXISTS (SELECT 1 FROM secondary_reviews WHERE analysis_id = ?1)",
+                    [review.analysis_id.to_string()],
+                    |row| row.get(0),
+                )?;
+                if already_reviewed {
+                    return Ok(ReviewOutcome::AlreadyReviewed);
+                }
+                if !matches!(
+                    status,
+                    ServerLifecycleStatus::Valide | ServerLifecycleStatus::Rejete
+                ) {
+                    return Ok(ReviewOutcome::NotReviewable(status));
+                }
+                if let Some(claim) = find_claim(&tx, review.analysis_id)? {
+                    if claim.is_active(Utc::now()) && claim.reviewer_id != review.reviewer_id {
+                        return Ok(ReviewOutcome::ClaimedByOther(claim));
+                    }
+                }
+
+                tx.execute(
+                    "INSERT INTO secondary_reviews (
+                        id, analysis_id, reviewer_id, decision, comment, reviewed_at, recorded_at
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        review.id.to_string(),
+                        review.analysis_id.to_string(),
+                        review.reviewer_id,
+                        enum_text(&review.decision),
+                        review.comment,
+                        review.reviewed_at.to_rfc3339(),
+                        review.recorded_at.to_rfc3339()
+                    ],
+                )?;
+                insert_signature(&tx, &signature)?;
+                apply_transition(
+                    &tx,
+                    review.analysis_id,
+                    &status_text,
+                    &enum_text(&reviewed_status),
+                    &format!("secondary review by {}", review.reviewer_id),
+                )?;
+                tx.execute(
+                    "DELETE FROM review_claims WHERE analysis_id = ?1",
+                    [review.analysis_id.to_string()],
+                )?;
+                tx.commit()?;
+                Ok(ReviewOutcome::Recorded(reviewed_status))
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn find_secondary_review(
+        &self,
+        analysis_id: Uuid,
+    ) -> Result<Option<SecondaryReview>, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let found = conn
+                    .query_row(
+                        "SELECT * FROM secondary_reviews WHERE analysis_id = ?1",
+                        [analysis_id.to_string()],
+                        parse_secondary_review_row,
+                    )
+                    .optional()?;
+                Ok(found)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn upsert_user(&self, user: User) -> Result<(), StorageError> {
+        let roles_json = serde_json::to_string(&user.roles)
+            .map_err(|err| StorageError::Serde(err.to_string()))?;
+        self.conn
+            .call(move |conn| {
+                conn.execute(
+                    "INSERT INTO users (user_id, display_name, roles_json, active, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5)
+                     ON CONFLICT (user_id) DO UPDATE SET
+                        display_name = excluded.display_name,
+                        roles_json = excluded.roles_json,
+                        active = excluded.active,
+                        updated_at = excluded.updated_at",
+                    params![
+                        user.user_id,
+                        user.display_name,
+                        roles_json,
+                        user.active,
+                        user.updated_at.to_rfc3339()
+                    ],
+                )?;
+                Ok(())
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn set_user_password(
+        &self,
+        user_id: String,
+        password_hash: String,
+    ) -> Result<(), StorageError> {
+        self.conn
+            .call(move |conn| {
+                conn.execute(
+                    "UPDATE users SET password_hash = ?1 WHERE user_id = ?2",
+                    params![password_hash, user_id],
+                )?;
+                Ok(())
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn find_password_hash(&self, user_id: &str) -> Result<Option<String>, StorageError> {
+        let user_id = user_id.to_string();
+        self.conn
+            .call(move |conn| {
+                let hash = conn
+                    .query_row(
+                        "SELECT password_hash FROM users WHERE user_id = ?1",
+                        [user_id],
+                        |row| row.get::<_, Option<String>>(0),
+                    )
+                    .optional()?;
+                Ok(hash.flatten())
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn find_user(&self, user_id: &str) -> Result<Option<User>, StorageError> {
+        let user_id = user_id.to_string();
+        self.conn
+            .call(move |conn| {
+                let found = conn
+                    .query_row(
+                        "SELECT * FROM users WHERE user_id = ?1",
+                        [user_id],
+                        parse_user_row,
+                    )
+                    .optional()?;
+                Ok(found)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn list_users(&self) -> Result<Vec<User>, StorageError> {
+        self.conn
+            .call(|conn| {
+                let mut stmt = conn.prepare("SELECT * FROM users ORDER BY user_id")?;
+                let users = stmt
+                    .query_map([], parse_user_row)?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(users)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Chain hash of an analysis: the record hash its signatures bind to.
+    pub async fn analysis_record_hash(&self, id: Uuid) -> Result<Option<String>, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let hash = conn
+                    .query_row(
+                        "SELECT chain_hash FROM analyses WHERE id = ?1",
+                        [id.to_string()],
+                        |row| row.get::<_, Option<String>>(0),
+                    )
+                    .optional()?;
+                Ok(hash.flatten())
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn record_signature(
+        &self,
+        signature: ElectronicSignature,
+    ) -> Result<(), StorageError> {
+        self.conn
+            .call(move |conn| Ok(insert_signature(conn, &signature)?))
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn list_signatures(
+        &self,
+        record_type: Option<SignedRecordType>,
+        record_id: Option<String>,
+    ) -> Result<Vec<ElectronicSignature>, StorageError> {
+        let record_type_text = record_type.map(|record_type| enum_text(&record_type));
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT * FROM electronic_signatures
+                     WHERE (?1 IS NULL OR record_type = ?1)
+                       AND (?2 IS NULL OR record_id = ?2)
+                     ORDER BY signed_at ASC, rowid ASC",
+                )?;
+                let signatures = stmt
+                    .query_map(params![record_type_text, record_id], parse_signature_row)?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(signatures)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn insert_audit_export(&self, export: AuditExport) -> Result<(), StorageError> {
+        self.conn
+            .call(move |conn| {
+                conn.execute(
+                    "INSERT INTO audit_exports (
+                        id, format, from_at, to_at, status, requested_by, requested_at,
+                        completed_at, expires_at, record_count, file_sha256, error, csv_dialect
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                    params![
+                        export.id.to_string(),
+                        enum_text(&export.format),
+                        export.from.map(|at| at.to_rfc3339()),
+                        export.to.map(|at| at.to_rfc3339()),
+                        enum_text(&export.status),
+                        export.requested_by,
+                        export.requested_at.to_rfc3339(),
+                        export.completed_at.map(|at| at.to_rfc3339()),
+                        export.expires_at.map(|at| at.to_rfc3339()),
+                        export.record_count.map(|count| count as i64),
+                        export.file_sha256,
+                        export.error,
+                        enum_text(&export.csv_dialect)
+                    ],
+                )?;
+                Ok(())
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn find_audit_export(
+        &self,
+        export_id: Uuid,
+    ) -> Result<Option<AuditExport>, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let found = conn
+                    .query_row(
+                        "SELECT * FROM audit_exports WHERE id = ?1",
+                        [export_id.to_string()],
+                        parse_audit_export_row,
+                    )
+                    .optional()?;
+                Ok(found)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Moves an export to `running`, `failed` or `expired`.
+    pub async fn set_audit_export_status(
+        &self,
+        export_id: Uuid,
+        status: AuditExportStatus,
+        error: Option<String>,
+    ) -> Result<(), StorageError> {
+        let status_text = enum_text(&status);
+        self.conn
+            .call(move |conn| {
+                conn.execute(
+                    "UPDATE audit_exports SET status = ?1, error = ?2 WHERE id = ?3",
+                    params![status_text, error, export_id.to_string()],
+                )?;
+                Ok(())
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Stores the signed manifest and marks the export ready, in one
+    /// transaction so a retried job never finds a manifest without its file.
+    pub async fn complete_audit_export(
+        &self,
+        signed: SignedExportManifest,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), StorageError> {
+        let manifest_json = serde_json::to_string(&signed.manifest)
+            .map_err(|err| StorageError::Serde(err.to_string()))?;
+        let ready = enum_text(&AuditExportStatus::Ready);
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+                let manifest = &signed.manifest;
+                tx.execute(
+                    "INSERT INTO export_manifests (id, manifest_json, signature, generated_at)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        manifest.export_id.to_string(),
+                        manifest_json,
+                        signed.signature,
+                        manifest.generated_at.to_rfc3339()
+                    ],
+                )?;
+                tx.execute(
+                    "UPDATE audit_exports
+                     SET status = ?1, completed_at = ?2, expires_at = ?3, record_count = ?4,
+                         file_sha256 = ?5, error = NULL
+                     WHERE id = ?6",
+                    params![
+                        ready,
+                        manifest.generated_at.to_rfc3339(),
+                        expires_at.to_rfc3339(),
+                        manifest.record_count as i64,
+                        manifest.file_sha256,
+                        manifest.export_id.to_string()
+                    ],
+                )?;
+                tx.commit()?;
+                Ok(())
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Ready exports whose retention period ended at `now`.
+    pub async fn list_expired_audit_exports(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<AuditExport>, StorageError> {
+        let ready = enum_text(&AuditExportStatus::Ready);
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT * FROM audit_exports
+                     WHERE status = ?1 AND expires_at <= ?2
+                     ORDER BY expires_at ASC",
+                )?;
+                let exports = stmt
+                    .query_map(params![ready, now.to_rfc3339()], parse_audit_export_row)?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(exports)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Moves the exported analyses to `exporte_audit`, in one transaction.
+    /// Only analyses with a final verdict move: `revu_secondairement`, or
+    /// `valide`/`rejete` outside the review queue. Returns each moved
+    /// analysis with the status it left.
+    pub async fn mark_exported_audit(
+        &self,
+        analysis_ids: Vec<Uuid>,
+        reason: &str,
+    ) -> Result<Vec<(Uuid, ServerLifecycleStatus)>, StorageError> {
+        let exported = enum_text(&ServerLifecycleStatus::ExporteAudit);
+        let exportable = [
+            ServerLifecycleStatus::Valide,
+            ServerLifecycleStatus::Rejete,
+            ServerLifecycleStatus::RevuSecondairement,
+        ]
+        .map(|status| enum_text(&status));
+        let reason = reason.to_string();
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+                let mut moved = Vec::new();
+                {
+                    let mut eligible = tx.prepare(&format!(
+                        "SELECT e.server_lifecycle_status FROM analyses e
+                         WHERE e.id = ?1
+                           AND e.server_lifecycle_status IN (?2, ?3, ?4)
+                           AND e.id NOT IN (
+                               SELECT a.id FROM analyses a
+                               JOIN analysis_verifications v ON v.analysis_id = a.id
+                               WHERE {REVIEW_QUEUE_PREDICATE}
+                           )"
+                    ))?;
+                    for analysis_id in analysis_ids {
+                        let status_text: Option<String> = eligible
+                            .query_row(
+                                params![
+                                    analysis_id.to_string(),
+                                    exportable[0],
+                                    exportable[1],
+                                    exportable[2]
+                                ],
+                                |row| row.get(0),
+                            )
+                            .optional()?;
+                        let Some(status_text) = status_text else {
+                            continue;
+                        };
+                        if apply_transition(&tx, analysis_id, &status_text, &exported, &reason)? {
+                            moved.push((analysis_id, json_column(&status_text)?));
+                        }
+                    }
+                }
+                tx.commit()?;
+                Ok(moved)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn find_export_manifest(
+        &self,
+        export_id: Uuid,
+    ) -> Result<Option<SignedExportManifest>, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let found = conn
+                    .query_row(
+                        "SELECT manifest_json, signature FROM export_manifests WHERE id = ?1",
+                        [export_id.to_string()],
+                        |row| {
+                            Ok(SignedExportManifest {
+                                manifest: json_column(&row.get::<_, String>(0)?)?,
+                                signature: row.get(1)?,
+                            })
+                        },
+                    )
+                    .optional()?;
+                Ok(found)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn chain_heads(&self) -> Result<ChainHeads, StorageError> {
+        self.conn
+            .call(|conn| {
+                Ok(ChainHeads {
+                    analyses: chain_head(conn, "analyses")?,
+                    audit_events: chain_head(conn, "audit_events")?,
+                })
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn record_timestamp(&self, timestamp: TrustedTimestamp) -> Result<(), StorageError> {
+        self.conn
+            .call(move |conn| {
+                conn.execute(
+                    "INSERT INTO trusted_timestamps (
+                        id, subject_type, subject_id, message, message_sha256, tsa_url, policy,
+                        serial_number, nonce, gen_time, requested_at, token
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                    params![
+                        timestamp.id.to_string(),
+                        enum_text(&timestamp.subject_type),
+                        timestamp.subject_id,
+                        timestamp.message,
+                        timestamp.message_sha256,
+                        timestamp.tsa_url,
+                        timestamp.policy,
+                        timestamp.serial_number,
+                        timestamp.nonce,
+                        timestamp.gen_time.to_rfc3339(),
+                        timestamp.requested_at.to_rfc3339(),
+                        timestamp.token
+                    ],
+                )?;
+                Ok(())
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn list_timestamps(
+        &self,
+        subject_type: Option<TimestampSubject>,
+        subject_id: Option<String>,
+    ) -> Result<Vec<TrustedTimestamp>, StorageError> {
+        let subject_type_text = subject_type.map(|subject_type| enum_text(&subject_type));
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT * FROM trusted_timestamps
+                     WHERE (?1 IS NULL OR subject_type = ?1)
+                       AND (?2 IS NULL OR subject_id = ?2)
+                     ORDER BY gen_time ASC, rowid ASC",
+                )?;
+                let timestamps = stmt
+                    .query_map(params![subject_type_text, subject_id], parse_timestamp_row)?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(timestamps)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn find_timestamp(&self, id: Uuid) -> Result<Option<TrustedTimestamp>, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let found = conn
+                    .query_row(
+                        "SELECT * FROM trusted_timestamps WHERE id = ?1",
+                        [id.to_string()],
+                        parse_timestamp_row,
+                    )
+                    .optional()?;
+                Ok(found)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Recomputes both hash chains, reporting the first broken link of each,
+    /// and re-checks every stored timestamp token against its subject and
+    /// the signature of `tsa_certificate`.
+    pub async fn verify_chains(
+        &self,
+        tsa_certificate: Option<TsaCertificate>,
+    ) -> Result<IntegrityReport, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let analyses = verify_chain(conn, "analyses", analysis_row_chain_content)?;
+                let audit_events =
+                    verify_chain(conn, "audit_events", audit_event_row_chain_content)?;
+                let timestamps = verify_timestamps(conn, tsa_certificate.as_ref())?;
+                Ok(IntegrityReport {
+                    intact: analyses.is_intact()
+                        && audit_events.is_intact()
+                        && timestamps.first_invalid.is_none(),
+                    analyses,
+                    audit_events,
+                    timestamps,
+                    verified_at: Utc::now(),
+                })
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Every analysis photo, flagged with whether the server verification
+    /// found it intact on receipt.
+    pub async fn list_stored_images(&self) -> Result<Vec<StoredImage>, StorageError> {
+        self.conn
+            .call(|conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT a.id, a.image_uri, a.image_sha256, a.image_content_type,
+                            v.failed_checks_json
+                     FROM analyses a
+                     LEFT JOIN analysis_verifications v ON v.analysis_id = a.id
+                     ORDER BY a.rowid",
+                )?;
+                let images = stmt
+                    .query_map([], |row| {
+                        let failed_checks: Option<Vec<FailedCheck>> = row
+                            .get::<_, Option<String>>(4)?
+                            .map(|raw| json_column(&raw))
+                            .transpose()?;
+                        Ok(StoredImage {
+                            analysis_id: parse_uuid(row.get(0)?)?,
+                            image: ImageReference {
+                                uri: row.get(1)?,
+                                sha256: row.get(2)?,
+                                content_type: row.get(3)?,
+                            },
+                            verified_intact: failed_checks.is_some_and(|checks| {
+                                !checks.iter().any(|failed| {
+                                    matches!(
+                                        failed.check,
+                                        VerificationCheck::ImageAvailable
+                                            | VerificationCheck::ImageIntegrity
+                                    )
+                                })
+                            }),
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(images)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Rows reported by `PRAGMA integrity_check`; `["ok"]` when sound.
+    pub async fn sqlite_integrity_check(&self) -> Result<Vec<String>, StorageError> {
+        self.conn
+            .call(|conn| {
+                let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+                let rows = stmt
+                    .query_map([], |row| row.get(0))?
+                    .collect::<Result<Vec<String>, _>>()?;
+                Ok(rows)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn latest_audit_event(
+        &self,
+        event_type: AuditEventType,
+    ) -> Result<Option<AuditEvent>, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let found = conn
+                    .query_row(
+                        "SELECT * FROM audit_events WHERE event_type = ?1
+                         ORDER BY rowid DESC LIMIT 1",
+                        [enum_text(&event_type)],
+                        parse_audit_event_row,
+                    )
+                    .optional()?;
+                Ok(found)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn record_audit_event(&self, event: AuditEvent) -> Result<(), StorageError> {
+        let details_json = serde_json::to_string(&event.details)
+            .map_err(|err| StorageError::Serde(err.to_string()))?;
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+                tx.execute(
+                    "INSERT INTO audit_events (
+                        id, event_type, analysis_id, actor, client_ip, device, payload_sha256,
+                        details_json, occurred_at
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        event.id.to_string(),
+                        enum_text(&event.event_type),
+                        event.analysis_id.map(|id| id.to_string()),
+                        event.actor,
+                        event.client_ip,
+                        event.device,
+                        event.payload_sha256,
+                        details_json,
+                        event.occurred_at.to_rfc3339()
+                    ],
+                )?;
+                append_to_chain(
+                    &tx,
+                    "audit_events",
+                    &event.id.to_string(),
+                    &audit_event_chain_content(&event)?,
+                )?;
+                tx.commit()?;
+                Ok(())
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn list_audit_events(
+        &self,
+        filter: AuditEventFilter,
+        limit: usize,
+        offset: usize,
+    ) -> Result<(Vec<AuditEvent>, Option<String>), StorageError> {
+        let AuditEventFilter {
+            event_type,
+            analysis_id,
+            actor,
+            from,
+            to,
+        } = filter;
+        let filter_params = vec![
+            event_type.map(|event_type| enum_text(&event_type)),
+            analysis_id.map(|id| id.to_string()),
+            actor,
+            from.map(|dt| dt.to_rfc3339()),
+            to.map(|dt| dt.to_rfc3339()),
+        ];
+        let predicate = "(?1 IS NULL OR event_type = ?1)
+            AND (?2 IS NULL OR analysis_id = ?2)
+            AND (?3 IS NULL OR actor = ?3)
+            AND (?4 IS NULL OR occurred_at >= ?4)
+            AND (?5 IS NULL OR occurred_at <= ?5)";
+
+        let (items, total): (Vec<AuditEvent>, i64) = self
+            .conn
+            .call(move |conn| {
+                let total: i64 = conn.query_row(
+                    &format!("SELECT COUNT(*) FROM audit_events WHERE {predicate}"),
+                    rusqlite::params_from_iter(&filter_params),
+                    |row| row.get(0),
+                )?;
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT * FROM audit_events WHERE {predicate}
+                     ORDER BY occurred_at ASC, rowid ASC LIMIT ?6 OFFSET ?7"
+                ))?;
+                let mut all_params: Vec<Box<dyn rusqlite::ToSql>> = filter_params
+                    .into_iter()
+                    .map(|param| Box::new(param) as Box<dyn rusqlite::ToSql>)
+                    .collect();
+                all_params.push(Box::new(limit as i64));
+                all_params.push(Box::new(offset as i64));
+                let events = stmt
+                    .query_map(
+                        rusqlite::params_from_iter(all_params.iter()),
+                        parse_audit_event_row,
+                    )?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((events, total))
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))?;
+
+        let next_offset = offset + items.len();
+        let next_cursor = (next_offset < total as usize).then(|| next_offset.to_string());
+        Ok((items, next_cursor))
+    }
+
+    pub async fn list_deviations(
+        &self,
+        status: Option<DeviationStatus>,
+        bath_id: Option<String>,
+    ) -> Result<Vec<Deviation>, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT * FROM deviations
+                     WHERE (?1 IS NULL OR status = ?1) AND (?2 IS NULL OR bath_id = ?2)
+                     ORDER BY opened_at",
+                )?;
+                let deviations = stmt
+                    .query_map(
+                        params![status.map(|status| enum_text(&status)), bath_id],
+                        parse_deviation_row,
+                    )?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(deviations)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Closes the open deviations that the validated in-range `recontrol`
+    /// answers. Returns how many were closed.
+    pub async fn close_deviations(&self, recontrol: Analysis) -> Result<usize, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let closed = close_answered_deviations(conn, &recontrol)?;
+                Ok(closed)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn find_deviation(&self, id: Uuid) -> Result<Option<Deviation>, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let found = conn
+                    .query_row(
+                        "SELECT * FROM deviations WHERE id = ?1",
+                        [id.to_string()],
+                        parse_deviation_row,
+                    )
+                    .optional()?;
+                Ok(found)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn create_corrective_action(
+        &self,
+        action: CorrectiveAction,
+    ) -> Result<(), StorageError> {
+        self.conn
+            .call(move |conn| {
+                conn.execute(
+                    "INSERT INTO corrective_actions (
+                        id, analysis_id, deviation_id, description, owner_id, due_at, status,
+                        created_by, created_at
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        action.id.to_string(),
+                        action.analysis_id.to_string(),
+                        action.deviation_id.map(|id| id.to_string()),
+                        action.description,
+                        action.owner_id,
+                        action.due_at.to_rfc3339(),
+                        enum_text(&action.status),
+                        action.created_by,
+                        action.created_at.to_rfc3339()
+                    ],
+                )?;
+                Ok(())
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn find_corrective_action(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<CorrectiveAction>, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let found = conn
+                    .query_row(
+                        "SELECT * FROM corrective_actions WHERE id = ?1",
+                        [id.to_string()],
+                        parse_corrective_action_row,
+                    )
+                    .optional()?;
+                Ok(found)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn list_corrective_actions(
+        &self,
+        status: Option<CorrectiveActionStatus>,
+        analysis_id: Option<Uuid>,
+        deviation_id: Option<Uuid>,
+    ) -> Result<Vec<CorrectiveAction>, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT * FROM corrective_actions
+                     WHERE (?1 IS NULL OR status = ?1)
+                       AND (?2 IS NULL OR analysis_id = ?2)
+                       AND (?3 IS NULL OR deviation_id = ?3)
+                     ORDER BY due_at, created_at",
+                )?;
+                let actions = stmt
+                    .query_map(
+                        params![
+                            status.map(|status| enum_text(&status)),
+                            analysis_id.map(|id| id.to_string()),
+                            deviation_id.map(|id| id.to_string())
+                        ],
+                        parse_corrective_action_row,
+                    )?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(actions)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// `open` -> `completed`: records what was actually done on the line.
+    pub async fn complete_corrective_action(
+        &self,
+        id: Uuid,
+        completed_by: String,
+        action_taken: String,
+        completed_at: DateTime<Utc>,
+    ) -> Result<CorrectiveActionOutcome, StorageError> {
+        self.update_corrective_action(
+            id,
+            CorrectiveActionStatus::Open,
+            CorrectiveActionStatus::Completed,
+            "action_taken = ?4, completed_by = ?5, completed_at = ?6",
+            [action_taken, completed_by, completed_at.to_rfc3339()],
+        )
+        .await
+    }
+
+    /// `completed` -> `verified`: effectiveness check that closes the action.
+    pub async fn verify_corrective_action(
+        &self,
+        id: Uuid,
+        verified_by: String,
+        verification_comment: String,
+        verified_at: DateTime<Utc>,
+    ) -> Result<CorrectiveActionOutcome, StorageError> {
+        self.update_corrective_action(
+            id,
+            CorrectiveActionStatus::Completed,
+            CorrectiveActionStatus::Verified,
+            "verified_by = ?4, verification_comment = ?5, verified_at = ?6",
+            [verified_by, verification_comment, verified_at.to_rfc3339()],
+        )
+        .await
+    }
+
+    async fn update_corrective_action(
+        &self,
+        id: Uuid,
+        from: CorrectiveActionStatus,
+        to: CorrectiveActionStatus,
+        assignments: &'static str,
+        values: [String; 3],
+    ) -> Result<CorrectiveActionOutcome, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let [first, second, third] = values;
+                let updated = conn
+                    .query_row(
+                        &format!(
+                            "UPDATE corrective_actions SET status = ?3, {assignments}
+                             WHERE id = ?1 AND status = ?2
+                             RETURNING *"
+                        ),
+                        params![
+                            id.to_string(),
+                            enum_text(&from),
+                            enum_text(&to),
+                            first,
+                            second,
+                            third
+                        ],
+                        parse_corrective_action_row,
+                    )
+                    .optional()?;
+                if let Some(action) = updated {
+                    return Ok(CorrectiveActionOutcome::Updated(Box::new(action)));
+                }
+
+                let current = conn
+                    .query_row(
+                        "SELECT status FROM corrective_actions WHERE id = ?1",
+                        [id.to_string()],
+                        |row| row.get::<_, String>(0),
+                    )
+                    .optional()?;
+                Ok(match current {
+                    Some(status) => CorrectiveActionOutcome::InvalidStatus(json_column(&status)?),
+                    None => CorrectiveActionOutcome::NotFound,
+                })
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Secondary reviews of the analyses captured in an audit export window.
+    pub async fn list_secondary_reviews_for_audit(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<SecondaryReview>, StorageError> {
+        let from_s = from.map(|dt| dt.to_rfc3339());
+        let to_s = to.map(|dt| dt.to_rfc3339());
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT r.* FROM secondary_reviews r
+                     JOIN analyses a ON a.id = r.analysis_id
+                     WHERE (?1 IS NULL OR a.captured_at >= ?1)
+                       AND (?2 IS NULL OR a.captured_at <= ?2)
+                     ORDER BY r.reviewed_at ASC",
+                )?;
+                let reviews = stmt
+                    .query_map(params![from_s, to_s], parse_secondary_review_row)?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(reviews)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Deviations opened or closed by an analysis captured in the period.
+    pub async fn list_deviations_for_audit(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Deviation>, StorageError> {
+        let from_s = from.map(|dt| dt.to_rfc3339());
+        let to_s = to.map(|dt| dt.to_rfc3339());
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT * FROM deviations
+                     WHERE ((?1 IS NULL OR opened_at >= ?1) AND (?2 IS NULL OR opened_at <= ?2))
+                        OR (closed_at IS NOT NULL
+                            AND (?1 IS NULL OR closed_at >= ?1)
+                            AND (?2 IS NULL OR closed_at <= ?2))
+                     ORDER BY opened_at ASC",
+                )?;
+                let deviations = stmt
+                    .query_map(params![from_s, to_s], parse_deviation_row)?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(deviations)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Appends a stop/restart event after checking the bath sequence: stops
+    /// and restarts alternate, and a restart needs a compliant analysis of the
+    /// same bath, validated by the server, current and not voided, captured
+    /// between the stop and the restart.
+    pub async fn record_production_event(
+        &self,
+        mut event: ProductionEvent,
+    ) -> Result<ProductionEventOutcome, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+
+                for analysis_id in &event.linked_analysis_ids {
+                    let exists: bool = tx.query_row(
+                        "SELECT EXISTS (SELECT 1 FROM analyses WHERE id = ?1)",
+                        [analysis_id.to_string()],
+                        |row| row.get(0),
+                    )?;
+                    if !exists {
+                        return Ok(ProductionEventOutcome::UnknownAnalysis(*analysis_id));
+                    }
+                }
+
+                let last = tx
+                    .query_row(
+                        "SELECT * FROM production_events WHERE bath_id = ?1
+                         ORDER BY occurred_at DESC, recorded_at DESC LIMIT 1",
+                        [&event.bath_id],
+                        parse_production_event_row,
+                    )
+                    .optional()?;
+                if let Some(last) = &last {
+                    if event.occurred_at < last.occurred_at {
+                        return Ok(ProductionEventOutcome::OutOfOrder(last.occurred_at));
+                    }
+                }
+                let stopped_at = last
+                    .filter(|last| last.kind == ProductionEventKind::Stop)
+                    .map(|stop| stop.occurred_at);
+
+                match (event.kind, stopped_at) {
+                    (ProductionEventKind::Stop, Some(_)) => {
+                        return Ok(ProductionEventOutcome::AlreadyStopped)
+                    }
+                    (ProductionEventKind::Restart, None) => {
+                        return Ok(ProductionEventOutcome::NotStopped)
+                    }
+                    (ProductionEventKind::Restart, Some(stopped_at)) => {
+                        let recontrol = tx
+                            .query_row(
+                                &format!(
+                                    "SELECT a.id FROM analyses a
+                                     WHERE a.bath_id = ?1 AND a.compliance_status = ?2
+                                       AND a.server_lifecycle_status IN (?3, ?4, ?5)
+                                       AND a.captured_at > ?6 AND a.captured_at <= ?7
+                                       AND {CURRENT_VERSION_PREDICATE}
+                                       AND COALESCE(json_extract(a.correction_json, '$.voided'), 0) = 0
+                                     ORDER BY a.captured_at DESC LIMIT 1"
+                                ),
+                                params![
+                                    event.bath_id,
+                                    enum_text(&ComplianceStatus::ConformeProduction),
+                                    enum_text(&ServerLifecycleStatus::Valide),
+                                    enum_text(&ServerLifecycleStatus::RevuSecondairement),
+                                    enum_text(&ServerLifecycleStatus::ExporteAudit),
+                                    stopped_at.to_rfc3339(),
+                                    event.occurred_at.to_rfc3339()
+                                ],
+                                |row| row.get::<_, String>(0),
+                            )
+                            .optional()?;
+                        let Some(recontrol) = recontrol else {
+                            return Ok(ProductionEventOutcome::NoCompliantRecontrol { stopped_at });
+                        };
+                        let recontrol = parse_uuid(recontrol)?;
+                        if !event.linked_analysis_ids.contains(&recontrol) {
+                            event.linked_analysis_ids.push(recontrol);
+                        }
+                        event.recontrol_analysis_id = Some(recontrol);
+                    }
+                    (ProductionEventKind::Stop, None) => {}
+                }
+
+                tx.execute(
+                    "INSERT INTO production_events (
+                        id, bath_id, kind, recorded_by, occurred_at, reason,
+                        linked_analysis_ids_json, recontrol_analysis_id, recorded_at
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        event.id.to_string(),
+                        event.bath_id,
+                        enum_text(&event.kind),
+                        event.recorded_by,
+                        event.occurred_at.to_rfc3339(),
+                        event.reason,
+                        serde_json::to_string(&event.linked_analysis_ids).unwrap(),
+                        event.recontrol_analysis_id.map(|id| id.to_string()),
+                        event.recorded_at.to_rfc3339()
+                    ],
+                )?;
+                tx.commit()?;
+                Ok(ProductionEventOutcome::Recorded(Box::new(event)))
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn list_production_events(
+        &self,
+        bath_id: Option<String>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<ProductionEvent>, StorageError> {
+        let from_s = from.map(|dt| dt.to_rfc3339());
+        let to_s = to.map(|dt| dt.to_rfc3339());
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT * FROM production_events
+                     WHERE (?1 IS NULL OR bath_id = ?1)
+                       AND (?2 IS NULL OR occurred_at >= ?2)
+                       AND (?3 IS NULL OR occurred_at <= ?3)
+                     ORDER BY occurred_at ASC, recorded_at ASC",
+                )?;
+                let events = stmt
+                    .query_map(params![bath_id, from_s, to_s], parse_production_event_row)?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(events)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn list_paginated(
+        &self,
+        limit: usize,
+        offset: usize,
+        sample_id: Option<&str>,
+    ) -> Result<(Vec<Analysis>, Option<String>), StorageError> {
+        let sample_filter = sample_id.map(str::to_owned);
+        let limit_i64 = limit as i64;
+        let offset_i64 = offset as i64;
+
+        let (items, total): (Vec<Analysis>, i64) = self
+            .conn
+            .call(move |conn| {
+                // Superseded versions are only reachable through the detail view.
+                let total: i64 = if let Some(ref sample) = sample_filter {
+                    conn.query_row(
+                        &format!("SELECT COUNT(*) FROM analyses a WHERE sample_id = ?1 AND {CURRENT_VERSION_PREDICATE}"),
+                        [sample],
+                        |row| row.get(0),
+                    )?
+                } else {
+                    conn.query_row(
+                        &format!("SELECT COUNT(*) FROM analyses a WHERE {CURRENT_VERSION_PREDICATE}"),
+                        [],
+                        |row| row.get(0),
+                    )?
+                };
+
+                let mut stmt = if sample_filter.is_some() {
+                    conn.prepare(&format!(
+                        "SELECT * FROM analyses a WHERE sample_id = ?1 AND {CURRENT_VERSION_PREDICATE} ORDER BY received_at DESC LIMIT ?2 OFFSET ?3",
+                    ))?
+                } else {
+                    conn.prepare(&format!("SELECT * FROM analyses a WHERE {CURRENT_VERSION_PREDICATE} ORDER BY received_at DESC LIMIT ?1 OFFSET ?2"))?
+                };
+
+                let analyses = if let Some(ref sample) = sample_filter {
+                    stmt.query_map(params![sample, limit_i64, offset_i64], parse_analysis_row)?
+                        .collect::<Result<Vec<_>, _>>()?
+                } else {
+                    stmt.query_map(params![limit_i64, offset_i64], parse_analysis_row)?
+                        .collect::<Result<Vec<_>, _>>()?
+                };
+
+                Ok((analyses, total))
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))?;
+
+        let next_offset = offset + items.len();
+        let next_cursor = (next_offset < total as usize).then(|| next_offset.to_string());
+
+        Ok((items, next_cursor))
+    }
+
+    /// Current, non-voided analyses captured within `[from, to]`, in capture
+    /// order.
+    pub async fn list_compliance_observations(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        bath_id: Option<String>,
+    ) -> Result<Vec<ComplianceObservation>, StorageError> {
+        let rejected = enum_text(&ServerLifecycleStatus::Rejete);
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT a.captured_at, a.ppm_estime, a.compliance_status,
+                        EXISTS (
+                            SELECT 1 FROM analysis_lifecycle_transitions t
+                            WHERE t.analysis_id = a.id AND t.to_status = ?4
+                        ) AS rejected,
+                        (SELECT r.decision FROM secondary_reviews r WHERE r.analysis_id = a.id)
+                            AS review_decision,
+                        EXISTS (
+                            SELECT 1 FROM analysis_verifications v
+                            WHERE v.analysis_id = a.id AND {REVIEW_QUEUE_PREDICATE}
+                        ) AS awaiting_review
+                     FROM analyses a
+                     WHERE a.captured_at >= ?1 AND a.captured_at <= ?2
+                       AND (?3 IS NULL OR a.bath_id = ?3)
+                       AND {CURRENT_VERSION_PREDICATE}
+                       AND COALESCE(json_extract(a.correction_json, '$.voided'), 0) = 0
+                     ORDER BY a.captured_at ASC"
+                ))?;
+                let observations = stmt
+                    .query_map(
+                        params![from.to_rfc3339(), to.to_rfc3339(), bath_id, rejected],
+                        |row| {
+                            Ok(ComplianceObservation {
+                                captured_at: parse_timestamp(row.get("captured_at")?)?,
+                                ppm_estime: row.get("ppm_estime")?,
+                                compliance_status: json_column(
+                                    &row.get::<_, String>("compliance_status")?,
+                                )?,
+                                rejected: row.get("rejected")?,
+                                review_decision: row
+                                    .get::<_, Option<String>>("review_decision")?
+                                    .map(|decision| json_column(&decision))
+                                    .transpose()?,
+                                awaiting_review: row.get("awaiting_review")?,
+                            })
+                        },
+                    )?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(observations)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Latest `limit` validated checks of a bath, oldest first. Voided,
+    /// superseded and review-rejected analyses are left out.
+    /// Latest `limit` points of the bath, oldest first.
+    pub async fn list_spc_points(
+        &self,
+        bath_id: String,
+        limit: usize,
+    ) -> Result<Vec<SpcPoint>, StorageError> {
+        let mut points = self.spc_points(bath_id, limit, "DESC").await?;
+        points.reverse();
+        Ok(points)
+    }
+
+    /// First `count` points of the bath, the Phase I baseline.
+    pub async fn list_spc_baseline_points(
+        &self,
+        bath_id: String,
+        count: usize,
+    ) -> Result<Vec<SpcPoint>, StorageError> {
+        self.spc_points(bath_id, count, "ASC").await
+    }
+
+    async fn spc_points(
+        &self,
+        bath_id: String,
+        limit: usize,
+        order: &'static str,
+    ) -> Result<Vec<SpcPoint>, StorageError> {
+        let valide = enum_text(&ServerLifecycleStatus::Valide);
+        let rejected = enum_text(&ReviewDecision::Rejected);
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT a.id, a.captured_at, a.ppm_estime FROM analyses a
+                     WHERE a.bath_id = ?1
+                       AND EXISTS (
+                           SELECT 1 FROM analysis_lifecycle_transitions t
+                           WHERE t.analysis_id = a.id AND t.to_status = ?2
+                       )
+                       AND NOT EXISTS (
+                           SELECT 1 FROM secondary_reviews r
+                           WHERE r.analysis_id = a.id AND r.decision = ?3
+                       )
+                       AND {CURRENT_VERSION_PREDICATE}
+                       AND COALESCE(json_extract(a.correction_json, '$.voided'), 0) = 0
+                     ORDER BY a.captured_at {order}, a.id {order}
+                     LIMIT ?4"
+                ))?;
+                let points = stmt
+                    .query_map(params![bath_id, valide, rejected, limit as i64], |row| {
+                        Ok(SpcPoint {
+                            analysis_id: parse_uuid(row.get("id")?)?,
+                            captured_at: parse_timestamp(row.get("captured_at")?)?,
+                            ppm_estime: row.get("ppm_estime")?,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(points)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Current, non-voided analyses captured within `[from, to)`.
+    pub async fn list_shift_checks(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Analysis>, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT a.* FROM analyses a
+                     WHERE a.captured_at >= ?1 AND a.captured_at < ?2
+                       AND {CURRENT_VERSION_PREDICATE}
+                       AND COALESCE(json_extract(a.correction_json, '$.voided'), 0) = 0
+                     ORDER BY a.captured_at"
+                ))?;
+                let analyses = stmt
+                    .query_map(
+                        params![from.to_rfc3339(), to.to_rfc3339()],
+                        parse_analysis_row,
+                    )?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(analyses)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Returns false when the shift already has a report.
+    pub async fn insert_shift_report(&self, report: &ShiftReport) -> Result<bool, StorageError> {
+        let report_json =
+            serde_json::to_string(report).map_err(|err| StorageError::Serde(err.to_string()))?;
+        let (id, shift_start, shift_end, generated_at) = (
+            report.id.to_string(),
+            report.shift_start.to_rfc3339(),
+            report.shift_end.to_rfc3339(),
+            report.generated_at.to_rfc3339(),
+        );
+        self.conn
+            .call(move |conn| {
+                let inserted = conn.execute(
+                    "INSERT OR IGNORE INTO shift_reports (
+                        id, shift_start, shift_end, generated_at, report_json
+                    ) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![id, shift_start, shift_end, generated_at, report_json],
+                )?;
+                Ok(inserted > 0)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Start of the latest shift that has a report.
+    pub async fn latest_shift_start(&self) -> Result<Option<DateTime<Utc>>, StorageError> {
+        self.conn
+            .call(|conn| {
+                let latest: Option<String> =
+                    conn.query_row("SELECT MAX(shift_start) FROM shift_reports", [], |row| {
+                        row.get(0)
+                    })?;
+                Ok(latest.map(parse_timestamp).transpose()?)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn shift_report_exists(
+        &self,
+        shift_start: DateTime<Utc>,
+    ) -> Result<bool, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let exists = conn.query_row(
+                    "SELECT EXISTS (SELECT 1 FROM shift_reports WHERE shift_start = ?1)",
+                    [shift_start.to_rfc3339()],
+                    |row| row.get(0),
+                )?;
+                Ok(exists)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn find_shift_report(&self, id: Uuid) -> Result<Option<ShiftReport>, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let found = conn
+                    .query_row(
+                        "SELECT report_json FROM shift_reports WHERE id = ?1",
+                        [id.to_string()],
+                        |row| json_column(&row.get::<_, String>(0)?),
+                    )
+                    .optional()?;
+                Ok(found)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Reports of the shifts starting within `[from, to]`, latest first.
+    pub async fn list_shift_reports(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<ShiftReport>, StorageError> {
+        let from_s = from.map(|dt| dt.to_rfc3339());
+        let to_s = to.map(|dt| dt.to_rfc3339());
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT report_json FROM shift_reports
+                     WHERE (?1 IS NULL OR shift_start >= ?1) AND (?2 IS NULL OR shift_start <= ?2)
+                     ORDER BY shift_start DESC",
+                )?;
+                let reports = stmt
+                    .query_map(params![from_s, to_s], |row| {
+                        json_column(&row.get::<_, String>(0)?)
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(reports)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn insert_control_plan(&self, plan: ControlPlan) -> Result<(), StorageError> {
+        self.conn
+            .call(move |conn| {
+                conn.execute(
+                    "INSERT INTO control_plans (
+                        id, bath_id, interval_minutes, grace_minutes, active, effective_from,
+                        updated_by
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        plan.id.to_string(),
+                        plan.bath_id,
+                        plan.interval_minutes,
+                        plan.grace_minutes,
+                        plan.active,
+                        plan.effective_from.to_rfc3339(),
+                        plan.updated_by,
+                    ],
+                )?;
+                Ok(())
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Every version of the control plans, by bath then effective date.
+    pub async fn list_control_plans(
+        &self,
+        bath_id: Option<String>,
+    ) -> Result<Vec<ControlPlan>, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT * FROM control_plans
+                     WHERE (?1 IS NULL OR bath_id = ?1)
+                     ORDER BY bath_id, effective_from, rowid",
+                )?;
+                let plans = stmt
+                    .query_map(params![bath_id], parse_control_plan_row)?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(plans)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Checks of a bath captured since `since`, whatever their verdict:
+    /// current, non-voided analyses in capture order.
+    pub async fn list_check_times(
+        &self,
+        bath_id: String,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(Uuid, DateTime<Utc>)>, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT a.id, a.captured_at FROM analyses a
+                     WHERE a.bath_id = ?1 AND a.captured_at >= ?2
+                       AND {CURRENT_VERSION_PREDICATE}
+                       AND COALESCE(json_extract(a.correction_json, '$.voided'), 0) = 0
+                     ORDER BY a.captured_at"
+                ))?;
+                let checks = stmt
+                    .query_map(params![bath_id, since.to_rfc3339()], |row| {
+                        Ok((
+                            parse_uuid(row.get("id")?)?,
+                            parse_timestamp(row.get("captured_at")?)?,
+                        ))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(checks)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Returns false when the missed check was already alerted.
+    pub async fn mark_control_gap_alerted(
+        &self,
+        bath_id: String,
+        due_at: DateTime<Utc>,
+    ) -> Result<bool, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let inserted = conn.execute(
+                    "INSERT OR IGNORE INTO control_gap_alerts (bath_id, due_at, alerted_at)
+                     VALUES (?1, ?2, ?3)",
+                    params![bath_id, due_at.to_rfc3339(), Utc::now().to_rfc3339()],
+                )?;
+                Ok(inserted > 0)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn find_spc_baseline(
+        &self,
+        bath_id: String,
+    ) -> Result<Option<SpcBaseline>, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let found = conn
+                    .query_row(
+                        "SELECT * FROM spc_baselines WHERE bath_id = ?1",
+                        [bath_id],
+                        parse_spc_baseline_row,
+                    )
+                    .optional()?;
+                Ok(found)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Freezes the limits of a bath. Returns false when they were already
+    /// frozen; the first stored limits are kept.
+    pub async fn freeze_spc_baseline(&self, baseline: SpcBaseline) -> Result<bool, StorageError> {
+        let limits_json = serde_json::to_string(&baseline.limits)
+            .map_err(|err| StorageError::Serde(err.to_string()))?;
+        self.conn
+            .call(move |conn| {
+                let inserted = conn.execute(
+                    "INSERT OR IGNORE INTO spc_baselines (
+                        bath_id, baseline_points, limits_json, frozen_at
+                    ) VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        baseline.bath_id,
+                        baseline.baseline_points as i64,
+                        limits_json,
+                        baseline.frozen_at.to_rfc3339(),
+                    ],
+                )?;
+                Ok(inserted > 0)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Stores the signals and returns those not raised before for the same
+    /// analysis and rule.
+    pub async fn insert_spc_signals(
+        &self,
+        signals: Vec<SpcSignal>,
+    ) -> Result<Vec<SpcSignal>, StorageError> {
+        let signals = signals
+            .into_iter()
+            .map(|signal| {
+                serde_json::to_string(&signal.limits)
+                    .map(|limits_json| (signal, limits_json))
+                    .map_err(|err| StorageError::Serde(err.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+                let mut inserted = Vec::new();
+                for (signal, limits_json) in signals {
+                    let changed = tx.execute(
+                        "INSERT OR IGNORE INTO spc_signals (
+                            id, bath_id, analysis_id, captured_at, rule, severity, direction,
+                            ppm_estime, limits_json, raised_at
+                        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                        params![
+                            signal.id.to_string(),
+                            signal.bath_id,
+                            signal.analysis_id.to_string(),
+                            signal.captured_at.to_rfc3339(),
+                            enum_text(&signal.rule),
+                            enum_text(&signal.severity),
+                            enum_text(&signal.direction),
+                            signal.ppm_estime,
+                            limits_json,
+                            signal.raised_at.to_rfc3339(),
+                        ],
+                    )?;
+                    if changed > 0 {
+                        inserted.push(signal);
+                    }
+                }
+                tx.commit()?;
+                Ok(inserted)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    pub async fn list_spc_signals(
+        &self,
+        filter: SpcSignalFilter,
+    ) -> Result<Vec<SpcSignal>, StorageError> {
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT * FROM spc_signals
+                     WHERE (?1 IS NULL OR bath_id = ?1)
+                       AND (?2 IS NULL OR rule = ?2)
+                       AND (?3 IS NULL OR captured_at >= ?3)
+                       AND (?4 IS NULL OR captured_at <= ?4)
+                     ORDER BY captured_at, rule",
+                )?;
+                let signals = stmt
+                    .query_map(
+                        params![
+                            filter.bath_id,
+                            filter.rule.map(|rule| enum_text(&rule)),
+                            filter.from.map(|dt| dt.to_rfc3339()),
+                            filter.to.map(|dt| dt.to_rfc3339()),
+                        ],
+                        parse_spc_signal_row,
+                    )?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(signals)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// One page of the audit register, in `(captured_at, id)` order, starting
+    /// after `after`. `limit: None` returns the rest of the register.
+    pub async fn list_audit_register_page(
+        &self,
+        scope: AuditRegisterScope,
+        after: Option<(DateTime<Utc>, Uuid)>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Analysis>, StorageError> {
+        let from_s = scope.from.map(|dt| dt.to_rfc3339());
+        let to_s = scope.to.map(|dt| dt.to_rfc3339());
+        let received_until = scope.received_until.to_rfc3339();
+        let after_captured_at = after.map(|(captured_at, _)| captured_at.to_rfc3339());
+        let after_id = after.map(|(_, id)| id.to_string());
+        // SQLite reads a negative LIMIT as no limit.
+        let limit = limit.map_or(-1, |limit| limit as i64);
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT * FROM analyses
+                     WHERE (?1 IS NULL OR captured_at >= ?1)
+                       AND (?2 IS NULL OR captured_at <= ?2)
+                       AND received_at <= ?3
+                       AND (?4 IS NULL OR captured_at > ?4 OR (captured_at = ?4 AND id > ?5))
+                     ORDER BY captured_at ASC, id ASC
+                     LIMIT ?6",
+                )?;
+
+                let analyses = stmt
+                    .query_map(
+                        params![
+                            from_s,
+                            to_s,
+                            received_until,
+                            after_captured_at,
+                            after_id,
+                            limit
+                        ],
+                        parse_analysis_row,
+                    )?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(analyses)
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+
+    /// Corrections, CAPA and secondary review signatures of the given
+    /// register rows.
+    pub async fn audit_register_links(
+        &self,
+        scope: AuditRegisterScope,
+        analysis_ids: Vec<Uuid>,
+    ) -> Result<AuditRegisterLinks, StorageError> {
+        let from_s = scope.from.map(|dt| dt.to_rfc3339());
+        let to_s = scope.to.map(|dt| dt.to_rfc3339());
+        let received_until = scope.received_until.to_rfc3339();
+        let ids_json = serde_json::to_string(&analysis_ids)
+            .map_err(|err| StorageError::Serde(err.to_string()))?;
+        let review = enum_text(&SignedRecordType::SecondaryReview);
+
+        self.conn
+            .call(move |conn| {
+                let superseded_by = conn
+                    .prepare(
+                        "SELECT supersedes_analysis_id, id FROM analyses
+                         WHERE supersedes_analysis_id IN (SELECT value FROM json_each(?1))
+                           AND (?2 IS NULL OR captured_at >= ?2)
+                           AND (?3 IS NULL OR captured_at <= ?3)
+                           AND received_at <= ?4",
+                    )?
+                    .query_map(params![ids_json, from_s, to_s, received_until], |row| {
+                        Ok((parse_uuid(row.get(0)?)?, parse_uuid(row.get(1)?)?))
+                    })?
+                    .collect::<Result<HashMap<_, _>, _>>()?;
+                let corrective_actions = conn
+                    .prepare(
+                        "SELECT * FROM corrective_actions
+                         WHERE analysis_id IN (SELECT value FROM json_each(?1))
+                         ORDER BY created_at ASC, rowid ASC",
+                    )?
+                    .query_map([&ids_json], parse_corrective_action_row)?
+                    .collect::<Result<Vec<_>, _>>()?;
+                let review_signatures = conn
+                    .prepare(
+                        "SELECT * FROM electronic_signatures
+                         WHERE record_type = ?1
+                           AND record_id IN (SELECT value FROM json_each(?2))
+                         ORDER BY signed_at ASC, rowid ASC",
+                    )?
+                    .query_map(params![review, ids_json], parse_signature_row)?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(AuditRegisterLinks {
+                    superseded_by,
+                    corrective_actions,
+                    review_signatures,
+                })
+            })
+            .await
+            .map_err(|err| StorageError::Sqlite(err.to_string()))
+    }
+}
+
+fn parse_analysis_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Analysis> {
+    let compliance_status_json: String = row.get("compliance_status")?;
+    let lifecycle_status_json: String = row.get("server_lifecycle_status")?;
+    let acquisition_json: String = row.get("acquisition_metadata_json")?;
+
+    let compliance_status = serde_json::from_str::<ComplianceStatus>(&compliance_status_json)
         .map_err(|e| {
             rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
         })?;
@@ -306,6 +3054,12 @@ fn parse_analysis_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Analysis> {
             },
         )?,
         sample_id: row.get("sample_id")?,
+        operator_id: row.get("operator_id")?,
+        bath_id: row.get("bath_id")?,
+        parent_analysis_id: row
+            .get::<_, Option<String>>("parent_analysis_id")?
+            .map(parse_uuid)
+            .transpose()?,
         ppm_estime: row.get("ppm_estime")?,
         ppm_min: row.get("ppm_min")?,
         ppm_max: row.get("ppm_max")?,
@@ -324,5 +3078,797 @@ fn parse_analysis_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Analysis> {
         },
         acquisition_metadata,
         server_lifecycle_status: lifecycle_status,
+        version: row.get("version")?,
+        supersedes_analysis_id: row
+            .get::<_, Option<String>>("supersedes_analysis_id")?
+            .map(parse_uuid)
+            .transpose()?,
+        correction: row
+            .get::<_, Option<String>>("correction_json")?
+            .map(|raw| json_column(&raw))
+            .transpose()?,
+    })
+}
+
+fn parse_verification_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<AnalysisVerification> {
+    let server_decision_json: Option<String> = row.get("server_decision_json")?;
+
+    Ok(AnalysisVerification {
+        analysis_id: parse_uuid(row.get("analysis_id")?)?,
+        validated_schema: row.get("validated_schema")?,
+        validated_business_rules: row.get("validated_business_rules")?,
+        failed_checks: json_column(&row.get::<_, String>("failed_checks_json")?)?,
+        review_reasons: json_column(&row.get::<_, String>("review_reasons_json")?)?,
+        server_ppm_estime: row.get("server_ppm_estime")?,
+        server_decision: server_decision_json
+            .as_deref()
+            .map(json_column)
+            .transpose()?,
+        verified_at: parse_timestamp(row.get("verified_at")?)?,
+    })
+}
+
+fn parse_secondary_review_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SecondaryReview> {
+    Ok(SecondaryReview {
+        id: parse_uuid(row.get("id")?)?,
+        analysis_id: parse_uuid(row.get("analysis_id")?)?,
+        reviewer_id: row.get("reviewer_id")?,
+        decision: json_column(&row.get::<_, String>("decision")?)?,
+        comment: row.get("comment")?,
+        reviewed_at: parse_timestamp(row.get("reviewed_at")?)?,
+        recorded_at: parse_timestamp(row.get("recorded_at")?)?,
+    })
+}
+
+fn parse_user_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<User> {
+    Ok(User {
+        user_id: row.get("user_id")?,
+        display_name: row.get("display_name")?,
+        roles: json_column(&row.get::<_, String>("roles_json")?)?,
+        active: row.get("active")?,
+        updated_at: parse_timestamp(row.get("updated_at")?)?,
+    })
+}
+
+fn parse_signature_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ElectronicSignature> {
+    Ok(ElectronicSignature {
+        id: parse_uuid(row.get("id")?)?,
+        record_type: json_column(&row.get::<_, String>("record_type")?)?,
+        record_id: row.get("record_id")?,
+        record_hash: row.get("record_hash")?,
+        signer_id: row.get("signer_id")?,
+        signer_display_name: row.get("signer_display_name")?,
+        meaning: json_column(&row.get::<_, String>("meaning")?)?,
+        signed_at: parse_timestamp(row.get("signed_at")?)?,
+        signature_hash: row.get("signature_hash")?,
+    })
+}
+
+fn parse_timestamp_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<TrustedTimestamp> {
+    Ok(TrustedTimestamp {
+        id: parse_uuid(row.get("id")?)?,
+        subject_type: json_column(&row.get::<_, String>("subject_type")?)?,
+        subject_id: row.get("subject_id")?,
+        message: row.get("message")?,
+        message_sha256: row.get("message_sha256")?,
+        tsa_url: row.get("tsa_url")?,
+        policy: row.get("policy")?,
+        serial_number: row.get("serial_number")?,
+        nonce: row.get("nonce")?,
+        gen_time: parse_timestamp(row.get("gen_time")?)?,
+        requested_at: parse_timestamp(row.get("requested_at")?)?,
+        token: row.get("token")?,
+    })
+}
+
+fn insert_signature(
+    conn: &rusqlite::Connection,
+    signature: &ElectronicSignature,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO electronic_signatures (
+            id, record_type, record_id, record_hash, signer_id, signer_display_name,
+            meaning, signed_at, signature_hash
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            signature.id.to_string(),
+            enum_text(&signature.record_type),
+            signature.record_id,
+            signature.record_hash,
+            signature.signer_id,
+            signature.signer_display_name,
+            enum_text(&signature.meaning),
+            signature.signed_at.to_rfc3339(),
+            signature.signature_hash
+        ],
+    )?;
+    Ok(())
+}
+
+fn parse_audit_event_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<AuditEvent> {
+    Ok(AuditEvent {
+        id: parse_uuid(row.get("id")?)?,
+        event_type: json_column(&row.get::<_, String>("event_type")?)?,
+        analysis_id: row
+            .get::<_, Option<String>>("analysis_id")?
+            .map(parse_uuid)
+            .transpose()?,
+        actor: row.get("actor")?,
+        client_ip: row.get("client_ip")?,
+        device: row.get("device")?,
+        payload_sha256: row.get("payload_sha256")?,
+        details: json_column(&row.get::<_, String>("details_json")?)?,
+        occurred_at: parse_timestamp(row.get("occurred_at")?)?,
+    })
+}
+
+fn find_successor_id(conn: &rusqlite::Connection, id: Uuid) -> rusqlite::Result<Option<Uuid>> {
+    conn.query_row(
+        "SELECT id FROM analyses WHERE supersedes_analysis_id = ?1",
+        [id.to_string()],
+        |row| row.get::<_, String>(0),
+    )
+    .optional()?
+    .map(parse_uuid)
+    .transpose()
+}
+
+fn insert_analysis_row(
+    conn: &rusqlite::Connection,
+    analysis: &Analysis,
+    acquisition_json: &str,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO analyses (
+            id, client_analysis_id, sample_id, ppm_estime, ppm_min, ppm_max,
+            compliance_status, analysis_result, recommended_action, confidence,
+            analysis_rules_version, calibration_version, captured_at, received_at,
+            image_uri, image_sha256, image_content_type, acquisition_metadata_json,
+            server_lifecycle_status, operator_id, bath_id, parent_analysis_id,
+            version, supersedes_analysis_id, correction_json
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25)",
+        params![
+            analysis.id.to_string(),
+            analysis.client_analysis_id.to_string(),
+            analysis.sample_id,
+            analysis.ppm_estime,
+            analysis.ppm_min,
+            analysis.ppm_max,
+            serde_json::to_string(&analysis.compliance_status).unwrap(),
+            analysis.analysis_result,
+            analysis.recommended_action,
+            analysis.confidence,
+            analysis.analysis_rules_version,
+            analysis.calibration_version,
+            analysis.captured_at.to_rfc3339(),
+            analysis.received_at.to_rfc3339(),
+            analysis.image.uri,
+            analysis.image.sha256,
+            analysis.image.content_type,
+            acquisition_json,
+            serde_json::to_string(&analysis.server_lifecycle_status).unwrap(),
+            analysis.operator_id,
+            analysis.bath_id,
+            analysis.parent_analysis_id.map(|id| id.to_string()),
+            analysis.version,
+            analysis.supersedes_analysis_id.map(|id| id.to_string()),
+            analysis
+                .correction
+                .as_ref()
+                .map(|correction| serde_json::to_string(correction).unwrap()),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Opens a deviation for every out-of-range result.
+fn open_deviation(conn: &rusqlite::Connection, analysis: &Analysis) -> rusqlite::Result<()> {
+    if analysis.compliance_status != ComplianceStatus::ConformeProduction {
+        conn.execute(
+            "INSERT INTO deviations (
+                id, bath_id, opening_analysis_id, opening_compliance_status, opened_at, status
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                Uuid::new_v4().to_string(),
+                analysis.bath_id,
+                analysis.id.to_string(),
+                enum_text(&analysis.compliance_status),
+                analysis.captured_at.to_rfc3339(),
+                enum_text(&DeviationStatus::Open)
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Follows a correction on the deviations of its earlier versions. A voided
+/// out-of-range result voids its open deviation and a voided re-control
+/// reopens the deviations it closed; otherwise a `bath_id` change moves the
+/// deviation to the corrected bath.
+fn correct_deviations(conn: &rusqlite::Connection, correction: &Analysis) -> rusqlite::Result<()> {
+    let Some(original_id) = correction.supersedes_analysis_id else {
+        return Ok(());
+    };
+    let versions = "WITH RECURSIVE versions(id, supersedes_id) AS (
+            SELECT id, supersedes_analysis_id FROM analyses WHERE id = ?1
+            UNION
+            SELECT a.id, a.supersedes_analysis_id
+            FROM analyses a JOIN versions v ON a.id = v.supersedes_id
+        )";
+    if correction.correction.as_ref().is_some_and(|c| c.voided) {
+        conn.execute(
+            &format!(
+                "{versions}
+                UPDATE deviations SET status = ?2, closing_analysis_id = ?3, closed_at = ?4
+                WHERE status = ?5 AND opening_analysis_id IN (SELECT id FROM versions)"
+            ),
+            params![
+                original_id.to_string(),
+                enum_text(&DeviationStatus::Voided),
+                correction.id.to_string(),
+                correction.received_at.to_rfc3339(),
+                enum_text(&DeviationStatus::Open)
+            ],
+        )?;
+        conn.execute(
+            &format!(
+                "{versions}
+                UPDATE deviations SET status = ?2, closing_analysis_id = NULL, closed_at = NULL
+                WHERE status = ?3 AND closing_analysis_id IN (SELECT id FROM versions)"
+            ),
+            params![
+                original_id.to_string(),
+                enum_text(&DeviationStatus::Open),
+                enum_text(&DeviationStatus::Closed)
+            ],
+        )?;
+    } else {
+        conn.execute(
+            &format!(
+                "{versions}
+                UPDATE deviations SET bath_id = ?2
+                WHERE bath_id <> ?2 AND opening_analysis_id IN (SELECT id FROM versions)"
+            ),
+            params![original_id.to_string(), correction.bath_id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Closes the open deviations of the bath of `recontrol` whose opening
+/// analysis is one of its ancestors through `parent_analysis_id` or
+/// `supersedes_analysis_id`.
+fn close_answered_deviations(
+    conn: &rusqlite::Connection,
+    recontrol: &Analysis,
+) -> rusqlite::Result<usize> {
+    let Some(parent_id) = recontrol.parent_analysis_id else {
+        return Ok(0);
+    };
+    conn.execute(
+        "WITH RECURSIVE chain(id, parent_id, supersedes_id) AS (
+            SELECT id, parent_analysis_id, supersedes_analysis_id FROM analyses WHERE id = ?1
+            UNION
+            SELECT a.id, a.parent_analysis_id, a.supersedes_analysis_id
+            FROM analyses a JOIN chain c ON a.id = c.parent_id OR a.id = c.supersedes_id
+        )
+        UPDATE deviations SET status = ?2, closing_analysis_id = ?3, closed_at = ?4
+        WHERE status = ?5 AND bath_id = ?6
+          AND opening_analysis_id IN (SELECT id FROM chain)",
+        params![
+            parent_id.to_string(),
+            enum_text(&DeviationStatus::Closed),
+            recontrol.id.to_string(),
+            recontrol.captured_at.to_rfc3339(),
+            enum_text(&DeviationStatus::Open),
+            recontrol.bath_id
+        ],
+    )
+}
+
+fn parse_deviation_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Deviation> {
+    Ok(Deviation {
+        id: parse_uuid(row.get("id")?)?,
+        bath_id: row.get("bath_id")?,
+        opening_analysis_id: parse_uuid(row.get("opening_analysis_id")?)?,
+        opening_compliance_status: json_column(
+            &row.get::<_, String>("opening_compliance_status")?,
+        )?,
+        opened_at: parse_timestamp(row.get("opened_at")?)?,
+        status: json_column(&row.get::<_, String>("status")?)?,
+        closing_analysis_id: row
+            .get::<_, Option<String>>("closing_analysis_id")?
+            .map(parse_uuid)
+            .transpose()?,
+        closed_at: row
+            .get::<_, Option<String>>("closed_at")?
+            .map(parse_timestamp)
+            .transpose()?,
+    })
+}
+
+fn parse_control_plan_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ControlPlan> {
+    Ok(ControlPlan {
+        id: parse_uuid(row.get("id")?)?,
+        bath_id: row.get("bath_id")?,
+        interval_minutes: row.get("interval_minutes")?,
+        grace_minutes: row.get("grace_minutes")?,
+        active: row.get("active")?,
+        effective_from: parse_timestamp(row.get("effective_from")?)?,
+        updated_by: row.get("updated_by")?,
+    })
+}
+
+fn parse_spc_baseline_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SpcBaseline> {
+    Ok(SpcBaseline {
+        bath_id: row.get("bath_id")?,
+        baseline_points: row.get::<_, i64>("baseline_points")? as usize,
+        limits: json_column(&row.get::<_, String>("limits_json")?)?,
+        frozen_at: parse_timestamp(row.get("frozen_at")?)?,
+    })
+}
+
+fn parse_spc_signal_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SpcSignal> {
+    Ok(SpcSignal {
+        id: parse_uuid(row.get("id")?)?,
+        bath_id: row.get("bath_id")?,
+        analysis_id: parse_uuid(row.get("analysis_id")?)?,
+        captured_at: parse_timestamp(row.get("captured_at")?)?,
+        rule: json_column(&row.get::<_, String>("rule")?)?,
+        severity: json_column(&row.get::<_, String>("severity")?)?,
+        direction: json_column(&row.get::<_, String>("direction")?)?,
+        ppm_estime: row.get("ppm_estime")?,
+        limits: json_column(&row.get::<_, String>("limits_json")?)?,
+        raised_at: parse_timestamp(row.get("raised_at")?)?,
+    })
+}
+
+fn parse_corrective_action_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<CorrectiveAction> {
+    let optional_timestamp = |column: &str| -> rusqlite::Result<Option<DateTime<Utc>>> {
+        row.get::<_, Option<String>>(column)?
+            .map(parse_timestamp)
+            .transpose()
+    };
+
+    Ok(CorrectiveAction {
+        id: parse_uuid(row.get("id")?)?,
+        analysis_id: parse_uuid(row.get("analysis_id")?)?,
+        deviation_id: row
+            .get::<_, Option<String>>("deviation_id")?
+            .map(parse_uuid)
+            .transpose()?,
+        description: row.get("description")?,
+        owner_id: row.get("owner_id")?,
+        due_at: parse_timestamp(row.get("due_at")?)?,
+        status: json_column(&row.get::<_, String>("status")?)?,
+        created_by: row.get("created_by")?,
+        created_at: parse_timestamp(row.get("created_at")?)?,
+        action_taken: row.get("action_taken")?,
+        completed_by: row.get("completed_by")?,
+        completed_at: optional_timestamp("completed_at")?,
+        verified_by: row.get("verified_by")?,
+        verification_comment: row.get("verification_comment")?,
+        verified_at: optional_timestamp("verified_at")?,
+    })
+}
+
+fn parse_production_event_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ProductionEvent> {
+    Ok(ProductionEvent {
+        id: parse_uuid(row.get("id")?)?,
+        bath_id: row.get("bath_id")?,
+        kind: json_column(&row.get::<_, String>("kind")?)?,
+        recorded_by: row.get("recorded_by")?,
+        occurred_at: parse_timestamp(row.get("occurred_at")?)?,
+        reason: row.get("reason")?,
+        linked_analysis_ids: json_column(&row.get::<_, String>("linked_analysis_ids_json")?)?,
+        recontrol_analysis_id: row
+            .get::<_, Option<String>>("recontrol_analysis_id")?
+            .map(parse_uuid)
+            .transpose()?,
+        recorded_at: parse_timestamp(row.get("recorded_at")?)?,
+    })
+}
+
+/// Returns whether the column had to be added.
+fn add_column_if_missing(
+    conn: &rusqlite::Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> rusqlite::Result<bool> {
+    let exists: bool = conn.query_row(
+        &format!("SELECT EXISTS (SELECT 1 FROM pragma_table_info('{table}') WHERE name = ?1)"),
+        [column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        conn.execute_batch(&format!(
+            "ALTER TABLE {table} ADD COLUMN {column} {definition}"
+        ))?;
+    }
+    Ok(!exists)
+}
+
+fn find_claim(
+    conn: &rusqlite::Connection,
+    analysis_id: Uuid,
+) -> rusqlite::Result<Option<ReviewClaim>> {
+    conn.query_row(
+        "SELECT * FROM review_claims WHERE analysis_id = ?1",
+        [analysis_id.to_string()],
+        |row| {
+            Ok(ReviewClaim {
+                analysis_id: parse_uuid(row.get("analysis_id")?)?,
+                reviewer_id: row.get("reviewer_id")?,
+                claimed_at: parse_timestamp(row.get("claimed_at")?)?,
+                expires_at: parse_timestamp(row.get("expires_at")?)?,
+            })
+        },
+    )
+    .optional()
+}
+
+/// Appends a history row when the analysis is still in `from`; the
+/// `lifecycle_transitions_apply` trigger then moves the status. `from` and
+/// `to` are the stored (JSON) representations.
+fn apply_transition(
+    conn: &rusqlite::Connection,
+    analysis_id: Uuid,
+    from: &str,
+    to: &str,
+    reason: &str,
+) -> rusqlite::Result<bool> {
+    let inserted = conn.execute(
+        "INSERT INTO analysis_lifecycle_transitions (
+            analysis_id, from_status, to_status, reason, transitioned_at
+        )
+        SELECT ?1, ?2, ?3, ?4, ?5
+        WHERE EXISTS (
+            SELECT 1 FROM analyses WHERE id = ?1 AND server_lifecycle_status = ?2
+        )",
+        params![
+            analysis_id.to_string(),
+            from,
+            to,
+            reason,
+            Utc::now().to_rfc3339()
+        ],
+    )?;
+    Ok(inserted == 1)
+}
+
+fn parse_audit_export_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<AuditExport> {
+    let optional_timestamp = |column: &str| -> rusqlite::Result<Option<DateTime<Utc>>> {
+        row.get::<_, Option<String>>(column)?
+            .map(parse_timestamp)
+            .transpose()
+    };
+    Ok(AuditExport {
+        id: parse_uuid(row.get("id")?)?,
+        format: json_column(&row.get::<_, String>("format")?)?,
+        csv_dialect: json_column(&row.get::<_, String>("csv_dialect")?)?,
+        from: optional_timestamp("from_at")?,
+        to: optional_timestamp("to_at")?,
+        status: json_column(&row.get::<_, String>("status")?)?,
+        requested_by: row.get("requested_by")?,
+        requested_at: parse_timestamp(row.get("requested_at")?)?,
+        completed_at: optional_timestamp("completed_at")?,
+        expires_at: optional_timestamp("expires_at")?,
+        record_count: row
+            .get::<_, Option<i64>>("record_count")?
+            .map(|count| count as usize),
+        file_sha256: row.get("file_sha256")?,
+        error: row.get("error")?,
+    })
+}
+
+fn parse_job_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Job> {
+    Ok(Job {
+        id: parse_uuid(row.get("id")?)?,
+        kind: json_column(&row.get::<_, String>("kind")?)?,
+        subject_id: parse_uuid(row.get("subject_id")?)?,
+        status: json_column(&row.get::<_, String>("status")?)?,
+        attempts: row.get("attempts")?,
+        max_attempts: row.get("max_attempts")?,
+        run_after: parse_timestamp(row.get("run_after")?)?,
+        last_error: row.get("last_error")?,
+        created_at: parse_timestamp(row.get("created_at")?)?,
+        updated_at: parse_timestamp(row.get("updated_at")?)?,
+    })
+}
+
+/// `chain_prev_hash` of the first entry of each chain.
+const CHAIN_GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+fn chain_hash(prev_hash: &str, content: &str) -> String {
+    sha256_hex(format!("{prev_hash}\n{content}").as_bytes())
+}
+
+/// Canonical form of an analysis for the chain. The lifecycle status is left
+/// out: it is the one column legitimately updated after insertion, and each
+/// transition is itself traced in the audit events chain.
+fn analysis_chain_content(analysis: &Analysis) -> rusqlite::Result<String> {
+    let mut value = serde_json::to_value(analysis)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    if let Some(object) = value.as_object_mut() {
+        object.remove("server_lifecycle_status");
+    }
+    Ok(value.to_string())
+}
+
+fn audit_event_chain_content(event: &AuditEvent) -> rusqlite::Result<String> {
+    serde_json::to_string(event).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+fn analysis_row_chain_content(row: &rusqlite::Row<'_>) -> rusqlite::Result<String> {
+    analysis_chain_content(&parse_analysis_row(row)?)
+}
+
+fn audit_event_row_chain_content(row: &rusqlite::Row<'_>) -> rusqlite::Result<String> {
+    audit_event_chain_content(&parse_audit_event_row(row)?)
+}
+
+/// Links the freshly inserted row `id` to the last sealed row of `table`.
+fn append_to_chain(
+    conn: &rusqlite::Connection,
+    table: &str,
+    id: &str,
+    content: &str,
+) -> rusqlite::Result<()> {
+    let prev_hash = chain_head(conn, table)?;
+    let hash = chain_hash(&prev_hash, content);
+    conn.execute(
+        &format!("UPDATE {table} SET chain_prev_hash = ?1, chain_hash = ?2 WHERE id = ?3"),
+        params![prev_hash, hash, id],
+    )?;
+    Ok(())
+}
+
+/// Adds the chain columns to `table` and seals its existing rows, only on
+/// the start that adds them.
+fn add_chain_columns(
+    conn: &mut rusqlite::Connection,
+    table: &str,
+    content: fn(&rusqlite::Row<'_>) -> rusqlite::Result<String>,
+) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    add_column_if_missing(&tx, table, "chain_prev_hash", "TEXT")?;
+    if add_column_if_missing(&tx, table, "chain_hash", "TEXT")? {
+        chain_unhashed_rows(&tx, table, content)?;
+    }
+    tx.commit()
+}
+
+fn chain_unhashed_rows(
+    conn: &rusqlite::Connection,
+    table: &str,
+    content: fn(&rusqlite::Row<'_>) -> rusqlite::Result<String>,
+) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT * FROM {table} WHERE chain_hash IS NULL ORDER BY rowid"
+    ))?;
+    let pending = stmt
+        .query_map([], |row| Ok((row.get::<_, String>("id")?, content(row)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    for (id, content) in pending {
+        append_to_chain(conn, table, &id, &content)?;
+    }
+    Ok(())
+}
+
+/// Hash of the last sealed row of `table`, or the genesis hash.
+fn chain_head(conn: &rusqlite::Connection, table: &str) -> rusqlite::Result<String> {
+    let head: Option<String> = conn
+        .query_row(
+            &format!(
+                "SELECT chain_hash FROM {table}
+                 WHERE chain_hash IS NOT NULL ORDER BY rowid DESC LIMIT 1"
+            ),
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(head.unwrap_or_else(|| CHAIN_GENESIS_HASH.to_string()))
+}
+
+/// Walks `table` in insertion order, recomputing every link.
+fn verify_chain(
+    conn: &rusqlite::Connection,
+    table: &str,
+    content: fn(&rusqlite::Row<'_>) -> rusqlite::Result<String>,
+) -> rusqlite::Result<ChainVerification> {
+    let mut stmt = conn.prepare(&format!("SELECT * FROM {table} ORDER BY rowid"))?;
+    let mut rows = stmt.query([])?;
+    let mut expected_prev = CHAIN_GENESIS_HASH.to_string();
+    let mut entries = 0;
+    let mut first_broken_link = None;
+
+    while let Some(row) = rows.next()? {
+        entries += 1;
+        let prev_hash: Option<String> = row.get("chain_prev_hash")?;
+        let stored_hash: Option<String> = row.get("chain_hash")?;
+        if first_broken_link.is_none() {
+            let reason = match content(row) {
+                Err(_) => Some(BrokenLinkReason::Unreadable),
+                Ok(_) if prev_hash.as_deref() != Some(expected_prev.as_str()) => {
+                    Some(BrokenLinkReason::PreviousHashMismatch)
+                }
+                Ok(content)
+                    if stored_hash.as_deref()
+                        != Some(chain_hash(&expected_prev, &content).as_str()) =>
+                {
+                    Some(BrokenLinkReason::ContentHashMismatch)
+                }
+                Ok(_) => None,
+            };
+            if let Some(reason) = reason {
+                first_broken_link = Some(BrokenLink {
+                    position: entries,
+                    entry_id: row.get("id")?,
+                    reason,
+                });
+            }
+        }
+        if let Some(hash) = stored_hash {
+            expected_prev = hash;
+        }
+    }
+
+    Ok(ChainVerification {
+        entries,
+        head: expected_prev,
+        first_broken_link,
+    })
+}
+
+/// Checks every stored token is signed by the TSA and still covers its
+/// message, as recorded on receipt, and that the message still matches its
+/// subject.
+fn verify_timestamps(
+    conn: &rusqlite::Connection,
+    tsa_certificate: Option<&TsaCertificate>,
+) -> rusqlite::Result<TimestampsVerification> {
+    let mut stmt = conn.prepare("SELECT * FROM trusted_timestamps ORDER BY rowid")?;
+    let timestamps = stmt
+        .query_map([], parse_timestamp_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut first_invalid = None;
+    let mut last_chain_heads_gen_time = None;
+    for timestamp in &timestamps {
+        let verified = tsa_certificate.map(|certificate| {
+            timestamping::verify_token(&timestamp.token, timestamp.message.as_bytes(), certificate)
+        });
+        let reason = match verified {
+            None => Some(TimestampFailure::TsaCertificateMissing),
+            Some(Err(timestamping::TimestampError::ImprintMismatch)) => {
+                Some(TimestampFailure::ImprintMismatch)
+            }
+            Some(Err(err)) if err.is_signature_error() => Some(TimestampFailure::SignatureInvalid),
+            Some(Err(_)) => Some(TimestampFailure::Unreadable),
+            Some(Ok(info))
+                if info.message_sha256 != timestamp.message_sha256
+                    || info.nonce != Some(timestamp.nonce)
+                    || info.gen_time != timestamp.gen_time =>
+            {
+                Some(TimestampFailure::TokenMismatch)
+            }
+            Some(Ok(_)) if !timestamp_subject_matches(conn, timestamp)? => {
+                Some(TimestampFailure::SubjectMismatch)
+            }
+            Some(Ok(_)) => None,
+        };
+        match reason {
+            Some(reason) if first_invalid.is_none() => {
+                first_invalid = Some(InvalidTimestamp {
+                    timestamp_id: timestamp.id,
+                    subject_type: timestamp.subject_type,
+                    subject_id: timestamp.subject_id.clone(),
+                    reason,
+                });
+            }
+            None if timestamp.subject_type == TimestampSubject::ChainHeads => {
+                last_chain_heads_gen_time = last_chain_heads_gen_time.max(Some(timestamp.gen_time));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(TimestampsVerification {
+        tokens: timestamps.len(),
+        last_chain_heads_gen_time,
+        first_invalid,
+    })
+}
+
+fn timestamp_subject_matches(
+    conn: &rusqlite::Connection,
+    timestamp: &TrustedTimestamp,
+) -> rusqlite::Result<bool> {
+    match timestamp.subject_type {
+        TimestampSubject::ChainHeads => {
+            let Some((analyses, audit_events)) = timestamp.message.split_once('\n') else {
+                return Ok(false);
+            };
+            Ok(chain_contains(conn, "analyses", analyses)?
+                && chain_contains(conn, "audit_events", audit_events)?)
+        }
+        TimestampSubject::ExportManifest => {
+            let manifest_json: Option<String> = conn
+                .query_row(
+                    "SELECT manifest_json FROM export_manifests WHERE id = ?1",
+                    [&timestamp.subject_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(manifest_json.as_deref() == Some(timestamp.message.as_str()))
+        }
+    }
+}
+
+fn chain_contains(conn: &rusqlite::Connection, table: &str, hash: &str) -> rusqlite::Result<bool> {
+    if hash == CHAIN_GENESIS_HASH {
+        return Ok(true);
+    }
+    conn.query_row(
+        &format!("SELECT EXISTS (SELECT 1 FROM {table} WHERE chain_hash = ?1)"),
+        [hash],
+        |row| row.get(0),
+    )
+}
+
+/// Enums are stored as their serde JSON representation, like the original
+/// `analyses` columns.
+fn enum_text<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap()
+}
+
+fn json_column<T: serde::de::DeserializeOwned>(raw: &str) -> rusqlite::Result<T> {
+    serde_json::from_str(raw).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+fn parse_uuid(value: String) -> rusqlite::Result<Uuid> {
+    Uuid::parse_str(&value).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
     })
 }
+
+fn parse_timestamp(value: String) -> rusqlite::Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AnalysisStore;
+    use crate::domain::BrokenLinkReason;
+
+    #[tokio::test]
+    async fn rows_stored_without_a_chain_hash_stay_broken_across_restarts() {
+        let path = std::env::temp_dir().join(format!("peroxyde-store-{}.db", uuid::Uuid::new_v4()));
+        let store = AnalysisStore::new(&path).await.unwrap();
+        store
+            .execute_raw(
+                "INSERT INTO audit_events (id, event_type, details_json, occurred_at)
+                 VALUES ('6f1d3c2a-0b4e-4c8d-9a7f-2e5b8c1d0a93', '\"analysis_received\"', '{}', '2026-02-13T09:45:00Z')",
+            )
+            .await
+            .unwrap();
+        drop(store);
+
+        // `server-rust verify` opens the store the same way.
+        let store = AnalysisStore::new(&path).await.unwrap();
+        let report = store.verify_chains(None).await.unwrap();
+        assert!(!report.intact);
+        let broken = report.audit_events.first_broken_link.unwrap();
+        assert_eq!(broken.position, 1);
+        assert_eq!(broken.entry_id, "6f1d3c2a-0b4e-4c8d-9a7f-2e5b8c1d0a93");
+        assert_eq!(broken.reason, BrokenLinkReason::PreviousHashMismatch);
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,1058 @@
+//! RFC 3161 trusted timestamping: a minimal client for a Time Stamping
+//! Authority over plain HTTP, and the DER plumbing needed to build requests
+//! and read back the TSTInfo of the tokens it returns.
+//!
+//! On receipt and at every integrity run, a token must carry a SignerInfo
+//! for the TSA certificate configured with `TSA_CERT_PATH`, whose signed
+//! attributes hold the SHA-256 of the TSTInfo and are signed by that
+//! certificate's key (RSA PKCS#1 v1.5, ECDSA P-256 or Ed25519). The TSTInfo
+//! must then cover the exact bytes it was requested for, with the nonce and
+//! generation time recorded at the time. The certificate chain itself is not
+//! walked: the configured certificate is the trust anchor.
+
+use std::{fmt, path::Path, time::Duration};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use p256::ecdsa::signature::Verifier as _;
+use rand_core::{OsRng, RngCore};
+use rsa::{BigUint, Pkcs1v15Sign};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use uuid::Uuid;
+
+use crate::{
+    audit::AuditContext,
+    config::ServerConfig,
+    domain::{AuditEventType, TimestampSubject, TrustedTimestamp},
+    images::sha256_hex,
+    storage::{AnalysisStore, StorageError},
+};
+
+const TAG_BOOLEAN: u8 = 0x01;
+const TAG_INTEGER: u8 = 0x02;
+const TAG_BIT_STRING: u8 = 0x03;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_NULL: u8 = 0x05;
+const TAG_OID: u8 = 0x06;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_SET: u8 = 0x31;
+const TAG_CONTEXT_0: u8 = 0xa0;
+const TAG_CONTEXT_1: u8 = 0xa1;
+const TAG_CONTEXT_3: u8 = 0xa3;
+/// `[0] IMPLICIT SubjectKeyIdentifier` in a SignerIdentifier.
+const TAG_IMPLICIT_0: u8 = 0x80;
+
+const OID_SHA256: &str = "2.16.840.1.101.3.4.2.1";
+const OID_SIGNED_DATA: &str = "1.2.840.113549.1.7.2";
+const OID_TST_INFO: &str = "1.2.840.113549.1.9.16.1.4";
+const OID_CONTENT_TYPE: &str = "1.2.840.113549.1.9.3";
+const OID_MESSAGE_DIGEST: &str = "1.2.840.113549.1.9.4";
+const OID_RSA_ENCRYPTION: &str = "1.2.840.113549.1.1.1";
+const OID_SHA256_WITH_RSA: &str = "1.2.840.113549.1.1.11";
+const OID_EC_PUBLIC_KEY: &str = "1.2.840.10045.2.1";
+const OID_P256: &str = "1.2.840.10045.3.1.7";
+const OID_ECDSA_WITH_SHA256: &str = "1.2.840.10045.4.3.2";
+const OID_ED25519: &str = "1.3.101.112";
+const OID_SUBJECT_KEY_ID: &str = "2.5.29.14";
+
+/// Largest TSA reply accepted; real tokens with a certificate chain stay
+/// well under this.
+const MAX_RESPONSE_BYTES: usize = 256 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimestampError {
+    UnsupportedUrl(String),
+    Transport(String),
+    HttpStatus(u16),
+    /// The TSA answered with a PKIStatus other than granted.
+    Rejected {
+        status: i64,
+    },
+    Malformed(&'static str),
+    UnsupportedHashAlgorithm(String),
+    ImprintMismatch,
+    NonceMismatch,
+    /// The SignedData carries no SignerInfo.
+    Unsigned,
+    /// No SignerInfo names the configured TSA certificate.
+    UntrustedSigner,
+    /// The signed attributes do not cover this TSTInfo, or their signature
+    /// does not verify with the TSA key.
+    InvalidSignature,
+    UnsupportedSignatureAlgorithm(String),
+}
+
+impl TimestampError {
+    /// Whether the token was refused for its signature rather than its
+    /// content.
+    pub fn is_signature_error(&self) -> bool {
+        matches!(
+            self,
+            Self::Unsigned
+                | Self::UntrustedSigner
+                | Self::InvalidSignature
+                | Self::UnsupportedSignatureAlgorithm(_)
+        )
+    }
+}
+
+impl fmt::Display for TimestampError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedUrl(url) => write!(f, "unsupported TSA url (http only): {url}"),
+            Self::Transport(err) => write!(f, "TSA unreachable: {err}"),
+            Self::HttpStatus(status) => write!(f, "TSA answered HTTP {status}"),
+            Self::Rejected { status } => write!(f, "TSA refused the request (status {status})"),
+            Self::Malformed(what) => write!(f, "malformed timestamp token: {what}"),
+            Self::UnsupportedHashAlgorithm(oid) => {
+                write!(f, "timestamp token uses unsupported hash algorithm {oid}")
+            }
+            Self::ImprintMismatch => write!(f, "timestamp token covers another message"),
+            Self::NonceMismatch => write!(f, "timestamp token nonce does not match the request"),
+            Self::Unsigned => write!(f, "timestamp token carries no signer"),
+            Self::UntrustedSigner => {
+                write!(
+                    f,
+                    "timestamp token is not signed by the configured TSA certificate"
+                )
+            }
+            Self::InvalidSignature => write!(f, "timestamp token signature does not verify"),
+            Self::UnsupportedSignatureAlgorithm(oid) => {
+                write!(
+                    f,
+                    "timestamp token uses unsupported signature algorithm {oid}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TimestampError {}
+
+/// Fields of the TSTInfo carried by a timestamp token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TstInfo {
+    pub policy: String,
+    pub message_sha256: String,
+    /// Hex serial number assigned by the TSA.
+    pub serial_number: String,
+    pub gen_time: DateTime<Utc>,
+    pub nonce: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TimestampToken {
+    /// DER `TimeStampToken` (CMS ContentInfo), as returned by the TSA.
+    pub token: Vec<u8>,
+    pub info: TstInfo,
+    pub nonce: i64,
+}
+
+/// Certificate of the TSA (`TSA_CERT_PATH`): the only signer whose tokens
+/// are accepted.
+#[derive(Clone)]
+pub struct TsaCertificate {
+    /// DER `Name` of the issuer, compared byte for byte with the SignerInfo.
+    issuer: Vec<u8>,
+    /// Content of the serialNumber INTEGER.
+    serial_number: Vec<u8>,
+    subject_key_id: Option<Vec<u8>>,
+    key: TsaKey,
+}
+
+#[derive(Clone)]
+enum TsaKey {
+    Rsa(rsa::RsaPublicKey),
+    P256(p256::ecdsa::VerifyingKey),
+    Ed25519(ed25519_dalek::VerifyingKey),
+}
+
+impl TsaCertificate {
+    /// Reads a PEM or DER X.509 certificate.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|err| format!("{}: {err}", path.display()))?;
+        let der = match std::str::from_utf8(&bytes) {
+            Ok(text) if text.contains("-----BEGIN CERTIFICATE-----") => pem_certificate(text)
+                .ok_or_else(|| format!("{}: malformed PEM certificate", path.display()))?,
+            _ => bytes,
+        };
+        Self::from_der(&der).map_err(|err| format!("{}: {err}", path.display()))
+    }
+
+    pub fn from_der(der: &[u8]) -> Result<Self, TimestampError> {
+        let mut outer = DerReader::new(der);
+        let mut certificate = outer.expect(TAG_SEQUENCE, "Certificate")?;
+        let mut tbs = certificate.expect(TAG_SEQUENCE, "tbsCertificate")?;
+        if tbs.peek_tag() == Some(TAG_CONTEXT_0) {
+            tbs.skip()?;
+        }
+        let serial_number = tbs.expect(TAG_INTEGER, "serialNumber")?.rest.to_vec();
+        tbs.expect(TAG_SEQUENCE, "signature")?;
+        let issuer = tbs.read_raw(TAG_SEQUENCE, "issuer")?.to_vec();
+        tbs.expect(TAG_SEQUENCE, "validity")?;
+        tbs.expect(TAG_SEQUENCE, "subject")?;
+
+        let mut public_key_info = tbs.expect(TAG_SEQUENCE, "subjectPublicKeyInfo")?;
+        let mut algorithm = public_key_info.expect(TAG_SEQUENCE, "algorithm")?;
+        let key_algorithm = read_oid(&mut algorithm, "algorithm")?;
+        let public_key = match public_key_info
+            .expect(TAG_BIT_STRING, "subjectPublicKey")?
+            .rest
+        {
+            [0, key @ ..] => key,
+            _ => return Err(TimestampError::Malformed("subjectPublicKey")),
+        };
+        let key = match key_algorithm.as_str() {
+            OID_RSA_ENCRYPTION => {
+                let mut outer = DerReader::new(public_key);
+                let mut fields = outer.expect(TAG_SEQUENCE, "RSAPublicKey")?;
+                let modulus = fields.expect(TAG_INTEGER, "modulus")?.rest;
+                let exponent = fields.expect(TAG_INTEGER, "publicExponent")?.rest;
+                rsa::RsaPublicKey::new(
+                    BigUint::from_bytes_be(modulus),
+                    BigUint::from_bytes_be(exponent),
+                )
+                .map(TsaKey::Rsa)
+                .map_err(|_| TimestampError::Malformed("RSA public key"))?
+            }
+            OID_EC_PUBLIC_KEY => {
+                let curve = read_oid(&mut algorithm, "namedCurve")?;
+                if curve != OID_P256 {
+                    return Err(TimestampError::UnsupportedSignatureAlgorithm(curve));
+                }
+                p256::ecdsa::VerifyingKey::from_sec1_bytes(public_key)
+                    .map(TsaKey::P256)
+                    .map_err(|_| TimestampError::Malformed("EC public key"))?
+            }
+            OID_ED25519 => public_key
+                .try_into()
+                .ok()
+                .and_then(|key| ed25519_dalek::VerifyingKey::from_bytes(key).ok())
+                .map(TsaKey::Ed25519)
+                .ok_or(TimestampError::Malformed("Ed25519 public key"))?,
+            _ => return Err(TimestampError::UnsupportedSignatureAlgorithm(key_algorithm)),
+        };
+
+        // issuerUniqueID [1] and subjectUniqueID [2] may precede extensions [3].
+        let mut subject_key_id = None;
+        while let Some(tag) = tbs.peek_tag() {
+            if tag != TAG_CONTEXT_3 {
+                tbs.skip()?;
+                continue;
+            }
+            let mut explicit = tbs.expect(TAG_CONTEXT_3, "extensions")?;
+            let mut extensions = explicit.expect(TAG_SEQUENCE, "extensions")?;
+            while extensions.peek_tag().is_some() {
+                let mut extension = extensions.expect(TAG_SEQUENCE, "Extension")?;
+                if read_oid(&mut extension, "extnID")? != OID_SUBJECT_KEY_ID {
+                    continue;
+                }
+                if extension.peek_tag() == Some(TAG_BOOLEAN) {
+                    extension.skip()?;
+                }
+                let value = extension.expect(TAG_OCTET_STRING, "extnValue")?.rest;
+                let key_id = DerReader::new(value).expect(TAG_OCTET_STRING, "keyIdentifier")?;
+                subject_key_id = Some(key_id.rest.to_vec());
+            }
+        }
+
+        Ok(Self {
+            issuer,
+            serial_number,
+            subject_key_id,
+            key,
+        })
+    }
+
+    /// Checks `signature` over `signed` with the certificate key.
+    fn verify(
+        &self,
+        signature_algorithm: &str,
+        signed: &[u8],
+        signature: &[u8],
+    ) -> Result<(), TimestampError> {
+        let verified = match (&self.key, signature_algorithm) {
+            (TsaKey::Rsa(key), OID_RSA_ENCRYPTION | OID_SHA256_WITH_RSA) => key
+                .verify(
+                    Pkcs1v15Sign::new::<Sha256>(),
+                    &Sha256::digest(signed),
+                    signature,
+                )
+                .is_ok(),
+            (TsaKey::P256(key), OID_ECDSA_WITH_SHA256) => {
+                p256::ecdsa::Signature::from_der(signature)
+                    .is_ok_and(|signature| key.verify(signed, &signature).is_ok())
+            }
+            (TsaKey::Ed25519(key), OID_ED25519) => ed25519_dalek::Signature::from_slice(signature)
+                .is_ok_and(|signature| key.verify_strict(signed, &signature).is_ok()),
+            _ => {
+                return Err(TimestampError::UnsupportedSignatureAlgorithm(
+                    signature_algorithm.to_string(),
+                ))
+            }
+        };
+        if verified {
+            Ok(())
+        } else {
+            Err(TimestampError::InvalidSignature)
+        }
+    }
+}
+
+/// Talks to the Time Stamping Authority configured with `TSA_URL`, trusting
+/// the tokens signed by `TSA_CERT_PATH`.
+pub struct TsaClient {
+    url: String,
+    timeout: Duration,
+    certificate: TsaCertificate,
+}
+
+impl TsaClient {
+    pub fn new(url: impl Into<String>, timeout: Duration, certificate: TsaCertificate) -> Self {
+        Self {
+            url: url.into(),
+            timeout,
+            certificate,
+        }
+    }
+
+    /// `None` when no TSA is configured: timestamping is optional. A TSA
+    /// without a readable certificate is a configuration error.
+    pub fn from_config(config: &ServerConfig) -> Result<Option<Self>, String> {
+        let Some(url) = &config.tsa_url else {
+            return Ok(None);
+        };
+        let path = config
+            .tsa_cert_path
+            .as_ref()
+            .ok_or("TSA_URL is set without TSA_CERT_PATH")?;
+        let certificate = TsaCertificate::load(path)?;
+        Ok(Some(Self::new(
+            url.clone(),
+            Duration::from_secs(config.tsa_timeout_seconds),
+            certificate,
+        )))
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn certificate(&self) -> &TsaCertificate {
+        &self.certificate
+    }
+
+    /// Requests a token for the SHA-256 of `message` and checks the reply
+    /// covers it.
+    pub async fn timestamp(&self, message: &[u8]) -> Result<TimestampToken, TimestampError> {
+        let digest = sha256_hex(message);
+        // Kept positive so it round-trips through an SQLite integer.
+        let nonce = (OsRng.next_u64() >> 1) as i64;
+        let request = encode_request(&hex::decode(&digest).expect("hex digest"), nonce);
+
+        let reply = tokio::time::timeout(self.timeout, post(&self.url, &request))
+            .await
+            .map_err(|_| TimestampError::Transport("timed out".to_string()))??;
+        let token = parse_response(&reply)?;
+        let info = verify_token(&token, message, &self.certificate)?;
+        if info.nonce != Some(nonce) {
+            return Err(TimestampError::NonceMismatch);
+        }
+        Ok(TimestampToken { token, info, nonce })
+    }
+}
+
+/// Requests a token for `message` and stores it against its subject. A TSA
+/// failure is recorded in the audit trail and yields `None`: timestamping
+/// never blocks the operation it certifies.
+pub async fn timestamp_and_store(
+    store: &AnalysisStore,
+    tsa: &TsaClient,
+    audit: &AuditContext,
+    subject_type: TimestampSubject,
+    subject_id: String,
+    message: String,
+) -> Result<Option<TrustedTimestamp>, StorageError> {
+    let token = match tsa.timestamp(message.as_bytes()).await {
+        Ok(token) => token,
+        Err(err) => {
+            audit
+                .record(
+                    store,
+                    AuditEventType::TimestampRequestFailed,
+                    None,
+                    None,
+                    json!({
+                        "subject_type": subject_type,
+                        "subject_id": subject_id,
+                        "tsa_url": tsa.url(),
+                        "error": err.to_string(),
+                    }),
+                )
+                .await?;
+            return Ok(None);
+        }
+    };
+
+    let timestamp = TrustedTimestamp {
+        id: Uuid::new_v4(),
+        subject_type,
+        subject_id,
+        message_sha256: token.info.message_sha256,
+        message,
+        tsa_url: tsa.url().to_string(),
+        policy: token.info.policy,
+        serial_number: token.info.serial_number,
+        nonce: token.nonce,
+        gen_time: token.info.gen_time,
+        requested_at: Utc::now(),
+        token: token.token,
+    };
+    store.record_timestamp(timestamp.clone()).await?;
+    let event_type = match subject_type {
+        TimestampSubject::ChainHeads => AuditEventType::ChainHeadsTimestamped,
+        TimestampSubject::ExportManifest => AuditEventType::ExportTimestamped,
+    };
+    audit
+        .record(
+            store,
+            event_type,
+            None,
+            None,
+            json!({
+                "timestamp_id": timestamp.id,
+                "subject_id": timestamp.subject_id,
+                "message_sha256": timestamp.message_sha256,
+                "tsa_url": timestamp.tsa_url,
+                "gen_time": timestamp.gen_time,
+            }),
+        )
+        .await?;
+    Ok(Some(timestamp))
+}
+
+/// Checks a DER `TimeStampToken` was signed by `certificate` over its
+/// TSTInfo, then reads the TSTInfo and checks it is a SHA-256 imprint of
+/// `message`.
+pub fn verify_token(
+    token: &[u8],
+    message: &[u8],
+    certificate: &TsaCertificate,
+) -> Result<TstInfo, TimestampError> {
+    let signed = parse_signed_data(token)?;
+    let mut outcome = Err(TimestampError::Unsigned);
+    for signer in signed.signer_infos {
+        outcome = verify_signer(certificate, signed.tst_info, signer);
+        if outcome != Err(TimestampError::UntrustedSigner) {
+            break;
+        }
+    }
+    outcome?;
+
+    let info = parse_tst_info(signed.tst_info)?;
+    if info.message_sha256 != sha256_hex(message) {
+        return Err(TimestampError::ImprintMismatch);
+    }
+    Ok(info)
+}
+
+/// DER `TimeStampReq` for a SHA-256 digest, asking for the TSA certificate
+/// to be included so the token can be verified offline.
+fn encode_request(digest: &[u8], nonce: i64) -> Vec<u8> {
+    sequence(&[
+        integer(1),
+        message_imprint(digest),
+        integer(nonce),
+        tlv(TAG_BOOLEAN, &[0xff]),
+    ])
+}
+
+fn parse_response(reply: &[u8]) -> Result<Vec<u8>, TimestampError> {
+    let mut outer = DerReader::new(reply);
+    let mut response = outer.expect(TAG_SEQUENCE, "TimeStampResp")?;
+    let mut status_info = response.expect(TAG_SEQUENCE, "PKIStatusInfo")?;
+    let status = read_integer(&mut status_info, "PKIStatus")?;
+    // 0 granted, 1 granted with modifications.
+    if status > 1 {
+        return Err(TimestampError::Rejected { status });
+    }
+    let token = response.read_raw(TAG_SEQUENCE, "TimeStampToken")?;
+    Ok(token.to_vec())
+}
+
+/// DER TSTInfo and SignerInfos of a `TimeStampToken`.
+struct SignedToken<'a> {
+    tst_info: &'a [u8],
+    signer_infos: Vec<DerReader<'a>>,
+}
+
+fn parse_signed_data(token: &[u8]) -> Result<SignedToken<'_>, TimestampError> {
+    let mut outer = DerReader::new(token);
+    let mut content_info = outer.expect(TAG_SEQUENCE, "ContentInfo")?;
+    if read_oid(&mut content_info, "contentType")? != OID_SIGNED_DATA {
+        return Err(TimestampError::Malformed("token is not CMS SignedData"));
+    }
+    let mut explicit = content_info.expect(TAG_CONTEXT_0, "SignedData")?;
+    let mut signed_data = explicit.expect(TAG_SEQUENCE, "SignedData")?;
+    signed_data.expect(TAG_INTEGER, "SignedData version")?;
+    signed_data.expect(TAG_SET, "digestAlgorithms")?;
+    let mut encapsulated = signed_data.expect(TAG_SEQUENCE, "encapContentInfo")?;
+    if read_oid(&mut encapsulated, "eContentType")? != OID_TST_INFO {
+        return Err(TimestampError::Malformed("token does not carry a TSTInfo"));
+    }
+    let mut explicit = encapsulated.expect(TAG_CONTEXT_0, "eContent")?;
+    let tst_info = explicit.expect(TAG_OCTET_STRING, "eContent")?;
+
+    // certificates [0] and crls [1] are optional.
+    while matches!(signed_data.peek_tag(), Some(TAG_CONTEXT_0 | TAG_CONTEXT_1)) {
+        signed_data.skip()?;
+    }
+    let mut signer_set = signed_data.expect(TAG_SET, "signerInfos")?;
+    let mut signer_infos = Vec::new();
+    while signer_set.peek_tag().is_some() {
+        signer_infos.push(signer_set.expect(TAG_SEQUENCE, "SignerInfo")?);
+    }
+    Ok(SignedToken {
+        tst_info: tst_info.rest,
+        signer_infos,
+    })
+}
+
+/// Checks `signer` names `certificate`, that its signed attributes carry the
+/// TSTInfo content type and the SHA-256 of `tst_info`, and that the
+/// certificate key signed them.
+fn verify_signer(
+    certificate: &TsaCertificate,
+    tst_info: &[u8],
+    mut signer: DerReader<'_>,
+) -> Result<(), TimestampError> {
+    signer.expect(TAG_INTEGER, "SignerInfo version")?;
+    let named = match signer.next()? {
+        (TAG_SEQUENCE, _, content) => {
+            let mut issuer_and_serial = DerReader::new(content);
+            let issuer = issuer_and_serial.read_raw(TAG_SEQUENCE, "issuer")?;
+            let serial_number = issuer_and_serial.expect(TAG_INTEGER, "serialNumber")?;
+            issuer == certificate.issuer && serial_number.rest == certificate.serial_number
+        }
+        (TAG_IMPLICIT_0, _, key_id) => certificate.subject_key_id.as_deref() == Some(key_id),
+        _ => return Err(TimestampError::Malformed("SignerIdentifier")),
+    };
+    if !named {
+        return Err(TimestampError::UntrustedSigner);
+    }
+    let mut digest_algorithm = signer.expect(TAG_SEQUENCE, "digestAlgorithm")?;
+    let digest_algorithm = read_oid(&mut digest_algorithm, "digestAlgorithm")?;
+    if digest_algorithm != OID_SHA256 {
+        return Err(TimestampError::UnsupportedHashAlgorithm(digest_algorithm));
+    }
+    // RFC 3161 tokens always sign attributes rather than the bare content.
+    if signer.peek_tag() != Some(TAG_CONTEXT_0) {
+        return Err(TimestampError::InvalidSignature);
+    }
+    let signed_attributes = signer.read_raw(TAG_CONTEXT_0, "signedAttrs")?;
+    let mut content_type = None;
+    let mut message_digest = None;
+    let mut attributes = DerReader::new(signed_attributes).expect(TAG_CONTEXT_0, "signedAttrs")?;
+    while attributes.peek_tag().is_some() {
+        let mut attribute = attributes.expect(TAG_SEQUENCE, "Attribute")?;
+        let attribute_type = read_oid(&mut attribute, "attrType")?;
+        let mut values = attribute.expect(TAG_SET, "attrValues")?;
+        match attribute_type.as_str() {
+            OID_CONTENT_TYPE => content_type = Some(read_oid(&mut values, "contentType")?),
+            OID_MESSAGE_DIGEST => {
+                message_digest = Some(values.expect(TAG_OCTET_STRING, "messageDigest")?.rest)
+            }
+            _ => {}
+        }
+    }
+    if content_type.as_deref() != Some(OID_TST_INFO)
+        || message_digest != Some(Sha256::digest(tst_info).as_slice())
+    {
+        return Err(TimestampError::InvalidSignature);
+    }
+
+    let mut signature_algorithm = signer.expect(TAG_SEQUENCE, "signatureAlgorithm")?;
+    let signature_algorithm = read_oid(&mut signature_algorithm, "signatureAlgorithm")?;
+    let signature = signer.expect(TAG_OCTET_STRING, "signature")?.rest;
+    // The signature covers the attributes re-tagged as a SET OF.
+    let mut signed = signed_attributes.to_vec();
+    signed[0] = TAG_SET;
+    certificate.verify(&signature_algorithm, &signed, signature)
+}
+
+fn parse_tst_info(tst_info: &[u8]) -> Result<TstInfo, TimestampError> {
+    let mut outer = DerReader::new(tst_info);
+    let mut fields = outer.expect(TAG_SEQUENCE, "TSTInfo")?;
+    fields.expect(TAG_INTEGER, "TSTInfo version")?;
+    let policy = read_oid(&mut fields, "policy")?;
+    let mut imprint = fields.expect(TAG_SEQUENCE, "messageImprint")?;
+    let mut algorithm = imprint.expect(TAG_SEQUENCE, "hashAlgorithm")?;
+    let algorithm = read_oid(&mut algorithm, "hashAlgorithm")?;
+    if algorithm != OID_SHA256 {
+        return Err(TimestampError::UnsupportedHashAlgorithm(algorithm));
+    }
+    let hashed_message = imprint.expect(TAG_OCTET_STRING, "hashedMessage")?;
+    let serial_number = fields.expect(TAG_INTEGER, "serialNumber")?;
+    let gen_time = fields.expect(TAG_GENERALIZED_TIME, "genTime")?;
+    let gen_time = parse_generalized_time(gen_time.rest)?;
+
+    // accuracy and ordering may precede the optional nonce.
+    let mut nonce = None;
+    while let Some(tag) = fields.peek_tag() {
+        if tag == TAG_INTEGER {
+            nonce = Some(read_integer(&mut fields, "nonce")?);
+            break;
+        }
+        fields.skip()?;
+    }
+
+    Ok(TstInfo {
+        policy,
+        message_sha256: hex::encode(hashed_message.rest),
+        serial_number: hex::encode(serial_number.rest),
+        gen_time,
+        nonce,
+    })
+}
+
+/// DER body of the first certificate of a PEM file.
+fn pem_certificate(text: &str) -> Option<Vec<u8>> {
+    let body = text
+        .split("-----BEGIN CERTIFICATE-----")
+        .nth(1)?
+        .split("-----END CERTIFICATE-----")
+        .next()?;
+    let mut der = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in body.bytes().filter(|byte| !byte.is_ascii_whitespace()) {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ => return None,
+        };
+        buffer = ((buffer << 6) | value as u32) & 0xffff;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            der.push((buffer >> bits) as u8);
+        }
+    }
+    Some(der)
+}
+
+fn parse_generalized_time(raw: &[u8]) -> Result<DateTime<Utc>, TimestampError> {
+    let text = std::str::from_utf8(raw).map_err(|_| TimestampError::Malformed("genTime"))?;
+    NaiveDateTime::parse_from_str(text, "%Y%m%d%H%M%S%.fZ")
+        .map(|naive| naive.and_utc())
+        .map_err(|_| TimestampError::Malformed("genTime"))
+}
+
+/// Sends `body` as an `application/timestamp-query` and returns the reply
+/// body. One request per connection.
+async fn post(url: &str, body: &[u8]) -> Result<Vec<u8>, TimestampError> {
+    let unsupported = || TimestampError::UnsupportedUrl(url.to_string());
+    let rest = url.strip_prefix("http://").ok_or_else(unsupported)?;
+    let (authority, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, "/"),
+    };
+    if authority.is_empty() {
+        return Err(unsupported());
+    }
+    let address = if authority.contains(':') {
+        authority.to_string()
+    } else {
+        format!("{authority}:80")
+    };
+
+    let transport = |err: std::io::Error| TimestampError::Transport(err.to_string());
+    let mut stream = tokio::net::TcpStream::connect(&address)
+        .await
+        .map_err(transport)?;
+    let head = format!(
+        "POST {path} HTTP/1.1\r\nHost: {authority}\r\nContent-Type: application/timestamp-query\r\nAccept: application/timestamp-reply\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await.map_err(transport)?;
+    stream.write_all(body).await.map_err(transport)?;
+
+    let mut reply = Vec::new();
+    (&mut stream)
+        .take(MAX_RESPONSE_BYTES as u64 + 1)
+        .read_to_end(&mut reply)
+        .await
+        .map_err(transport)?;
+    if reply.len() > MAX_RESPONSE_BYTES {
+        return Err(TimestampError::Transport("reply too large".to_string()));
+    }
+    http_body(&reply)
+}
+
+fn http_body(reply: &[u8]) -> Result<Vec<u8>, TimestampError> {
+    let malformed = || TimestampError::Transport("malformed HTTP reply".to_string());
+    let split = reply
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(malformed)?;
+    let head = std::str::from_utf8(&reply[..split]).map_err(|_| malformed())?;
+    let body = &reply[split + 4..];
+
+    let mut lines = head.split("\r\n");
+    let status: u16 = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(malformed)?;
+    if status != 200 {
+        return Err(TimestampError::HttpStatus(status));
+    }
+
+    let mut content_length = None;
+    let mut chunked = false;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse::<usize>().ok();
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            chunked = value.eq_ignore_ascii_case("chunked");
+        }
+    }
+
+    if chunked {
+        return dechunk(body).ok_or_else(malformed);
+    }
+    match content_length {
+        Some(length) if length <= body.len() => Ok(body[..length].to_vec()),
+        Some(_) => Err(malformed()),
+        None => Ok(body.to_vec()),
+    }
+}
+
+fn dechunk(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    loop {
+        let line_end = body.windows(2).position(|window| window == b"\r\n")?;
+        let size = std::str::from_utf8(&body[..line_end]).ok()?;
+        let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Some(decoded);
+        }
+        decoded.extend_from_slice(body.get(..size)?);
+        body = body.get(size + 2..)?;
+    }
+}
+
+/// Cursor over a run of DER TLVs.
+struct DerReader<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> DerReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { rest: bytes }
+    }
+
+    fn peek_tag(&self) -> Option<u8> {
+        self.rest.first().copied()
+    }
+
+    /// Returns the tag, the full encoding and the content of the next TLV.
+    fn next(&mut self) -> Result<(u8, &'a [u8], &'a [u8]), TimestampError> {
+        let truncated = TimestampError::Malformed("truncated DER");
+        let (&tag, after_tag) = self.rest.split_first().ok_or(truncated.clone())?;
+        let (&first, mut after_length) = after_tag.split_first().ok_or(truncated.clone())?;
+        let length = if first < 0x80 {
+            first as usize
+        } else {
+            let count = (first & 0x7f) as usize;
+            if count == 0 || count > 4 || after_length.len() < count {
+                return Err(TimestampError::Malformed("unsupported DER length"));
+            }
+            let length = after_length[..count]
+                .iter()
+                .fold(0usize, |length, byte| (length << 8) | *byte as usize);
+            after_length = &after_length[count..];
+            length
+        };
+        if after_length.len() < length {
+            return Err(truncated);
+        }
+        let header = self.rest.len() - after_length.len();
+        let raw = &self.rest[..header + length];
+        let content = &after_length[..length];
+        self.rest = &after_length[length..];
+        Ok((tag, raw, content))
+    }
+
+    fn expect(&mut self, tag: u8, what: &'static str) -> Result<DerReader<'a>, TimestampError> {
+        match self.next()? {
+            (found, _, content) if found == tag => Ok(DerReader::new(content)),
+            _ => Err(TimestampError::Malformed(what)),
+        }
+    }
+
+    fn read_raw(&mut self, tag: u8, what: &'static str) -> Result<&'a [u8], TimestampError> {
+        match self.next()? {
+            (found, raw, _) if found == tag => Ok(raw),
+            _ => Err(TimestampError::Malformed(what)),
+        }
+    }
+
+    fn skip(&mut self) -> Result<(), TimestampError> {
+        self.next().map(|_| ())
+    }
+}
+
+fn read_integer(reader: &mut DerReader<'_>, what: &'static str) -> Result<i64, TimestampError> {
+    let content = reader.expect(TAG_INTEGER, what)?.rest;
+    let magnitude = match content {
+        [0, rest @ ..] => rest,
+        _ => content,
+    };
+    if magnitude.len() > 8 || content.first().is_some_and(|byte| byte & 0x80 != 0) {
+        return Err(TimestampError::Malformed(what));
+    }
+    let value = magnitude
+        .iter()
+        .fold(0u64, |value, byte| (value << 8) | *byte as u64);
+    i64::try_from(value).map_err(|_| TimestampError::Malformed(what))
+}
+
+fn read_oid(reader: &mut DerReader<'_>, what: &'static str) -> Result<String, TimestampError> {
+    let content = reader.expect(TAG_OID, what)?.rest;
+    let (&first, rest) = content
+        .split_first()
+        .ok_or(TimestampError::Malformed(what))?;
+    let mut arcs = vec![(first / 40) as u64, (first % 40) as u64];
+    let mut arc = 0u64;
+    for byte in rest {
+        arc = (arc << 7) | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            arcs.push(arc);
+            arc = 0;
+        }
+    }
+    Ok(arcs
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join("."))
+}
+
+fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+    let length = content.len();
+    if length < 0x80 {
+        encoded.push(length as u8);
+    } else {
+        let bytes: Vec<u8> = length
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|byte| *byte == 0)
+            .collect();
+        encoded.push(0x80 | bytes.len() as u8);
+        encoded.extend(bytes);
+    }
+    encoded.extend_from_slice(content);
+    encoded
+}
+
+fn sequence(items: &[Vec<u8>]) -> Vec<u8> {
+    tlv(TAG_SEQUENCE, &items.concat())
+}
+
+fn integer(value: i64) -> Vec<u8> {
+    let mut bytes: Vec<u8> = value
+        .to_be_bytes()
+        .into_iter()
+        .skip_while(|byte| *byte == 0)
+        .collect();
+    if bytes.first().is_none_or(|byte| byte & 0x80 != 0) {
+        bytes.insert(0, 0);
+    }
+    tlv(TAG_INTEGER, &bytes)
+}
+
+fn oid(dotted: &str) -> Vec<u8> {
+    let arcs: Vec<u64> = dotted.split('.').map(|arc| arc.parse().unwrap()).collect();
+    let mut content = vec![(arcs[0] * 40 + arcs[1]) as u8];
+    for arc in &arcs[2..] {
+        let mut groups = vec![(arc & 0x7f) as u8];
+        let mut rest = arc >> 7;
+        while rest > 0 {
+            groups.push((rest & 0x7f) as u8 | 0x80);
+            rest >>= 7;
+        }
+        content.extend(groups.into_iter().rev());
+    }
+    tlv(TAG_OID, &content)
+}
+
+fn message_imprint(digest: &[u8]) -> Vec<u8> {
+    sequence(&[
+        sequence(&[oid(OID_SHA256), tlv(TAG_NULL, &[])]),
+        tlv(TAG_OCTET_STRING, digest),
+    ])
+}
+
+/// Seed of the stand-in TSA signing key.
+#[cfg(test)]
+const STAND_IN_KEY_SEED: [u8; 32] = [7; 32];
+#[cfg(test)]
+const STAND_IN_CERT_SERIAL: i64 = 0x5151;
+
+/// `CN=Stand-in TSA`, issuer and subject of the stand-in certificate.
+#[cfg(test)]
+fn stand_in_name() -> Vec<u8> {
+    sequence(&[tlv(
+        TAG_SET,
+        &sequence(&[oid("2.5.4.3"), tlv(0x0c, b"Stand-in TSA")]),
+    )])
+}
+
+/// Self-signed DER certificate of the stand-in TSA key, to configure as
+/// `TSA_CERT_PATH`.
+#[cfg(test)]
+pub fn stand_in_certificate() -> Vec<u8> {
+    use ed25519_dalek::Signer;
+
+    let key = ed25519_dalek::SigningKey::from_bytes(&STAND_IN_KEY_SEED);
+    let algorithm = sequence(&[oid(OID_ED25519)]);
+    let tbs = sequence(&[
+        tlv(TAG_CONTEXT_0, &integer(2)),
+        integer(STAND_IN_CERT_SERIAL),
+        algorithm.clone(),
+        stand_in_name(),
+        sequence(&[
+            tlv(TAG_GENERALIZED_TIME, b"20260101000000Z"),
+            tlv(TAG_GENERALIZED_TIME, b"20360101000000Z"),
+        ]),
+        stand_in_name(),
+        sequence(&[
+            algorithm.clone(),
+            tlv(
+                TAG_BIT_STRING,
+                &[&[0][..], key.verifying_key().as_bytes()].concat(),
+            ),
+        ]),
+    ]);
+    let signature = key.sign(&tbs).to_bytes();
+    sequence(&[
+        tbs,
+        algorithm,
+        tlv(TAG_BIT_STRING, &[&[0][..], &signature[..]].concat()),
+    ])
+}
+
+/// Local TSA stand-in for tests: answers a `TimeStampReq` with a granted
+/// token signed by the key of `stand_in_certificate`.
+#[cfg(test)]
+pub fn stand_in_reply(request: &[u8], serial: i64, gen_time: DateTime<Utc>) -> Vec<u8> {
+    stand_in_response(request, serial, gen_time, true)
+}
+
+/// Forging stand-in: same token as `stand_in_reply`, with no signer.
+#[cfg(test)]
+pub fn forged_reply(request: &[u8], serial: i64, gen_time: DateTime<Utc>) -> Vec<u8> {
+    stand_in_response(request, serial, gen_time, false)
+}
+
+/// `token` with its SignerInfos removed, as a forger without the TSA key
+/// would produce.
+#[cfg(test)]
+pub fn strip_signers(token: &[u8]) -> Vec<u8> {
+    signed_data_token(parse_signed_data(token).unwrap().tst_info, false)
+}
+
+#[cfg(test)]
+fn stand_in_response(
+    request: &[u8],
+    serial: i64,
+    gen_time: DateTime<Utc>,
+    signed: bool,
+) -> Vec<u8> {
+    let mut outer = DerReader::new(request);
+    let mut fields = outer.expect(TAG_SEQUENCE, "TimeStampReq").unwrap();
+    fields.skip().unwrap();
+    let mut imprint = fields.expect(TAG_SEQUENCE, "messageImprint").unwrap();
+    imprint.skip().unwrap();
+    let digest = imprint
+        .expect(TAG_OCTET_STRING, "hashedMessage")
+        .unwrap()
+        .rest;
+    let mut nonce = None;
+    while let Some(tag) = fields.peek_tag() {
+        if tag == TAG_INTEGER {
+            nonce = Some(read_integer(&mut fields, "nonce").unwrap());
+        } else {
+            fields.skip().unwrap();
+        }
+    }
+
+    let mut tst_info = vec![
+        integer(1),
+        oid("1.3.6.1.4.1.99999.1"),
+        message_imprint(digest),
+        integer(serial),
+        tlv(
+            TAG_GENERALIZED_TIME,
+            gen_time.format("%Y%m%d%H%M%SZ").to_string().as_bytes(),
+        ),
+    ];
+    if let Some(nonce) = nonce {
+        tst_info.push(integer(nonce));
+    }
+    let token = signed_data_token(&sequence(&tst_info), signed);
+    sequence(&[sequence(&[integer(0)]), token])
+}
+
+/// CMS SignedData over `tst_info`, with a SignerInfo for the stand-in
+/// certificate when `signed`.
+#[cfg(test)]
+fn signed_data_token(tst_info: &[u8], signed: bool) -> Vec<u8> {
+    use ed25519_dalek::Signer;
+
+    let mut signer_infos = Vec::new();
+    if signed {
+        let signed_attributes = [
+            sequence(&[oid(OID_CONTENT_TYPE), tlv(TAG_SET, &oid(OID_TST_INFO))]),
+            sequence(&[
+                oid(OID_MESSAGE_DIGEST),
+                tlv(TAG_SET, &tlv(TAG_OCTET_STRING, &Sha256::digest(tst_info))),
+            ]),
+        ]
+        .concat();
+        let key = ed25519_dalek::SigningKey::from_bytes(&STAND_IN_KEY_SEED);
+        let signature = key.sign(&tlv(TAG_SET, &signed_attributes)).to_bytes();
+        signer_infos = sequence(&[
+            integer(1),
+            sequence(&[stand_in_name(), integer(STAND_IN_CERT_SERIAL)]),
+            sequence(&[oid(OID_SHA256)]),
+            tlv(TAG_CONTEXT_0, &signed_attributes),
+            sequence(&[oid(OID_ED25519)]),
+            tlv(TAG_OCTET_STRING, &signature),
+        ]);
+    }
+    let signed_data = sequence(&[
+        integer(3),
+        tlv(TAG_SET, &sequence(&[oid(OID_SHA256)])),
+        sequence(&[
+            oid(OID_TST_INFO),
+            tlv(TAG_CONTEXT_0, &tlv(TAG_OCTET_STRING, tst_info)),
+        ]),
+        tlv(TAG_SET, &signer_infos),
+    ]);
+    sequence(&[oid(OID_SIGNED_DATA), tlv(TAG_CONTEXT_0, &signed_data)])
+}
@@ -1,10 +1,18 @@
-use crate::domain::Analysis;
+use crate::{domain::Analysis, images::is_sha256_hex};
 
 pub fn validate_analysis(analysis: &Analysis) -> Result<(), &'static str> {
     if analysis.sample_id.trim().is_empty() {
         return Err("sample_id is required");
     }
 
+    if analysis.operator_id.trim().is_empty() {
+        return Err("operator_id is required");
+    }
+
+    if analysis.bath_id.trim().is_empty() {
+        return Err("bath_id is required");
+    }
+
     if !(0.0..=1.0).contains(&analysis.confidence) {
         return Err("confidence must be between 0 and 1");
     }
@@ -25,5 +33,9 @@ pub fn validate_analysis(analysis: &Analysis) -> Result<(), &'static str> {
         return Err("image reference is required");
     }
 
+    if !is_sha256_hex(&analysis.image.sha256) {
+        return Err("image.sha256 must be 64 lowercase hexadecimal characters");
+    }
+
     Ok(())
 }
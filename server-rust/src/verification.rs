@@ -0,0 +1,208 @@
+use chrono::Utc;
+
+use crate::{
+    calibration::{srgb_to_lab, CalibrationScale},
+    domain::{
+        Analysis, AnalysisVerification, ComplianceStatus, FailedCheck, ReviewReason,
+        VerificationCheck,
+    },
+    images::{sha256_hex, ImageStore},
+    rules, validation,
+};
+
+/// Below this confidence the mobile app itself only issues a warning, so the
+/// result is sent to a second reader.
+pub const CONFIDENCE_PASS_THRESHOLD: f32 = 0.75;
+/// Accepted gap between the server estimate and the client interval
+/// (`ppm_tolerance` of `data/validation/expected-results.csv`).
+pub const SERVER_PPM_TOLERANCE: f32 = 15.0;
+
+const SATURATION_CHANNEL_THRESHOLD: u8 = 250;
+const UNDEREXPOSED_CHANNEL_THRESHOLD: u8 = 12;
+const MIN_VALID_PIXEL_RATIO: f64 = 0.45;
+
+/// Second, independent reading of an analysis: the server re-applies the
+/// business rules and re-estimates the concentration from the stored photo.
+#[derive(Debug)]
+pub struct Verifier {
+    scale: CalibrationScale,
+    images: ImageStore,
+}
+
+impl Verifier {
+    pub fn new(scale: CalibrationScale, images: ImageStore) -> Self {
+        Self { scale, images }
+    }
+
+    pub fn images(&self) -> &ImageStore {
+        &self.images
+    }
+
+    pub fn calibration(&self) -> &CalibrationScale {
+        &self.scale
+    }
+
+    pub fn verify(&self, analysis: &Analysis) -> AnalysisVerification {
+        let mut failed_checks = Vec::new();
+
+        if let Err(message) = validation::validate_analysis(analysis) {
+            failed_checks.push(failed(VerificationCheck::PayloadRules, message));
+        }
+
+        let client_decision = rules::evaluate_ppm_reading(analysis.ppm_estime);
+        if client_decision.analysis_result != analysis.analysis_result
+            || client_decision.compliance() != analysis.compliance_status
+        {
+            failed_checks.push(failed(
+                VerificationCheck::DecisionConsistency,
+                format!(
+                    "ppm_estime {} gives \"{}\" but the client reported \"{}\"",
+                    analysis.ppm_estime, client_decision.analysis_result, analysis.analysis_result
+                ),
+            ));
+        }
+
+        let raised = analysis.acquisition_metadata.rejection_flags.raised();
+        if !raised.is_empty() {
+            failed_checks.push(failed(
+                VerificationCheck::CaptureConditions,
+                format!("rejection flags raised: {}", raised.join(", ")),
+            ));
+        }
+
+        let server_ppm_estime = match self.reanalyse_image(analysis) {
+            Ok(ppm) => Some(ppm),
+            Err(check) => {
+                failed_checks.push(check);
+                None
+            }
+        };
+        let server_decision = server_ppm_estime.map(rules::evaluate_ppm_reading);
+
+        if let (Some(ppm), Some(decision)) = (server_ppm_estime, &server_decision) {
+            if ppm < analysis.ppm_min - SERVER_PPM_TOLERANCE
+                || ppm > analysis.ppm_max + SERVER_PPM_TOLERANCE
+            {
+                failed_checks.push(failed(
+                    VerificationCheck::ServerPpmAgreement,
+                    format!(
+                        "server estimate {ppm:.1} ppm outside client interval [{}, {}]",
+                        analysis.ppm_min, analysis.ppm_max
+                    ),
+                ));
+            }
+            if decision.analysis_result != analysis.analysis_result {
+                failed_checks.push(failed(
+                    VerificationCheck::ServerDecisionAgreement,
+                    format!(
+                        "server decision \"{}\" differs from client \"{}\"",
+                        decision.analysis_result, analysis.analysis_result
+                    ),
+                ));
+            }
+        }
+
+        let mut review_reasons = Vec::new();
+        if analysis.confidence < CONFIDENCE_PASS_THRESHOLD {
+            review_reasons.push(ReviewReason::LowConfidence);
+        }
+        if failed_checks.iter().any(|failed| {
+            matches!(
+                failed.check,
+                VerificationCheck::DecisionConsistency
+                    | VerificationCheck::ServerPpmAgreement
+                    | VerificationCheck::ServerDecisionAgreement
+            )
+        }) {
+            review_reasons.push(ReviewReason::ClientServerDiscrepancy);
+        }
+        let server_compliance = server_decision
+            .as_ref()
+            .map(|decision| decision.compliance());
+        if analysis.compliance_status != ComplianceStatus::ConformeProduction
+            || server_compliance
+                .is_some_and(|status| status != ComplianceStatus::ConformeProduction)
+        {
+            review_reasons.push(ReviewReason::NonCompliant);
+        }
+
+        AnalysisVerification {
+            analysis_id: analysis.id,
+            // Only payloads that deserialized into a typed `Analysis` are ever
+            // stored, so the schema check is settled at receipt.
+            validated_schema: true,
+            validated_business_rules: failed_checks.is_empty(),
+            failed_checks,
+            review_reasons,
+            server_ppm_estime,
+            server_decision,
+            verified_at: Utc::now(),
+        }
+    }
+
+    fn reanalyse_image(&self, analysis: &Analysis) -> Result<f32, FailedCheck> {
+        let bytes = self
+            .images
+            .read(&analysis.image)
+            .map_err(|err| failed(VerificationCheck::ImageAvailable, err.to_string()))?;
+
+        let digest = sha256_hex(&bytes);
+        if !digest.eq_ignore_ascii_case(&analysis.image.sha256) {
+            return Err(failed(
+                VerificationCheck::ImageIntegrity,
+                "sha256 does not match declared reference",
+            ));
+        }
+
+        let decoded = image::load_from_memory(&bytes)
+            .map_err(|err| failed(VerificationCheck::ImageDecodable, err.to_string()))?
+            .to_rgb8();
+
+        // The server has no operator-selected ROI: it reads the central half of
+        // the frame, where the capture guide places the reactive pad.
+        let (width, height) = decoded.dimensions();
+        let (x0, y0) = (width / 4, height / 4);
+        let (x1, y1) = ((width * 3 / 4).max(x0 + 1), (height * 3 / 4).max(y0 + 1));
+
+        let mut sums = [0u64; 3];
+        let mut kept = 0u64;
+        let mut total = 0u64;
+        for y in y0..y1.min(height) {
+            for x in x0..x1.min(width) {
+                total += 1;
+                let [r, g, b] = decoded.get_pixel(x, y).0;
+                let saturated = r >= SATURATION_CHANNEL_THRESHOLD
+                    || g >= SATURATION_CHANNEL_THRESHOLD
+                    || b >= SATURATION_CHANNEL_THRESHOLD;
+                let underexposed = r <= UNDEREXPOSED_CHANNEL_THRESHOLD
+                    && g <= UNDEREXPOSED_CHANNEL_THRESHOLD
+                    && b <= UNDEREXPOSED_CHANNEL_THRESHOLD;
+                if saturated || underexposed {
+                    continue;
+                }
+                kept += 1;
+                sums[0] += u64::from(r);
+                sums[1] += u64::from(g);
+                sums[2] += u64::from(b);
+            }
+        }
+
+        if total == 0 || (kept as f64 / total as f64) < MIN_VALID_PIXEL_RATIO {
+            return Err(failed(
+                VerificationCheck::ImageQuality,
+                format!("only {kept}/{total} usable pixels in the central zone"),
+            ));
+        }
+
+        let mean = |sum: u64| (sum as f64 / kept as f64).round() as u8;
+        let lab = srgb_to_lab(mean(sums[0]), mean(sums[1]), mean(sums[2]));
+        Ok(self.scale.estimate_ppm(lab).ppm_estime as f32)
+    }
+}
+
+fn failed(check: VerificationCheck, detail: impl Into<String>) -> FailedCheck {
+    FailedCheck {
+        check,
+        detail: detail.into(),
+    }
+}
@@ -0,0 +1,211 @@
+//! Minimal single-sheet XLSX (Office Open XML) writer for the audit register:
+//! typed cells, a bold frozen header with an autofilter, and `containsText`
+//! conditional fills that Excel re-evaluates when a cell is edited.
+
+use std::{
+    fmt::Write as _,
+    io::{Cursor, Write},
+};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::pdf::Rgb;
+
+pub const CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+/// Style indexes in `styles.xml`.
+const HEADER_STYLE: usize = 1;
+const DATE_TIME_STYLE: usize = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Empty,
+    Text(String),
+    /// Decimal with a `.` separator, written as is.
+    Number(String),
+    Boolean(bool),
+    /// Shown as `dd/mm/yyyy hh:mm:ss`; spreadsheets have no time zone.
+    DateTime(NaiveDateTime),
+}
+
+/// Fills the cells of `column` whose text contains `text`.
+pub struct Highlight {
+    pub column: usize,
+    pub text: String,
+    pub fill: Rgb,
+}
+
+pub struct Sheet {
+    pub name: String,
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<Cell>>,
+    pub highlights: Vec<Highlight>,
+}
+
+impl Sheet {
+    pub fn to_xlsx(&self) -> Result<Vec<u8>, String> {
+        let last_column = column_name(self.header.len().max(1) - 1);
+        let last_row = self.rows.len() + 1;
+        let parts = [
+            ("[Content_Types].xml", CONTENT_TYPES.to_string()),
+            ("_rels/.rels", ROOT_RELATIONSHIPS.to_string()),
+            (
+                "xl/workbook.xml",
+                format!(
+                    "{XML_DECLARATION}<workbook xmlns=\"{MAIN_NS}\" xmlns:r=\"{RELATIONSHIPS_NS}\"><sheets><sheet name=\"{name}\" sheetId=\"1\" r:id=\"rId1\"/></sheets><definedNames><definedName name=\"_xlnm._FilterDatabase\" localSheetId=\"0\" hidden=\"1\">'{name}'!$A$1:${last_column}${last_row}</definedName></definedNames></workbook>",
+                    name = escape(&self.name)
+                ),
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                WORKBOOK_RELATIONSHIPS.to_string(),
+            ),
+            ("xl/styles.xml", self.styles()),
+            ("xl/worksheets/sheet1.xml", self.worksheet()),
+        ];
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        for (path, xml) in parts {
+            zip.start_file(path, options)
+                .map_err(|err| err.to_string())?;
+            zip.write_all(xml.as_bytes())
+                .map_err(|err| err.to_string())?;
+        }
+        let zip = zip.finish().map_err(|err| err.to_string())?;
+        Ok(zip.into_inner())
+    }
+
+    fn worksheet(&self) -> String {
+        let last_column = column_name(self.header.len().max(1) - 1);
+        let last_row = self.rows.len() + 1;
+        let mut xml = format!(
+            "{XML_DECLARATION}<worksheet xmlns=\"{MAIN_NS}\"><sheetViews><sheetView workbookViewId=\"0\"><pane ySplit=\"1\" topLeftCell=\"A2\" activePane=\"bottomLeft\" state=\"frozen\"/></sheetView></sheetViews><sheetFormatPr defaultRowHeight=\"15\"/><cols><col min=\"1\" max=\"{}\" width=\"20\" customWidth=\"1\"/></cols><sheetData>",
+            self.header.len().max(1)
+        );
+
+        xml.push_str("<row r=\"1\">");
+        for (index, title) in self.header.iter().enumerate() {
+            let _ = write!(
+                xml,
+                "<c r=\"{}1\" t=\"inlineStr\" s=\"{HEADER_STYLE}\"><is><t>{}</t></is></c>",
+                column_name(index),
+                escape(title)
+            );
+        }
+        xml.push_str("</row>");
+
+        for (row_index, row) in self.rows.iter().enumerate() {
+            let row_number = row_index + 2;
+            let _ = write!(xml, "<row r=\"{row_number}\">");
+            for (index, cell) in row.iter().enumerate() {
+                let reference = format!("{}{row_number}", column_name(index));
+                let _ = match cell {
+                    Cell::Empty => Ok(()),
+                    Cell::Text(text) => write!(
+                        xml,
+                        "<c r=\"{reference}\" t=\"inlineStr\"><is><t xml:space=\"preserve\">{}</t></is></c>",
+                        escape(text)
+                    ),
+                    Cell::Number(number) => {
+                        write!(xml, "<c r=\"{reference}\"><v>{}</v></c>", escape(number))
+                    }
+                    Cell::Boolean(value) => write!(
+                        xml,
+                        "<c r=\"{reference}\" t=\"b\"><v>{}</v></c>",
+                        u8::from(*value)
+                    ),
+                    Cell::DateTime(at) => write!(
+                        xml,
+                        "<c r=\"{reference}\" s=\"{DATE_TIME_STYLE}\"><v>{}</v></c>",
+                        excel_serial(*at)
+                    ),
+                };
+            }
+            xml.push_str("</row>");
+        }
+        let _ = write!(
+            xml,
+            "</sheetData><autoFilter ref=\"A1:{last_column}{last_row}\"/>"
+        );
+
+        for (priority, highlight) in self.highlights.iter().enumerate() {
+            let column = column_name(highlight.column);
+            let text = escape(&highlight.text);
+            let _ = write!(
+                xml,
+                "<conditionalFormatting sqref=\"{column}2:{column}{last_row}\"><cfRule type=\"containsText\" dxfId=\"{priority}\" priority=\"{}\" operator=\"containsText\" text=\"{text}\"><formula>NOT(ISERROR(SEARCH(\"{text}\",{column}2)))</formula></cfRule></conditionalFormatting>",
+                priority + 1
+            );
+        }
+        xml.push_str("</worksheet>");
+        xml
+    }
+
+    fn styles(&self) -> String {
+        let mut dxfs = String::new();
+        for highlight in &self.highlights {
+            let [r, g, b] = highlight.fill;
+            let _ = write!(
+                dxfs,
+                "<dxf><fill><patternFill patternType=\"solid\"><bgColor rgb=\"FF{r:02X}{g:02X}{b:02X}\"/></patternFill></fill></dxf>"
+            );
+        }
+        format!(
+            "{XML_DECLARATION}<styleSheet xmlns=\"{MAIN_NS}\"><numFmts count=\"1\"><numFmt numFmtId=\"164\" formatCode=\"dd/mm/yyyy hh:mm:ss\"/></numFmts><fonts count=\"2\"><font><sz val=\"11\"/><name val=\"Calibri\"/></font><font><b/><sz val=\"11\"/><name val=\"Calibri\"/></font></fonts><fills count=\"2\"><fill><patternFill patternType=\"none\"/></fill><fill><patternFill patternType=\"gray125\"/></fill></fills><borders count=\"1\"><border><left/><right/><top/><bottom/><diagonal/></border></borders><cellStyleXfs count=\"1\"><xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\"/></cellStyleXfs><cellXfs count=\"3\"><xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\"/><xf numFmtId=\"0\" fontId=\"1\" fillId=\"0\" borderId=\"0\" xfId=\"0\" applyFont=\"1\"/><xf numFmtId=\"164\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\" applyNumberFormat=\"1\"/></cellXfs><cellStyles count=\"1\"><cellStyle name=\"Normal\" xfId=\"0\" builtinId=\"0\"/></cellStyles><dxfs count=\"{}\">{dxfs}</dxfs></styleSheet>",
+            self.highlights.len()
+        )
+    }
+}
+
+const XML_DECLARATION: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n";
+const MAIN_NS: &str = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+const RELATIONSHIPS_NS: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+const CONTENT_TYPES: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\"><Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/><Default Extension=\"xml\" ContentType=\"application/xml\"/><Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/><Override PartName=\"/xl/worksheets/sheet1.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/><Override PartName=\"/xl/styles.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml\"/></Types>";
+
+const ROOT_RELATIONSHIPS: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\"><Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"xl/workbook.xml\"/></Relationships>";
+
+const WORKBOOK_RELATIONSHIPS: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\"><Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet1.xml\"/><Relationship Id=\"rId2\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles\" Target=\"styles.xml\"/></Relationships>";
+
+/// `A`, `B`, … `Z`, `AA`, … for a zero-based column index.
+fn column_name(index: usize) -> String {
+    let mut name = Vec::new();
+    let mut rest = index + 1;
+    while rest > 0 {
+        let letter = (rest - 1) % 26;
+        name.push(b'A' + letter as u8);
+        rest = (rest - 1) / 26;
+    }
+    name.reverse();
+    String::from_utf8(name).expect("ascii column name")
+}
+
+/// Days since 1899-12-30, Excel's (1900 date system) epoch.
+fn excel_serial(at: NaiveDateTime) -> String {
+    let epoch = NaiveDate::from_ymd_opt(1899, 12, 30)
+        .expect("valid epoch")
+        .and_hms_opt(0, 0, 0)
+        .expect("valid epoch");
+    let seconds = (at - epoch).num_seconds();
+    format!("{:.6}", seconds as f64 / 86_400.0)
+}
+
+/// XML text escape; control characters XML 1.0 forbids are dropped.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' | '\n' | '\r' => escaped.push(ch),
+            ch if (ch as u32) < 0x20 => {}
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}